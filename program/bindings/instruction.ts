@@ -0,0 +1,1542 @@
+// Generated from the borsh schemas of the program by
+// `cargo run --features codegen --bin ts-bindings`, do not edit
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import BN from 'bn.js';
+import { DistributionType, EntropySource, FlashSchedule, MintMode, SwapConfig } from './state';
+
+export type InitCashArgsFields = {
+  amount: BN;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  cashBump: number;
+  distributionType: DistributionType;
+  maxNumRedemptions: number;
+  minAmount?: BN;
+  cashReference: string;
+  isLocked: boolean;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  burnBps: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature: boolean;
+  swap?: SwapConfig;
+  entropySource: EntropySource;
+  flash?: FlashSchedule;
+  intendedRecipient?: string;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  requireVoucher: boolean;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
+};
+
+export class InitCashArgs extends Borsh.Data<InitCashArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SwapConfig.SCHEMA,
+    ...EntropySource.SCHEMA,
+    ...FlashSchedule.SCHEMA,
+    ...InitCashArgs.struct([
+      ['amount', 'u64'],
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+      ['cashBump', 'u8'],
+      ['distributionType', 'u8'],
+      ['maxNumRedemptions', 'u16'],
+      ['minAmount', { kind: 'option', type: 'u64' }],
+      ['cashReference', 'string'],
+      ['isLocked', 'u8'],
+      ['kycRequiredAbove', { kind: 'option', type: 'u64' }],
+      ['kycAuthority', { kind: 'option', type: 'pubkeyAsString' }],
+      ['referralCeilingsEnabled', 'u8'],
+      ['maxReferrerFeeBps', 'u16'],
+      ['maxRefereeFeeBps', 'u16'],
+      ['burnBps', 'u16'],
+      ['metadataUri', { kind: 'option', type: 'string' }],
+      ['claimRequiresWalletSignature', 'u8'],
+      ['swap', { kind: 'option', type: SwapConfig }],
+      ['entropySource', EntropySource],
+      ['flash', { kind: 'option', type: FlashSchedule }],
+      ['intendedRecipient', { kind: 'option', type: 'pubkeyAsString' }],
+      ['maxPriorityFeePerRedeem', 'u64'],
+      ['allowFreezableMint', 'u8'],
+      ['commitRevealDelay', { kind: 'option', type: 'u64' }],
+      ['refundDestination', { kind: 'option', type: 'pubkeyAsString' }],
+      ['approvalThreshold', { kind: 'option', type: 'u64' }],
+      ['approvalTtl', { kind: 'option', type: 'u64' }],
+      ['requireVoucher', 'u8'],
+      ['autoCancelAt', { kind: 'option', type: 'u64' }],
+      ['autoCancelTip', 'u64'],
+      ['maxSignatureAgeSlots', { kind: 'option', type: 'u64' }],
+      ['clawbackWindowSeconds', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+  amount: BN;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  cashBump: number;
+  distributionType: DistributionType;
+  maxNumRedemptions: number;
+  minAmount?: BN;
+  cashReference: string;
+  isLocked: boolean;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  burnBps: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature: boolean;
+  swap?: SwapConfig;
+  entropySource: EntropySource;
+  flash?: FlashSchedule;
+  intendedRecipient?: string;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  requireVoucher: boolean;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
+}
+
+export type InitCashInstructionFields = {
+  amount: BN;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  cashBump: number;
+  distributionType: DistributionType;
+  maxNumRedemptions: number;
+  minAmount?: BN;
+  cashReference: string;
+  isLocked: boolean;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  burnBps: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature: boolean;
+  swap?: SwapConfig;
+  entropySource: EntropySource;
+  flash?: FlashSchedule;
+  intendedRecipient?: string;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  requireVoucher: boolean;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
+};
+
+export class InitCashInstruction extends Borsh.Data<InitCashInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SwapConfig.SCHEMA,
+    ...EntropySource.SCHEMA,
+    ...FlashSchedule.SCHEMA,
+    ...InitCashInstruction.struct([
+      ['instruction', 'u8'],
+      ['amount', 'u64'],
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+      ['cashBump', 'u8'],
+      ['distributionType', 'u8'],
+      ['maxNumRedemptions', 'u16'],
+      ['minAmount', { kind: 'option', type: 'u64' }],
+      ['cashReference', 'string'],
+      ['isLocked', 'u8'],
+      ['kycRequiredAbove', { kind: 'option', type: 'u64' }],
+      ['kycAuthority', { kind: 'option', type: 'pubkeyAsString' }],
+      ['referralCeilingsEnabled', 'u8'],
+      ['maxReferrerFeeBps', 'u16'],
+      ['maxRefereeFeeBps', 'u16'],
+      ['burnBps', 'u16'],
+      ['metadataUri', { kind: 'option', type: 'string' }],
+      ['claimRequiresWalletSignature', 'u8'],
+      ['swap', { kind: 'option', type: SwapConfig }],
+      ['entropySource', EntropySource],
+      ['flash', { kind: 'option', type: FlashSchedule }],
+      ['intendedRecipient', { kind: 'option', type: 'pubkeyAsString' }],
+      ['maxPriorityFeePerRedeem', 'u64'],
+      ['allowFreezableMint', 'u8'],
+      ['commitRevealDelay', { kind: 'option', type: 'u64' }],
+      ['refundDestination', { kind: 'option', type: 'pubkeyAsString' }],
+      ['approvalThreshold', { kind: 'option', type: 'u64' }],
+      ['approvalTtl', { kind: 'option', type: 'u64' }],
+      ['requireVoucher', 'u8'],
+      ['autoCancelAt', { kind: 'option', type: 'u64' }],
+      ['autoCancelTip', 'u64'],
+      ['maxSignatureAgeSlots', { kind: 'option', type: 'u64' }],
+      ['clawbackWindowSeconds', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+
+  instruction = 0;
+  amount: BN;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  cashBump: number;
+  distributionType: DistributionType;
+  maxNumRedemptions: number;
+  minAmount?: BN;
+  cashReference: string;
+  isLocked: boolean;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  burnBps: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature: boolean;
+  swap?: SwapConfig;
+  entropySource: EntropySource;
+  flash?: FlashSchedule;
+  intendedRecipient?: string;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  requireVoucher: boolean;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
+}
+
+export type RecipientPermitFields = {
+  signature: Uint8Array;
+  expiry: BN;
+};
+
+export class RecipientPermit extends Borsh.Data<RecipientPermitFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RecipientPermit.struct([
+      ['signature', [64]],
+      ['expiry', 'u64'],
+    ]),
+  ]);
+  signature: Uint8Array;
+  expiry: BN;
+}
+
+export type InitCashRedemptionArgsFields = {
+  cashBump: number;
+  cashReference: string;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  weightPpm?: number;
+  rateUsd?: string;
+  redemptionBump: number;
+  redemptionIndexBump: number;
+  priorityFeeReimbursement?: BN;
+  recipientPermit?: RecipientPermit;
+  revealNonce?: Uint8Array;
+  createReceipt: boolean;
+  voucherHash?: Uint8Array;
+  saveBps?: number;
+  signedAtSlot?: BN;
+};
+
+export class InitCashRedemptionArgs extends Borsh.Data<InitCashRedemptionArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RecipientPermit.SCHEMA,
+    ...InitCashRedemptionArgs.struct([
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['referrerFeeBps', { kind: 'option', type: 'u16' }],
+      ['refereeFeeBps', { kind: 'option', type: 'u16' }],
+      ['weightPpm', { kind: 'option', type: 'u32' }],
+      ['rateUsd', { kind: 'option', type: 'string' }],
+      ['redemptionBump', 'u8'],
+      ['redemptionIndexBump', 'u8'],
+      ['priorityFeeReimbursement', { kind: 'option', type: 'u64' }],
+      ['recipientPermit', { kind: 'option', type: RecipientPermit }],
+      ['revealNonce', { kind: 'option', type: [32] }],
+      ['createReceipt', 'u8'],
+      ['voucherHash', { kind: 'option', type: [32] }],
+      ['saveBps', { kind: 'option', type: 'u16' }],
+      ['signedAtSlot', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+  cashBump: number;
+  cashReference: string;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  weightPpm?: number;
+  rateUsd?: string;
+  redemptionBump: number;
+  redemptionIndexBump: number;
+  priorityFeeReimbursement?: BN;
+  recipientPermit?: RecipientPermit;
+  revealNonce?: Uint8Array;
+  createReceipt: boolean;
+  voucherHash?: Uint8Array;
+  saveBps?: number;
+  signedAtSlot?: BN;
+}
+
+export type RedeemInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  weightPpm?: number;
+  rateUsd?: string;
+  redemptionBump: number;
+  redemptionIndexBump: number;
+  priorityFeeReimbursement?: BN;
+  recipientPermit?: RecipientPermit;
+  revealNonce?: Uint8Array;
+  createReceipt: boolean;
+  voucherHash?: Uint8Array;
+  saveBps?: number;
+  signedAtSlot?: BN;
+};
+
+export class RedeemInstruction extends Borsh.Data<RedeemInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RecipientPermit.SCHEMA,
+    ...RedeemInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['referrerFeeBps', { kind: 'option', type: 'u16' }],
+      ['refereeFeeBps', { kind: 'option', type: 'u16' }],
+      ['weightPpm', { kind: 'option', type: 'u32' }],
+      ['rateUsd', { kind: 'option', type: 'string' }],
+      ['redemptionBump', 'u8'],
+      ['redemptionIndexBump', 'u8'],
+      ['priorityFeeReimbursement', { kind: 'option', type: 'u64' }],
+      ['recipientPermit', { kind: 'option', type: RecipientPermit }],
+      ['revealNonce', { kind: 'option', type: [32] }],
+      ['createReceipt', 'u8'],
+      ['voucherHash', { kind: 'option', type: [32] }],
+      ['saveBps', { kind: 'option', type: 'u16' }],
+      ['signedAtSlot', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+
+  instruction = 1;
+  cashBump: number;
+  cashReference: string;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  weightPpm?: number;
+  rateUsd?: string;
+  redemptionBump: number;
+  redemptionIndexBump: number;
+  priorityFeeReimbursement?: BN;
+  recipientPermit?: RecipientPermit;
+  revealNonce?: Uint8Array;
+  createReceipt: boolean;
+  voucherHash?: Uint8Array;
+  saveBps?: number;
+  signedAtSlot?: BN;
+}
+
+export type CancelCashRedemptionArgsFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class CancelCashRedemptionArgs extends Borsh.Data<CancelCashRedemptionArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CancelCashRedemptionArgs.struct([
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+  cashBump: number;
+  cashReference: string;
+}
+
+export type CancelInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class CancelInstruction extends Borsh.Data<CancelInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CancelInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+
+  instruction = 2;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type CloseInstructionFields = {};
+
+export class CloseInstruction extends Borsh.Data<CloseInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CloseInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 3;
+}
+
+export type InitConfigArgsFields = {
+  configBump: number;
+};
+
+export class InitConfigArgs extends Borsh.Data<InitConfigArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...InitConfigArgs.struct([
+      ['configBump', 'u8'],
+    ]),
+  ]);
+  configBump: number;
+}
+
+export type InitConfigInstructionFields = {
+  configBump: number;
+};
+
+export class InitConfigInstruction extends Borsh.Data<InitConfigInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...InitConfigInstruction.struct([
+      ['instruction', 'u8'],
+      ['configBump', 'u8'],
+    ]),
+  ]);
+
+  instruction = 4;
+  configBump: number;
+}
+
+export type MintRegistryArgsFields = {
+  registryBump: number;
+};
+
+export class MintRegistryArgs extends Borsh.Data<MintRegistryArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...MintRegistryArgs.struct([
+      ['registryBump', 'u8'],
+    ]),
+  ]);
+  registryBump: number;
+}
+
+export type AddMintInstructionFields = {
+  registryBump: number;
+};
+
+export class AddMintInstruction extends Borsh.Data<AddMintInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AddMintInstruction.struct([
+      ['instruction', 'u8'],
+      ['registryBump', 'u8'],
+    ]),
+  ]);
+
+  instruction = 5;
+  registryBump: number;
+}
+
+export type RemoveMintInstructionFields = {};
+
+export class RemoveMintInstruction extends Borsh.Data<RemoveMintInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RemoveMintInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 6;
+}
+
+export type SetMintModeArgsFields = {
+  registryBump: number;
+  mode: MintMode;
+};
+
+export class SetMintModeArgs extends Borsh.Data<SetMintModeArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SetMintModeArgs.struct([
+      ['registryBump', 'u8'],
+      ['mode', 'u8'],
+    ]),
+  ]);
+  registryBump: number;
+  mode: MintMode;
+}
+
+export type SetMintModeInstructionFields = {
+  registryBump: number;
+  mode: MintMode;
+};
+
+export class SetMintModeInstruction extends Borsh.Data<SetMintModeInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SetMintModeInstruction.struct([
+      ['instruction', 'u8'],
+      ['registryBump', 'u8'],
+      ['mode', 'u8'],
+    ]),
+  ]);
+
+  instruction = 7;
+  registryBump: number;
+  mode: MintMode;
+}
+
+export type InitStatsArgsFields = {
+  statsBump: number;
+};
+
+export class InitStatsArgs extends Borsh.Data<InitStatsArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...InitStatsArgs.struct([
+      ['statsBump', 'u8'],
+    ]),
+  ]);
+  statsBump: number;
+}
+
+export type InitStatsInstructionFields = {
+  statsBump: number;
+};
+
+export class InitStatsInstruction extends Borsh.Data<InitStatsInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...InitStatsInstruction.struct([
+      ['instruction', 'u8'],
+      ['statsBump', 'u8'],
+    ]),
+  ]);
+
+  instruction = 8;
+  statsBump: number;
+}
+
+export type CreateBufferArgsFields = {
+  bufferBump: number;
+  cashReference: string;
+  size: number;
+};
+
+export class CreateBufferArgs extends Borsh.Data<CreateBufferArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CreateBufferArgs.struct([
+      ['bufferBump', 'u8'],
+      ['cashReference', 'string'],
+      ['size', 'u32'],
+    ]),
+  ]);
+  bufferBump: number;
+  cashReference: string;
+  size: number;
+}
+
+export type CreateBufferInstructionFields = {
+  bufferBump: number;
+  cashReference: string;
+  size: number;
+};
+
+export class CreateBufferInstruction extends Borsh.Data<CreateBufferInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CreateBufferInstruction.struct([
+      ['instruction', 'u8'],
+      ['bufferBump', 'u8'],
+      ['cashReference', 'string'],
+      ['size', 'u32'],
+    ]),
+  ]);
+
+  instruction = 9;
+  bufferBump: number;
+  cashReference: string;
+  size: number;
+}
+
+export type WriteBufferChunkArgsFields = {
+  offset: number;
+  data: Uint8Array;
+};
+
+export class WriteBufferChunkArgs extends Borsh.Data<WriteBufferChunkArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...WriteBufferChunkArgs.struct([
+      ['offset', 'u32'],
+      ['data', ['u8']],
+    ]),
+  ]);
+  offset: number;
+  data: Uint8Array;
+}
+
+export type WriteBufferChunkInstructionFields = {
+  offset: number;
+  data: Uint8Array;
+};
+
+export class WriteBufferChunkInstruction extends Borsh.Data<WriteBufferChunkInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...WriteBufferChunkInstruction.struct([
+      ['instruction', 'u8'],
+      ['offset', 'u32'],
+      ['data', ['u8']],
+    ]),
+  ]);
+
+  instruction = 10;
+  offset: number;
+  data: Uint8Array;
+}
+
+export type FinalizeBufferArgsFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class FinalizeBufferArgs extends Borsh.Data<FinalizeBufferArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FinalizeBufferArgs.struct([
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+  cashBump: number;
+  cashReference: string;
+}
+
+export type FinalizeBufferInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class FinalizeBufferInstruction extends Borsh.Data<FinalizeBufferInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FinalizeBufferInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+
+  instruction = 11;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type UpdateConfigArgsFields = {
+  permissionlessInit: boolean;
+  feeWallet: string;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  maxDustDecimals: number;
+  maxRedemptions?: number;
+  maxFeeReserveBps?: number;
+  swapAmmProgram?: string;
+  recoveryWallet?: string;
+  recoveryTimelock?: BN;
+  referralVestingDelay?: BN;
+};
+
+export class UpdateConfigArgs extends Borsh.Data<UpdateConfigArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...UpdateConfigArgs.struct([
+      ['permissionlessInit', 'u8'],
+      ['feeWallet', 'pubkeyAsString'],
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+      ['maxDustDecimals', 'u8'],
+      ['maxRedemptions', { kind: 'option', type: 'u16' }],
+      ['maxFeeReserveBps', { kind: 'option', type: 'u16' }],
+      ['swapAmmProgram', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryWallet', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryTimelock', { kind: 'option', type: 'u64' }],
+      ['referralVestingDelay', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+  permissionlessInit: boolean;
+  feeWallet: string;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  maxDustDecimals: number;
+  maxRedemptions?: number;
+  maxFeeReserveBps?: number;
+  swapAmmProgram?: string;
+  recoveryWallet?: string;
+  recoveryTimelock?: BN;
+  referralVestingDelay?: BN;
+}
+
+export type UpdateConfigInstructionFields = {
+  permissionlessInit: boolean;
+  feeWallet: string;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  maxDustDecimals: number;
+  maxRedemptions?: number;
+  maxFeeReserveBps?: number;
+  swapAmmProgram?: string;
+  recoveryWallet?: string;
+  recoveryTimelock?: BN;
+  referralVestingDelay?: BN;
+};
+
+export class UpdateConfigInstruction extends Borsh.Data<UpdateConfigInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...UpdateConfigInstruction.struct([
+      ['instruction', 'u8'],
+      ['permissionlessInit', 'u8'],
+      ['feeWallet', 'pubkeyAsString'],
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+      ['maxDustDecimals', 'u8'],
+      ['maxRedemptions', { kind: 'option', type: 'u16' }],
+      ['maxFeeReserveBps', { kind: 'option', type: 'u16' }],
+      ['swapAmmProgram', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryWallet', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryTimelock', { kind: 'option', type: 'u64' }],
+      ['referralVestingDelay', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+
+  instruction = 12;
+  permissionlessInit: boolean;
+  feeWallet: string;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  maxDustDecimals: number;
+  maxRedemptions?: number;
+  maxFeeReserveBps?: number;
+  swapAmmProgram?: string;
+  recoveryWallet?: string;
+  recoveryTimelock?: BN;
+  referralVestingDelay?: BN;
+}
+
+export type TransferAuthorityInstructionFields = {};
+
+export class TransferAuthorityInstruction extends Borsh.Data<TransferAuthorityInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...TransferAuthorityInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 13;
+}
+
+export type RedemptionEntryFields = {
+  cashReference: string;
+  wallet: string;
+};
+
+export class RedemptionEntry extends Borsh.Data<RedemptionEntryFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RedemptionEntry.struct([
+      ['cashReference', 'string'],
+      ['wallet', 'pubkeyAsString'],
+    ]),
+  ]);
+  cashReference: string;
+  wallet: string;
+}
+
+export type ValidateRedemptionsArgsFields = {
+  entries: RedemptionEntry[];
+};
+
+export class ValidateRedemptionsArgs extends Borsh.Data<ValidateRedemptionsArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RedemptionEntry.SCHEMA,
+    ...ValidateRedemptionsArgs.struct([
+      ['entries', [RedemptionEntry]],
+    ]),
+  ]);
+  entries: RedemptionEntry[];
+}
+
+export type ValidateRedemptionsInstructionFields = {
+  entries: RedemptionEntry[];
+};
+
+export class ValidateRedemptionsInstruction extends Borsh.Data<ValidateRedemptionsInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RedemptionEntry.SCHEMA,
+    ...ValidateRedemptionsInstruction.struct([
+      ['instruction', 'u8'],
+      ['entries', [RedemptionEntry]],
+    ]),
+  ]);
+
+  instruction = 14;
+  entries: RedemptionEntry[];
+}
+
+export type UpdateMetadataArgsFields = {
+  uri: string;
+};
+
+export class UpdateMetadataArgs extends Borsh.Data<UpdateMetadataArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...UpdateMetadataArgs.struct([
+      ['uri', 'string'],
+    ]),
+  ]);
+  uri: string;
+}
+
+export type UpdateMetadataInstructionFields = {
+  uri: string;
+};
+
+export class UpdateMetadataInstruction extends Borsh.Data<UpdateMetadataInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...UpdateMetadataInstruction.struct([
+      ['instruction', 'u8'],
+      ['uri', 'string'],
+    ]),
+  ]);
+
+  instruction = 15;
+  uri: string;
+}
+
+export type CancelBeginArgsFields = {
+  cashBump: number;
+  cashReference: string;
+  maxAmount: BN;
+};
+
+export class CancelBeginArgs extends Borsh.Data<CancelBeginArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CancelBeginArgs.struct([
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['maxAmount', 'u64'],
+    ]),
+  ]);
+  cashBump: number;
+  cashReference: string;
+  maxAmount: BN;
+}
+
+export type CancelBeginInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+  maxAmount: BN;
+};
+
+export class CancelBeginInstruction extends Borsh.Data<CancelBeginInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CancelBeginInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['maxAmount', 'u64'],
+    ]),
+  ]);
+
+  instruction = 16;
+  cashBump: number;
+  cashReference: string;
+  maxAmount: BN;
+}
+
+export type CancelFinalizeInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class CancelFinalizeInstruction extends Borsh.Data<CancelFinalizeInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CancelFinalizeInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+
+  instruction = 17;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type EmergencyHaltInstructionFields = {};
+
+export class EmergencyHaltInstruction extends Borsh.Data<EmergencyHaltInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...EmergencyHaltInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 18;
+}
+
+export type EmergencyReleaseInstructionFields = {};
+
+export class EmergencyReleaseInstruction extends Borsh.Data<EmergencyReleaseInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...EmergencyReleaseInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 19;
+}
+
+export type EmergencyWithdrawInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class EmergencyWithdrawInstruction extends Borsh.Data<EmergencyWithdrawInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...EmergencyWithdrawInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+
+  instruction = 20;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type ExpireSlotInstructionFields = {};
+
+export class ExpireSlotInstruction extends Borsh.Data<ExpireSlotInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ExpireSlotInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 21;
+}
+
+export type RotatePassKeyArgsFields = {
+  force: boolean;
+};
+
+export class RotatePassKeyArgs extends Borsh.Data<RotatePassKeyArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RotatePassKeyArgs.struct([
+      ['force', 'u8'],
+    ]),
+  ]);
+  force: boolean;
+}
+
+export type RotatePassKeyInstructionFields = {
+  force: boolean;
+};
+
+export class RotatePassKeyInstruction extends Borsh.Data<RotatePassKeyInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RotatePassKeyInstruction.struct([
+      ['instruction', 'u8'],
+      ['force', 'u8'],
+    ]),
+  ]);
+
+  instruction = 22;
+  force: boolean;
+}
+
+export type InitiateRecoveryInstructionFields = {};
+
+export class InitiateRecoveryInstruction extends Borsh.Data<InitiateRecoveryInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...InitiateRecoveryInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 23;
+}
+
+export type ExecuteRecoveryInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class ExecuteRecoveryInstruction extends Borsh.Data<ExecuteRecoveryInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ExecuteRecoveryInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+
+  instruction = 24;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type CloseReferralStatsInstructionFields = {};
+
+export class CloseReferralStatsInstruction extends Borsh.Data<CloseReferralStatsInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CloseReferralStatsInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 25;
+}
+
+export type SetDisabledFeaturesArgsFields = {
+  disabledFeatures: BN;
+};
+
+export class SetDisabledFeaturesArgs extends Borsh.Data<SetDisabledFeaturesArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SetDisabledFeaturesArgs.struct([
+      ['disabledFeatures', 'u64'],
+    ]),
+  ]);
+  disabledFeatures: BN;
+}
+
+export type SetDisabledFeaturesInstructionFields = {
+  disabledFeatures: BN;
+};
+
+export class SetDisabledFeaturesInstruction extends Borsh.Data<SetDisabledFeaturesInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SetDisabledFeaturesInstruction.struct([
+      ['instruction', 'u8'],
+      ['disabledFeatures', 'u64'],
+    ]),
+  ]);
+
+  instruction = 26;
+  disabledFeatures: BN;
+}
+
+export type CommitRedemptionArgsFields = {
+  commitment: Uint8Array;
+  commitBump: number;
+};
+
+export class CommitRedemptionArgs extends Borsh.Data<CommitRedemptionArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CommitRedemptionArgs.struct([
+      ['commitment', [32]],
+      ['commitBump', 'u8'],
+    ]),
+  ]);
+  commitment: Uint8Array;
+  commitBump: number;
+}
+
+export type CommitRedemptionInstructionFields = {
+  commitment: Uint8Array;
+  commitBump: number;
+};
+
+export class CommitRedemptionInstruction extends Borsh.Data<CommitRedemptionInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CommitRedemptionInstruction.struct([
+      ['instruction', 'u8'],
+      ['commitment', [32]],
+      ['commitBump', 'u8'],
+    ]),
+  ]);
+
+  instruction = 27;
+  commitment: Uint8Array;
+  commitBump: number;
+}
+
+export type SweepCommitInstructionFields = {};
+
+export class SweepCommitInstruction extends Borsh.Data<SweepCommitInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SweepCommitInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 28;
+}
+
+export type SplitCashArgsFields = {
+  cashBump: number;
+  cashReference: string;
+  childBump: number;
+  childReference: string;
+  amount: BN;
+  maxNumRedemptions: number;
+};
+
+export class SplitCashArgs extends Borsh.Data<SplitCashArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SplitCashArgs.struct([
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['childBump', 'u8'],
+      ['childReference', 'string'],
+      ['amount', 'u64'],
+      ['maxNumRedemptions', 'u16'],
+    ]),
+  ]);
+  cashBump: number;
+  cashReference: string;
+  childBump: number;
+  childReference: string;
+  amount: BN;
+  maxNumRedemptions: number;
+}
+
+export type SplitCashInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+  childBump: number;
+  childReference: string;
+  amount: BN;
+  maxNumRedemptions: number;
+};
+
+export class SplitCashInstruction extends Borsh.Data<SplitCashInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SplitCashInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['childBump', 'u8'],
+      ['childReference', 'string'],
+      ['amount', 'u64'],
+      ['maxNumRedemptions', 'u16'],
+    ]),
+  ]);
+
+  instruction = 29;
+  cashBump: number;
+  cashReference: string;
+  childBump: number;
+  childReference: string;
+  amount: BN;
+  maxNumRedemptions: number;
+}
+
+export type BackfillVersionArgsFields = {
+  createdByVersion: number;
+};
+
+export class BackfillVersionArgs extends Borsh.Data<BackfillVersionArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...BackfillVersionArgs.struct([
+      ['createdByVersion', 'u16'],
+    ]),
+  ]);
+  createdByVersion: number;
+}
+
+export type BackfillVersionInstructionFields = {
+  createdByVersion: number;
+};
+
+export class BackfillVersionInstruction extends Borsh.Data<BackfillVersionInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...BackfillVersionInstruction.struct([
+      ['instruction', 'u8'],
+      ['createdByVersion', 'u16'],
+    ]),
+  ]);
+
+  instruction = 30;
+  createdByVersion: number;
+}
+
+export type ApproveRedemptionInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  weightPpm?: number;
+  rateUsd?: string;
+  redemptionBump: number;
+  redemptionIndexBump: number;
+  priorityFeeReimbursement?: BN;
+  recipientPermit?: RecipientPermit;
+  revealNonce?: Uint8Array;
+  createReceipt: boolean;
+  voucherHash?: Uint8Array;
+  saveBps?: number;
+  signedAtSlot?: BN;
+};
+
+export class ApproveRedemptionInstruction extends Borsh.Data<ApproveRedemptionInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RecipientPermit.SCHEMA,
+    ...ApproveRedemptionInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['referrerFeeBps', { kind: 'option', type: 'u16' }],
+      ['refereeFeeBps', { kind: 'option', type: 'u16' }],
+      ['weightPpm', { kind: 'option', type: 'u32' }],
+      ['rateUsd', { kind: 'option', type: 'string' }],
+      ['redemptionBump', 'u8'],
+      ['redemptionIndexBump', 'u8'],
+      ['priorityFeeReimbursement', { kind: 'option', type: 'u64' }],
+      ['recipientPermit', { kind: 'option', type: RecipientPermit }],
+      ['revealNonce', { kind: 'option', type: [32] }],
+      ['createReceipt', 'u8'],
+      ['voucherHash', { kind: 'option', type: [32] }],
+      ['saveBps', { kind: 'option', type: 'u16' }],
+      ['signedAtSlot', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+
+  instruction = 31;
+  cashBump: number;
+  cashReference: string;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  weightPpm?: number;
+  rateUsd?: string;
+  redemptionBump: number;
+  redemptionIndexBump: number;
+  priorityFeeReimbursement?: BN;
+  recipientPermit?: RecipientPermit;
+  revealNonce?: Uint8Array;
+  createReceipt: boolean;
+  voucherHash?: Uint8Array;
+  saveBps?: number;
+  signedAtSlot?: BN;
+}
+
+export type RejectRedemptionInstructionFields = {};
+
+export class RejectRedemptionInstruction extends Borsh.Data<RejectRedemptionInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RejectRedemptionInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 32;
+}
+
+export type FeeWalletRegistryArgsFields = {
+  registryBump: number;
+};
+
+export class FeeWalletRegistryArgs extends Borsh.Data<FeeWalletRegistryArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FeeWalletRegistryArgs.struct([
+      ['registryBump', 'u8'],
+    ]),
+  ]);
+  registryBump: number;
+}
+
+export type AddFeeWalletInstructionFields = {
+  registryBump: number;
+};
+
+export class AddFeeWalletInstruction extends Borsh.Data<AddFeeWalletInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AddFeeWalletInstruction.struct([
+      ['instruction', 'u8'],
+      ['registryBump', 'u8'],
+    ]),
+  ]);
+
+  instruction = 33;
+  registryBump: number;
+}
+
+export type SupersedeFeeWalletArgsFields = {
+  epoch: number;
+};
+
+export class SupersedeFeeWalletArgs extends Borsh.Data<SupersedeFeeWalletArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SupersedeFeeWalletArgs.struct([
+      ['epoch', 'u16'],
+    ]),
+  ]);
+  epoch: number;
+}
+
+export type SupersedeFeeWalletInstructionFields = {
+  epoch: number;
+};
+
+export class SupersedeFeeWalletInstruction extends Borsh.Data<SupersedeFeeWalletInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SupersedeFeeWalletInstruction.struct([
+      ['instruction', 'u8'],
+      ['epoch', 'u16'],
+    ]),
+  ]);
+
+  instruction = 34;
+  epoch: number;
+}
+
+export type ReferralFeesArgsFields = {
+  mint: string;
+};
+
+export class ReferralFeesArgs extends Borsh.Data<ReferralFeesArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ReferralFeesArgs.struct([
+      ['mint', 'pubkeyAsString'],
+    ]),
+  ]);
+  mint: string;
+}
+
+export type ClaimReferralFeesInstructionFields = {
+  mint: string;
+};
+
+export class ClaimReferralFeesInstruction extends Borsh.Data<ClaimReferralFeesInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ClaimReferralFeesInstruction.struct([
+      ['instruction', 'u8'],
+      ['mint', 'pubkeyAsString'],
+    ]),
+  ]);
+
+  instruction = 35;
+  mint: string;
+}
+
+export type FlagReferrerInstructionFields = {
+  mint: string;
+};
+
+export class FlagReferrerInstruction extends Borsh.Data<FlagReferrerInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FlagReferrerInstruction.struct([
+      ['instruction', 'u8'],
+      ['mint', 'pubkeyAsString'],
+    ]),
+  ]);
+
+  instruction = 36;
+  mint: string;
+}
+
+export type MigrateBatchArgsFields = {
+  count: number;
+};
+
+export class MigrateBatchArgs extends Borsh.Data<MigrateBatchArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...MigrateBatchArgs.struct([
+      ['count', 'u8'],
+    ]),
+  ]);
+  count: number;
+}
+
+export type MigrateBatchInstructionFields = {
+  count: number;
+};
+
+export class MigrateBatchInstruction extends Borsh.Data<MigrateBatchInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...MigrateBatchInstruction.struct([
+      ['instruction', 'u8'],
+      ['count', 'u8'],
+    ]),
+  ]);
+
+  instruction = 37;
+  count: number;
+}
+
+export type ProcessAutoCancelQueueArgsFields = {
+  links: CancelCashRedemptionArgs[];
+};
+
+export class ProcessAutoCancelQueueArgs extends Borsh.Data<ProcessAutoCancelQueueArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CancelCashRedemptionArgs.SCHEMA,
+    ...ProcessAutoCancelQueueArgs.struct([
+      ['links', [CancelCashRedemptionArgs]],
+    ]),
+  ]);
+  links: CancelCashRedemptionArgs[];
+}
+
+export type ProcessAutoCancelQueueInstructionFields = {
+  links: CancelCashRedemptionArgs[];
+};
+
+export class ProcessAutoCancelQueueInstruction extends Borsh.Data<ProcessAutoCancelQueueInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CancelCashRedemptionArgs.SCHEMA,
+    ...ProcessAutoCancelQueueInstruction.struct([
+      ['instruction', 'u8'],
+      ['links', [CancelCashRedemptionArgs]],
+    ]),
+  ]);
+
+  instruction = 38;
+  links: CancelCashRedemptionArgs[];
+}
+
+export type SetMintCapArgsFields = {
+  registryBump: number;
+  dailyCap?: BN;
+};
+
+export class SetMintCapArgs extends Borsh.Data<SetMintCapArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SetMintCapArgs.struct([
+      ['registryBump', 'u8'],
+      ['dailyCap', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+  registryBump: number;
+  dailyCap?: BN;
+}
+
+export type SetMintCapInstructionFields = {
+  registryBump: number;
+  dailyCap?: BN;
+};
+
+export class SetMintCapInstruction extends Borsh.Data<SetMintCapInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SetMintCapInstruction.struct([
+      ['instruction', 'u8'],
+      ['registryBump', 'u8'],
+      ['dailyCap', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+
+  instruction = 39;
+  registryBump: number;
+  dailyCap?: BN;
+}
+
+export type CloseStaleWalletLimitsInstructionFields = {};
+
+export class CloseStaleWalletLimitsInstruction extends Borsh.Data<CloseStaleWalletLimitsInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...CloseStaleWalletLimitsInstruction.struct([
+      ['instruction', 'u8'],
+    ]),
+  ]);
+
+  instruction = 40;
+}
+
+export type ClawbackRedemptionArgsFields = {
+  cashBump: number;
+  cashReference: string;
+  amount: BN;
+};
+
+export class ClawbackRedemptionArgs extends Borsh.Data<ClawbackRedemptionArgsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ClawbackRedemptionArgs.struct([
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['amount', 'u64'],
+    ]),
+  ]);
+  cashBump: number;
+  cashReference: string;
+  amount: BN;
+}
+
+export type ClawbackRedemptionInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+  amount: BN;
+};
+
+export class ClawbackRedemptionInstruction extends Borsh.Data<ClawbackRedemptionInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ClawbackRedemptionInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+      ['amount', 'u64'],
+    ]),
+  ]);
+
+  instruction = 41;
+  cashBump: number;
+  cashReference: string;
+  amount: BN;
+}
+
+export type RevokeClawbackInstructionFields = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class RevokeClawbackInstruction extends Borsh.Data<RevokeClawbackInstructionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RevokeClawbackInstruction.struct([
+      ['instruction', 'u8'],
+      ['cashBump', 'u8'],
+      ['cashReference', 'string'],
+    ]),
+  ]);
+
+  instruction = 42;
+  cashBump: number;
+  cashReference: string;
+}
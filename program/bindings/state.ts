@@ -0,0 +1,868 @@
+// Generated from the borsh schemas of the program by
+// `cargo run --features codegen --bin ts-bindings`, do not edit
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import BN from 'bn.js';
+
+export enum AccountType {
+  Uninitialized = 0,
+  Cash = 1,
+  Config = 2,
+  MintRegistry = 3,
+  Stats = 4,
+  Buffer = 5,
+  Metadata = 6,
+  ReferralStats = 7,
+  RedemptionCommit = 8,
+  PendingRedemption = 9,
+  FeeWalletRegistry = 10,
+  AutoCancelQueue = 11,
+  WalletLimit = 12,
+}
+
+export enum CashState {
+  Initialized = 0,
+  Redeemed = 1,
+  Redeeming = 2,
+  Canceled = 3,
+  CancelPending = 4,
+}
+
+export enum DistributionType {
+  Fixed = 0,
+  Random = 1,
+  Weighted = 2,
+  Equal = 3,
+}
+
+export enum CompletionReason {
+  None = 0,
+  SlotsExhausted = 1,
+  FundsExhausted = 2,
+  BelowMinThreshold = 3,
+  Canceled = 4,
+  Expired = 5,
+  Emergency = 6,
+  Recovered = 7,
+}
+
+export type SwapConfigFields = {
+  targetMint: string;
+  ammProgram: string;
+  maxSlippageBps: number;
+};
+
+export class SwapConfig extends Borsh.Data<SwapConfigFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SwapConfig.struct([
+      ['targetMint', 'pubkeyAsString'],
+      ['ammProgram', 'pubkeyAsString'],
+      ['maxSlippageBps', 'u16'],
+    ]),
+  ]);
+  targetMint: string;
+  ammProgram: string;
+  maxSlippageBps: number;
+}
+
+export type EntropySourceSlotHashesFields = {};
+
+export class EntropySourceSlotHashes extends Borsh.Data<EntropySourceSlotHashesFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...EntropySourceSlotHashes.struct([]),
+  ]);
+}
+
+export type EntropySourceVrfAccountFields = {
+  value: string;
+};
+
+export class EntropySourceVrfAccount extends Borsh.Data<EntropySourceVrfAccountFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...EntropySourceVrfAccount.struct([
+      ['value', 'pubkeyAsString'],
+    ]),
+  ]);
+  value: string;
+}
+
+export type EntropySourceFields =
+  | { enum: 'slotHashes'; slotHashes: EntropySourceSlotHashes }
+  | { enum: 'vrfAccount'; vrfAccount: EntropySourceVrfAccount };
+
+export class EntropySource extends Borsh.Data<EntropySourceFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...EntropySourceSlotHashes.SCHEMA,
+    ...EntropySourceVrfAccount.SCHEMA,
+    [
+      EntropySource,
+      {
+        kind: 'enum',
+        field: 'enum',
+        values: [
+          ['slotHashes', EntropySourceSlotHashes],
+          ['vrfAccount', EntropySourceVrfAccount],
+        ],
+      },
+    ],
+  ]);
+  enum: 'slotHashes' | 'vrfAccount';
+  slotHashes?: EntropySourceSlotHashes;
+  vrfAccount?: EntropySourceVrfAccount;
+}
+
+export type FlashScheduleFields = {
+  startAt: BN;
+  interval: BN;
+};
+
+export class FlashSchedule extends Borsh.Data<FlashScheduleFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FlashSchedule.struct([
+      ['startAt', 'u64'],
+      ['interval', 'u64'],
+    ]),
+  ]);
+  startAt: BN;
+  interval: BN;
+}
+
+export type CashFields = {
+  accountType: AccountType;
+  authority: string;
+  state: CashState;
+  amount: BN;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  remainingAmount: BN;
+  distributionType: DistributionType;
+  owner: string;
+  mint: string;
+  totalRedemptions: number;
+  maxNumRedemptions: number;
+  minAmount: BN;
+  passKey?: string;
+  totalWeightPpm: number;
+  feePayer: string;
+  rentPaidByFeePayer: BN;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  completionReason: CompletionReason;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  bufferHash?: Uint8Array;
+  feeWallet?: string;
+  inProgress: boolean;
+  vault: string;
+  tokenProgram: string;
+  burnBps: number;
+  totalBurned: BN;
+  claimRequiresWalletSignature: boolean;
+  createdAt: BN;
+  canceledAt?: BN;
+  completedAt?: BN;
+  swap?: SwapConfig;
+  entropySource: EntropySource;
+  lastEntropyRound: BN;
+  halted: boolean;
+  flash?: FlashSchedule;
+  expiredSlots: number;
+  forfeitedAmount: BN;
+  passRotations: number;
+  intendedRecipient?: string;
+  recoveryRequestedAt?: BN;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  decimals?: number;
+  createdByVersion: number;
+  lastTouchedVersion: number;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  reservedAmount: BN;
+  pendingApprovals: number;
+  feeWalletEpoch?: number;
+  requireVoucher: boolean;
+  mintOwner?: string;
+  mintSupplyAtInit?: BN;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  paramsHash?: Uint8Array;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
+};
+
+export class Cash extends Borsh.Data<CashFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SwapConfig.SCHEMA,
+    ...EntropySource.SCHEMA,
+    ...FlashSchedule.SCHEMA,
+    ...Cash.struct([
+      ['accountType', 'u8'],
+      ['authority', 'pubkeyAsString'],
+      ['state', 'u8'],
+      ['amount', 'u64'],
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+      ['remainingAmount', 'u64'],
+      ['distributionType', 'u8'],
+      ['owner', 'pubkeyAsString'],
+      ['mint', 'pubkeyAsString'],
+      ['totalRedemptions', 'u16'],
+      ['maxNumRedemptions', 'u16'],
+      ['minAmount', 'u64'],
+      ['passKey', { kind: 'option', type: 'pubkeyAsString' }],
+      ['totalWeightPpm', 'u32'],
+      ['feePayer', 'pubkeyAsString'],
+      ['rentPaidByFeePayer', 'u64'],
+      ['kycRequiredAbove', { kind: 'option', type: 'u64' }],
+      ['kycAuthority', { kind: 'option', type: 'pubkeyAsString' }],
+      ['completionReason', 'u8'],
+      ['referralCeilingsEnabled', 'u8'],
+      ['maxReferrerFeeBps', 'u16'],
+      ['maxRefereeFeeBps', 'u16'],
+      ['bufferHash', { kind: 'option', type: [32] }],
+      ['feeWallet', { kind: 'option', type: 'pubkeyAsString' }],
+      ['inProgress', 'u8'],
+      ['vault', 'pubkeyAsString'],
+      ['tokenProgram', 'pubkeyAsString'],
+      ['burnBps', 'u16'],
+      ['totalBurned', 'u64'],
+      ['claimRequiresWalletSignature', 'u8'],
+      ['createdAt', 'u64'],
+      ['canceledAt', { kind: 'option', type: 'u64' }],
+      ['completedAt', { kind: 'option', type: 'u64' }],
+      ['swap', { kind: 'option', type: SwapConfig }],
+      ['entropySource', EntropySource],
+      ['lastEntropyRound', 'u64'],
+      ['halted', 'u8'],
+      ['flash', { kind: 'option', type: FlashSchedule }],
+      ['expiredSlots', 'u16'],
+      ['forfeitedAmount', 'u64'],
+      ['passRotations', 'u8'],
+      ['intendedRecipient', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryRequestedAt', { kind: 'option', type: 'u64' }],
+      ['maxPriorityFeePerRedeem', 'u64'],
+      ['allowFreezableMint', 'u8'],
+      ['commitRevealDelay', { kind: 'option', type: 'u64' }],
+      ['decimals', { kind: 'option', type: 'u8' }],
+      ['createdByVersion', 'u16'],
+      ['lastTouchedVersion', 'u16'],
+      ['refundDestination', { kind: 'option', type: 'pubkeyAsString' }],
+      ['approvalThreshold', { kind: 'option', type: 'u64' }],
+      ['approvalTtl', { kind: 'option', type: 'u64' }],
+      ['reservedAmount', 'u64'],
+      ['pendingApprovals', 'u16'],
+      ['feeWalletEpoch', { kind: 'option', type: 'u16' }],
+      ['requireVoucher', 'u8'],
+      ['mintOwner', { kind: 'option', type: 'pubkeyAsString' }],
+      ['mintSupplyAtInit', { kind: 'option', type: 'u64' }],
+      ['autoCancelAt', { kind: 'option', type: 'u64' }],
+      ['autoCancelTip', 'u64'],
+      ['paramsHash', { kind: 'option', type: [32] }],
+      ['maxSignatureAgeSlots', { kind: 'option', type: 'u64' }],
+      ['clawbackWindowSeconds', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+  accountType: AccountType;
+  authority: string;
+  state: CashState;
+  amount: BN;
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+  remainingAmount: BN;
+  distributionType: DistributionType;
+  owner: string;
+  mint: string;
+  totalRedemptions: number;
+  maxNumRedemptions: number;
+  minAmount: BN;
+  passKey?: string;
+  totalWeightPpm: number;
+  feePayer: string;
+  rentPaidByFeePayer: BN;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  completionReason: CompletionReason;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  bufferHash?: Uint8Array;
+  feeWallet?: string;
+  inProgress: boolean;
+  vault: string;
+  tokenProgram: string;
+  burnBps: number;
+  totalBurned: BN;
+  claimRequiresWalletSignature: boolean;
+  createdAt: BN;
+  canceledAt?: BN;
+  completedAt?: BN;
+  swap?: SwapConfig;
+  entropySource: EntropySource;
+  lastEntropyRound: BN;
+  halted: boolean;
+  flash?: FlashSchedule;
+  expiredSlots: number;
+  forfeitedAmount: BN;
+  passRotations: number;
+  intendedRecipient?: string;
+  recoveryRequestedAt?: BN;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  decimals?: number;
+  createdByVersion: number;
+  lastTouchedVersion: number;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  reservedAmount: BN;
+  pendingApprovals: number;
+  feeWalletEpoch?: number;
+  requireVoucher: boolean;
+  mintOwner?: string;
+  mintSupplyAtInit?: BN;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  paramsHash?: Uint8Array;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
+}
+
+export type FeeScheduleFields = {
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+};
+
+export class FeeSchedule extends Borsh.Data<FeeScheduleFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FeeSchedule.struct([
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+    ]),
+  ]);
+  feeBps: number;
+  networkFee: BN;
+  baseFeeToRedeem: BN;
+  rentFeeToRedeem: BN;
+}
+
+export type ConfigFields = {
+  accountType: AccountType;
+  admin: string;
+  bump: number;
+  permissionlessInit: boolean;
+  feeWallet: string;
+  fees: FeeSchedule;
+  maxDustDecimals: number;
+  maxRedemptions?: number;
+  maxFeeReserveBps?: number;
+  swapAmmProgram?: string;
+  recoveryWallet?: string;
+  recoveryTimelock?: BN;
+  disabledFeatures: BN;
+  referralVestingDelay?: BN;
+};
+
+export class Config extends Borsh.Data<ConfigFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FeeSchedule.SCHEMA,
+    ...Config.struct([
+      ['accountType', 'u8'],
+      ['admin', 'pubkeyAsString'],
+      ['bump', 'u8'],
+      ['permissionlessInit', 'u8'],
+      ['feeWallet', 'pubkeyAsString'],
+      ['fees', FeeSchedule],
+      ['maxDustDecimals', 'u8'],
+      ['maxRedemptions', { kind: 'option', type: 'u16' }],
+      ['maxFeeReserveBps', { kind: 'option', type: 'u16' }],
+      ['swapAmmProgram', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryWallet', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryTimelock', { kind: 'option', type: 'u64' }],
+      ['disabledFeatures', 'u64'],
+      ['referralVestingDelay', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+  accountType: AccountType;
+  admin: string;
+  bump: number;
+  permissionlessInit: boolean;
+  feeWallet: string;
+  fees: FeeSchedule;
+  maxDustDecimals: number;
+  maxRedemptions?: number;
+  maxFeeReserveBps?: number;
+  swapAmmProgram?: string;
+  recoveryWallet?: string;
+  recoveryTimelock?: BN;
+  disabledFeatures: BN;
+  referralVestingDelay?: BN;
+}
+
+export enum MintMode {
+  Disabled = 0,
+  Allowlist = 1,
+  Denylist = 2,
+}
+
+export type MintCapFields = {
+  mint: string;
+  dailyCap: BN;
+};
+
+export class MintCap extends Borsh.Data<MintCapFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...MintCap.struct([
+      ['mint', 'pubkeyAsString'],
+      ['dailyCap', 'u64'],
+    ]),
+  ]);
+  mint: string;
+  dailyCap: BN;
+}
+
+export type MintRegistryFields = {
+  accountType: AccountType;
+  mode: MintMode;
+  mints: string[];
+  caps: MintCap[];
+};
+
+export class MintRegistry extends Borsh.Data<MintRegistryFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...MintCap.SCHEMA,
+    ...MintRegistry.struct([
+      ['accountType', 'u8'],
+      ['mode', 'u8'],
+      ['mints', ['pubkeyAsString']],
+      ['caps', [MintCap]],
+    ]),
+  ]);
+  accountType: AccountType;
+  mode: MintMode;
+  mints: string[];
+  caps: MintCap[];
+}
+
+export type MintStatsFields = {
+  mint: string;
+  valueLoaded: BN;
+  redeemed: BN;
+  feesCollected: BN;
+  refunded: BN;
+};
+
+export class MintStats extends Borsh.Data<MintStatsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...MintStats.struct([
+      ['mint', 'pubkeyAsString'],
+      ['valueLoaded', 'u64'],
+      ['redeemed', 'u64'],
+      ['feesCollected', 'u64'],
+      ['refunded', 'u64'],
+    ]),
+  ]);
+  mint: string;
+  valueLoaded: BN;
+  redeemed: BN;
+  feesCollected: BN;
+  refunded: BN;
+}
+
+export type StatsFields = {
+  accountType: AccountType;
+  linkCount: BN;
+  valueLoaded: BN;
+  redeemed: BN;
+  feesCollected: BN;
+  refunded: BN;
+  mints: MintStats[];
+};
+
+export class Stats extends Borsh.Data<StatsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...MintStats.SCHEMA,
+    ...Stats.struct([
+      ['accountType', 'u8'],
+      ['linkCount', 'u64'],
+      ['valueLoaded', 'u64'],
+      ['redeemed', 'u64'],
+      ['feesCollected', 'u64'],
+      ['refunded', 'u64'],
+      ['mints', [MintStats]],
+    ]),
+  ]);
+  accountType: AccountType;
+  linkCount: BN;
+  valueLoaded: BN;
+  redeemed: BN;
+  feesCollected: BN;
+  refunded: BN;
+  mints: MintStats[];
+}
+
+export type AuxiliaryHeaderFields = {
+  rentPayer: string;
+};
+
+export class AuxiliaryHeader extends Borsh.Data<AuxiliaryHeaderFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AuxiliaryHeader.struct([
+      ['rentPayer', 'pubkeyAsString'],
+    ]),
+  ]);
+  rentPayer: string;
+}
+
+export type BufferFields = {
+  header: AuxiliaryHeader;
+  accountType: AccountType;
+  authority: string;
+  cash: string;
+  finalized: boolean;
+  hash: Uint8Array;
+  dataLen: number;
+};
+
+export class Buffer extends Borsh.Data<BufferFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AuxiliaryHeader.SCHEMA,
+    ...Buffer.struct([
+      ['header', AuxiliaryHeader],
+      ['accountType', 'u8'],
+      ['authority', 'pubkeyAsString'],
+      ['cash', 'pubkeyAsString'],
+      ['finalized', 'u8'],
+      ['hash', [32]],
+      ['dataLen', 'u32'],
+    ]),
+  ]);
+  header: AuxiliaryHeader;
+  accountType: AccountType;
+  authority: string;
+  cash: string;
+  finalized: boolean;
+  hash: Uint8Array;
+  dataLen: number;
+}
+
+export type CashMetadataFields = {
+  header: AuxiliaryHeader;
+  accountType: AccountType;
+  cash: string;
+  uri: string;
+};
+
+export class CashMetadata extends Borsh.Data<CashMetadataFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AuxiliaryHeader.SCHEMA,
+    ...CashMetadata.struct([
+      ['header', AuxiliaryHeader],
+      ['accountType', 'u8'],
+      ['cash', 'pubkeyAsString'],
+      ['uri', 'string'],
+    ]),
+  ]);
+  header: AuxiliaryHeader;
+  accountType: AccountType;
+  cash: string;
+  uri: string;
+}
+
+export type ReferralMintEarningsFields = {
+  mint: string;
+  totalEarned: BN;
+};
+
+export class ReferralMintEarnings extends Borsh.Data<ReferralMintEarningsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ReferralMintEarnings.struct([
+      ['mint', 'pubkeyAsString'],
+      ['totalEarned', 'u64'],
+    ]),
+  ]);
+  mint: string;
+  totalEarned: BN;
+}
+
+export type VestingBucketFields = {
+  mint: string;
+  day: number;
+  amount: BN;
+};
+
+export class VestingBucket extends Borsh.Data<VestingBucketFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...VestingBucket.struct([
+      ['mint', 'pubkeyAsString'],
+      ['day', 'u32'],
+      ['amount', 'u64'],
+    ]),
+  ]);
+  mint: string;
+  day: number;
+  amount: BN;
+}
+
+export type ReferralStatsFields = {
+  accountType: AccountType;
+  referrer: string;
+  redemptionCount: BN;
+  mints: ReferralMintEarnings[];
+  flaggedAt?: BN;
+  vesting: VestingBucket[];
+};
+
+export class ReferralStats extends Borsh.Data<ReferralStatsFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...ReferralMintEarnings.SCHEMA,
+    ...VestingBucket.SCHEMA,
+    ...ReferralStats.struct([
+      ['accountType', 'u8'],
+      ['referrer', 'pubkeyAsString'],
+      ['redemptionCount', 'u64'],
+      ['mints', [ReferralMintEarnings]],
+      ['flaggedAt', { kind: 'option', type: 'u64' }],
+      ['vesting', [VestingBucket]],
+    ]),
+  ]);
+  accountType: AccountType;
+  referrer: string;
+  redemptionCount: BN;
+  mints: ReferralMintEarnings[];
+  flaggedAt?: BN;
+  vesting: VestingBucket[];
+}
+
+export type RedemptionCommitFields = {
+  accountType: AccountType;
+  cash: string;
+  wallet: string;
+  rentPayer: string;
+  commitment: Uint8Array;
+  slot: BN;
+};
+
+export class RedemptionCommit extends Borsh.Data<RedemptionCommitFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...RedemptionCommit.struct([
+      ['accountType', 'u8'],
+      ['cash', 'pubkeyAsString'],
+      ['wallet', 'pubkeyAsString'],
+      ['rentPayer', 'pubkeyAsString'],
+      ['commitment', [32]],
+      ['slot', 'u64'],
+    ]),
+  ]);
+  accountType: AccountType;
+  cash: string;
+  wallet: string;
+  rentPayer: string;
+  commitment: Uint8Array;
+  slot: BN;
+}
+
+export type PendingRedemptionFields = {
+  accountType: AccountType;
+  cash: string;
+  wallet: string;
+  rentPayer: string;
+  amount: BN;
+  requestedAt: BN;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  priorityFeeReimbursement?: BN;
+};
+
+export class PendingRedemption extends Borsh.Data<PendingRedemptionFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...PendingRedemption.struct([
+      ['accountType', 'u8'],
+      ['cash', 'pubkeyAsString'],
+      ['wallet', 'pubkeyAsString'],
+      ['rentPayer', 'pubkeyAsString'],
+      ['amount', 'u64'],
+      ['requestedAt', 'u64'],
+      ['referrerFeeBps', { kind: 'option', type: 'u16' }],
+      ['refereeFeeBps', { kind: 'option', type: 'u16' }],
+      ['priorityFeeReimbursement', { kind: 'option', type: 'u64' }],
+    ]),
+  ]);
+  accountType: AccountType;
+  cash: string;
+  wallet: string;
+  rentPayer: string;
+  amount: BN;
+  requestedAt: BN;
+  referrerFeeBps?: number;
+  refereeFeeBps?: number;
+  priorityFeeReimbursement?: BN;
+}
+
+export type RedemptionIndexFields = {
+  header: AuxiliaryHeader;
+  wallet: string;
+  amount: BN;
+};
+
+export class RedemptionIndex extends Borsh.Data<RedemptionIndexFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AuxiliaryHeader.SCHEMA,
+    ...RedemptionIndex.struct([
+      ['header', AuxiliaryHeader],
+      ['wallet', 'pubkeyAsString'],
+      ['amount', 'u64'],
+    ]),
+  ]);
+  header: AuxiliaryHeader;
+  wallet: string;
+  amount: BN;
+}
+
+export type RedemptionReceiptFields = {
+  header: AuxiliaryHeader;
+  authority: string;
+  voucherHash?: Uint8Array;
+  wallet: string;
+  amount: BN;
+  redeemedAt: BN;
+  clawedBack: BN;
+};
+
+export class RedemptionReceipt extends Borsh.Data<RedemptionReceiptFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AuxiliaryHeader.SCHEMA,
+    ...RedemptionReceipt.struct([
+      ['header', AuxiliaryHeader],
+      ['authority', 'pubkeyAsString'],
+      ['voucherHash', { kind: 'option', type: [32] }],
+      ['wallet', 'pubkeyAsString'],
+      ['amount', 'u64'],
+      ['redeemedAt', 'u64'],
+      ['clawedBack', 'u64'],
+    ]),
+  ]);
+  header: AuxiliaryHeader;
+  authority: string;
+  voucherHash?: Uint8Array;
+  wallet: string;
+  amount: BN;
+  redeemedAt: BN;
+  clawedBack: BN;
+}
+
+export type FeeWalletEntryFields = {
+  epoch: number;
+  wallet: string;
+  superseded: boolean;
+};
+
+export class FeeWalletEntry extends Borsh.Data<FeeWalletEntryFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FeeWalletEntry.struct([
+      ['epoch', 'u16'],
+      ['wallet', 'pubkeyAsString'],
+      ['superseded', 'u8'],
+    ]),
+  ]);
+  epoch: number;
+  wallet: string;
+  superseded: boolean;
+}
+
+export type FeeWalletRegistryFields = {
+  accountType: AccountType;
+  entries: FeeWalletEntry[];
+};
+
+export class FeeWalletRegistry extends Borsh.Data<FeeWalletRegistryFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...FeeWalletEntry.SCHEMA,
+    ...FeeWalletRegistry.struct([
+      ['accountType', 'u8'],
+      ['entries', [FeeWalletEntry]],
+    ]),
+  ]);
+  accountType: AccountType;
+  entries: FeeWalletEntry[];
+}
+
+export type AutoCancelEntryFields = {
+  cash: string;
+  dueAt: BN;
+};
+
+export class AutoCancelEntry extends Borsh.Data<AutoCancelEntryFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AutoCancelEntry.struct([
+      ['cash', 'pubkeyAsString'],
+      ['dueAt', 'u64'],
+    ]),
+  ]);
+  cash: string;
+  dueAt: BN;
+}
+
+export type AutoCancelQueueFields = {
+  accountType: AccountType;
+  head: number;
+  len: number;
+  entries: AutoCancelEntry[];
+};
+
+export class AutoCancelQueue extends Borsh.Data<AutoCancelQueueFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...AutoCancelEntry.SCHEMA,
+    ...AutoCancelQueue.struct([
+      ['accountType', 'u8'],
+      ['head', 'u16'],
+      ['len', 'u16'],
+      ['entries', [AutoCancelEntry]],
+    ]),
+  ]);
+  accountType: AccountType;
+  head: number;
+  len: number;
+  entries: AutoCancelEntry[];
+}
+
+export type WalletLimitFields = {
+  accountType: AccountType;
+  wallet: string;
+  mint: string;
+  rentPayer: string;
+  windowStart: BN;
+  amount: BN;
+};
+
+export class WalletLimit extends Borsh.Data<WalletLimitFields> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...WalletLimit.struct([
+      ['accountType', 'u8'],
+      ['wallet', 'pubkeyAsString'],
+      ['mint', 'pubkeyAsString'],
+      ['rentPayer', 'pubkeyAsString'],
+      ['windowStart', 'u64'],
+      ['amount', 'u64'],
+    ]),
+  ]);
+  accountType: AccountType;
+  wallet: string;
+  mint: string;
+  rentPayer: string;
+  windowStart: BN;
+  amount: BN;
+}
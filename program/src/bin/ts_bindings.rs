@@ -0,0 +1,26 @@
+//! Write the TypeScript bindings to `bindings/`, `--check` only compares them with the
+//! checked-in files and fails when they drifted from the Rust types
+use std::{fs, path::Path, process};
+
+fn main() {
+    let check = std::env::args().any(|arg| arg == "--check");
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("bindings");
+    let mut drifted = false;
+    for (name, contents) in cash::codegen::generate() {
+        let path = dir.join(&name);
+        if check {
+            if fs::read_to_string(&path).ok().as_deref() != Some(contents.as_str()) {
+                eprintln!("{} is out of date", path.display());
+                drifted = true;
+            }
+            continue;
+        }
+        fs::create_dir_all(&dir).expect("create bindings dir");
+        fs::write(&path, contents).expect("write bindings");
+        println!("wrote {}", path.display());
+    }
+    if drifted {
+        eprintln!("run `cargo run --features codegen --bin ts-bindings` to regenerate them");
+        process::exit(1);
+    }
+}
@@ -0,0 +1,365 @@
+//! Off chain helpers for building instructions
+//!
+//! Amounts on chain are raw token units, users type ui amounts in the mint decimals. The
+//! conversion is done on strings so it neither depends on the locale nor loses precision to
+//! floats: a ui amount is digits with at most one `.` and at most `decimals` fractional digits.
+//!
+//! A redemption reads optional accounts depending on the configuration of the link,
+//! `plan_redeem_accounts` derives them from a fetched `Cash` so integrators do not have to.
+//!
+//! The economic parameters of a link can be checked against its `params_hash` without
+//! trusting any api: `canonical_cash_params` is the serialization the hash is computed over.
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    keccak,
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::{
+    error::CashError,
+    find_buffer_program_address, find_fee_wallet_registry_program_address,
+    find_mint_registry_program_address, find_pending_redemption_address, find_redemption_address,
+    find_redemption_by_index_address, find_redemption_commit_address, find_referral_stats_address,
+    find_stats_program_address, find_wallet_limit_address,
+    instruction::{
+        redeem_cash, CashInstruction, InitCashArgs, InitCashRedemptionArgs, SwapRedemptionAccounts,
+    },
+    state::cash::{Cash, EntropySource},
+    utils::{is_native_mint, MEMO_PROGRAM_ID},
+};
+
+/// The borsh encoding of the `CashParams` of a link, keccak of it is its `params_hash`
+pub fn canonical_cash_params(cash: &Cash) -> Vec<u8> {
+    borsh::to_vec(&cash.params()).unwrap_or_default()
+}
+
+/// Whether the economic parameters of a fetched link match its stored hash, links created
+/// before the hash was stored can not be verified
+pub fn verify_cash_params(cash: &Cash) -> bool {
+    cash.params_hash == Some(keccak::hashv(&[&canonical_cash_params(cash)]).to_bytes())
+}
+
+/// Parse a ui amount into raw token units, e.g. `"1.5"` with 6 decimals is `1_500_000`
+pub fn ui_amount_to_raw(ui: &str, decimals: u8) -> Result<u64, CashError> {
+    let (whole, fraction) = match ui.split_once('.') {
+        Some((whole, fraction)) if !fraction.is_empty() => (whole, fraction),
+        Some(_) => return Err(CashError::InvalidUiAmount),
+        None => (ui, ""),
+    };
+    if whole.is_empty()
+        || fraction.len() > decimals as usize
+        || !whole.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit())
+    {
+        return Err(CashError::InvalidUiAmount);
+    }
+    whole
+        .bytes()
+        .chain(fraction.bytes())
+        .chain(std::iter::repeat(b'0').take(decimals as usize - fraction.len()))
+        .try_fold(0u64, |raw, digit| {
+            raw.checked_mul(10)?.checked_add((digit - b'0') as u64)
+        })
+        .ok_or(CashError::Overflow)
+}
+
+/// Format raw token units as a ui amount, the fraction is trimmed of trailing zeros and
+/// omitted when whole, e.g. `1_500_000` with 6 decimals is `"1.5"`
+pub fn raw_to_ui_amount(raw: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", raw, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+impl InitCashArgs {
+    /// Set the total amount of the link from a ui amount in the mint decimals
+    pub fn with_ui_amount(mut self, ui: &str, decimals: u8) -> Result<Self, CashError> {
+        self.amount = ui_amount_to_raw(ui, decimals)?;
+        Ok(self)
+    }
+
+    /// Set the minimum random payout of the link from a ui amount in the mint decimals
+    pub fn with_ui_min_amount(mut self, ui: &str, decimals: u8) -> Result<Self, CashError> {
+        self.min_amount = Some(ui_amount_to_raw(ui, decimals)?);
+        Ok(self)
+    }
+
+    /// Set the kyc threshold of the link from a ui amount in the mint decimals
+    pub fn with_ui_kyc_required_above(mut self, ui: &str, decimals: u8) -> Result<Self, CashError> {
+        self.kyc_required_above = Some(ui_amount_to_raw(ui, decimals)?);
+        Ok(self)
+    }
+}
+
+/// The accounts of a redemption that a `Cash` does not record
+#[derive(Debug, Clone, Default)]
+pub struct RedeemOptions {
+    pub authority: Pubkey,
+    pub fee_payer: Pubkey,
+    /// The wallet signs, required when the link asks for its consent and no permit is passed
+    pub wallet_is_signer: bool,
+    /// The platform fee wallet of a link recording none, or the wallet the fee wallet
+    /// registry resolves for the epoch of the link
+    pub platform_wallet: Option<Pubkey>,
+    /// Required when the args carry referral fee bps and the link charges a platform fee
+    pub referrer: Option<Pubkey>,
+    /// The pool accounts of a swapping link
+    pub swap: Option<SwapRedemptionAccounts>,
+    /// The kyc attestation of the wallet, for a payout above the kyc threshold of the link, see
+    /// `find_kyc_attestation_address`
+    pub kyc_attestation: Option<Pubkey>,
+    /// The recipient token account requires memos
+    pub memo: bool,
+    pub stats: bool,
+    /// The config vests referrer fees, its `referral_vesting_delay` is set
+    pub referral_vesting: bool,
+    /// The mint registry sets a daily cap per wallet for the mint of the link
+    pub wallet_limit: bool,
+    /// Token account of the wallet receiving the saved share, required with `save_bps`
+    pub savings_token: Option<Pubkey>,
+    /// Redeems of the same link placed before this one in the transaction, each advances the
+    /// counter the redemption index pda is derived from
+    pub redemptions_ahead: u16,
+}
+
+/// The accounts of a redemption of a given link, in the order the processor reads them
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedeemAccountPlan {
+    pub accounts: Vec<AccountMeta>,
+    /// Accounts the redemption reads, they must exist before it lands
+    pub existing: Vec<Pubkey>,
+    /// Accounts the redemption creates when they do not exist yet
+    pub created: Vec<Pubkey>,
+}
+
+impl RedeemAccountPlan {
+    /// The `Redeem` instruction of the planned accounts
+    pub fn instruction(&self, program_id: &Pubkey, args: InitCashRedemptionArgs) -> Instruction {
+        Instruction::new_with_borsh(
+            *program_id,
+            &CashInstruction::Redeem(args),
+            self.accounts.clone(),
+        )
+    }
+}
+
+/// Derive every account a redemption of the link at `cash_key` by `wallet` reads, from the
+/// flags of the link and the args. The accounts are ordered by `redeem_cash`, the builder
+/// the processor is kept in line with
+pub fn plan_redeem_accounts(
+    program_id: &Pubkey,
+    cash_key: &Pubkey,
+    cash: &Cash,
+    wallet: &Pubkey,
+    args: &InitCashRedemptionArgs,
+    options: &RedeemOptions,
+) -> Result<RedeemAccountPlan, CashError> {
+    let token_program = &cash.token_program;
+    let platform_wallet = options
+        .platform_wallet
+        .or(cash.fee_wallet)
+        .ok_or(CashError::InvalidPlatformWallet)?;
+    if cash.swap.is_some() != options.swap.is_some() {
+        return Err(CashError::InvalidSwapAccounts);
+    }
+    // the processor only reads the referral accounts when there is a fee to split
+    let referrer = if args.referrer_fee_bps.is_some() && cash.platform_fee_per_redeem()? > 0 {
+        Some(options.referrer.ok_or(CashError::InvalidReferralFees)?)
+    } else {
+        None
+    };
+    let recipient_mint = cash.swap.map_or(cash.mint, |swap| swap.target_mint);
+    let wallet_token =
+        get_associated_token_address_with_program_id(wallet, &recipient_mint, token_program);
+    let platform_fee_token =
+        get_associated_token_address_with_program_id(&platform_wallet, &cash.mint, token_program);
+    let owner_token =
+        get_associated_token_address_with_program_id(&cash.owner, &cash.mint, token_program);
+    let fee_payer_token =
+        get_associated_token_address_with_program_id(&options.fee_payer, &cash.mint, token_program);
+    let referral_token = referrer.map(|referrer| {
+        get_associated_token_address_with_program_id(&referrer, &cash.mint, token_program)
+    });
+    let referral_stats =
+        referrer.map(|referrer| find_referral_stats_address(program_id, &referrer).0);
+    let (redemption, _) = find_redemption_address(program_id, cash_key, wallet);
+    let next_index = cash
+        .total_redemptions
+        .checked_add(1)
+        .and_then(|index| index.checked_add(options.redemptions_ahead))
+        .ok_or(CashError::Overflow)?;
+    let (redemption_index, _) = find_redemption_by_index_address(program_id, cash_key, next_index);
+    let buffer = cash
+        .buffer_hash
+        .map(|_| find_buffer_program_address(program_id, &args.cash_reference).0);
+    let vrf_account = match cash.entropy_source {
+        EntropySource::VrfAccount(vrf_account) => Some(vrf_account),
+        EntropySource::SlotHashes => None,
+    };
+    let stats = options.stats.then(|| find_stats_program_address(program_id).0);
+
+    let mut accounts = redeem_cash(
+        program_id,
+        &options.authority,
+        wallet,
+        options.wallet_is_signer,
+        &wallet_token,
+        &platform_wallet,
+        &platform_fee_token,
+        &cash.vault,
+        cash_key,
+        cash.pass_key.as_ref(),
+        &cash.owner,
+        &owner_token,
+        &options.fee_payer,
+        &fee_payer_token,
+        referrer.as_ref(),
+        referral_token.as_ref(),
+        options.savings_token.as_ref(),
+        &cash.mint,
+        &redemption,
+        &redemption_index,
+        token_program,
+        stats.as_ref(),
+        options.kyc_attestation.as_ref(),
+        options.memo.then_some(MEMO_PROGRAM_ID).as_ref(),
+        buffer.as_ref(),
+        options.swap.as_ref(),
+        vrf_account.as_ref(),
+        referral_stats.as_ref(),
+        cash.approval_threshold.is_some(),
+        cash.fee_wallet_epoch.is_some(),
+        options.referral_vesting,
+        options.wallet_limit,
+        args.clone(),
+    )
+    .accounts;
+    // found by key, a native sweep refunds it when the owner can not spend lamports
+    let is_native = is_native_mint(&cash.mint);
+    if let Some(refund_destination) = cash.refund_destination.filter(|_| is_native) {
+        if !accounts.iter().any(|meta| meta.pubkey == refund_destination) {
+            accounts.push(AccountMeta::new(refund_destination, false));
+        }
+    }
+
+    let mut existing = vec![*cash_key, cash.vault, cash.mint];
+    existing.extend(buffer);
+    existing.extend(vrf_account);
+    existing.extend(options.kyc_attestation);
+    existing.extend(options.savings_token);
+    if !is_native {
+        existing.push(owner_token);
+    }
+    if args.reveal_nonce.is_some() {
+        existing.push(find_redemption_commit_address(program_id, cash_key, wallet).0);
+    }
+    if cash.fee_wallet_epoch.is_some() {
+        existing.push(find_fee_wallet_registry_program_address(program_id).0);
+    }
+
+    let mut created = vec![fee_payer_token];
+    if !is_native {
+        created.push(wallet_token);
+        created.push(platform_fee_token);
+    }
+    created.extend(referral_token);
+    if !cash.is_single_claim() || args.create_receipt || args.voucher_hash.is_some() {
+        created.push(redemption);
+    }
+    created.push(redemption_index);
+    created.extend(referral_stats);
+    if options.referral_vesting && !is_native {
+        created.extend(referral_stats.map(|referral_stats| {
+            get_associated_token_address_with_program_id(&referral_stats, &cash.mint, token_program)
+        }));
+    }
+    if cash.approval_threshold.is_some() {
+        created.push(find_pending_redemption_address(program_id, cash_key, wallet).0);
+    }
+    if options.wallet_limit {
+        existing.push(find_mint_registry_program_address(program_id).0);
+        created.push(find_wallet_limit_address(program_id, wallet, &cash.mint).0);
+    }
+
+    Ok(RedeemAccountPlan {
+        accounts,
+        existing,
+        created,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::program_error::ProgramError;
+
+    /// `CashError` has no `PartialEq`, compare the program errors it converts into
+    fn raw(ui: &str, decimals: u8) -> Result<u64, ProgramError> {
+        ui_amount_to_raw(ui, decimals).map_err(Into::into)
+    }
+
+    #[test]
+    fn parses_ui_amounts() {
+        assert_eq!(raw("1.5", 6), Ok(1_500_000));
+        assert_eq!(raw("0.000001", 6), Ok(1));
+        assert_eq!(raw("42", 6), Ok(42_000_000));
+        assert_eq!(raw("007.10", 2), Ok(710));
+        assert_eq!(raw("0", 9), Ok(0));
+    }
+
+    #[test]
+    fn refuses_malformed_ui_amounts() {
+        let invalid = Err(CashError::InvalidUiAmount.into());
+        let malformed = [
+            "", ".", ".5", "1.", "-1", "-0.5", "+1", " 1", "1 ", "1,5", "1.2.3", "1e6", "0x10",
+        ];
+        for ui in malformed.iter() {
+            assert_eq!(raw(ui, 6), invalid, "{:?}", ui);
+        }
+        // more fractional digits than the mint has
+        assert_eq!(raw("1.0000001", 6), invalid);
+        assert_eq!(raw("1.5", 0), invalid);
+    }
+
+    #[test]
+    fn refuses_ui_amounts_past_u64() {
+        let overflow = Err(CashError::Overflow.into());
+        assert_eq!(raw("18446744073709551615", 0), Ok(u64::MAX));
+        assert_eq!(raw("18446744073709551616", 0), overflow);
+        assert_eq!(raw("18446744073709.551615", 6), Ok(u64::MAX));
+        assert_eq!(raw("18446744073709.551616", 6), overflow);
+        assert_eq!(raw("18.446744073709551615", 18), Ok(u64::MAX));
+        assert_eq!(raw("19", 18), overflow);
+        assert_eq!(raw("1", 20), overflow);
+        assert_eq!(raw("0", 20), Ok(0));
+    }
+
+    #[test]
+    fn formats_raw_amounts() {
+        assert_eq!(raw_to_ui_amount(1_500_000, 6), "1.5");
+        assert_eq!(raw_to_ui_amount(1, 6), "0.000001");
+        assert_eq!(raw_to_ui_amount(42_000_000, 6), "42");
+        assert_eq!(raw_to_ui_amount(0, 6), "0");
+        assert_eq!(raw_to_ui_amount(0, 0), "0");
+        assert_eq!(raw_to_ui_amount(u64::MAX, 0), "18446744073709551615");
+        assert_eq!(raw_to_ui_amount(u64::MAX, 18), "18.446744073709551615");
+        assert_eq!(raw_to_ui_amount(1, 18), "0.000000000000000001");
+    }
+
+    #[test]
+    fn round_trips_raw_amounts() {
+        let amounts = [0, 1, 9, 10, 999_999, 1_000_000, 123_456_789, u64::MAX - 1, u64::MAX];
+        for decimals in [0, 1, 6, 9, 18, 19].iter() {
+            for amount in amounts.iter() {
+                let ui = raw_to_ui_amount(*amount, *decimals);
+                assert_eq!(raw(&ui, *decimals), Ok(*amount), "{} with {} decimals", ui, decimals);
+            }
+        }
+    }
+}
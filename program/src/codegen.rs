@@ -0,0 +1,482 @@
+//! TypeScript bindings of the borsh types of the program
+//!
+//! A type is exported by deriving `BorshSchema` under the `codegen` feature, the bindings are
+//! generated from those schemas so options, vectors and enum discriminants are laid out exactly
+//! as borsh encodes them. Structs and data enums become the mpl-core `Borsh.Data` classes the
+//! client already uses, fieldless enums become TypeScript enums encoded as `u8`, and every
+//! variant of `CashInstruction` becomes a class holding the instruction index followed by the
+//! fields of its args.
+//!
+//! `cargo run --features codegen --bin ts-bindings` writes the bindings to `bindings/`, with
+//! `--check` it fails when the checked-in bindings drifted from the Rust types.
+use std::collections::{BTreeMap, BTreeSet};
+
+use borsh::{
+    schema::{BorshSchemaContainer, Declaration, Definition, Fields},
+    BorshSchema,
+};
+
+use crate::{
+    instruction::CashInstruction,
+    state::{
+        auto_cancel::AutoCancelQueue,
+        buffer::Buffer,
+        cash::Cash,
+        commit::RedemptionCommit,
+        config::Config,
+        fee_wallet::FeeWalletRegistry,
+        metadata::CashMetadata,
+        mint_registry::MintRegistry,
+        pending::PendingRedemption,
+        redemption::{RedemptionIndex, RedemptionReceipt},
+        referral::ReferralStats,
+        stats::Stats,
+        wallet_limit::WalletLimit,
+    },
+};
+
+/// The enum whose variants are flattened into instruction classes
+const INSTRUCTION_ENUM: &str = "CashInstruction";
+
+const HEADER: &str = "// Generated from the borsh schemas of the program by\n\
+// `cargo run --features codegen --bin ts-bindings`, do not edit\n";
+
+/// A generated file and the types it exports, a type reached from several files is emitted
+/// by the first one and imported by the others
+struct BindingsFile {
+    name: &'static str,
+    roots: Vec<BorshSchemaContainer>,
+}
+
+fn schema<T: BorshSchema>() -> BorshSchemaContainer {
+    BorshSchemaContainer::for_type::<T>()
+}
+
+fn bindings_files() -> Vec<BindingsFile> {
+    vec![
+        BindingsFile {
+            name: "state",
+            roots: vec![
+                schema::<Cash>(),
+                schema::<Config>(),
+                schema::<MintRegistry>(),
+                schema::<Stats>(),
+                schema::<Buffer>(),
+                schema::<CashMetadata>(),
+                schema::<ReferralStats>(),
+                schema::<RedemptionCommit>(),
+                schema::<PendingRedemption>(),
+                schema::<RedemptionIndex>(),
+                schema::<RedemptionReceipt>(),
+                schema::<FeeWalletRegistry>(),
+                schema::<AutoCancelQueue>(),
+                schema::<WalletLimit>(),
+            ],
+        },
+        BindingsFile {
+            name: "instruction",
+            roots: vec![schema::<CashInstruction>()],
+        },
+    ]
+}
+
+/// Generate every bindings file, returns the file names and their contents
+pub fn generate() -> Vec<(String, String)> {
+    let mut owners = BTreeMap::new();
+    bindings_files()
+        .into_iter()
+        .map(|file| {
+            let mut generator = FileGenerator {
+                name: file.name,
+                definitions: BTreeMap::new(),
+                owners: &mut owners,
+                imports: BTreeMap::new(),
+                body: String::new(),
+            };
+            for root in &file.roots {
+                for (declaration, definition) in root.definitions() {
+                    generator
+                        .definitions
+                        .insert(declaration.clone(), definition.clone());
+                }
+            }
+            for root in &file.roots {
+                generator.emit(root.declaration());
+            }
+            (format!("{}.ts", file.name), generator.finish())
+        })
+        .collect()
+}
+
+/// How a declaration is encoded
+enum Kind<'a> {
+    /// A number, encoded by borsh-js under the same name
+    Number(&'a str),
+    Bool,
+    String,
+    Pubkey,
+    Option(&'a Declaration),
+    /// A fixed size array
+    Array(&'a Declaration, u64),
+    Vec(&'a Declaration),
+    /// A fieldless enum, encoded as its `u8` discriminant
+    FieldlessEnum,
+    /// A struct or a data enum, both become a class
+    Class,
+}
+
+struct FileGenerator<'a> {
+    name: &'static str,
+    definitions: BTreeMap<Declaration, Definition>,
+    /// The file that emitted each type so far
+    owners: &'a mut BTreeMap<Declaration, &'static str>,
+    /// Types of earlier files used by this one, by file
+    imports: BTreeMap<&'static str, BTreeSet<String>>,
+    body: String,
+}
+
+impl<'a> FileGenerator<'a> {
+    fn finish(self) -> String {
+        let mut out = String::from(HEADER);
+        out.push_str("import { Borsh } from '@metaplex-foundation/mpl-core';\n");
+        out.push_str("import BN from 'bn.js';\n");
+        for (file, names) in &self.imports {
+            let names = names.iter().cloned().collect::<Vec<_>>().join(", ");
+            out.push_str(&format!("import {{ {} }} from './{}';\n", names, file));
+        }
+        out.push_str(&self.body);
+        out
+    }
+
+    fn definition(&self, declaration: &str) -> &Definition {
+        self.definitions
+            .get(declaration)
+            .unwrap_or_else(|| panic!("no borsh schema for {}", declaration))
+    }
+
+    fn kind<'d>(&'d self, declaration: &'d Declaration) -> Kind<'d> {
+        match declaration.as_str() {
+            "u8" | "u16" | "u32" | "u64" | "u128" => return Kind::Number(declaration),
+            "bool" => return Kind::Bool,
+            "String" => return Kind::String,
+            "Pubkey" => return Kind::Pubkey,
+            _ => {}
+        }
+        match self.definition(declaration) {
+            Definition::Enum { variants, .. } if declaration.starts_with("Option<") => {
+                Kind::Option(&variants[1].2)
+            }
+            Definition::Sequence {
+                length_width: 0,
+                length_range,
+                elements,
+            } => Kind::Array(elements, *length_range.end()),
+            Definition::Sequence {
+                length_width: 4,
+                elements,
+                ..
+            } => Kind::Vec(elements),
+            Definition::Enum { variants, .. }
+                if variants
+                    .iter()
+                    .all(|(_, _, variant)| self.is_empty_struct(variant)) =>
+            {
+                Kind::FieldlessEnum
+            }
+            Definition::Enum { .. } | Definition::Struct { .. } => Kind::Class,
+            definition => panic!("{} is not supported: {:?}", declaration, definition),
+        }
+    }
+
+    fn is_empty_struct(&self, declaration: &str) -> bool {
+        matches!(
+            self.definition(declaration),
+            Definition::Struct {
+                fields: Fields::Empty
+            }
+        )
+    }
+
+    /// The borsh-js schema of a field
+    fn field_schema(&self, declaration: &Declaration) -> String {
+        match self.kind(declaration) {
+            Kind::Number(name) => format!("'{}'", name),
+            Kind::Bool | Kind::FieldlessEnum => "'u8'".to_string(),
+            Kind::String => "'string'".to_string(),
+            Kind::Pubkey => "'pubkeyAsString'".to_string(),
+            Kind::Option(inner) => {
+                format!("{{ kind: 'option', type: {} }}", self.field_schema(inner))
+            }
+            Kind::Array(inner, len) if inner == "u8" => format!("[{}]", len),
+            Kind::Array(inner, len) => panic!("[{}; {}] is not supported", inner, len),
+            Kind::Vec(inner) => format!("[{}]", self.field_schema(inner)),
+            Kind::Class => class_name(declaration),
+        }
+    }
+
+    /// The TypeScript type of a field, an option is an optional field of its inner type
+    fn field_type(&self, declaration: &Declaration) -> String {
+        match self.kind(declaration) {
+            Kind::Number("u64") | Kind::Number("u128") => "BN".to_string(),
+            Kind::Number(_) => "number".to_string(),
+            Kind::Bool => "boolean".to_string(),
+            Kind::String | Kind::Pubkey => "string".to_string(),
+            Kind::Option(inner) => self.field_type(inner),
+            Kind::Array(_, _) => "Uint8Array".to_string(),
+            Kind::Vec(inner) if inner == "u8" => "Uint8Array".to_string(),
+            Kind::Vec(inner) => format!("{}[]", self.field_type(inner)),
+            Kind::FieldlessEnum | Kind::Class => class_name(declaration),
+        }
+    }
+
+    /// The types a declaration refers to that need their own definition
+    fn dependencies(&self, declaration: &Declaration) -> Vec<Declaration> {
+        match self.kind(declaration) {
+            Kind::Option(inner) | Kind::Array(inner, _) | Kind::Vec(inner) => {
+                self.dependencies(inner)
+            }
+            Kind::FieldlessEnum | Kind::Class => vec![declaration.clone()],
+            _ => vec![],
+        }
+    }
+
+    /// The fields of a struct, unnamed fields are named `value`, `value1`, ...
+    fn fields(&self, declaration: &str) -> Vec<(String, Declaration)> {
+        match self.definition(declaration) {
+            Definition::Struct {
+                fields: Fields::NamedFields(fields),
+            } => fields.clone(),
+            Definition::Struct {
+                fields: Fields::UnnamedFields(fields),
+            } => fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| match index {
+                    0 => ("value".to_string(), field.clone()),
+                    _ => (format!("value{}", index), field.clone()),
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Emit a type once, after the types it depends on
+    fn emit(&mut self, declaration: &Declaration) {
+        if let Some(owner) = self.owners.get(declaration) {
+            if *owner != self.name {
+                self.imports
+                    .entry(owner)
+                    .or_default()
+                    .insert(class_name(declaration));
+            }
+            return;
+        }
+        self.owners.insert(declaration.clone(), self.name);
+        let definition = self.definition(declaration).clone();
+        match (self.kind(declaration), definition) {
+            (Kind::FieldlessEnum, Definition::Enum { variants, .. }) => {
+                self.emit_fieldless_enum(declaration, &variants)
+            }
+            (Kind::Class, Definition::Enum { variants, .. }) if declaration == INSTRUCTION_ENUM => {
+                self.emit_instructions(&variants)
+            }
+            (Kind::Class, Definition::Enum { variants, .. }) => {
+                self.emit_data_enum(declaration, &variants)
+            }
+            (Kind::Class, Definition::Struct { .. }) => {
+                let fields = self.fields(declaration);
+                self.emit_struct(&class_name(declaration), &fields, None)
+            }
+            _ => {}
+        }
+    }
+
+    fn emit_fields_dependencies(&mut self, fields: &[(String, Declaration)]) {
+        for (_, field) in fields {
+            for dependency in self.dependencies(field) {
+                self.emit(&dependency);
+            }
+        }
+    }
+
+    fn emit_fieldless_enum(&mut self, declaration: &str, variants: &[(i64, String, Declaration)]) {
+        let mut out = format!("\nexport enum {} {{\n", class_name(declaration));
+        for (discriminant, name, _) in variants {
+            out.push_str(&format!("  {} = {},\n", name, discriminant));
+        }
+        out.push_str("}\n");
+        self.body.push_str(&out);
+    }
+
+    /// A class of named fields, prefixed by `instruction` when it is an instruction
+    fn emit_struct(
+        &mut self,
+        name: &str,
+        fields: &[(String, Declaration)],
+        instruction: Option<i64>,
+    ) {
+        self.emit_fields_dependencies(fields);
+        let mut out = format!("\nexport type {}Fields = {{", name);
+        if !fields.is_empty() {
+            out.push('\n');
+        }
+        for (field, declaration) in fields {
+            out.push_str(&self.field_declaration(field, declaration));
+        }
+        out.push_str("};\n");
+
+        out.push_str(&format!(
+            "\nexport class {} extends Borsh.Data<{}Fields> {{\n",
+            name, name
+        ));
+        out.push_str("  static readonly SCHEMA = new Map<any, any>([\n");
+        for dependency in self.class_dependencies(fields) {
+            out.push_str(&format!("    ...{}.SCHEMA,\n", dependency));
+        }
+        out.push_str(&format!("    ...{}.struct([", name));
+        if instruction.is_some() || !fields.is_empty() {
+            out.push('\n');
+        }
+        if instruction.is_some() {
+            out.push_str("      ['instruction', 'u8'],\n");
+        }
+        for (field, declaration) in fields {
+            out.push_str(&format!(
+                "      ['{}', {}],\n",
+                camel_case(field),
+                self.field_schema(declaration)
+            ));
+        }
+        if instruction.is_some() || !fields.is_empty() {
+            out.push_str("    ");
+        }
+        out.push_str("]),\n  ]);\n");
+        if let Some(index) = instruction {
+            out.push_str(&format!("\n  instruction = {};\n", index));
+        }
+        for (field, declaration) in fields {
+            out.push_str(&self.field_declaration(field, declaration));
+        }
+        out.push_str("}\n");
+        self.body.push_str(&out);
+    }
+
+    fn emit_data_enum(&mut self, declaration: &str, variants: &[(i64, String, Declaration)]) {
+        let name = class_name(declaration);
+        for (_, variant, variant_declaration) in variants {
+            let fields = self.fields(variant_declaration);
+            self.owners.insert(variant_declaration.clone(), self.name);
+            self.emit_struct(&format!("{}{}", name, variant), &fields, None);
+        }
+        let tags = variants
+            .iter()
+            .map(|(_, variant, _)| format!("'{}'", camel_case(variant)))
+            .collect::<Vec<_>>();
+        let mut out = format!("\nexport type {}Fields =\n", name);
+        for (_, variant, _) in variants {
+            out.push_str(&format!(
+                "  | {{ enum: '{}'; {}: {}{} }}\n",
+                camel_case(variant),
+                camel_case(variant),
+                name,
+                variant
+            ));
+        }
+        out.pop();
+        out.push_str(";\n");
+        out.push_str(&format!(
+            "\nexport class {} extends Borsh.Data<{}Fields> {{\n",
+            name, name
+        ));
+        out.push_str("  static readonly SCHEMA = new Map<any, any>([\n");
+        for (_, variant, _) in variants {
+            out.push_str(&format!("    ...{}{}.SCHEMA,\n", name, variant));
+        }
+        out.push_str(&format!(
+            "    [\n      {},\n      {{\n        kind: 'enum',\n        field: 'enum',\n        values: [\n",
+            name
+        ));
+        for (_, variant, _) in variants {
+            out.push_str(&format!(
+                "          ['{}', {}{}],\n",
+                camel_case(variant),
+                name,
+                variant
+            ));
+        }
+        out.push_str("        ],\n      },\n    ],\n  ]);\n");
+        out.push_str(&format!("  enum: {};\n", tags.join(" | ")));
+        for (_, variant, _) in variants {
+            out.push_str(&format!(
+                "  {}?: {}{};\n",
+                camel_case(variant),
+                name,
+                variant
+            ));
+        }
+        out.push_str("}\n");
+        self.body.push_str(&out);
+    }
+
+    /// One class per instruction, the instruction index followed by the fields of its args
+    fn emit_instructions(&mut self, variants: &[(i64, String, Declaration)]) {
+        for (index, variant, variant_declaration) in variants {
+            let fields = match self.fields(variant_declaration).as_slice() {
+                [] => vec![],
+                [(_, args)] => {
+                    self.emit(args);
+                    self.fields(args)
+                }
+                _ => panic!("{} takes more than one args struct", variant),
+            };
+            self.emit_struct(&format!("{}Instruction", variant), &fields, Some(*index));
+        }
+    }
+
+    fn field_declaration(&self, field: &str, declaration: &Declaration) -> String {
+        let optional = matches!(self.kind(declaration), Kind::Option(_));
+        format!(
+            "  {}{}: {};\n",
+            camel_case(field),
+            if optional { "?" } else { "" },
+            self.field_type(declaration)
+        )
+    }
+
+    /// The classes whose schemas a class of these fields embeds
+    fn class_dependencies(&self, fields: &[(String, Declaration)]) -> Vec<String> {
+        let mut classes = Vec::new();
+        for (_, field) in fields {
+            for dependency in self.dependencies(field) {
+                let class = class_name(&dependency);
+                if matches!(self.kind(&dependency), Kind::Class) && !classes.contains(&class) {
+                    classes.push(class);
+                }
+            }
+        }
+        classes
+    }
+}
+
+/// `Enum__Variant` schemas of enum variants become `EnumVariant`
+fn class_name(declaration: &str) -> String {
+    declaration.replace("__", "")
+}
+
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for (index, c) in name.chars().enumerate() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else if index == 0 {
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
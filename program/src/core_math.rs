@@ -0,0 +1,501 @@
+//! Payout and fee math of a redemption, free of any solana dependency
+//!
+//! The processors plan a redemption through `compute_redemption` and the mobile sdk calls the
+//! same function through the `ffi` feature, so an offline preview is exactly what the program
+//! pays. Everything here is plain integer math over `repr(C)` values: the link state is
+//! flattened into a `RedemptionInput` by the caller and optional values are a flag plus a value.
+//! It only reaches for `core`, so together with `rounding` it builds under `no_std`.
+use crate::rounding::{distribute_with_remainder, floor_div, floor_share};
+
+/// Errors of the core math, each maps onto the `CashError` of the same name
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoreError {
+    Overflow = 1,
+    Underflow,
+    MaxRedemptionsReached,
+    NoRemainingAmount,
+    WeightNotProvided,
+    InvalidWeight,
+    TotalWeightExceeded,
+    InsufficientSettlementFunds,
+    InvalidReferralFees,
+    InvalidDistribution,
+}
+
+/// Distribution of a link, the discriminants are those of `DistributionType`
+pub const DISTRIBUTION_FIXED: u8 = 0;
+pub const DISTRIBUTION_RANDOM: u8 = 1;
+pub const DISTRIBUTION_WEIGHTED: u8 = 2;
+pub const DISTRIBUTION_EQUAL: u8 = 3;
+
+pub const BPS_DENOMINATOR: u64 = 10000;
+const PPM_DENOMINATOR: u64 = 1_000_000;
+
+/// The link state and redeem arguments a redemption depends on
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct RedemptionInput {
+    pub distribution: u8,
+    pub amount: u64,
+    pub remaining_amount: u64,
+    pub max_num_redemptions: u16,
+    pub total_redemptions: u16,
+    /// Redemptions plus expired slots
+    pub slots_used: u16,
+    pub total_weight_ppm: u32,
+    pub min_amount: u64,
+    /// Smallest random payout the mint allows
+    pub min_payout: u64,
+    pub has_weight: bool,
+    pub weight_ppm: u32,
+    /// Draw of a random redemption, ignored by the other distributions
+    pub entropy: u64,
+    pub vault_amount: u64,
+    pub fee_bps: u16,
+    pub base_fee_to_redeem: u64,
+    pub rent_fee_to_redeem: u64,
+    pub requested_priority_fee: u64,
+    pub max_priority_fee_per_redeem: u64,
+    pub is_native: bool,
+    pub recipient_token_exists: bool,
+    /// Referral bps, already clamped to the ceilings of the link
+    pub has_referrer: bool,
+    pub referrer_fee_bps: u16,
+    pub referee_fee_bps: u16,
+    pub burn_bps: u16,
+    /// Payout fixed when an approved redemption was requested, the fees are computed as usual
+    pub has_locked_payout: bool,
+    pub locked_payout: u64,
+}
+
+/// Every amount a redemption moves and the counters it leaves on the link
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct RedemptionOutput {
+    pub payout: u64,
+    pub burn_amount: u64,
+    pub platform_fee_per_redeem: u64,
+    pub platform_fee: u64,
+    pub referrer_fee: u64,
+    pub referee_fee: u64,
+    pub network_fee: u64,
+    pub priority_fee: u64,
+    pub rent_fee_waived: bool,
+    /// Fees the vault could not cover on the final slot, taken off the fees instead of failing
+    pub fee_shortfall: u64,
+    pub vault_debit: u64,
+    pub unclaimed_weight_refund: u64,
+    pub remaining_amount: u64,
+    pub total_redemptions: u16,
+    pub total_weight_ppm: u32,
+}
+
+/// `floor(amount * fee_bps / 10000)`, the platform fee of a link at init
+pub fn platform_fee(amount: u64, fee_bps: u16) -> Result<u64, CoreError> {
+    floor_share(amount, fee_bps as u64, BPS_DENOMINATOR)
+}
+
+/// The platform fee floored over the redemptions of a link
+pub fn platform_fee_per_redeem(
+    amount: u64,
+    fee_bps: u16,
+    max_num_redemptions: u16,
+) -> Result<u64, CoreError> {
+    floor_div(platform_fee(amount, fee_bps)?, max_num_redemptions as u64)
+}
+
+/// Share of the amount for a weight expressed in parts per million, rounded down
+pub fn weighted_share(amount: u64, weight_ppm: u32) -> Result<u64, CoreError> {
+    floor_share(amount, weight_ppm as u64, PPM_DENOMINATOR)
+}
+
+/// Compute every amount of a redemption
+pub fn compute_redemption(input: &RedemptionInput) -> Result<RedemptionOutput, CoreError> {
+    if input.slots_used >= input.max_num_redemptions {
+        return Err(CoreError::MaxRedemptionsReached);
+    }
+    if input.remaining_amount == 0 {
+        return Err(CoreError::NoRemainingAmount);
+    }
+
+    // a single claim link pays whatever remains in one go, a weighted one still pays its share
+    let single_claim =
+        input.max_num_redemptions == 1 && input.distribution != DISTRIBUTION_WEIGHTED;
+    let mut total_weight_ppm = input.total_weight_ppm;
+    let mut unclaimed_weight_refund = 0;
+    let payout = match input.distribution {
+        _ if input.has_locked_payout => input.locked_payout,
+        _ if single_claim => input.remaining_amount,
+        // the amount is a multiple of the redemptions for fixed links, there is no dust
+        DISTRIBUTION_FIXED => floor_div(input.amount, input.max_num_redemptions as u64)?,
+        DISTRIBUTION_RANDOM => random_payout(input)?,
+        DISTRIBUTION_WEIGHTED => {
+            let (payout, new_total_weight_ppm, refund) = weighted_payout(input)?;
+            total_weight_ppm = new_total_weight_ppm;
+            unclaimed_weight_refund = refund;
+            payout
+        }
+        // the equal amount per redemption, the dust is swept to the owner
+        DISTRIBUTION_EQUAL => floor_div(input.amount, input.max_num_redemptions as u64)?,
+        _ => return Err(CoreError::InvalidDistribution),
+    };
+
+    let remaining_amount = input
+        .remaining_amount
+        .checked_sub(payout)
+        .ok_or(CoreError::Overflow)?;
+    let total_redemptions = input
+        .total_redemptions
+        .checked_add(1)
+        .ok_or(CoreError::Overflow)?;
+
+    let mut platform_fee_per_redeem = if input.max_num_redemptions == 1 {
+        platform_fee(input.amount, input.fee_bps)?
+    } else {
+        platform_fee_per_redeem(input.amount, input.fee_bps, input.max_num_redemptions)?
+    };
+    let max_fee_to_redeem = input
+        .base_fee_to_redeem
+        .checked_add(input.rent_fee_to_redeem)
+        .ok_or(CoreError::Overflow)?;
+    let mut priority_fee = input
+        .requested_priority_fee
+        .min(input.max_priority_fee_per_redeem);
+
+    // the rent fee covers the recipient token account, it is kept in the vault when it already exists
+    let rent_fee_waived = !input.is_native && input.recipient_token_exists;
+    let mut network_fee = if rent_fee_waived {
+        max_fee_to_redeem
+            .checked_sub(input.rent_fee_to_redeem)
+            .ok_or(CoreError::Underflow)?
+    } else {
+        max_fee_to_redeem
+    };
+    let vault_required = checked_sum(&[
+        payout,
+        platform_fee_per_redeem,
+        max_fee_to_redeem,
+        priority_fee,
+    ])?;
+    let mut fee_shortfall = 0;
+    if input.vault_amount < vault_required {
+        let final_slot = remaining_amount == 0 || input.slots_used + 1 == input.max_num_redemptions;
+        if !final_slot {
+            return Err(CoreError::InsufficientSettlementFunds);
+        }
+        // nothing is left for a later redemption: the waived rent fee is swept anyway and the
+        // fees give way to the payout, the platform fee first and the network fees after it
+        let vault_debit =
+            checked_sum(&[payout, platform_fee_per_redeem, network_fee, priority_fee])?;
+        if input.vault_amount < payout {
+            return Err(CoreError::InsufficientSettlementFunds);
+        }
+        fee_shortfall = vault_debit.saturating_sub(input.vault_amount);
+        let mut uncovered = fee_shortfall;
+        for fee in [&mut platform_fee_per_redeem, &mut network_fee, &mut priority_fee].iter_mut() {
+            let cut = (**fee).min(uncovered);
+            **fee -= cut;
+            uncovered -= cut;
+        }
+    }
+    let vault_debit = checked_sum(&[payout, platform_fee_per_redeem, network_fee, priority_fee])?;
+
+    let (platform_fee, referrer_fee, referee_fee) = if input.has_referrer
+        && platform_fee_per_redeem > 0
+    {
+        referral_split(
+            platform_fee_per_redeem,
+            input.referrer_fee_bps,
+            input.referee_fee_bps,
+        )?
+    } else {
+        (platform_fee_per_redeem, 0, 0)
+    };
+
+    // burning is rejected at init for native mints, the burn is always a token burn
+    let burn_amount = floor_share(payout, input.burn_bps as u64, BPS_DENOMINATOR)?;
+
+    Ok(RedemptionOutput {
+        payout,
+        burn_amount,
+        platform_fee_per_redeem,
+        platform_fee,
+        referrer_fee,
+        referee_fee,
+        network_fee,
+        priority_fee,
+        rent_fee_waived,
+        fee_shortfall,
+        vault_debit,
+        unclaimed_weight_refund,
+        remaining_amount,
+        total_redemptions,
+        total_weight_ppm,
+    })
+}
+
+/// Split a platform fee between the platform, the referrer and the referee, the referral
+/// shares are floored and the dust goes to the platform
+pub fn referral_split(
+    platform_fee: u64,
+    referrer_fee_bps: u16,
+    referee_fee_bps: u16,
+) -> Result<(u64, u64, u64), CoreError> {
+    let commission_bps = referrer_fee_bps
+        .checked_add(referee_fee_bps)
+        .ok_or(CoreError::Overflow)?;
+    if commission_bps as u64 > BPS_DENOMINATOR {
+        return Err(CoreError::InvalidReferralFees);
+    }
+    let shares = distribute_with_remainder(
+        platform_fee,
+        &[
+            BPS_DENOMINATOR - commission_bps as u64,
+            referrer_fee_bps as u64,
+            referee_fee_bps as u64,
+        ],
+        BPS_DENOMINATOR,
+        0,
+    )?;
+    Ok((shares[0], shares[1], shares[2]))
+}
+
+/// Split a payout between the recipient and its savings account, the saved share is floored
+/// and the dust goes to the recipient
+pub fn savings_split(payout: u64, save_bps: u16) -> Result<(u64, u64), CoreError> {
+    let keep_bps = BPS_DENOMINATOR
+        .checked_sub(save_bps as u64)
+        .ok_or(CoreError::Underflow)?;
+    let shares = distribute_with_remainder(
+        payout,
+        &[keep_bps, save_bps as u64],
+        BPS_DENOMINATOR,
+        0,
+    )?;
+    Ok((shares[0], shares[1]))
+}
+
+/// A draw between the minimum and twice the average of the remaining redemptions, the last
+/// redemption takes whatever remains
+fn random_payout(input: &RedemptionInput) -> Result<u64, CoreError> {
+    let remaining_redemptions = input
+        .max_num_redemptions
+        .checked_sub(input.total_redemptions)
+        .filter(|remaining| *remaining > 0)
+        .ok_or(CoreError::MaxRedemptionsReached)?;
+    if remaining_redemptions <= 1 {
+        return Ok(input.remaining_amount);
+    }
+    let average_possible = floor_div(input.remaining_amount, remaining_redemptions as u64)?;
+    let max_possible = average_possible * 2;
+
+    // links created before the dust check may have a lower min amount
+    let min_possible = input
+        .min_amount
+        .max(input.min_payout)
+        .min(input.remaining_amount);
+    let max_possible = max_possible.min(input.remaining_amount);
+
+    if max_possible > min_possible {
+        let range = max_possible - min_possible + 1;
+        Ok(min_possible + (input.entropy % range))
+    } else {
+        Ok(min_possible)
+    }
+}
+
+/// The share of the weight of this redemption, returns the payout, the new total weight and
+/// what the final redemption leaves unclaimed for the owner
+fn weighted_payout(input: &RedemptionInput) -> Result<(u64, u32, u64), CoreError> {
+    if !input.has_weight {
+        return Err(CoreError::WeightNotProvided);
+    }
+    let weight_ppm = input.weight_ppm;
+    if weight_ppm == 0 || weight_ppm as u64 > PPM_DENOMINATOR {
+        return Err(CoreError::InvalidWeight);
+    }
+    let new_total_weight_ppm = input
+        .total_weight_ppm
+        .checked_add(weight_ppm)
+        .ok_or(CoreError::Overflow)?;
+    if new_total_weight_ppm as u64 > PPM_DENOMINATOR {
+        return Err(CoreError::TotalWeightExceeded);
+    }
+
+    let is_final_redemption = input
+        .total_redemptions
+        .checked_add(1)
+        .ok_or(CoreError::Overflow)?
+        == input.max_num_redemptions
+        || new_total_weight_ppm as u64 == PPM_DENOMINATOR;
+
+    let mut unclaimed_weight_refund = 0;
+    let payout = if is_final_redemption {
+        // the final redemption receives the rounding dust accumulated so far: the theoretical
+        // cumulative share minus what was already paid out
+        let paid = input
+            .amount
+            .checked_sub(input.remaining_amount)
+            .ok_or(CoreError::Underflow)?;
+        let theoretical = weighted_share(input.amount, new_total_weight_ppm)?;
+        let payout = theoretical.checked_sub(paid).ok_or(CoreError::Underflow)?;
+        unclaimed_weight_refund = input
+            .remaining_amount
+            .checked_sub(payout)
+            .ok_or(CoreError::Underflow)?;
+        payout
+    } else {
+        weighted_share(input.amount, weight_ppm)?
+    };
+    Ok((
+        payout.min(input.remaining_amount),
+        new_total_weight_ppm,
+        unclaimed_weight_refund,
+    ))
+}
+
+fn checked_sum(amounts: &[u64]) -> Result<u64, CoreError> {
+    amounts
+        .iter()
+        .try_fold(0u64, |sum, amount| sum.checked_add(*amount))
+        .ok_or(CoreError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(distribution: u8, amount: u64, max_num_redemptions: u16) -> RedemptionInput {
+        RedemptionInput {
+            distribution,
+            amount,
+            remaining_amount: amount,
+            max_num_redemptions,
+            vault_amount: u64::MAX,
+            ..RedemptionInput::default()
+        }
+    }
+
+    /// Apply a redemption to the link state the next one reads
+    fn redeem(input: &mut RedemptionInput) -> RedemptionOutput {
+        let output = compute_redemption(input).unwrap();
+        input.remaining_amount = output.remaining_amount;
+        input.total_redemptions = output.total_redemptions;
+        input.slots_used += 1;
+        input.total_weight_ppm = output.total_weight_ppm;
+        output
+    }
+
+    #[test]
+    fn a_single_claim_pays_what_remains() {
+        for distribution in [DISTRIBUTION_FIXED, DISTRIBUTION_RANDOM, DISTRIBUTION_EQUAL].iter() {
+            let mut link = input(*distribution, 1_001, 1);
+            link.remaining_amount = 997;
+            link.fee_bps = 150;
+            link.entropy = 12_345;
+            let output = compute_redemption(&link).unwrap();
+            assert_eq!(output.payout, 997);
+            assert_eq!(output.remaining_amount, 0);
+            assert_eq!(output.total_redemptions, 1);
+            // the whole platform fee of the link, not one floored over the redemptions
+            assert_eq!(output.platform_fee_per_redeem, 15);
+        }
+    }
+
+    #[test]
+    fn a_single_claim_weighted_link_still_pays_its_share() {
+        let mut link = input(DISTRIBUTION_WEIGHTED, 1_000, 1);
+        link.has_weight = true;
+        link.weight_ppm = 400_000;
+        let output = compute_redemption(&link).unwrap();
+        assert_eq!(output.payout, 400);
+        assert_eq!(output.unclaimed_weight_refund, 600);
+        assert_eq!(output.total_weight_ppm, 400_000);
+    }
+
+    #[test]
+    fn the_final_slot_takes_a_shortfall_off_the_fees() {
+        let mut link = input(DISTRIBUTION_FIXED, 1_000, 2);
+        link.remaining_amount = 500;
+        link.total_redemptions = 1;
+        link.slots_used = 1;
+        link.fee_bps = 100;
+        link.base_fee_to_redeem = 10;
+        link.rent_fee_to_redeem = 20;
+        link.recipient_token_exists = true;
+        link.requested_priority_fee = 4;
+        link.max_priority_fee_per_redeem = 4;
+        // 500 payout, 5 platform fee, 10 network fee and 4 priority fee leave 9 uncovered
+        link.vault_amount = 510;
+        let output = compute_redemption(&link).unwrap();
+        assert_eq!(output.payout, 500);
+        assert_eq!(output.fee_shortfall, 9);
+        assert_eq!(output.platform_fee_per_redeem, 0);
+        assert_eq!(output.network_fee, 6);
+        assert_eq!(output.priority_fee, 4);
+        assert_eq!(output.vault_debit, 510);
+        assert!(output.rent_fee_waived);
+    }
+
+    #[test]
+    fn only_the_final_slot_gives_way_on_the_fees() {
+        let mut link = input(DISTRIBUTION_FIXED, 1_000, 2);
+        link.fee_bps = 100;
+        link.base_fee_to_redeem = 10;
+        link.vault_amount = 505;
+        assert_eq!(
+            compute_redemption(&link),
+            Err(CoreError::InsufficientSettlementFunds)
+        );
+
+        // the final slot still never pays less than the payout
+        link.remaining_amount = 500;
+        link.total_redemptions = 1;
+        link.slots_used = 1;
+        link.vault_amount = 499;
+        assert_eq!(
+            compute_redemption(&link),
+            Err(CoreError::InsufficientSettlementFunds)
+        );
+    }
+
+    #[test]
+    fn the_final_weighted_redemption_takes_the_dust() {
+        let mut link = input(DISTRIBUTION_WEIGHTED, 10, 3);
+        link.has_weight = true;
+        let payouts: Vec<u64> = [333_333, 333_333, 333_334]
+            .iter()
+            .map(|weight_ppm| {
+                link.weight_ppm = *weight_ppm;
+                redeem(&mut link).payout
+            })
+            .collect();
+        assert_eq!(payouts, vec![3, 3, 4]);
+        assert_eq!(link.remaining_amount, 0);
+        assert_eq!(link.total_weight_ppm, 1_000_000);
+    }
+
+    #[test]
+    fn the_final_weighted_redemption_refunds_the_unclaimed_weight() {
+        let mut link = input(DISTRIBUTION_WEIGHTED, 10, 3);
+        link.has_weight = true;
+        link.weight_ppm = 333_333;
+        redeem(&mut link);
+        redeem(&mut link);
+        let output = redeem(&mut link);
+        // 999_999 ppm of 10 floors to 9, 6 was paid out so far
+        assert_eq!(output.payout, 3);
+        assert_eq!(output.unclaimed_weight_refund, 1);
+        assert_eq!(output.remaining_amount, 1);
+    }
+
+    #[test]
+    fn the_referral_split_sends_the_dust_to_the_platform() {
+        assert_eq!(referral_split(101, 1_000, 500), Ok((86, 10, 5)));
+        assert_eq!(
+            referral_split(101, 6_000, 5_000),
+            Err(CoreError::InvalidReferralFees)
+        );
+    }
+}
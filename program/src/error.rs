@@ -2,6 +2,8 @@
 use thiserror::Error;
 use solana_program::program_error::ProgramError;
 
+use crate::core_math::CoreError;
+
 #[derive(Error, Debug, Copy, Clone)]
 pub enum CashError {
     /// Invalid instruction
@@ -107,6 +109,251 @@ pub enum CashError {
     InvalidWeight,
     #[error("Total weight exceeded")]
     TotalWeightExceeded,
+    #[error("Admin is invalid")]
+    InvalidAdmin,
+    #[error("Invalid config key")]
+    InvalidConfigKey,
+    #[error("Invalid mint registry key")]
+    InvalidMintRegistryKey,
+    #[error("Mint is not allowed")]
+    MintNotAllowed,
+    #[error("Mint is already registered")]
+    MintAlreadyRegistered,
+    #[error("Mint is not registered")]
+    MintNotRegistered,
+    #[error("Mint registry is full")]
+    MintRegistryFull,
+    #[error("Fee payer is invalid")]
+    InvalidFeePayer,
+    #[error("Invalid stats key")]
+    InvalidStatsKey,
+    #[error("Insufficient funds to create the cash link")]
+    InsufficientFundsForCashLink,
+    #[error("The kyc threshold and the kyc authority must be set together")]
+    InvalidKycConfig,
+    #[error("A valid kyc attestation is required for this amount")]
+    KycRequired,
+    #[error("The kyc attestation has expired")]
+    KycExpired,
+    #[error("The recipient token account requires a memo but the memo program was not provided")]
+    RecipientRequiresMemo,
+    #[error("Referral fees were supplied but the cash link has no referral ceilings")]
+    ReferralNotConfigured,
+    #[error("Invalid buffer key")]
+    InvalidBufferKey,
+    #[error("The buffer is finalized and can not be written")]
+    BufferFinalized,
+    #[error("The buffer is not finalized")]
+    BufferNotFinalized,
+    #[error("The write is out of the buffer bounds")]
+    BufferOutOfBounds,
+    #[error("The buffer content does not match the expected hash")]
+    BufferHashMismatch,
+    #[error("Fee bps must be less than or equal to 10000")]
+    InvalidFeeBps,
+    #[error("Permissionless init is disabled")]
+    PermissionlessInitDisabled,
+    #[error("Invalid platform fee wallet")]
+    InvalidPlatformWallet,
+    #[error("Too many redemptions")]
+    TooManyRedemptions,
+    #[error("Invalid cash key")]
+    InvalidCashKey,
+    #[error("The cash link is already being processed further up the call stack")]
+    ReentrancyDetected,
+    #[error("The min amount can produce dust payouts for this mint")]
+    MinAmountTooSmallForMint,
+    #[error("The pass key can only cancel a link that has not been redeemed")]
+    PassKeyCancelNotAllowed,
+    #[error("Invalid clock sysvar")]
+    InvalidClockSysvar,
+    #[error("Invalid rent sysvar")]
+    InvalidRentSysvar,
+    #[error("The mint does not support burning from the vault")]
+    BurnNotSupportedForMint,
+    #[error("Invalid metadata key")]
+    InvalidMetadataKey,
+    #[error("The metadata uri is empty, too long or has characters that are not allowed")]
+    InvalidMetadataUri,
+    #[error("The cash link is being canceled in chunks, finish it with CancelFinalize")]
+    CancelPending,
+    #[error("The cash link is not being canceled in chunks")]
+    CancelNotPending,
+    #[error("The vault still holds tokens")]
+    VaultNotEmpty,
+    #[error("The redemption fee reserve is too large for the amount")]
+    FeeReserveTooLarge,
+    #[error("The amm program is not allowed by the config")]
+    AmmProgramNotAllowed,
+    #[error("Invalid swap config")]
+    InvalidSwapConfig,
+    #[error("Invalid swap accounts")]
+    InvalidSwapAccounts,
+    #[error("The swap returned less than the slippage bound allows")]
+    SlippageExceeded,
+    #[error("The vrf round was already consumed")]
+    StaleVrf,
+    #[error("Invalid entropy account")]
+    InvalidEntropyAccount,
+    #[error("Cash link is halted")]
+    CashHalted,
+    #[error("Cash link is not halted")]
+    CashNotHalted,
+    #[error("No recovery wallet is configured")]
+    RecoveryWalletNotSet,
+    #[error("Invalid recovery token account")]
+    InvalidRecoveryToken,
+    #[error("Invalid flash schedule")]
+    InvalidFlashSchedule,
+    #[error("The flash slot is not open yet")]
+    SlotNotYetOpen,
+    #[error("The open flash slot was already claimed")]
+    SlotAlreadyClaimed,
+    #[error("The flash slot expired")]
+    SlotExpired,
+    #[error("The flash slot has not expired")]
+    SlotNotExpired,
+    #[error("The cash link is not locked by a pass key")]
+    NotALockedLink,
+    #[error("The wallet is not the intended recipient of the cash link")]
+    NotIntendedRecipient,
+    #[error("The recovery timelock of the cash link is still running")]
+    RecoveryTimelockActive,
+    #[error("No recovery was requested for the cash link")]
+    RecoveryNotRequested,
+    #[error("Invalid referral stats key")]
+    InvalidReferralStatsKey,
+    #[error("The smallest payout of the link cannot cover its per redemption fees")]
+    PayoutCannotCoverFees,
+    #[error("The vault cannot cover the worst case redemption sequence")]
+    ReserveInsufficientForWorstCase,
+    #[error("The same token account was passed for two different wallets")]
+    DuplicateAccount,
+    #[error("The recipient permit was not verified for the wallet")]
+    InvalidRecipientPermit,
+    #[error("The recipient permit expired")]
+    RecipientPermitExpired,
+    #[error("The redemption would leave the vault short of the remaining redemptions")]
+    WouldBreakVaultInvariant,
+    #[error("Invalid ui amount")]
+    InvalidUiAmount,
+    #[error("The feature is disabled by the config")]
+    FeatureDisabled,
+    #[error("The vault token account is frozen")]
+    VaultFrozen,
+    #[error("The recipient token account is frozen")]
+    RecipientAccountFrozen,
+    #[error("The mint has a freeze authority, pass allow_freezable_mint to accept it")]
+    FreezableMintNotAllowed,
+    #[error("The commit-reveal delay is invalid for this link")]
+    InvalidCommitRevealDelay,
+    #[error("The cash link does not use commit-reveal redemptions")]
+    CommitRevealDisabled,
+    #[error("The commit can not be revealed yet")]
+    CommitTooRecent,
+    #[error("No commit of the wallet for the cash link")]
+    CommitMissing,
+    #[error("The revealed nonce does not match the commit")]
+    NonceMismatch,
+    #[error("The slot hash of the commit is no longer available")]
+    CommitExpired,
+    #[error("The commit can still be revealed")]
+    CommitNotStale,
+    #[error("The cash link was initialized with different args")]
+    InitArgsMismatch,
+    #[error("The split exceeds the unredeemed amount or redemptions of the link")]
+    SplitExceedsRemaining,
+    #[error("The split leaves a link its distribution can not pay out")]
+    SplitBreaksDistribution,
+    #[error("The program version of the link is already recorded")]
+    VersionAlreadyRecorded,
+    #[error("Invalid program version")]
+    InvalidProgramVersion,
+    #[error("The refund destination can not spend lamports and the link designates none")]
+    RefundDestinationUnspendable,
+    #[error("The wallet already has a redemption waiting for approval")]
+    PendingApprovalExists,
+    #[error("No redemption of the wallet is waiting for approval")]
+    NoPendingApproval,
+    #[error("The redemption waited for approval past the ttl of the link")]
+    ApprovalExpired,
+    #[error("Invalid approval threshold")]
+    InvalidApprovalThreshold,
+    #[error("The fee wallet epoch of the link has no live wallet in the registry")]
+    MissingFeeWalletEpoch,
+    #[error("The fee wallet registry is full")]
+    FeeWalletRegistryFull,
+    #[error("The redemption counters of the link are inconsistent")]
+    InvalidRedemptionCounters,
+    #[error("No escrowed referrer fee of the mint has vested yet")]
+    ReferralFeesNotVested,
+    #[error("Every vesting bucket of the referrer is taken by other mints")]
+    ReferralVestingFull,
+    #[error("The referrer was flagged and earns no more fees")]
+    ReferrerFlagged,
+    #[error("The referral stats still hold escrowed fees")]
+    ReferralFeesEscrowed,
+    #[error("The account data is not a recognized legacy cash layout")]
+    UnrecognizedLegacyLayout,
+    #[error("The link requires a voucher hash on every redemption")]
+    VoucherRequired,
+    #[error("The mint changed its owner program or decimals since the link was created")]
+    MintConfigurationChanged,
+    #[error("The auto-cancel time must be in the future")]
+    InvalidAutoCancelSchedule,
+    #[error("The auto-cancel queue is full")]
+    AutoCancelQueueFull,
+    #[error("The auto-cancel queue was not passed or is not initialized")]
+    MissingAutoCancelQueue,
+    #[error("The redemption would take the wallet past the daily cap of the mint")]
+    DailyLimitExceeded,
+    #[error("Every daily cap slot of the mint registry is taken")]
+    MintCapsFull,
+    #[error("The wallet limit pda was not passed or does not match the wallet and mint")]
+    InvalidWalletLimitKey,
+    #[error("The window of the wallet limit has not ended yet")]
+    WalletLimitNotStale,
+    #[error("The recipient of the redemption is a platform fee wallet")]
+    RecipientIsFeeWallet,
+    #[error("The savings token account is not owned by the redeeming wallet")]
+    SecondaryRecipientOwnerMismatch,
+    #[error("The redemption index pda does not follow the redemption counter of the link")]
+    InvalidRedemptionIndexKey,
+    #[error("The redemption was signed longer ago than the link allows")]
+    SignatureExpired,
+    #[error("The clawback window of the redemption is closed")]
+    ClawbackWindowExpired,
+    #[error("The clawback exceeds what is left of the redeemed amount")]
+    ClawbackExceedsRedeemed,
+    #[error("The clawback window of the redemption is still open")]
+    ClawbackWindowOpen,
+    #[error("Invalid clawback window")]
+    InvalidClawbackWindow,
+    #[error("The recipient token account is delegated to another account")]
+    RecipientDelegated,
+}
+
+impl From<CoreError> for CashError {
+    fn from(e: CoreError) -> Self {
+        match e {
+            CoreError::Overflow => CashError::Overflow,
+            CoreError::Underflow => CashError::Underflow,
+            CoreError::MaxRedemptionsReached => CashError::MaxRedemptionsReached,
+            CoreError::NoRemainingAmount => CashError::NoRemainingAmount,
+            CoreError::WeightNotProvided => CashError::WeightNotProvided,
+            CoreError::InvalidWeight => CashError::InvalidWeight,
+            CoreError::TotalWeightExceeded => CashError::TotalWeightExceeded,
+            CoreError::InsufficientSettlementFunds => CashError::InsufficientSettlementFunds,
+            CoreError::InvalidReferralFees => CashError::InvalidReferralFees,
+            CoreError::InvalidDistribution => CashError::AccountInvalidState,
+        }
+    }
+}
+
+impl From<CoreError> for ProgramError {
+    fn from(e: CoreError) -> Self {
+        CashError::from(e).into()
+    }
 }
 
 impl From<CashError> for ProgramError {
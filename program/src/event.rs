@@ -0,0 +1,279 @@
+//! Program events
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, program::set_return_data, pubkey::Pubkey};
+
+use crate::state::cash::{Cash, CompletionReason};
+
+/// Emitted after every successful redemption
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct RedemptionEvent {
+    pub cash: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub total_redemptions: u16,
+    pub remaining_amount: u64,
+    /// The part of a weighted link returned to the owner because the weights did not sum to 100%
+    pub unclaimed_weight_refund: u64,
+    /// Whether the rent fee was charged for creating the recipient token account
+    pub rent_fee_charged: bool,
+    /// Lamports the fee payer actually spent creating the recipient token account
+    pub recipient_token_rent: u64,
+    /// Owner of the account the payout went to
+    pub recipient_owner: Pubkey,
+    /// Program versions that created the link and that emitted the event
+    pub created_by_version: u16,
+    pub last_touched_version: u16,
+    /// The voucher hash stored in the receipt of the redemption
+    pub voucher_hash: Option<[u8; 32]>,
+}
+
+/// Emitted when a cash link reaches a terminal state
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct CompletionEvent {
+    pub cash: Pubkey,
+    pub reason: CompletionReason,
+    pub total_redemptions: u16,
+    pub remaining_amount: u64,
+    /// Unix timestamp the link reached its terminal state
+    pub timestamp: u64,
+    /// Program versions that created the link and that emitted the event
+    pub created_by_version: u16,
+    pub last_touched_version: u16,
+}
+
+impl CompletionEvent {
+    /// Emit the event and return the completion reason to the caller
+    pub fn emit(self) {
+        set_return_data(&[self.reason as u8]);
+        CashEvent::Completion(self).emit();
+    }
+}
+
+/// Emitted when a link is halted or released
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct HaltEvent {
+    pub cash: Pubkey,
+    pub authority: Pubkey,
+    pub admin: Pubkey,
+    /// Whether the link is halted after the instruction
+    pub halted: bool,
+    pub timestamp: u64,
+}
+
+/// Emitted when the vault of a link is moved to the recovery wallet
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct EmergencyWithdrawEvent {
+    pub cash: Pubkey,
+    pub authority: Pubkey,
+    pub admin: Pubkey,
+    pub recovery_token: Pubkey,
+    /// The whole vault amount, fee reserves included
+    pub amount: u64,
+    /// The unredeemed amount of the link at the time of the withdraw
+    pub remaining_amount: u64,
+    pub timestamp: u64,
+}
+
+/// Emitted when a config admin starts the recovery of an abandoned link
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct RecoveryRequestedEvent {
+    pub cash: Pubkey,
+    pub admin: Pubkey,
+    pub requested_at: u64,
+    /// The earliest unix timestamp the recovery can be executed
+    pub executable_at: u64,
+}
+
+/// Emitted when a config admin disables or re-enables features
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct FeaturesUpdatedEvent {
+    pub admin: Pubkey,
+    pub previous_disabled_features: u64,
+    pub disabled_features: u64,
+    pub timestamp: u64,
+}
+
+/// Emitted when a link is created or its economic parameters change, with the hash a third
+/// party recomputes from the account, see `Cash::compute_params_hash`
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ParamsHashEvent {
+    pub cash: Pubkey,
+    pub params_hash: [u8; 32],
+    pub timestamp: u64,
+}
+
+/// Return data of a redemption waiting for approval, distinct from every completion reason
+pub const PENDING_APPROVAL: u8 = u8::MAX;
+
+/// Emitted when a redemption at or above the approval threshold waits for approval
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ApprovalRequestedEvent {
+    pub cash: Pubkey,
+    pub wallet: Pubkey,
+    /// The payout reserved until the redemption is approved or rejected
+    pub amount: u64,
+    pub requested_at: u64,
+}
+
+impl ApprovalRequestedEvent {
+    /// Emit the event and flag the redemption as pending to the caller
+    pub fn emit(self) {
+        set_return_data(&[PENDING_APPROVAL]);
+        CashEvent::ApprovalRequested(self).emit();
+    }
+}
+
+/// Emitted when a redemption waiting for approval is rejected and its payout released
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ApprovalRejectedEvent {
+    pub cash: Pubkey,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub rejected_by: Pubkey,
+    /// Whether the request outlived the approval ttl of the link
+    pub expired: bool,
+    pub timestamp: u64,
+}
+
+/// Emitted when part of a payout is clawed back within the clawback window of the link
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct ClawbackEvent {
+    pub cash: Pubkey,
+    pub wallet: Pubkey,
+    pub authority: Pubkey,
+    pub admin: Pubkey,
+    /// The vault of a live link, the token account of the owner otherwise
+    pub destination: Pubkey,
+    pub amount: u64,
+    /// Total clawed back from the payout, this clawback included
+    pub clawed_back: u64,
+    pub timestamp: u64,
+}
+
+/// What a ledger posting pays for
+#[repr(u8)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum LegKind {
+    /// The payout of a redemption, or the funding of a link
+    Principal,
+    PlatformFee,
+    /// The referrer and referee shares of the platform fee
+    Referral,
+    NetworkFee,
+    /// The rent fee reimbursed for creating the recipient token account
+    Rent,
+    PriorityFee,
+    /// The vault leftover of a fully redeemed link
+    Sweep,
+    /// Burned out of the payout, the mint account stands for the burned supply
+    Burn,
+    /// The vault returned to the owner of a canceled link
+    Refund,
+    /// Paid to the cranker of an auto-cancel
+    Tip,
+    /// The payout swapped through the pool, in the target mint on the way out
+    Swap,
+    /// Taken back from the recipient of a payout within the clawback window
+    Clawback,
+}
+
+/// The balance change of an account in a mint, in raw units
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct Posting {
+    pub account: Pubkey,
+    pub mint: Pubkey,
+    pub delta: i128,
+    pub leg: LegKind,
+}
+
+/// The double-entry postings of the transfers an instruction performed. Every leg debits one
+/// account and credits another by the same amount, the postings of each mint sum to zero.
+/// Native links post lamport moves in the native mint between the economic parties, the
+/// unwrap through the fee payer is not a leg
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct LedgerEvent {
+    pub cash: Pubkey,
+    pub postings: Vec<Posting>,
+}
+
+impl LedgerEvent {
+    pub fn new(cash: &Pubkey) -> Self {
+        Self {
+            cash: *cash,
+            postings: Vec::new(),
+        }
+    }
+
+    /// Record a transfer of `amount` from `from` to `to`, nothing is posted for zero
+    pub fn post(&mut self, leg: LegKind, mint: &Pubkey, from: &Pubkey, to: &Pubkey, amount: u64) {
+        if amount == 0 {
+            return;
+        }
+        for (account, delta) in [(from, -(amount as i128)), (to, amount as i128)].iter() {
+            self.postings.push(Posting {
+                account: **account,
+                mint: *mint,
+                delta: *delta,
+                leg,
+            });
+        }
+    }
+
+    /// Emit the postings as a single log, a ledger without postings is not logged
+    pub fn emit(self) {
+        if !self.postings.is_empty() {
+            CashEvent::Ledger(self).emit();
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+pub enum CashEvent {
+    Redemption(RedemptionEvent),
+    Completion(CompletionEvent),
+    Halt(HaltEvent),
+    EmergencyWithdraw(EmergencyWithdrawEvent),
+    RecoveryRequested(RecoveryRequestedEvent),
+    FeaturesUpdated(FeaturesUpdatedEvent),
+    ApprovalRequested(ApprovalRequestedEvent),
+    ApprovalRejected(ApprovalRejectedEvent),
+    Ledger(LedgerEvent),
+    ParamsHash(ParamsHashEvent),
+    Clawback(ClawbackEvent),
+}
+
+impl ParamsHashEvent {
+    /// Emit the stored hash of a link, links without one emit nothing
+    pub fn emit_for(cash_key: &Pubkey, cash: &Cash, timestamp: u64) {
+        if let Some(params_hash) = cash.params_hash {
+            CashEvent::ParamsHash(ParamsHashEvent {
+                cash: *cash_key,
+                params_hash,
+                timestamp,
+            })
+            .emit();
+        }
+    }
+}
+
+impl CashEvent {
+    /// Log the borsh encoded event so indexers can pick it up from the transaction logs
+    pub fn emit(&self) {
+        if let Ok(data) = borsh::to_vec(self) {
+            sol_log_data(&[&data]);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Fee schedule shared by cash links and the config defaults
+//!
+//! A link pays a platform fee in bps of its amount, a network fee once at init, and a per
+//! redemption fee covering the base cost and the recipient token account rent of every
+//! redemption. The whole per redemption fee is reserved in the vault at init.
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{error::CashError, instruction::InitCashArgs};
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct FeeSchedule {
+    pub fee_bps: u16,
+    pub network_fee: u64,
+    pub base_fee_to_redeem: u64,
+    pub rent_fee_to_redeem: u64,
+}
+
+impl FeeSchedule {
+    pub fn from_args(args: &InitCashArgs) -> Self {
+        Self {
+            fee_bps: args.fee_bps,
+            network_fee: args.network_fee,
+            base_fee_to_redeem: args.base_fee_to_redeem,
+            rent_fee_to_redeem: args.rent_fee_to_redeem,
+        }
+    }
+
+    /// Replace the fee args of an init, e.g. with the config defaults of a permissionless link
+    pub fn apply(&self, args: &mut InitCashArgs) {
+        args.fee_bps = self.fee_bps;
+        args.network_fee = self.network_fee;
+        args.base_fee_to_redeem = self.base_fee_to_redeem;
+        args.rent_fee_to_redeem = self.rent_fee_to_redeem;
+    }
+}
+
+/// The platform fee and the burn together may take at most the whole amount
+pub fn validate_schedule(schedule: &FeeSchedule, burn_bps: u16) -> Result<(), CashError> {
+    if schedule.fee_bps as u32 + burn_bps as u32 > 10000 {
+        return Err(CashError::InvalidFeeBps);
+    }
+    Ok(())
+}
+
+/// The most a single redemption reimburses the fee payer
+pub fn compute_per_action_fee(schedule: &FeeSchedule) -> Result<u64, CashError> {
+    schedule
+        .base_fee_to_redeem
+        .checked_add(schedule.rent_fee_to_redeem)
+        .ok_or(CashError::Overflow)
+}
+
+/// The priority fee reimbursements reserved in the vault at init, every redemption at the cap
+pub fn compute_priority_reserve(max_priority_fee: u64, actions: u16) -> Result<u64, CashError> {
+    max_priority_fee
+        .checked_mul(actions as u64)
+        .ok_or(CashError::Overflow)
+}
+
+/// The per redemption fees reserved in the vault at init for `actions` redemptions
+pub fn compute_total_reserve(schedule: &FeeSchedule, actions: u16) -> Result<u64, CashError> {
+    compute_per_action_fee(schedule)?
+        .checked_mul(actions as u64)
+        .ok_or(CashError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_program::program_error::ProgramError;
+
+    /// `CashError` has no `PartialEq`, compare the program errors it converts into
+    fn checked<T>(result: Result<T, CashError>) -> Result<T, ProgramError> {
+        result.map_err(Into::into)
+    }
+
+    fn schedule(fee_bps: u16) -> FeeSchedule {
+        FeeSchedule {
+            fee_bps,
+            network_fee: 5_000,
+            base_fee_to_redeem: 5_000,
+            rent_fee_to_redeem: 2_039_280,
+        }
+    }
+
+    #[test]
+    fn the_fee_and_the_burn_take_at_most_the_whole_amount() {
+        let invalid = Err(CashError::InvalidFeeBps.into());
+        assert_eq!(checked(validate_schedule(&schedule(0), 0)), Ok(()));
+        assert_eq!(checked(validate_schedule(&schedule(2_500), 7_500)), Ok(()));
+        assert_eq!(checked(validate_schedule(&schedule(10_000), 0)), Ok(()));
+        assert_eq!(checked(validate_schedule(&schedule(2_500), 7_501)), invalid);
+        assert_eq!(checked(validate_schedule(&schedule(10_001), 0)), invalid);
+        // the sum is taken wide, two maximal bps do not wrap around
+        assert_eq!(checked(validate_schedule(&schedule(u16::MAX), u16::MAX)), invalid);
+    }
+
+    #[test]
+    fn reserves_the_fees_of_every_redemption() {
+        assert_eq!(checked(compute_per_action_fee(&schedule(0))), Ok(2_044_280));
+        assert_eq!(checked(compute_total_reserve(&schedule(0), 0)), Ok(0));
+        assert_eq!(checked(compute_total_reserve(&schedule(0), 1)), Ok(2_044_280));
+        assert_eq!(checked(compute_total_reserve(&schedule(0), 3)), Ok(6_132_840));
+        assert_eq!(checked(compute_priority_reserve(1_000, 3)), Ok(3_000));
+    }
+
+    #[test]
+    fn refuses_a_reserve_that_overflows() {
+        let overflow = Err(CashError::Overflow.into());
+        let fees = FeeSchedule {
+            base_fee_to_redeem: u64::MAX,
+            rent_fee_to_redeem: 1,
+            ..FeeSchedule::default()
+        };
+        assert_eq!(checked(compute_total_reserve(&fees, 1)), overflow);
+        let fees = FeeSchedule {
+            base_fee_to_redeem: u64::MAX / 2 + 1,
+            ..FeeSchedule::default()
+        };
+        assert_eq!(checked(compute_total_reserve(&fees, 1)), Ok(u64::MAX / 2 + 1));
+        assert_eq!(checked(compute_total_reserve(&fees, 2)), overflow);
+        assert_eq!(checked(compute_priority_reserve(u64::MAX, 2)), overflow);
+    }
+}
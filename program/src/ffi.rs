@@ -0,0 +1,33 @@
+//! C-ABI exports of the core math, built with the `ffi` feature
+//!
+//! The types are `repr(C)` so cbindgen generates the matching header. A call returns 0 on
+//! success and the `CoreError` discriminant otherwise, the output is only written on success.
+use crate::core_math::{compute_redemption, RedemptionInput, RedemptionOutput};
+
+/// Status of a call that succeeded
+pub const CASH_FFI_OK: u32 = 0;
+/// Status of a call given a null pointer
+pub const CASH_FFI_NULL_POINTER: u32 = u32::MAX;
+
+/// Compute a redemption exactly as the program plans it
+///
+/// # Safety
+///
+/// `input` must point to a valid `RedemptionInput` and `output` to writable memory for a
+/// `RedemptionOutput`, either may be null
+#[no_mangle]
+pub unsafe extern "C" fn cash_compute_redemption(
+    input: *const RedemptionInput,
+    output: *mut RedemptionOutput,
+) -> u32 {
+    if input.is_null() || output.is_null() {
+        return CASH_FFI_NULL_POINTER;
+    }
+    match compute_redemption(&*input) {
+        Ok(result) => {
+            *output = result;
+            CASH_FFI_OK
+        }
+        Err(error) => error as u32,
+    }
+}
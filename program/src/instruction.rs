@@ -9,13 +9,26 @@ use solana_program::{
 };
 use spl_associated_token_account::get_associated_token_address_with_program_id;
 
-use crate::state::cash::DistributionType;
+use crate::{
+    find_auto_cancel_queue_program_address, find_config_program_address,
+    find_fee_wallet_registry_program_address, find_mint_registry_program_address,
+    find_pending_redemption_address, find_program_data_address, find_redemption_address,
+    find_redemption_commit_address, find_referral_stats_address, find_wallet_limit_address,
+    state::{
+        cash::{DistributionType, EntropySource, FlashSchedule, SwapConfig},
+        mint_registry::MintMode,
+    },
+    utils::is_native_mint,
+};
 
 /// Initialize a cash arguments
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
 /// Initialize a cash params
 pub struct InitCashArgs {
+    /// The total pot shared by all redemptions, a Fixed link pays amount / max_num_redemptions
+    /// per claim so the amount must be a multiple of the redemptions
     pub amount: u64,
     pub fee_bps: u16,
     pub network_fee: u64,
@@ -27,11 +40,90 @@ pub struct InitCashArgs {
     pub min_amount: Option<u64>,
     pub cash_reference: String,
     pub is_locked: bool,
+    pub kyc_required_above: Option<u64>,
+    pub kyc_authority: Option<Pubkey>,
+    /// Opt in to clamping the redeem time referral bps to the ceilings below
+    pub referral_ceilings_enabled: bool,
+    pub max_referrer_fee_bps: u16,
+    pub max_referee_fee_bps: u16,
+    /// Share of every payout burned, fee_bps plus burn_bps can not exceed 10000
+    pub burn_bps: u16,
+    /// Display metadata uri stored in the metadata pda of the link, at most 96 bytes
+    pub metadata_uri: Option<String>,
+    /// Require the redeeming wallet to sign, the authority can no longer claim on its behalf
+    pub claim_requires_wallet_signature: bool,
+    /// Swap every payout to another mint, the amm program must be allow listed in the config
+    pub swap: Option<SwapConfig>,
+    /// Where the random draws of a Random link come from
+    pub entropy_source: EntropySource,
+    /// Open one redemption slot per interval, Fixed and Equal links only
+    pub flash: Option<FlashSchedule>,
+    /// Bind the link to a single wallet, which must sign its only redemption
+    pub intended_recipient: Option<Pubkey>,
+    /// Most priority fees reimbursed per redemption on top of the static network fees, reserved
+    /// in the vault at init. Zero disables priority fee reimbursement
+    pub max_priority_fee_per_redeem: u64,
+    /// Accept a mint with a freeze authority, its holder can freeze the vault and lock the link
+    pub allow_freezable_mint: bool,
+    /// Require wallets of a Random link drawing from slot hashes to commit this many slots
+    /// before they redeem, at most `MAX_COMMIT_REVEAL_DELAY`
+    pub commit_reveal_delay: Option<u64>,
+    /// Receives the lamport refunds of the link in place of an owner that can not spend them,
+    /// e.g. a pda owned by another program
+    pub refund_destination: Option<Pubkey>,
+    /// Hold payouts at or above this amount until the authority and the owner approve them,
+    /// not available to Weighted and flash links
+    pub approval_threshold: Option<u64>,
+    /// Seconds a redemption may wait for approval, `None` waits forever
+    pub approval_ttl: Option<u64>,
+    /// Refuse redemptions without a voucher hash, see `InitCashRedemptionArgs::voucher_hash`
+    pub require_voucher: bool,
+    /// Queue the link for the auto-cancel crank from this unix timestamp, the
+    /// `AutoCancelQueue` has to be passed
+    pub auto_cancel_at: Option<u64>,
+    /// Tip the crank takes from the fee reserve of the link when it cancels it
+    pub auto_cancel_tip: u64,
+    /// Slots a signed redemption stays valid, see `InitCashRedemptionArgs::signed_at_slot`
+    pub max_signature_age_slots: Option<u64>,
+    /// Seconds during which a payout can be clawed back, at most `MAX_CLAWBACK_WINDOW`. Every
+    /// redemption is then signed by its wallet and approves the link as delegate of the payout,
+    /// a recipient token account delegated to another account is refused rather than overwritten.
+    /// Not available to native, swap and approval threshold links
+    pub clawback_window_seconds: Option<u64>,
+}
+
+/// Update metadata arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct UpdateMetadataArgs {
+    pub uri: String,
+}
+
+/// Maximum number of entries checked by a single `ValidateRedemptions`
+pub const MAX_VALIDATE_REDEMPTIONS: usize = 16;
+
+/// A planned redemption to validate
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct RedemptionEntry {
+    pub cash_reference: String,
+    pub wallet: Pubkey,
+}
+
+/// Validate redemptions arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct ValidateRedemptionsArgs {
+    pub entries: Vec<RedemptionEntry>,
 }
 
 /// Initialize a redemption arguments
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
 /// Initialize a cash params
 pub struct InitCashRedemptionArgs {
     pub cash_bump: u8,
@@ -41,19 +133,274 @@ pub struct InitCashRedemptionArgs {
     pub weight_ppm: Option<u32>,
     pub rate_usd: Option<String>,
     pub redemption_bump: u8,
+    pub redemption_index_bump: u8,
+    /// Priority fees the fee payer attached, reimbursed up to the cap of the link
+    pub priority_fee_reimbursement: Option<u64>,
+    /// A claim the wallet signed off chain, accepted in place of the wallet signature
+    pub recipient_permit: Option<RecipientPermit>,
+    /// The nonce committed to by the wallet, required by commit-reveal links
+    pub reveal_nonce: Option<[u8; 32]>,
+    /// Create the redemption receipt of a single claim link, other links always create it
+    pub create_receipt: bool,
+    /// Hash of the off chain voucher authorizing the payout, its preimage stays off chain.
+    /// Stored in the receipt with the authority, which is then always created
+    pub voucher_hash: Option<[u8; 32]>,
+    /// Share of the payout in bps sent to a savings token account of the wallet, passed right
+    /// after the referral accounts. Token links without a swap only
+    pub save_bps: Option<u16>,
+    /// Slot the authority signed the redemption at, required by links with a
+    /// `max_signature_age_slots`
+    pub signed_at_slot: Option<u64>,
+}
+
+/// A recipient signature over the permit message of a claim, verified by an Ed25519 program
+/// instruction earlier in the transaction
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct RecipientPermit {
+    pub signature: [u8; 64],
+    /// Unix timestamp the permit stops being accepted
+    pub expiry: u64,
 }
 
 /// Cancel a cash link
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
 /// Cancel a cash params
 pub struct CancelCashRedemptionArgs {
     pub cash_bump: u8,
     pub cash_reference: String,
 }
 
+/// Cancel begin arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct CancelBeginArgs {
+    pub cash_bump: u8,
+    pub cash_reference: String,
+    /// Most tokens refunded to the owner by this call
+    pub max_amount: u64,
+}
+
+/// Initialize the config arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+/// Initialize the config params
+pub struct InitConfigArgs {
+    pub config_bump: u8,
+}
+
+/// Update the config arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct UpdateConfigArgs {
+    pub permissionless_init: bool,
+    pub fee_wallet: Pubkey,
+    pub fee_bps: u16,
+    pub network_fee: u64,
+    pub base_fee_to_redeem: u64,
+    pub rent_fee_to_redeem: u64,
+    pub max_dust_decimals: u8,
+    pub max_redemptions: Option<u16>,
+    pub max_fee_reserve_bps: Option<u16>,
+    pub swap_amm_program: Option<Pubkey>,
+    pub recovery_wallet: Option<Pubkey>,
+    pub recovery_timelock: Option<u64>,
+    pub referral_vesting_delay: Option<u64>,
+}
+
+/// Commit redemption arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct CommitRedemptionArgs {
+    /// `keccak(wallet || nonce)`, see `redemption_commitment`
+    pub commitment: [u8; 32],
+    pub commit_bump: u8,
+}
+
+/// Split cash arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct SplitCashArgs {
+    pub cash_bump: u8,
+    pub cash_reference: String,
+    pub child_bump: u8,
+    pub child_reference: String,
+    /// Principal moved to the child, its fee reserves follow
+    pub amount: u64,
+    /// Redemptions moved to the child
+    pub max_num_redemptions: u16,
+}
+
+/// Maximum number of links grown by a single `MigrateBatch`
+pub const MAX_MIGRATE_BATCH: u8 = 12;
+
+/// Migrate batch arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct MigrateBatchArgs {
+    /// Number of cash accounts following the fixed accounts, at most `MAX_MIGRATE_BATCH`
+    pub count: u8,
+}
+
+/// Maximum number of links canceled by a single `ProcessAutoCancelQueue`
+pub const MAX_AUTO_CANCEL_BATCH: u8 = 5;
+
+/// Process auto-cancel queue arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct ProcessAutoCancelQueueArgs {
+    /// The seeds of each link of the batch, in the order of their accounts
+    pub links: Vec<CancelCashRedemptionArgs>,
+}
+
+/// Clawback redemption arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct ClawbackRedemptionArgs {
+    pub cash_bump: u8,
+    pub cash_reference: String,
+    /// Taken back from the recipient token account, at most what the receipt has left
+    pub amount: u64,
+}
+
+/// Backfill version arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct BackfillVersionArgs {
+    /// `PROGRAM_VERSION` of the release that created the link
+    pub created_by_version: u16,
+}
+
+/// Set disabled features arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct SetDisabledFeaturesArgs {
+    /// Bits of the `Feature`s to disable, every other feature is enabled
+    pub disabled_features: u64,
+}
+
+/// Rotate pass key arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct RotatePassKeyArgs {
+    /// Rotate without the signature of the current pass key, for a lost kiosk
+    pub force: bool,
+}
+
+/// Mint registry arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+/// Mint registry params
+pub struct MintRegistryArgs {
+    pub registry_bump: u8,
+}
+
+/// Set mint mode arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+/// Set mint mode params
+pub struct SetMintModeArgs {
+    pub registry_bump: u8,
+    pub mode: MintMode,
+}
+
+/// Set mint cap arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+/// Set mint cap params
+pub struct SetMintCapArgs {
+    pub registry_bump: u8,
+    /// Most a wallet may redeem of the mint per window in raw units, none uncaps the mint
+    pub daily_cap: Option<u64>,
+}
+
+/// Fee wallet registry arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct FeeWalletRegistryArgs {
+    pub registry_bump: u8,
+}
+
+/// Supersede a fee wallet arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct SupersedeFeeWalletArgs {
+    /// The epoch whose links pay their fees to the latest live wallet from now on
+    pub epoch: u16,
+}
+
+/// Referral fees of a mint arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct ReferralFeesArgs {
+    /// The mint the escrowed fees are in
+    pub mint: Pubkey,
+}
+
+/// Initialize the stats arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+/// Initialize the stats params
+pub struct InitStatsArgs {
+    pub stats_bump: u8,
+}
+
+/// Create a buffer arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct CreateBufferArgs {
+    pub buffer_bump: u8,
+    pub cash_reference: String,
+    pub size: u32,
+}
+
+/// Write a buffer chunk arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct WriteBufferChunkArgs {
+    pub offset: u32,
+    pub data: Vec<u8>,
+}
+
+/// Finalize a buffer arguments
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct FinalizeBufferArgs {
+    pub cash_bump: u8,
+    pub cash_reference: String,
+}
+
+/// The instructions of the program, every variant is routed by the processor and has a builder
+///
+/// The first byte of the instruction data is the position of the variant, deployed clients
+/// depend on it: new variants are appended and no variant is ever removed or reordered.
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone,)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
 pub enum CashInstruction {
 
     /// Starts the trade by creating and populating an cash account and transferring ownership of the given temp token account to the PDA
@@ -74,41 +421,101 @@ pub enum CashInstruction {
     /// 10. `[]` The system program
     /// 11. `[]` The token program
     /// 12. `[]` The associated token program
+    /// 13. `[][optional]` The mint registry pda, required for a permissionless init whether or
+    ///     not the registry was created
+    /// 14. `[writable][optional]` The stats pda
+    /// 15. `[][optional]` The config pda, required for a permissionless init where the owner is the authority,
+    ///     its dust decimals bound the min amount of random links
+    /// 16. `[writable][optional]` The metadata pda, required when a metadata uri is set
+    /// 17. `[][optional]` The fee wallet registry pda, a permissionless link stores the epoch of
+    ///     its latest wallet instead of the fee wallet of the config
     InitCash (InitCashArgs),
     /// Redeem the cash
     ///
     ///
     /// Accounts expected:
     ///
-    /// 0. `[signer]` The account of the authority
-    /// 1. `[]` The user wallet
+    /// The leading accounts always come in this order, whichever features the link uses:
+    ///
+    /// 0. `[signer]` The account of the authority, only ever a signer so it may be a PDA signing through a CPI
+    /// 1. `[writable]` The user wallet, a signer when the link requires the wallet signature and
+    ///     no recipient permit is passed, always on links with a clawback window
     /// 2. `[writable]` The platform fee wallet for the token they will receive should the trade go through
     /// 3. `[writable]` The platform fee token account for the token they will receive should the trade go through
     /// 4. `[writable]` The cash account holding the cash info
-    /// 5. `[writable][optional]` The pass key account required to sign this transaction
-    /// 6. `[writable]` The owner wallet that created the cash vault
-    /// 7. `[writable]` The owner token account belonging to the owner wallet that created the cash vault
-    /// 8. `[writable]` The payer token account of the payer that initialized the cash  
-    /// 9. `[writable]` The fee payer wallet that pays network and rent fees
-    /// 10. `[writable]` The fee payer's associated token account that collects the rent or network fees
-    /// 11. `[writable]` The vault token account to get tokens. This value is Optional. if the mint is set, then this must be set.
-    /// 12. `[writable]` The recipient token account for the token they will receive belonging to the user wallet
-    /// 13. `[]` The mint account for the token
-    /// 14. `[writable]` The redemption account pda
+    /// 5. `[writable]` The owner wallet that created the cash vault
+    /// 6. `[writable]` The owner token account belonging to the owner wallet that created the cash vault
+    /// 7. `[writable][signer]` The fee payer wallet that pays network and rent fees
+    /// 8. `[writable]` The fee payer's associated token account that collects the rent or network fees
+    /// 9. `[writable]` The vault token account of the cash link
+    /// 10. `[writable]` The recipient token account for the token they will receive belonging to the user wallet,
+    ///     in the target mint when the link swaps its payouts
+    /// 11. `[writable]` The mint account for the token, burned from when the link burns a share of each payout
+    /// 12. `[writable]` The redemption account pda, only created for a single claim link when
+    ///     `create_receipt` is set
+    /// 13. `[writable]` The redemption index pda, derived from the cash account and the redemption number
+    /// 14. `[]` The clock sysvar
     /// 15. `[]` The rent account
     /// 16. `[]` The recent slot hash account
     /// 17. `[]` The token program
     /// 18. `[]` The system program
-    /// 19. `[writable][Optional]` The referrer wallet account
-    /// 20. `[writable][Optional]` The referrer token account
-    /// 21. `[]` The associated program
+    /// 19. `[]` The associated token program
+    ///
+    /// They are followed, in this order, by the accounts the link and the args call for:
+    ///
+    /// - `[signer]` The pass key, when the link is locked
+    /// - `[writable]` The referrer wallet and `[writable]` the referrer token account, when
+    ///   referral fee bps are passed
+    /// - `[writable]` The savings token account of the wallet, when `save_bps` is passed
+    ///
+    /// The remaining optional accounts are found by key, their position does not matter:
+    ///
+    /// - `[writable]` The stats pda
+    /// - `[]` The kyc attestation of the wallet, required when the amount is above the kyc
+    ///   threshold, see `find_kyc_attestation_address`
+    /// - `[]` The memo program, required when the recipient token account requires memos
+    /// - `[]` The finalized buffer holding the weights schedule of the cash link
+    /// - `[]` The config pda, required, its disabled features gate the redemption and its dust
+    ///   decimals bound random payouts
+    /// - `[]` The amm program of a swapping link, followed by `[writable]` the pool, `[writable]`
+    ///   the pool source reserve, `[writable]` the pool target reserve and `[]` the target mint
+    /// - `[]` The vrf account of a link drawing from a vrf
+    /// - `[writable]` The referral stats pda of the referrer, created on its first referrer fee
+    /// - `[]` The instructions sysvar, required with a recipient permit
+    /// - `[writable]` The commit pda of the wallet and `[writable]` its rent payer, required
+    ///   by commit-reveal links
+    /// - `[writable]` The refund destination of the link, required by a native link sweeping its
+    ///   vault to an owner owned by another program
+    /// - `[writable]` The pending redemption pda of the wallet, required by links with an
+    ///   approval threshold
+    /// - `[]` The fee wallet registry pda, required by links storing a fee wallet epoch, the
+    ///   platform fee wallet is then the wallet of that epoch or the latest one once superseded
+    /// - `[]` The mint registry pda, its daily cap of the link mint then limits the wallet
+    /// - `[writable]` The wallet limit pda of the wallet and mint, required when the mint is
+    ///   capped, created on the first capped redemption of the wallet
+    ///
+    /// A key passed twice, e.g. a referrer that is also the recipient, must carry the same
+    /// privileges in both positions
+    ///
+    /// A payout at or above the approval threshold of the link is not paid: it is reserved,
+    /// the pending redemption pda is created and the return data is `PENDING_APPROVAL`
+    ///
+    /// Several redeems of the same link may share a transaction. Each one reads the link as
+    /// the previous one left it, so they behave exactly like separate transactions in order:
+    ///
+    /// - the redemption index pda of each follows the counter after the redeems ahead of it,
+    ///   a stale one fails with `InvalidRedemptionIndexKey` before any transfer
+    /// - a wallet already paid by an earlier redeem fails on its receipt
+    /// - once a redeem completes the link and closes its vault, the following ones fail with
+    ///   `AccountAlreadyRedeemed` before the vault is read
     Redeem(InitCashRedemptionArgs),
     /// Cancel the cash
     ///
     ///
     /// Accounts expected:
     ///
-    /// 0. `[signer]` The account of the authority
+    /// 0. `[signer]` The account of the authority, only ever a signer so it may be a PDA signing through a CPI.
+    ///    The pass key of a locked link may sign instead while the link has not been redeemed
     /// 1. `[writable]` The cash account holding the cash info   
     /// 2. `[writable]` The owner wallet
     /// 3. `[writable]` The owner associated token account of the owner if it's not a native mint
@@ -116,172 +523,1898 @@ pub enum CashInstruction {
     /// 5. `[writable]` The vault token account to get tokens from and eventually close. This value is Optional. if the mint is set, then this must be set.
     /// 6. `[]` The token program
     /// 7. `[]` The system program   
+    /// 8. `[writable][optional]` The stats pda
+    /// 9. `[writable][optional]` The refund destination of the link, receives the lamport
+    ///    refunds when the owner is owned by another program
     Cancel(CancelCashRedemptionArgs),
     /// Close the cash
     ///
     ///
     /// Accounts expected:
     ///
-    /// 0. `[signer]` The account of the authority
+    /// 0. `[signer]` The account of the authority, only ever a signer so it may be a PDA signing through a CPI
     /// 1. `[writable]` The cash account holding the cash info     
     /// 2. `[writable]` The destination account to send their rent fees to
+    /// 3. `[writable]` The fee payer that funded the cash account, reimbursed before the destination
+    /// 4. `[]` The system program
+    /// 5. `[writable][optional]` The buffer of the cash link, closed with it
+    /// 6. `[writable][optional]` The metadata pda of the cash link, closed with it
+    /// 7. `[writable][optional]` The rent payers recorded by the buffer and the metadata, each gets
+    ///    back the rent of the accounts it funded
+    /// 8. `[writable][optional]` The refund destination of the link, receives the rent when the
+    ///    destination is owned by another program
     Close,
-}
+    /// Initialize the program config
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The admin account allowed to manage the program config
+    /// 1. `[signer][writable]` The fee payer
+    /// 2. `[writable]` The config pda
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The system program
+    /// 5. `[]` The program data account of the program, the admin must be its upgrade authority
+    InitConfig(InitConfigArgs),
+    /// Add a mint to the mint registry, creating the registry if needed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[signer][writable]` The fee payer
+    /// 3. `[writable]` The mint registry pda
+    /// 4. `[]` The mint to add
+    /// 5. `[]` The rent sysvar
+    /// 6. `[]` The system program
+    AddMint(MintRegistryArgs),
+    /// Remove a mint from the mint registry
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The mint registry pda
+    /// 3. `[]` The mint to remove
+    RemoveMint,
+    /// Set the mint registry mode, creating the registry if needed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[signer][writable]` The fee payer
+    /// 3. `[writable]` The mint registry pda
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The system program
+    SetMintMode(SetMintModeArgs),
+    /// Initialize the program statistics account
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[signer][writable]` The fee payer
+    /// 3. `[writable]` The stats pda
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The system program
+    /// 6. `[][optional]` The mint registry pda, its first mints get a per mint breakdown
+    InitStats(InitStatsArgs),
 
-/// Create `InitCash` instruction
-pub fn init_cash(
-    program_id: &Pubkey,
-    authority: &Pubkey,
-    owner: &Pubkey,
-    fee_payer: &Pubkey,
-    fee_payer_token: &Pubkey,
-    cash_link_pda: &Pubkey,
-    pass_key: &Pubkey,
-    mint: &Pubkey,
-    token_program_id: &Pubkey,
-    args: InitCashArgs,
-) -> Instruction {
-    let accounts = vec![
-        AccountMeta::new_readonly(*authority, true),
-        AccountMeta::new_readonly(*owner, true),
-        AccountMeta::new(*fee_payer, true),
-        AccountMeta::new(*fee_payer_token, false),
-        AccountMeta::new(*cash_link_pda, false),
-        AccountMeta::new_readonly(*pass_key, false),
-        AccountMeta::new_readonly(*mint, false),
-        AccountMeta::new_readonly(sysvar::rent::id(), false),
-        AccountMeta::new_readonly(system_program::id(), false),
-        AccountMeta::new_readonly(sysvar::clock::id(), false),   
-        AccountMeta::new_readonly(*token_program_id, false),
-        AccountMeta::new(get_associated_token_address_with_program_id(cash_link_pda, &mint, token_program_id), false),
-        AccountMeta::new(get_associated_token_address_with_program_id(owner, &mint, token_program_id), false),     
-        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
-    ];
+    /// Create the buffer of a cash link, sized up front
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The buffer authority, the authority of the cash link
+    /// 1. `[signer][writable]` The fee payer
+    /// 2. `[writable]` The buffer pda
+    /// 3. `[]` The rent sysvar
+    /// 4. `[]` The system program
+    CreateBuffer(CreateBufferArgs),
 
-    Instruction::new_with_borsh(
-        *program_id,
-        &CashInstruction::InitCash(args),
-        accounts,
-    )
-}
+    /// Write a chunk of bytes into a buffer
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The buffer authority
+    /// 1. `[writable]` The buffer pda
+    WriteBufferChunk(WriteBufferChunkArgs),
 
-/// Create `CancelCash` instruction
-pub fn cancel_cash(
-    program_id: &Pubkey,
-    authority: &Pubkey,
-    cash: &Pubkey,
-    owner: &Pubkey,
-    owner_token: &Pubkey,
-    vault_token: &Pubkey,
-    mint: &Pubkey,
-    fee_payer: &Pubkey,
-    token_program_id: &Pubkey,
-    args: CancelCashRedemptionArgs,
-) -> Instruction {
-    let accounts = vec![
-        AccountMeta::new_readonly(*authority, true),
-        AccountMeta::new(*cash, false),
-        AccountMeta::new(*owner, false),
-        AccountMeta::new(*owner_token, false),
-        AccountMeta::new(*fee_payer, false),
-        AccountMeta::new(*vault_token, false),
-        AccountMeta::new_readonly(*mint, false),
-        AccountMeta::new_readonly(sysvar::clock::id(), false),
-        AccountMeta::new_readonly(*token_program_id, false),
-        AccountMeta::new_readonly(system_program::id(), false),
-    ];
+    /// Hash the buffer content and bind it to its cash link, the buffer is read only afterwards
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The buffer authority
+    /// 1. `[writable]` The buffer pda
+    /// 2. `[writable]` The cash link pda
+    FinalizeBuffer(FinalizeBufferArgs),
 
-    Instruction::new_with_borsh(
-        *program_id,
-        &CashInstruction::Cancel(args),
-        accounts,
-    )
-}
+    /// Update the program config, including the permissionless init settings
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[writable]` The config pda
+    UpdateConfig(UpdateConfigArgs),
 
-/// Create `RedeemCash` instruction
-pub fn redeem_cash(
-    program_id: &Pubkey,
-    authority: &Pubkey,
-    wallet: &Pubkey,
-    wallet_token: &Pubkey,
-    platform_wallet: &Pubkey,
-    platform_fee_token: &Pubkey,
-    vault_token: &Pubkey,
-    cash: &Pubkey,
-    pass_key: Option<&Pubkey>, // pass_key is now optional
-    owner_wallet: &Pubkey,
-    owner_token: &Pubkey,
-    fee_payer: &Pubkey,
-    fee_payer_token: &Pubkey,
-    referral_wallet: Option<&Pubkey>,
-    referral_token: Option<&Pubkey>,
-    mint: &Pubkey,
-    token_program_id: &Pubkey,
-    args: InitCashRedemptionArgs
-) -> Instruction {
-    let mut accounts = vec![
-        AccountMeta::new_readonly(*authority, true),
-        AccountMeta::new_readonly(*wallet, true),
+    /// Hand the authority of a cash link over to a new key, e.g. a multisig vault PDA
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The current authority of the cash link
+    /// 1. `[writable]` The cash account holding the cash info
+    /// 2. `[]` The new authority, it does not need to sign so it may be a PDA
+    TransferAuthority,
+
+    /// Check a batch of planned redemptions without moving funds or writing state, meant to be
+    /// simulated. The return data is a little endian u16 bitmask of the entries that would succeed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[]` The cash account of every entry, in order
+    /// 2. `[][optional]` Pass keys that would sign the redemptions and existing redemption receipts
+    ValidateRedemptions(ValidateRedemptionsArgs),
+
+    /// Replace the display metadata uri of a live cash link
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The owner of the cash link
+    /// 1. `[]` The cash account holding the cash info
+    /// 2. `[writable]` The metadata pda
+    UpdateMetadata(UpdateMetadataArgs),
+
+    /// Start or continue a chunked cancel of a large link, the link can no longer be redeemed
+    /// and up to `max_amount` tokens go back to the owner. Wrapped SOL links use `Cancel`
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[writable]` The cash account holding the cash info
+    /// 2. `[]` The owner wallet
+    /// 3. `[writable]` The owner token account
+    /// 4. `[writable]` The vault token account
+    /// 5. `[]` The token mint
+    /// 6. `[]` The token program
+    /// 7. `[writable][optional]` The stats pda
+    CancelBegin(CancelBeginArgs),
+
+    /// Finish a chunked cancel once the vault is empty, the vault is closed and the link canceled
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[writable]` The cash account holding the cash info
+    /// 2. `[writable]` The fee payer, receives the vault rent
+    /// 3. `[writable]` The vault token account
+    /// 4. `[]` The token program
+    CancelFinalize(CancelCashRedemptionArgs),
+
+    /// Halt a link during an incident, redeem, cancel and close are rejected until it is released
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[signer]` The config admin, the second signature of the two person control
+    /// 2. `[]` The config pda
+    /// 3. `[writable]` The cash account holding the cash info
+    EmergencyHalt,
+
+    /// Release a halted link, same accounts as EmergencyHalt
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[signer]` The config admin
+    /// 2. `[]` The config pda
+    /// 3. `[writable]` The cash account holding the cash info
+    EmergencyRelease,
+
+    /// Move the whole vault of a live link, halted or not, to the token account of the recovery
+    /// wallet of the config and cancel the link with the Emergency completion reason
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[signer]` The config admin
+    /// 2. `[]` The config pda
+    /// 3. `[writable]` The cash account holding the cash info
+    /// 4. `[writable]` The vault token account
+    /// 5. `[writable]` The associated token account of the recovery wallet for the link mint
+    /// 6. `[]` The mint
+    /// 7. `[writable]` The fee payer of the link, receives the vault rent
+    /// 8. `[]` The token program
+    EmergencyWithdraw(CancelCashRedemptionArgs),
+
+    /// Forfeit the oldest unclaimed slot of a flash link once the next slot opened, its amount
+    /// moves from the remaining amount to the forfeited amount. Anyone may call it.
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable]` The cash account holding the cash info
+    ExpireSlot,
+
+    /// Replace the pass key of a locked link, e.g. when the kiosk holding it is swapped
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[writable]` The cash account holding the cash info
+    /// 2. `[]` The new pass key
+    /// 3. `[signer][optional]` The current pass key, required unless the rotation is forced
+    RotatePassKey(RotatePassKeyArgs),
+
+    /// Start the recovery of a link whose owner and authority keys are lost, it can be executed
+    /// once the recovery timelock of the config elapsed. Any redemption or authority action on
+    /// the link in the meantime clears the request.
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The cash account holding the cash info
+    InitiateRecovery,
+
+    /// Refund a link under recovery to its owner and close it, the admin never receives funds
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable, signer]` The config admin, pays for the owner token account when missing
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The cash account holding the cash info
+    /// 3. `[writable]` The owner wallet, receives the cash account rent left after the fee payer
+    /// 4. `[writable]` The associated token account of the owner for the link mint
+    /// 5. `[writable]` The vault token account
+    /// 6. `[]` The mint
+    /// 7. `[writable]` The fee payer of the link
+    /// 8. `[]` The token program
+    /// 9. `[]` The system program
+    /// 10. `[]` The associated token program
+    ExecuteRecovery(CancelCashRedemptionArgs),
+
+    /// Close a stale referral stats account and reclaim its rent
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The referral stats pda
+    /// 3. `[writable]` The destination of the rent
+    CloseReferralStats,
+
+    /// Disable or re-enable features program wide
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[writable]` The config pda
+    SetDisabledFeatures(SetDisabledFeaturesArgs),
+
+    /// Commit a wallet to redeem a commit-reveal link
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The wallet that will redeem
+    /// 1. `[writable][signer]` The fee payer, funds the commit and gets its rent back
+    /// 2. `[]` The cash account
+    /// 3. `[writable]` The commit pda, derived from the cash account and the wallet
+    /// 4. `[]` The rent sysvar
+    /// 5. `[]` The system program
+    CommitRedemption(CommitRedemptionArgs),
+
+    /// Close a commit that can no longer be revealed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable]` The commit pda
+    /// 1. `[writable]` The rent payer recorded on the commit
+    /// 2. `[]` The cash account, closed or not
+    SweepCommit,
+
+    /// Move part of the unredeemed amount and redemptions of a link to a new child link, the
+    /// child behaves as a link of its own thereafter
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[signer]` The owner of the link
+    /// 2. `[writable][signer]` The fee payer of the child link and its vault
+    /// 3. `[writable]` The cash account being split
+    /// 4. `[writable]` The vault token account of the cash account
+    /// 5. `[writable]` The child cash account
+    /// 6. `[writable]` The vault token account of the child
+    /// 7. `[]` The mint
+    /// 8. `[]` The rent sysvar
+    /// 9. `[]` The system program
+    /// 10. `[]` The token program
+    /// 11. `[]` The associated token program
+    /// 12. `[][optional]` The config pda
+    SplitCash(SplitCashArgs),
+
+    /// Record the program version that created a legacy link, growing it to the current
+    /// layout
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable, signer]` The config admin, pays the rent of the grown account
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The cash account
+    /// 3. `[]` The system program
+    BackfillVersion(BackfillVersionArgs),
+
+    /// Pay a redemption waiting for approval, the payout reserved at request time with the
+    /// fees computed as for any redemption. The referral bps and the priority fee of the
+    /// request replace those of the args
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// The accounts of Redeem, the owner wallet signs along with the authority and the pending
+    /// redemption pda of the wallet is required. Its rent payer receives its rent back
+    ApproveRedemption(InitCashRedemptionArgs),
+
+    /// Release the payout of a redemption waiting for approval back to the link. The authority
+    /// or the owner may reject it, anyone once the approval ttl elapsed or the link is canceled
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority or the owner
+    /// 1. `[writable]` The cash account, closed or not
+    /// 2. `[writable]` The pending redemption pda
+    /// 3. `[writable]` The rent payer recorded on the pending redemption
+    RejectRedemption,
+
+    /// Append a platform fee wallet under the next epoch, creating the fee wallet registry if
+    /// needed. Permissionless links created from now on store this epoch
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[signer][writable]` The fee payer
+    /// 3. `[writable]` The fee wallet registry pda
+    /// 4. `[]` The fee wallet
+    /// 5. `[]` The rent sysvar
+    /// 6. `[]` The system program
+    AddFeeWallet(FeeWalletRegistryArgs),
+
+    /// Mark a fee wallet epoch superseded, the redemptions of its links pay their platform fees
+    /// to the latest live wallet. An epoch can not be restored
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The fee wallet registry pda
+    SupersedeFeeWallet(SupersedeFeeWalletArgs),
+
+    /// Pay a referrer its escrowed fees of a mint once the config vesting delay passed since
+    /// the end of the day each of them was earned
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer][writable]` The referrer, funds its token account when missing
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The referral stats pda of the referrer
+    /// 3. `[writable]` The escrow, the associated token account of the referral stats, the
+    ///    referral stats for the native mint
+    /// 4. `[writable]` The associated token account of the referrer, the referrer for the
+    ///    native mint
+    /// 5. `[]` The mint
+    /// 6. `[]` The token program
+    /// 7. `[]` The system program
+    /// 8. `[]` The associated token program
+    ClaimReferralFees(ReferralFeesArgs),
+
+    /// Flag a referrer farming referrals, it earns no more fees and its escrowed fees of the
+    /// mint still unvested when first flagged go to the config fee wallet
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[writable]` The referral stats pda of the referrer
+    /// 3. `[writable]` The escrow, the associated token account of the referral stats, the
+    ///    referral stats for the native mint
+    /// 4. `[writable]` The token account of the config fee wallet, the fee wallet for the
+    ///    native mint
+    /// 5. `[]` The mint
+    /// 6. `[]` The token program
+    FlagReferrer(ReferralFeesArgs),
+
+    /// Grow legacy links to the current layout, anyone may pay for it as no economic field
+    /// changes. Links already at the current layout are skipped, the batch fails with
+    /// `UnrecognizedLegacyLayout` on an account whose data is not a recognized legacy layout.
+    /// Returns the number of migrated links as a `u8`
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[writable, signer]` The fee payer, pays the rent of the grown accounts
+    /// 1. `[]` The system program
+    /// 2..2+count. `[writable]` The cash accounts
+    MigrateBatch(MigrateBatchArgs),
+
+    /// Cancel up to `MAX_AUTO_CANCEL_BATCH` queued links whose auto-cancel time passed, anyone
+    /// may crank it. Each link is canceled as its authority would and tips the cranker its
+    /// `auto_cancel_tip` out of its fee reserve. Links not yet due, not queued or busy are
+    /// skipped, canceled or redeemed links are dequeued. Returns the number of canceled links
+    /// as a `u8`
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cranker
+    /// 1. `[writable]` The auto-cancel queue
+    /// 2. `[]` The token program of the links of the batch
+    ///
+    /// Then for each link:
+    ///
+    /// 0. `[writable]` The cash account
+    /// 1. `[writable]` The owner
+    /// 2. `[writable]` The token account of the owner
+    /// 3. `[writable]` The fee payer of the link, receives the rent of the vault
+    /// 4. `[writable]` The vault
+    /// 5. `[]` The mint
+    /// 6. `[writable]` The token account of the cranker receiving the tip
+    ProcessAutoCancelQueue(ProcessAutoCancelQueueArgs),
+
+    /// Set or clear the daily cap per wallet of a mint, creating the mint registry if needed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    /// 2. `[signer][writable]` The fee payer
+    /// 3. `[writable]` The mint registry pda
+    /// 4. `[]` The mint
+    /// 5. `[]` The rent sysvar
+    /// 6. `[]` The system program
+    SetMintCap(SetMintCapArgs),
+
+    /// Close wallet limits whose window ended, each refunds its rent payer. Returns the number
+    /// of closed accounts as a `u8`
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The config admin
+    /// 1. `[]` The config pda
+    ///
+    /// Then the `[writable]` wallet limit pdas to close, followed by `[writable]` the rent payer
+    /// of each, found by key
+    CloseStaleWalletLimits,
+
+    /// Take back up to the amount of a redemption of a link with a clawback window, while the
+    /// window is open. The link spends the delegation approved at redemption, so the recipient
+    /// token account must still hold the funds. They return to the vault and the remaining
+    /// amount of a live link, to the token account of the owner once the link was canceled or
+    /// redeemed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cash authority
+    /// 1. `[signer]` The config admin
+    /// 2. `[]` The config pda
+    /// 3. `[writable]` The cash account holding the cash info
+    /// 4. `[writable]` The redemption receipt of the wallet
+    /// 5. `[writable]` The recipient token account the payout went to
+    /// 6. `[writable]` The vault of a live link, the token account of the owner otherwise
+    /// 7. `[]` The mint
+    /// 8. `[]` The token program
+    ClawbackRedemption(ClawbackRedemptionArgs),
+
+    /// Spend what is left of the clawback delegation of a payout once the window ended, anyone
+    /// may crank it. The token program only lets the wallet revoke, so the link moves the
+    /// delegated amount to the vault and straight back, which clears the delegation. Only what
+    /// the wallet still holds can be spent, it revokes the rest itself, as it does once the link
+    /// was canceled or redeemed
+    ///
+    ///
+    /// Accounts expected:
+    ///
+    /// 0. `[signer]` The cranker
+    /// 1. `[]` The cash account holding the cash info
+    /// 2. `[]` The redemption receipt of the wallet
+    /// 3. `[writable]` The recipient token account the payout went to
+    /// 4. `[writable]` The vault token account
+    /// 5. `[]` The mint
+    /// 6. `[]` The token program
+    /// 7. `[]` The memo program, when the recipient token account requires memos
+    RevokeClawback(CancelCashRedemptionArgs),
+}
+
+/// Create `InitCash` instruction
+pub fn init_cash(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    fee_payer: &Pubkey,
+    fee_payer_token: &Pubkey,
+    cash_link_pda: &Pubkey,
+    pass_key: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+    mint_registry: Option<&Pubkey>,
+    stats: Option<&Pubkey>,
+    config: Option<&Pubkey>,
+    metadata: Option<&Pubkey>,
+    fee_wallet_registry: Option<&Pubkey>,
+    auto_cancel_queue: Option<&Pubkey>,
+    args: InitCashArgs,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*fee_payer_token, false),
+        AccountMeta::new(*cash_link_pda, false),
+        AccountMeta::new_readonly(*pass_key, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),   
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new(get_associated_token_address_with_program_id(cash_link_pda, &mint, token_program_id), false),
+        AccountMeta::new(get_associated_token_address_with_program_id(owner, &mint, token_program_id), false),     
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    if let Some(mint_registry) = mint_registry {
+        accounts.push(AccountMeta::new_readonly(*mint_registry, false));
+    }
+
+    if let Some(stats) = stats {
+        accounts.push(AccountMeta::new(*stats, false));
+    }
+
+    if let Some(config) = config {
+        accounts.push(AccountMeta::new_readonly(*config, false));
+    }
+
+    if let Some(metadata) = metadata {
+        accounts.push(AccountMeta::new(*metadata, false));
+    }
+
+    if let Some(fee_wallet_registry) = fee_wallet_registry {
+        accounts.push(AccountMeta::new_readonly(*fee_wallet_registry, false));
+    }
+
+    if let Some(auto_cancel_queue) = auto_cancel_queue {
+        accounts.push(AccountMeta::new(*auto_cancel_queue, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::InitCash(args),
+        accounts,
+    )
+}
+
+/// Create `CancelCash` instruction
+pub fn cancel_cash(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    cash: &Pubkey,
+    owner: &Pubkey,
+    owner_token: &Pubkey,
+    vault_token: &Pubkey,
+    mint: &Pubkey,
+    fee_payer: &Pubkey,
+    token_program_id: &Pubkey,
+    stats: Option<&Pubkey>,
+    refund_destination: Option<&Pubkey>,
+    args: CancelCashRedemptionArgs,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new(*owner, false),
+        AccountMeta::new(*owner_token, false),
+        AccountMeta::new(*fee_payer, false),
+        AccountMeta::new(*vault_token, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    if let Some(stats) = stats {
+        accounts.push(AccountMeta::new(*stats, false));
+    }
+
+    if let Some(refund_destination) = refund_destination {
+        accounts.push(AccountMeta::new(*refund_destination, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::Cancel(args),
+        accounts,
+    )
+}
+
+/// Create a `Cancel` instruction signed by the pass key of a locked link instead of its authority,
+/// the refund goes to the associated token account of the owner
+pub fn cancel_cash_with_pass_key(
+    program_id: &Pubkey,
+    pass_key: &Pubkey,
+    cash: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    fee_payer: &Pubkey,
+    token_program_id: &Pubkey,
+    stats: Option<&Pubkey>,
+    refund_destination: Option<&Pubkey>,
+    args: CancelCashRedemptionArgs,
+) -> Instruction {
+    cancel_cash(
+        program_id,
+        pass_key,
+        cash,
+        owner,
+        &get_associated_token_address_with_program_id(owner, mint, token_program_id),
+        &get_associated_token_address_with_program_id(cash, mint, token_program_id),
+        mint,
+        fee_payer,
+        token_program_id,
+        stats,
+        refund_destination,
+        args,
+    )
+}
+
+/// The pool accounts a swapping link redeems through
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwapRedemptionAccounts {
+    pub amm_program: Pubkey,
+    pub pool: Pubkey,
+    pub pool_source: Pubkey,
+    pub pool_target: Pubkey,
+    pub target_mint: Pubkey,
+}
+
+/// Give every occurrence of a key the union of its privileges, a transaction lists each key
+/// once and some RPC providers reject instructions that disagree on them
+fn merge_duplicate_accounts(accounts: &mut [AccountMeta]) {
+    for i in 0..accounts.len() {
+        let (is_signer, is_writable) = accounts
+            .iter()
+            .filter(|meta| meta.pubkey == accounts[i].pubkey)
+            .fold((false, false), |(is_signer, is_writable), meta| {
+                (is_signer || meta.is_signer, is_writable || meta.is_writable)
+            });
+        accounts[i].is_signer = is_signer;
+        accounts[i].is_writable = is_writable;
+    }
+}
+
+/// Create `RedeemCash` instruction
+pub fn redeem_cash(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    wallet: &Pubkey,
+    wallet_is_signer: bool,
+    wallet_token: &Pubkey,
+    platform_wallet: &Pubkey,
+    platform_fee_token: &Pubkey,
+    vault_token: &Pubkey,
+    cash: &Pubkey,
+    pass_key: Option<&Pubkey>,
+    owner_wallet: &Pubkey,
+    owner_token: &Pubkey,
+    fee_payer: &Pubkey,
+    fee_payer_token: &Pubkey,
+    referral_wallet: Option<&Pubkey>,
+    referral_token: Option<&Pubkey>,
+    savings_token: Option<&Pubkey>,
+    mint: &Pubkey,
+    redemption: &Pubkey,
+    redemption_index: &Pubkey,
+    token_program_id: &Pubkey,
+    stats: Option<&Pubkey>,
+    kyc_attestation: Option<&Pubkey>,
+    memo_program: Option<&Pubkey>,
+    buffer: Option<&Pubkey>,
+    swap: Option<&SwapRedemptionAccounts>,
+    vrf_account: Option<&Pubkey>,
+    referral_stats: Option<&Pubkey>,
+    has_approval_threshold: bool,
+    has_fee_wallet_epoch: bool,
+    referral_vesting: bool,
+    wallet_limit: bool,
+    args: InitCashRedemptionArgs
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*wallet, wallet_is_signer),
         AccountMeta::new(*platform_wallet, false),
         AccountMeta::new(*platform_fee_token, false),
         AccountMeta::new(*cash, false),
-        AccountMeta::new(*owner_wallet, false),
-        AccountMeta::new(*owner_token, false),
+        AccountMeta::new(*owner_wallet, false),
+        AccountMeta::new(*owner_token, false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*fee_payer_token, false),
+        AccountMeta::new(*vault_token, false),
+        AccountMeta::new(*wallet_token, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new(*redemption, false),
+        AccountMeta::new(*redemption_index, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    if let Some(pass_key) = pass_key {
+        accounts.push(AccountMeta::new_readonly(*pass_key, true));
+    }
+
+    if let Some((referral_wallet, referral_token)) = referral_wallet.zip(referral_token) {
+        accounts.push(AccountMeta::new(*referral_wallet, false));
+        accounts.push(AccountMeta::new(*referral_token, false));
+    }
+
+    if let Some(savings_token) = savings_token {
+        accounts.push(AccountMeta::new(*savings_token, false));
+    }
+
+    if let Some(stats) = stats {
+        accounts.push(AccountMeta::new(*stats, false));
+    }
+
+    if let Some(kyc_attestation) = kyc_attestation {
+        accounts.push(AccountMeta::new_readonly(*kyc_attestation, false));
+    }
+
+    if let Some(memo_program) = memo_program {
+        accounts.push(AccountMeta::new_readonly(*memo_program, false));
+    }
+
+    if let Some(buffer) = buffer {
+        accounts.push(AccountMeta::new_readonly(*buffer, false));
+    }
+
+    if let Some(swap) = swap {
+        accounts.push(AccountMeta::new_readonly(swap.amm_program, false));
+        accounts.push(AccountMeta::new(swap.pool, false));
+        accounts.push(AccountMeta::new(swap.pool_source, false));
+        accounts.push(AccountMeta::new(swap.pool_target, false));
+        accounts.push(AccountMeta::new_readonly(swap.target_mint, false));
+    }
+
+    if let Some(vrf_account) = vrf_account {
+        accounts.push(AccountMeta::new_readonly(*vrf_account, false));
+    }
+
+    if let Some(referral_stats) = referral_stats {
+        accounts.push(AccountMeta::new(*referral_stats, false));
+        // a vesting referrer fee is held by the associated token account of the referral stats
+        if referral_vesting && !is_native_mint(mint) {
+            let escrow = get_associated_token_address_with_program_id(
+                referral_stats,
+                mint,
+                token_program_id,
+            );
+            accounts.push(AccountMeta::new(escrow, false));
+        }
+    }
+
+    if args.recipient_permit.is_some() {
+        accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    }
+
+    // the commit rent goes back to the fee payer that funded it
+    if args.reveal_nonce.is_some() {
+        let (commit, _) = find_redemption_commit_address(program_id, cash, wallet);
+        accounts.push(AccountMeta::new(commit, false));
+    }
+
+    if has_approval_threshold {
+        let (pending, _) = find_pending_redemption_address(program_id, cash, wallet);
+        accounts.push(AccountMeta::new(pending, false));
+    }
+
+    if has_fee_wallet_epoch {
+        let (fee_wallet_registry, _) = find_fee_wallet_registry_program_address(program_id);
+        accounts.push(AccountMeta::new_readonly(fee_wallet_registry, false));
+    }
+
+    if wallet_limit {
+        let (mint_registry, _) = find_mint_registry_program_address(program_id);
+        let (limit, _) = find_wallet_limit_address(program_id, wallet, mint);
+        accounts.push(AccountMeta::new_readonly(mint_registry, false));
+        accounts.push(AccountMeta::new(limit, false));
+    }
+
+    let (config, _) = find_config_program_address(program_id);
+    accounts.push(AccountMeta::new_readonly(config, false));
+
+    merge_duplicate_accounts(&mut accounts);
+
+    // Construct and return the instruction
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::Redeem(args),
+        accounts,
+    )
+}
+
+
+/// Create `Close` instruction
+pub fn close_cash(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    cash: &Pubkey,
+    destination: &Pubkey,
+    fee_payer: &Pubkey,
+    buffer: Option<&Pubkey>,
+    metadata: Option<&Pubkey>,
+    rent_payers: &[Pubkey],
+    refund_destination: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new(*destination, false),
+        AccountMeta::new(*fee_payer, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    if let Some(buffer) = buffer {
+        accounts.push(AccountMeta::new(*buffer, false));
+    }
+
+    if let Some(metadata) = metadata {
+        accounts.push(AccountMeta::new(*metadata, false));
+    }
+
+    for rent_payer in rent_payers {
+        accounts.push(AccountMeta::new(*rent_payer, false));
+    }
+
+    if let Some(refund_destination) = refund_destination {
+        accounts.push(AccountMeta::new(*refund_destination, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::Close,
+        accounts,
+    )
+}
+
+/// Create `TransferAuthority` instruction
+pub fn transfer_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    cash: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new_readonly(*new_authority, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::TransferAuthority,
+        accounts,
+    )
+}
+
+/// Create `InitConfig` instruction
+pub fn init_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    fee_payer: &Pubkey,
+    config: &Pubkey,
+    args: InitConfigArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*config, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(find_program_data_address(program_id).0, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::InitConfig(args),
+        accounts,
+    )
+}
+
+/// Create `UpdateConfig` instruction
+pub fn update_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    args: UpdateConfigArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(*config, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::UpdateConfig(args),
+        accounts,
+    )
+}
+
+/// Create `AddMint` instruction
+pub fn add_mint(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    fee_payer: &Pubkey,
+    mint_registry: &Pubkey,
+    mint: &Pubkey,
+    args: MintRegistryArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*mint_registry, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::AddMint(args),
+        accounts,
+    )
+}
+
+/// Create `RemoveMint` instruction
+pub fn remove_mint(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    mint_registry: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*mint_registry, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::RemoveMint,
+        accounts,
+    )
+}
+
+/// Create `SetMintMode` instruction
+pub fn set_mint_mode(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    fee_payer: &Pubkey,
+    mint_registry: &Pubkey,
+    args: SetMintModeArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*mint_registry, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::SetMintMode(args),
+        accounts,
+    )
+}
+
+/// Create `InitStats` instruction
+pub fn init_stats(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    fee_payer: &Pubkey,
+    stats: &Pubkey,
+    mint_registry: Option<&Pubkey>,
+    args: InitStatsArgs,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*stats, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    if let Some(mint_registry) = mint_registry {
+        accounts.push(AccountMeta::new_readonly(*mint_registry, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::InitStats(args),
+        accounts,
+    )
+}
+
+/// Create `CreateBuffer` instruction
+pub fn create_buffer(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    fee_payer: &Pubkey,
+    buffer: &Pubkey,
+    args: CreateBufferArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*buffer, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::CreateBuffer(args),
+        accounts,
+    )
+}
+
+/// Create `WriteBufferChunk` instruction
+pub fn write_buffer_chunk(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    buffer: &Pubkey,
+    args: WriteBufferChunkArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*buffer, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::WriteBufferChunk(args),
+        accounts,
+    )
+}
+
+/// Create `FinalizeBuffer` instruction
+pub fn finalize_buffer(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    buffer: &Pubkey,
+    cash: &Pubkey,
+    args: FinalizeBufferArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*buffer, false),
+        AccountMeta::new(*cash, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::FinalizeBuffer(args),
+        accounts,
+    )
+}
+
+/// Create `ValidateRedemptions` instruction
+pub fn validate_redemptions(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    cash_accounts: &[Pubkey],
+    pass_keys: &[Pubkey],
+    receipts: &[Pubkey],
+    args: ValidateRedemptionsArgs,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new_readonly(*authority, true)];
+    accounts.extend(
+        cash_accounts
+            .iter()
+            .map(|cash| AccountMeta::new_readonly(*cash, false)),
+    );
+    accounts.extend(
+        pass_keys
+            .iter()
+            .map(|pass_key| AccountMeta::new_readonly(*pass_key, true)),
+    );
+    accounts.extend(
+        receipts
+            .iter()
+            .map(|receipt| AccountMeta::new_readonly(*receipt, false)),
+    );
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::ValidateRedemptions(args),
+        accounts,
+    )
+}
+
+/// Create `UpdateMetadata` instruction
+pub fn update_metadata(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    cash: &Pubkey,
+    metadata: &Pubkey,
+    args: UpdateMetadataArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new_readonly(*cash, false),
+        AccountMeta::new(*metadata, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::UpdateMetadata(args),
+        accounts,
+    )
+}
+
+/// Create `CancelBegin` instruction
+pub fn cancel_begin(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    cash: &Pubkey,
+    owner: &Pubkey,
+    owner_token: &Pubkey,
+    vault_token: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+    stats: Option<&Pubkey>,
+    args: CancelBeginArgs,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new_readonly(*owner, false),
+        AccountMeta::new(*owner_token, false),
+        AccountMeta::new(*vault_token, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    if let Some(stats) = stats {
+        accounts.push(AccountMeta::new(*stats, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::CancelBegin(args),
+        accounts,
+    )
+}
+
+/// Create `CancelFinalize` instruction
+pub fn cancel_finalize(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    cash: &Pubkey,
+    fee_payer: &Pubkey,
+    vault_token: &Pubkey,
+    token_program_id: &Pubkey,
+    args: CancelCashRedemptionArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new(*fee_payer, false),
+        AccountMeta::new(*vault_token, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::CancelFinalize(args),
+        accounts,
+    )
+}
+
+/// Create EmergencyHalt instruction
+pub fn emergency_halt(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    cash: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*cash, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &CashInstruction::EmergencyHalt, accounts)
+}
+
+/// Create EmergencyRelease instruction
+pub fn emergency_release(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    cash: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*cash, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &CashInstruction::EmergencyRelease, accounts)
+}
+
+/// Create EmergencyWithdraw instruction
+pub fn emergency_withdraw(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    cash: &Pubkey,
+    vault_token: &Pubkey,
+    recovery_token: &Pubkey,
+    mint: &Pubkey,
+    fee_payer: &Pubkey,
+    token_program_id: &Pubkey,
+    args: CancelCashRedemptionArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new(*vault_token, false),
+        AccountMeta::new(*recovery_token, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(*fee_payer, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::EmergencyWithdraw(args),
+        accounts,
+    )
+}
+
+/// Create ExpireSlot instruction
+pub fn expire_slot(program_id: &Pubkey, cash: &Pubkey) -> Instruction {
+    let accounts = vec![AccountMeta::new(*cash, false)];
+
+    Instruction::new_with_borsh(*program_id, &CashInstruction::ExpireSlot, accounts)
+}
+
+/// Create RotatePassKey instruction, `current_pass_key` may only be omitted for a forced rotation
+pub fn rotate_pass_key(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    cash: &Pubkey,
+    new_pass_key: &Pubkey,
+    current_pass_key: Option<&Pubkey>,
+    args: RotatePassKeyArgs,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new_readonly(*new_pass_key, false),
+    ];
+
+    if let Some(current_pass_key) = current_pass_key {
+        accounts.push(AccountMeta::new_readonly(*current_pass_key, true));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::RotatePassKey(args),
+        accounts,
+    )
+}
+
+/// Create InitiateRecovery instruction
+pub fn initiate_recovery(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    cash: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*cash, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &CashInstruction::InitiateRecovery, accounts)
+}
+
+/// Create ExecuteRecovery instruction
+pub fn execute_recovery(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    cash: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    fee_payer: &Pubkey,
+    token_program_id: &Pubkey,
+    args: CancelCashRedemptionArgs,
+) -> Instruction {
+    let owner_token = get_associated_token_address_with_program_id(owner, mint, token_program_id);
+    let vault_token = get_associated_token_address_with_program_id(cash, mint, token_program_id);
+    let accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new(*owner, false),
+        AccountMeta::new(owner_token, false),
+        AccountMeta::new(vault_token, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(*fee_payer, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::ExecuteRecovery(args),
+        accounts,
+    )
+}
+
+/// Create CloseReferralStats instruction
+pub fn close_referral_stats(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    referral_stats: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*referral_stats, false),
+        AccountMeta::new(*destination, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &CashInstruction::CloseReferralStats, accounts)
+}
+
+/// Create SetDisabledFeatures instruction
+pub fn set_disabled_features(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    args: SetDisabledFeaturesArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(*config, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::SetDisabledFeatures(args),
+        accounts,
+    )
+}
+
+/// Create CommitRedemption instruction
+pub fn commit_redemption(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    fee_payer: &Pubkey,
+    cash: &Pubkey,
+    commitment: [u8; 32],
+) -> Instruction {
+    let (commit, commit_bump) = find_redemption_commit_address(program_id, cash, wallet);
+    let accounts = vec![
+        AccountMeta::new_readonly(*wallet, true),
         AccountMeta::new(*fee_payer, true),
-        AccountMeta::new(*fee_payer_token, false),
-        AccountMeta::new(*vault_token, false),
-        AccountMeta::new(*wallet_token, false),
+        AccountMeta::new_readonly(*cash, false),
+        AccountMeta::new(commit, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::CommitRedemption(CommitRedemptionArgs {
+            commitment,
+            commit_bump,
+        }),
+        accounts,
+    )
+}
+
+/// Create SweepCommit instruction
+pub fn sweep_commit(
+    program_id: &Pubkey,
+    commit: &Pubkey,
+    rent_payer: &Pubkey,
+    cash: &Pubkey,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*commit, false),
+        AccountMeta::new(*rent_payer, false),
+        AccountMeta::new_readonly(*cash, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &CashInstruction::SweepCommit, accounts)
+}
+
+/// Create SplitCash instruction
+pub fn split_cash(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    fee_payer: &Pubkey,
+    cash: &Pubkey,
+    child: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+    config: Option<&Pubkey>,
+    args: SplitCashArgs,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*owner, true),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(cash, mint, token_program_id),
+            false,
+        ),
+        AccountMeta::new(*child, false),
+        AccountMeta::new(
+            get_associated_token_address_with_program_id(child, mint, token_program_id),
+            false,
+        ),
         AccountMeta::new_readonly(*mint, false),
-        AccountMeta::new_readonly(sysvar::clock::id(), false),
         AccountMeta::new_readonly(sysvar::rent::id(), false),
-        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
         AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
     ];
 
-    // Add pass_key if it's Some, otherwise continue with next accounts
-    if let Some(pass_key_account) = pass_key {
-        accounts.insert(5, AccountMeta::new_readonly(*pass_key_account, false)); // Insert pass_key after cash
+    if let Some(config) = config {
+        accounts.push(AccountMeta::new_readonly(*config, false));
     }
 
-    // Add referral wallet if provided
-    if let Some(referral) = referral_wallet {
-        accounts.push(AccountMeta::new(*referral, false));
+    Instruction::new_with_borsh(*program_id, &CashInstruction::SplitCash(args), accounts)
+}
+
+/// Create BackfillVersion instruction
+pub fn backfill_version(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    cash: &Pubkey,
+    args: BackfillVersionArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::BackfillVersion(args),
+        accounts,
+    )
+}
+
+/// Create ApproveRedemption instruction from the accounts `redeem_cash` builds for the
+/// request, the owner wallet co-signs it
+pub fn approve_redemption(
+    program_id: &Pubkey,
+    redeem_accounts: Vec<AccountMeta>,
+    args: InitCashRedemptionArgs,
+) -> Instruction {
+    let mut accounts = redeem_accounts;
+    // the owner wallet follows the authority, the wallet, the platform accounts and the cash
+    accounts[5].is_signer = true;
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::ApproveRedemption(args),
+        accounts,
+    )
+}
+
+/// Create RejectRedemption instruction
+pub fn reject_redemption(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    cash: &Pubkey,
+    wallet: &Pubkey,
+    rent_payer: &Pubkey,
+) -> Instruction {
+    let (pending, _) = find_pending_redemption_address(program_id, cash, wallet);
+    let accounts = vec![
+        AccountMeta::new_readonly(*signer, true),
+        AccountMeta::new(*cash, false),
+        AccountMeta::new(pending, false),
+        AccountMeta::new(*rent_payer, false),
+    ];
+
+    Instruction::new_with_borsh(*program_id, &CashInstruction::RejectRedemption, accounts)
+}
+
+/// Create `AddFeeWallet` instruction
+pub fn add_fee_wallet(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    fee_payer: &Pubkey,
+    fee_wallet_registry: &Pubkey,
+    fee_wallet: &Pubkey,
+    args: FeeWalletRegistryArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*fee_wallet_registry, false),
+        AccountMeta::new_readonly(*fee_wallet, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::AddFeeWallet(args),
+        accounts,
+    )
+}
+
+/// Create `SupersedeFeeWallet` instruction
+pub fn supersede_fee_wallet(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    fee_wallet_registry: &Pubkey,
+    args: SupersedeFeeWalletArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*fee_wallet_registry, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::SupersedeFeeWallet(args),
+        accounts,
+    )
+}
+
+/// Create `ClaimReferralFees` instruction
+pub fn claim_referral_fees(
+    program_id: &Pubkey,
+    referrer: &Pubkey,
+    token_program_id: &Pubkey,
+    args: ReferralFeesArgs,
+) -> Instruction {
+    let (config, _) = find_config_program_address(program_id);
+    let (referral_stats, _) = find_referral_stats_address(program_id, referrer);
+    let (escrow, referrer_token) = if is_native_mint(&args.mint) {
+        (referral_stats, *referrer)
+    } else {
+        (
+            get_associated_token_address_with_program_id(
+                &referral_stats,
+                &args.mint,
+                token_program_id,
+            ),
+            get_associated_token_address_with_program_id(referrer, &args.mint, token_program_id),
+        )
+    };
+    let accounts = vec![
+        AccountMeta::new(*referrer, true),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(referral_stats, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new(referrer_token, false),
+        AccountMeta::new_readonly(args.mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::ClaimReferralFees(args),
+        accounts,
+    )
+}
+
+/// Create `FlagReferrer` instruction
+pub fn flag_referrer(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    referrer: &Pubkey,
+    fee_wallet: &Pubkey,
+    token_program_id: &Pubkey,
+    args: ReferralFeesArgs,
+) -> Instruction {
+    let (config, _) = find_config_program_address(program_id);
+    let (referral_stats, _) = find_referral_stats_address(program_id, referrer);
+    let (escrow, fee_wallet_token) = if is_native_mint(&args.mint) {
+        (referral_stats, *fee_wallet)
+    } else {
+        (
+            get_associated_token_address_with_program_id(
+                &referral_stats,
+                &args.mint,
+                token_program_id,
+            ),
+            get_associated_token_address_with_program_id(fee_wallet, &args.mint, token_program_id),
+        )
+    };
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(referral_stats, false),
+        AccountMeta::new(escrow, false),
+        AccountMeta::new(fee_wallet_token, false),
+        AccountMeta::new_readonly(args.mint, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::FlagReferrer(args),
+        accounts,
+    )
+}
+
+/// Create `MigrateBatch` instruction
+pub fn migrate_batch(program_id: &Pubkey, fee_payer: &Pubkey, cashes: &[Pubkey]) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(cashes.iter().map(|cash| AccountMeta::new(*cash, false)));
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::MigrateBatch(MigrateBatchArgs {
+            count: cashes.len() as u8,
+        }),
+        accounts,
+    )
+}
+
+/// The accounts of a link canceled by the auto-cancel crank
+#[derive(Debug, Clone, PartialEq)]
+pub struct AutoCancelLink {
+    pub cash: Pubkey,
+    pub owner: Pubkey,
+    pub owner_token: Pubkey,
+    pub fee_payer: Pubkey,
+    pub vault_token: Pubkey,
+    pub mint: Pubkey,
+    /// The token account of the cranker in the mint of the link
+    pub cranker_token: Pubkey,
+    pub args: CancelCashRedemptionArgs,
+}
+
+/// Create `ProcessAutoCancelQueue` instruction
+pub fn process_auto_cancel_queue(
+    program_id: &Pubkey,
+    cranker: &Pubkey,
+    token_program_id: &Pubkey,
+    links: &[AutoCancelLink],
+) -> Instruction {
+    let (queue, _) = find_auto_cancel_queue_program_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*cranker, true),
+        AccountMeta::new(queue, false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    for link in links {
+        accounts.extend([
+            AccountMeta::new(link.cash, false),
+            AccountMeta::new(link.owner, false),
+            AccountMeta::new(link.owner_token, false),
+            AccountMeta::new(link.fee_payer, false),
+            AccountMeta::new(link.vault_token, false),
+            AccountMeta::new_readonly(link.mint, false),
+            AccountMeta::new(link.cranker_token, false),
+        ]);
     }
 
-    // Add referral token if provided
-    if let Some(referral_token_account) = referral_token {
-        accounts.push(AccountMeta::new(*referral_token_account, false));
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::ProcessAutoCancelQueue(ProcessAutoCancelQueueArgs {
+            links: links.iter().map(|link| link.args.clone()).collect(),
+        }),
+        accounts,
+    )
+}
+
+/// Create `SetMintCap` instruction
+pub fn set_mint_cap(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    fee_payer: &Pubkey,
+    mint_registry: &Pubkey,
+    mint: &Pubkey,
+    args: SetMintCapArgs,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+        AccountMeta::new(*fee_payer, true),
+        AccountMeta::new(*mint_registry, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::SetMintCap(args),
+        accounts,
+    )
+}
+
+/// Create `CloseStaleWalletLimits` instruction
+pub fn close_stale_wallet_limits(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    config: &Pubkey,
+    wallet_limits: &[Pubkey],
+    rent_payers: &[Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(*config, false),
+    ];
+    accounts.extend(wallet_limits.iter().map(|limit| AccountMeta::new(*limit, false)));
+    for rent_payer in rent_payers {
+        if !accounts.iter().any(|meta| meta.pubkey == *rent_payer) {
+            accounts.push(AccountMeta::new(*rent_payer, false));
+        }
     }
-    
-    // Include associated token program ID
-    accounts.push(AccountMeta::new_readonly(spl_associated_token_account::id(), false));
 
-    // Construct and return the instruction
     Instruction::new_with_borsh(
         *program_id,
-        &CashInstruction::Redeem(args),
+        &CashInstruction::CloseStaleWalletLimits,
         accounts,
     )
 }
 
+/// The payout a clawback instruction acts on
+pub struct ClawbackPayout {
+    pub cash: Pubkey,
+    /// The redeeming wallet, its redemption receipt is derived from it
+    pub wallet: Pubkey,
+    /// The token account the payout went to
+    pub recipient_token: Pubkey,
+    pub mint: Pubkey,
+    pub token_program_id: Pubkey,
+    pub args: CancelCashRedemptionArgs,
+}
 
-/// Create `Close` instruction
-pub fn close_cash(
+/// Create `ClawbackRedemption` instruction, `destination` is the vault of a live link and the
+/// token account of the owner otherwise
+pub fn clawback_redemption(
     program_id: &Pubkey,
     authority: &Pubkey,
-    cash: &Pubkey,
-    destination: &Pubkey
+    admin: &Pubkey,
+    payout: &ClawbackPayout,
+    destination: &Pubkey,
+    amount: u64,
 ) -> Instruction {
+    let (config, _) = find_config_program_address(program_id);
+    let (receipt, _) = find_redemption_address(program_id, &payout.cash, &payout.wallet);
     let accounts = vec![
         AccountMeta::new_readonly(*authority, true),
-        AccountMeta::new(*cash, false),
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(config, false),
+        AccountMeta::new(payout.cash, false),
+        AccountMeta::new(receipt, false),
+        AccountMeta::new(payout.recipient_token, false),
         AccountMeta::new(*destination, false),
-        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(payout.mint, false),
+        AccountMeta::new_readonly(payout.token_program_id, false),
     ];
 
     Instruction::new_with_borsh(
         *program_id,
-        &CashInstruction::Close,
+        &CashInstruction::ClawbackRedemption(ClawbackRedemptionArgs {
+            cash_bump: payout.args.cash_bump,
+            cash_reference: payout.args.cash_reference.clone(),
+            amount,
+        }),
+        accounts,
+    )
+}
+
+/// Create `RevokeClawback` instruction
+pub fn revoke_clawback(
+    program_id: &Pubkey,
+    cranker: &Pubkey,
+    payout: &ClawbackPayout,
+    vault_token: &Pubkey,
+    memo_program: Option<&Pubkey>,
+) -> Instruction {
+    let (receipt, _) = find_redemption_address(program_id, &payout.cash, &payout.wallet);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*cranker, true),
+        AccountMeta::new_readonly(payout.cash, false),
+        AccountMeta::new_readonly(receipt, false),
+        AccountMeta::new(payout.recipient_token, false),
+        AccountMeta::new(*vault_token, false),
+        AccountMeta::new_readonly(payout.mint, false),
+        AccountMeta::new_readonly(payout.token_program_id, false),
+    ];
+    if let Some(memo_program) = memo_program {
+        accounts.push(AccountMeta::new_readonly(*memo_program, false));
+    }
+
+    Instruction::new_with_borsh(
+        *program_id,
+        &CashInstruction::RevokeClawback(payout.args.clone()),
         accounts,
     )
-}
\ No newline at end of file
+}
@@ -0,0 +1,50 @@
+//! Stand-in for the kyc attestation program a link names as its kyc authority, used by the tests
+//!
+//! It has a single instruction attesting a wallet until an expiry, the data being the little
+//! endian expiry unix timestamp. It writes the attestation the way the cash program reads it:
+//! the attested wallet followed by the expiry, at the address `find_kyc_attestation_address`
+//! derives under the stub program.
+//!
+//! Accounts expected:
+//!
+//! 0. `[writable]` The attestation pda of the wallet, owned by the stub program
+//! 1. `[]` The attested wallet
+use crate::find_kyc_attestation_address;
+
+use arrayref::array_ref;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// Attested wallet + expiry unix timestamp
+pub const ATTESTATION_LEN: usize = 32 + 8;
+
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let attestation_info = next_account_info(account_info_iter)?;
+    let wallet_info = next_account_info(account_info_iter)?;
+    if instruction_data.len() != 8 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (attestation_key, _) = find_kyc_attestation_address(program_id, wallet_info.key);
+    if attestation_info.key != &attestation_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if attestation_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut data = attestation_info.try_borrow_mut_data()?;
+    if data.len() < ATTESTATION_LEN {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    data[..32].copy_from_slice(wallet_info.key.as_ref());
+    data[32..ATTESTATION_LEN].copy_from_slice(array_ref![instruction_data, 0, 8]);
+    Ok(())
+}
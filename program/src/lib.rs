@@ -1,19 +1,42 @@
+pub mod client;
+pub mod core_math;
 pub mod error;
+pub mod event;
+pub mod fees;
 pub mod instruction;
+pub mod permit;
 pub mod processor;
 pub mod state;
 pub mod utils;
 pub mod math;
+pub mod rounding;
 
 
 #[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(test)]
+mod kyc_stub;
+#[cfg(test)]
+mod test_utils;
+
 use solana_program::{declare_id, pubkey::Pubkey};
-use state::cash::Cash;
+use state::{
+    auto_cancel::AutoCancelQueue, buffer::Buffer, cash::Cash, commit::RedemptionCommit, config::Config, fee_wallet::FeeWalletRegistry, metadata::CashMetadata, mint_registry::MintRegistry, pending::PendingRedemption, redemption::RedemptionIndex,
+    referral::ReferralStats, stats::Stats, wallet_limit::WalletLimit, KYC_ATTESTATION_PREFIX,
+    REDEMPTION_PREFIX,
+};
 
 declare_id!("cashXAE5UP18RyU7ByFWfxu93kGg69KzoktacNQDukW");
 
+/// Version of the deployed program, bump it on every release. Links record the version that
+/// created them and the last one that stored them
+pub const PROGRAM_VERSION: u16 = 1;
+
 
 /// Generates cash link program address
 pub fn find_cash_link_program_address(program_id: &Pubkey, pass_key: Pubkey) -> (Pubkey, u8) {
@@ -24,4 +47,122 @@ pub fn find_cash_link_program_address(program_id: &Pubkey, pass_key: Pubkey) ->
         ],
         program_id,
     )
-}
\ No newline at end of file
+}
+
+/// Generates config program address
+pub fn find_config_program_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Config::PREFIX.as_bytes()], program_id)
+}
+
+/// Generates the address of the program data account holding the upgrade authority
+pub fn find_program_data_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[program_id.as_ref()], &utils::UPGRADEABLE_LOADER_ID)
+}
+
+/// Generates mint registry program address
+pub fn find_mint_registry_program_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MintRegistry::PREFIX.as_bytes()], program_id)
+}
+
+/// Generates fee wallet registry program address
+pub fn find_fee_wallet_registry_program_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FeeWalletRegistry::PREFIX.as_bytes()], program_id)
+}
+
+/// Generates auto-cancel queue program address
+pub fn find_auto_cancel_queue_program_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AutoCancelQueue::PREFIX.as_bytes()], program_id)
+}
+
+/// Generates stats program address
+pub fn find_stats_program_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[Stats::PREFIX.as_bytes()], program_id)
+}
+
+/// Generates the lifetime referral stats address of a referrer
+pub fn find_referral_stats_address(program_id: &Pubkey, referrer: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ReferralStats::PREFIX.as_bytes(), referrer.as_ref()], program_id)
+}
+
+/// Generates the daily limit address of a wallet for a capped mint
+pub fn find_wallet_limit_address(
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[WalletLimit::PREFIX.as_bytes(), wallet.as_ref(), mint.as_ref()],
+        program_id,
+    )
+}
+
+/// Generates the commit address of a wallet for a commit-reveal cash link
+pub fn find_redemption_commit_address(
+    program_id: &Pubkey,
+    cash: &Pubkey,
+    wallet: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RedemptionCommit::PREFIX.as_bytes(), cash.as_ref(), wallet.as_ref()],
+        program_id,
+    )
+}
+
+/// Generates the pending redemption address of a wallet for a cash link with an approval
+/// threshold
+pub fn find_pending_redemption_address(
+    program_id: &Pubkey,
+    cash: &Pubkey,
+    wallet: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[PendingRedemption::PREFIX.as_bytes(), cash.as_ref(), wallet.as_ref()],
+        program_id,
+    )
+}
+
+/// Generates the kyc attestation address of a wallet, the attestation program of a link being
+/// its kyc authority
+pub fn find_kyc_attestation_address(kyc_authority: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[KYC_ATTESTATION_PREFIX.as_bytes(), wallet.as_ref()],
+        kyc_authority,
+    )
+}
+
+/// Generates the buffer address of a cash link
+pub fn find_buffer_program_address(program_id: &Pubkey, cash_reference: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[Buffer::PREFIX.as_bytes(), cash_reference.as_bytes()],
+        program_id,
+    )
+}
+
+/// Generates the metadata address of a cash link
+pub fn find_cash_metadata_address(program_id: &Pubkey, cash: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CashMetadata::PREFIX.as_bytes(), cash.as_ref()], program_id)
+}
+
+/// Seeds of the redemption receipt of a wallet for a cash link, the only place receipts are
+/// derived from so the program and the address helpers can not drift apart. A receipt is
+/// always scoped to a single link, the pass key of a locked link is not part of it
+pub fn redemption_receipt_seeds<'a>(cash: &'a Pubkey, wallet: &'a Pubkey) -> [&'a [u8]; 3] {
+    [REDEMPTION_PREFIX.as_bytes(), cash.as_ref(), wallet.as_ref()]
+}
+
+/// Generates the redemption receipt address of a wallet for a cash link
+pub fn find_redemption_address(program_id: &Pubkey, cash: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&redemption_receipt_seeds(cash, wallet), program_id)
+}
+
+/// Generates the address of the n-th (1 based) redemption index entry of a cash link
+pub fn find_redemption_by_index_address(program_id: &Pubkey, cash: &Pubkey, index: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            RedemptionIndex::PREFIX.as_bytes(),
+            cash.as_ref(),
+            &index.to_le_bytes(),
+        ],
+        program_id,
+    )
+}
@@ -0,0 +1,169 @@
+//! Recipient permits for gasless claims
+//!
+//! A recipient that holds no SOL can not sign the redeem transaction itself. It signs a permit
+//! off chain instead and the relayer submits it together with an Ed25519 program instruction
+//! verifying that signature. The program then finds the verified signature through the
+//! instructions sysvar and accepts it in place of the wallet signer.
+//!
+//! The permit message is the domain, the cash link, the wallet, the expiry and the number of
+//! redemptions of the link, so a permit can neither be replayed on another link nor after the
+//! link moved on.
+use arrayref::array_ref;
+use solana_program::{
+    account_info::AccountInfo,
+    ed25519_program,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{
+        clock::Clock,
+        instructions::{self, load_current_index_checked, load_instruction_at_checked},
+    },
+};
+
+use crate::{
+    error::CashError,
+    instruction::RecipientPermit,
+    utils::find_optional_account,
+};
+
+pub const PERMIT_DOMAIN: &[u8] = b"cash-claim";
+
+/// Header of the Ed25519 program data, the signature count and a padding byte
+const ED25519_HEADER_LEN: usize = 2;
+/// Seven u16 offsets per signature
+const ED25519_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+/// An instruction index of the offsets pointing into the Ed25519 instruction itself
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// The message a recipient signs to permit a claim of the link to its wallet
+pub fn permit_message(
+    cash: &Pubkey,
+    wallet: &Pubkey,
+    expiry: u64,
+    total_redemptions: u16,
+) -> Vec<u8> {
+    let mut message = Vec::with_capacity(PERMIT_DOMAIN.len() + 2 * PUBKEY_LEN + 8 + 2);
+    message.extend_from_slice(PERMIT_DOMAIN);
+    message.extend_from_slice(cash.as_ref());
+    message.extend_from_slice(wallet.as_ref());
+    message.extend_from_slice(&expiry.to_le_bytes());
+    message.extend_from_slice(&total_redemptions.to_le_bytes());
+    message
+}
+
+/// Create the Ed25519 program instruction verifying a permit signed off chain, it must come
+/// before the redeem instruction in the same transaction
+pub fn new_permit_ed25519_instruction(
+    wallet: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Instruction {
+    let public_key_offset = ED25519_HEADER_LEN + ED25519_OFFSETS_LEN;
+    let signature_offset = public_key_offset + PUBKEY_LEN;
+    let message_offset = signature_offset + SIGNATURE_LEN;
+
+    let mut data = Vec::with_capacity(message_offset + message.len());
+    data.push(1);
+    data.push(0);
+    for value in [
+        signature_offset as u16,
+        CURRENT_INSTRUCTION,
+        public_key_offset as u16,
+        CURRENT_INSTRUCTION,
+        message_offset as u16,
+        message.len() as u16,
+        CURRENT_INSTRUCTION,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(wallet.as_ref());
+    data.extend_from_slice(signature);
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Check the permit is unexpired and that an earlier instruction of the transaction had the
+/// Ed25519 program verify it for the wallet
+pub fn verify_recipient_permit(
+    accounts: &[AccountInfo],
+    permit: &RecipientPermit,
+    cash: &Pubkey,
+    wallet: &Pubkey,
+    total_redemptions: u16,
+    clock: &Clock,
+) -> ProgramResult {
+    if clock.unix_timestamp as u64 >= permit.expiry {
+        return Err(CashError::RecipientPermitExpired.into());
+    }
+    let instructions_info = find_optional_account(accounts, &instructions::id())
+        .ok_or(CashError::InvalidRecipientPermit)?;
+    let message = permit_message(cash, wallet, permit.expiry, total_redemptions);
+    let current_index = load_current_index_checked(instructions_info)?;
+    for index in 0..current_index {
+        let instruction = load_instruction_at_checked(index as usize, instructions_info)?;
+        if instruction.program_id != ed25519_program::id() {
+            continue;
+        }
+        if ed25519_data_verifies(&instruction.data, wallet, &permit.signature, &message)? {
+            return Ok(());
+        }
+    }
+    msg!("No Ed25519 verification of the permit of wallet {}", wallet);
+    Err(CashError::InvalidRecipientPermit.into())
+}
+
+/// Whether any signature of the Ed25519 program data is the permit signature of the wallet
+/// over the message. Only data inlined in the Ed25519 instruction itself is considered
+fn ed25519_data_verifies(
+    data: &[u8],
+    wallet: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Result<bool, ProgramError> {
+    if data.len() < ED25519_HEADER_LEN {
+        return Ok(false);
+    }
+    let num_signatures = data[0] as usize;
+    for i in 0..num_signatures {
+        let start = ED25519_HEADER_LEN + i * ED25519_OFFSETS_LEN;
+        if data.len() < start + ED25519_OFFSETS_LEN {
+            return Ok(false);
+        }
+        let offsets = array_ref![data, start, ED25519_OFFSETS_LEN];
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+        let (signature_offset, signature_index) = (read(0) as usize, read(2));
+        let (public_key_offset, public_key_index) = (read(4) as usize, read(6));
+        let (message_offset, message_size, message_index) =
+            (read(8) as usize, read(10) as usize, read(12));
+        if signature_index != CURRENT_INSTRUCTION
+            || public_key_index != CURRENT_INSTRUCTION
+            || message_index != CURRENT_INSTRUCTION
+        {
+            continue;
+        }
+        let (Some(signed_key), Some(signed_signature), Some(signed_message)) = (
+            data.get(public_key_offset..public_key_offset + PUBKEY_LEN),
+            data.get(signature_offset..signature_offset + SIGNATURE_LEN),
+            data.get(message_offset..message_offset + message_size),
+        ) else {
+            continue;
+        };
+        if signed_key == wallet.as_ref()
+            && signed_signature == signature.as_ref()
+            && signed_message == message
+        {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
@@ -3,7 +3,26 @@ use crate::instruction::CashInstruction;
 
 use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg, pubkey::Pubkey};
 
+pub mod approval;
+pub mod auto_cancel;
+pub mod buffer;
 pub mod cash;
+pub mod clawback;
+pub mod commit;
+pub mod config;
+pub mod emergency;
+pub mod entropy;
+pub mod fee_wallet;
+pub mod metadata;
+pub mod migration;
+pub mod mint_registry;
+pub mod recovery;
+pub mod redemption;
+pub mod referral;
+pub mod split;
+pub mod stats;
+pub mod swap;
+pub mod wallet_limit;
 
 
 pub struct Processor;
@@ -24,7 +43,7 @@ impl Processor {
             }
             CashInstruction::Redeem(args) => {
                 msg!("Instruction: Redeem Cash");
-                cash::process_redemption(accounts, args, program_id)
+                redemption::process_redemption(accounts, args, program_id)
             }
             CashInstruction::Cancel(args) => {
                 msg!("Instruction: Cancel Cash");
@@ -34,6 +53,162 @@ impl Processor {
                 msg!("Instruction: Close");
                 cash::process_close(accounts,  program_id)
             }
+            CashInstruction::InitConfig(args) => {
+                msg!("Instruction: InitConfig");
+                config::process_init_config(accounts, args, program_id)
+            }
+            CashInstruction::AddMint(args) => {
+                msg!("Instruction: AddMint");
+                mint_registry::process_add_mint(accounts, args, program_id)
+            }
+            CashInstruction::RemoveMint => {
+                msg!("Instruction: RemoveMint");
+                mint_registry::process_remove_mint(accounts, program_id)
+            }
+            CashInstruction::SetMintMode(args) => {
+                msg!("Instruction: SetMintMode");
+                mint_registry::process_set_mint_mode(accounts, args, program_id)
+            }
+            CashInstruction::InitStats(args) => {
+                msg!("Instruction: InitStats");
+                stats::process_init_stats(accounts, args, program_id)
+            }
+            CashInstruction::CreateBuffer(args) => {
+                msg!("Instruction: CreateBuffer");
+                buffer::process_create_buffer(accounts, args, program_id)
+            }
+            CashInstruction::WriteBufferChunk(args) => {
+                msg!("Instruction: WriteBufferChunk");
+                buffer::process_write_buffer_chunk(accounts, args, program_id)
+            }
+            CashInstruction::FinalizeBuffer(args) => {
+                msg!("Instruction: FinalizeBuffer");
+                buffer::process_finalize_buffer(accounts, args, program_id)
+            }
+            CashInstruction::UpdateConfig(args) => {
+                msg!("Instruction: UpdateConfig");
+                config::process_update_config(accounts, args, program_id)
+            }
+            CashInstruction::TransferAuthority => {
+                msg!("Instruction: TransferAuthority");
+                cash::process_transfer_authority(accounts, program_id)
+            }
+            CashInstruction::ValidateRedemptions(args) => {
+                msg!("Instruction: ValidateRedemptions");
+                redemption::process_validate_redemptions(accounts, args, program_id)
+            }
+            CashInstruction::UpdateMetadata(args) => {
+                msg!("Instruction: UpdateMetadata");
+                metadata::process_update_metadata(accounts, args, program_id)
+            }
+            CashInstruction::CancelBegin(args) => {
+                msg!("Instruction: CancelBegin");
+                cash::process_cancel_begin(accounts, program_id, args)
+            }
+            CashInstruction::CancelFinalize(args) => {
+                msg!("Instruction: CancelFinalize");
+                cash::process_cancel_finalize(accounts, program_id, args)
+            }
+            CashInstruction::EmergencyHalt => {
+                msg!("Instruction: EmergencyHalt");
+                emergency::process_emergency_halt(accounts, program_id)
+            }
+            CashInstruction::EmergencyRelease => {
+                msg!("Instruction: EmergencyRelease");
+                emergency::process_emergency_release(accounts, program_id)
+            }
+            CashInstruction::EmergencyWithdraw(args) => {
+                msg!("Instruction: EmergencyWithdraw");
+                emergency::process_emergency_withdraw(accounts, program_id, args)
+            }
+            CashInstruction::ExpireSlot => {
+                msg!("Instruction: ExpireSlot");
+                cash::process_expire_slot(accounts, program_id)
+            }
+            CashInstruction::RotatePassKey(args) => {
+                msg!("Instruction: RotatePassKey");
+                cash::process_rotate_pass_key(accounts, program_id, args)
+            }
+            CashInstruction::InitiateRecovery => {
+                msg!("Instruction: InitiateRecovery");
+                recovery::process_initiate_recovery(accounts, program_id)
+            }
+            CashInstruction::ExecuteRecovery(args) => {
+                msg!("Instruction: ExecuteRecovery");
+                recovery::process_execute_recovery(accounts, program_id, args)
+            }
+            CashInstruction::CloseReferralStats => {
+                msg!("Instruction: CloseReferralStats");
+                referral::process_close_referral_stats(accounts, program_id)
+            }
+            CashInstruction::SetDisabledFeatures(args) => {
+                msg!("Instruction: SetDisabledFeatures");
+                config::process_set_disabled_features(accounts, args, program_id)
+            }
+            CashInstruction::CommitRedemption(args) => {
+                msg!("Instruction: CommitRedemption");
+                commit::process_commit_redemption(accounts, args, program_id)
+            }
+            CashInstruction::SweepCommit => {
+                msg!("Instruction: SweepCommit");
+                commit::process_sweep_commit(accounts, program_id)
+            }
+            CashInstruction::SplitCash(args) => {
+                msg!("Instruction: SplitCash");
+                split::process_split_cash(accounts, args, program_id)
+            }
+            CashInstruction::BackfillVersion(args) => {
+                msg!("Instruction: BackfillVersion");
+                migration::process_backfill_version(accounts, args, program_id)
+            }
+            CashInstruction::ApproveRedemption(args) => {
+                msg!("Instruction: ApproveRedemption");
+                approval::process_approve_redemption(accounts, args, program_id)
+            }
+            CashInstruction::RejectRedemption => {
+                msg!("Instruction: RejectRedemption");
+                approval::process_reject_redemption(accounts, program_id)
+            }
+            CashInstruction::AddFeeWallet(args) => {
+                msg!("Instruction: AddFeeWallet");
+                fee_wallet::process_add_fee_wallet(accounts, args, program_id)
+            }
+            CashInstruction::SupersedeFeeWallet(args) => {
+                msg!("Instruction: SupersedeFeeWallet");
+                fee_wallet::process_supersede_fee_wallet(accounts, args, program_id)
+            }
+            CashInstruction::ClaimReferralFees(args) => {
+                msg!("Instruction: ClaimReferralFees");
+                referral::process_claim_referral_fees(accounts, args, program_id)
+            }
+            CashInstruction::FlagReferrer(args) => {
+                msg!("Instruction: FlagReferrer");
+                referral::process_flag_referrer(accounts, args, program_id)
+            }
+            CashInstruction::MigrateBatch(args) => {
+                msg!("Instruction: MigrateBatch");
+                migration::process_migrate_batch(accounts, args, program_id)
+            }
+            CashInstruction::ProcessAutoCancelQueue(args) => {
+                msg!("Instruction: ProcessAutoCancelQueue");
+                auto_cancel::process_auto_cancel_queue(accounts, args, program_id)
+            }
+            CashInstruction::SetMintCap(args) => {
+                msg!("Instruction: SetMintCap");
+                mint_registry::process_set_mint_cap(accounts, args, program_id)
+            }
+            CashInstruction::CloseStaleWalletLimits => {
+                msg!("Instruction: CloseStaleWalletLimits");
+                wallet_limit::process_close_stale_wallet_limits(accounts, program_id)
+            }
+            CashInstruction::ClawbackRedemption(args) => {
+                msg!("Instruction: ClawbackRedemption");
+                clawback::process_clawback_redemption(accounts, args, program_id)
+            }
+            CashInstruction::RevokeClawback(args) => {
+                msg!("Instruction: RevokeClawback");
+                clawback::process_revoke_clawback(accounts, args, program_id)
+            }
         }
     }
 }
@@ -0,0 +1,237 @@
+//! Two-phase redemptions of links with an approval threshold
+//!
+//! A redemption whose payout meets the threshold of its link does not move any funds: the
+//! payout is reserved on the link and written to a pending redemption pda of the wallet. The
+//! authority and the owner then approve it, paying exactly the reserved payout with the fees
+//! computed as for any redemption, or reject it, releasing the reservation back to the link.
+//! Once the approval ttl of the link elapsed a request can only be rejected, by anyone.
+use crate::{
+    error::CashError,
+    event::{ApprovalRejectedEvent, ApprovalRequestedEvent, CashEvent},
+    find_pending_redemption_address,
+    instruction::InitCashRedemptionArgs,
+    processor::{
+        config::find_config,
+        redemption::{
+            assert_redemption_features, assert_vault_invariant, attach_referral_escrow,
+            commit_state, execute_plan, plan_redemption, validate_accounts, RedemptionContext,
+            RedemptionPlan,
+        },
+    },
+    state::{
+        cash::Cash,
+        config::min_payout_for_mint,
+        pending::PendingRedemption,
+        AccountType,
+    },
+    utils::{
+        assert_account_key, assert_owned_by, assert_signer, cmp_pubkeys, create_new_account_raw,
+        empty_account_balance, find_optional_account, load_account, store_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::Sysvar,
+};
+
+/// Reserve the planned payout and record the request instead of paying it
+pub fn request_approval<'a, 'b>(
+    program_id: &Pubkey,
+    ctx: RedemptionContext<'a, 'b>,
+    pending_info: &'b AccountInfo<'a>,
+    args: &InitCashRedemptionArgs,
+    plan: &RedemptionPlan,
+) -> ProgramResult {
+    // the receipt is only created on approval, a wallet that redeemed can not request again
+    if ctx.redemption_info.lamports() > 0 && !ctx.redemption_info.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let (_, pending_bump) =
+        find_pending_redemption_address(program_id, ctx.cash_info.key, ctx.wallet_info.key);
+    create_new_account_raw(
+        program_id,
+        pending_info,
+        ctx.rent_info,
+        ctx.fee_payer_info,
+        ctx.system_program_info,
+        PendingRedemption::LEN,
+        &[
+            PendingRedemption::PREFIX.as_bytes(),
+            ctx.cash_info.key.as_ref(),
+            ctx.wallet_info.key.as_ref(),
+            &[pending_bump],
+        ],
+    )?;
+    let (referrer_fee_bps, referee_fee_bps, _) = ctx
+        .cash
+        .clamp_referral_fee_bps(args.referrer_fee_bps, args.referee_fee_bps)?;
+    let requested_at = ctx.clock.unix_timestamp as u64;
+    PendingRedemption::pack(
+        PendingRedemption {
+            account_type: AccountType::PendingRedemption,
+            cash: *ctx.cash_info.key,
+            wallet: *ctx.wallet_info.key,
+            rent_payer: *ctx.fee_payer_info.key,
+            amount: plan.payout,
+            requested_at,
+            referrer_fee_bps,
+            referee_fee_bps,
+            priority_fee_reimbursement: args.priority_fee_reimbursement,
+        },
+        &mut pending_info.try_borrow_mut_data()?,
+    )?;
+
+    let mut cash = ctx.cash;
+    cash.reserve(plan.payout)?;
+    if let Some(round) = plan.entropy_round {
+        cash.last_entropy_round = round;
+    }
+    cash.clear_recovery();
+    msg!("Redemption of {} waits for approval", plan.payout);
+    ApprovalRequestedEvent {
+        cash: *ctx.cash_info.key,
+        wallet: *ctx.wallet_info.key,
+        amount: plan.payout,
+        requested_at,
+    }
+    .emit();
+    store_account(cash, ctx.cash_info)
+}
+
+pub fn process_approve_redemption(
+    accounts: &[AccountInfo],
+    args: InitCashRedemptionArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process approve redemption");
+    let mut ctx = validate_accounts(accounts, &args, program_id)?;
+    // the authority signature is checked with the accounts, the owner co-signs the approval
+    assert_signer(ctx.owner_wallet_info)?;
+    let config = find_config(accounts, program_id)?.ok_or(CashError::InvalidConfigKey)?;
+    assert_redemption_features(&config, &ctx, &args)?;
+    attach_referral_escrow(accounts, program_id, &config, &mut ctx)?;
+
+    let pending_info =
+        find_pending_redemption(accounts, program_id, ctx.cash_info.key, ctx.wallet_info.key)
+            .ok_or(CashError::NoPendingApproval)?;
+    let pending = load_pending(pending_info, program_id)?;
+    if pending.expired(ctx.cash.approval_ttl, ctx.clock.unix_timestamp as u64) {
+        return Err(CashError::ApprovalExpired.into());
+    }
+    // the referral accounts were validated against the args, they must match the request
+    if args.referrer_fee_bps.is_some() != pending.referrer_fee_bps.is_some() {
+        return Err(CashError::InvalidReferralFees.into());
+    }
+    let mut args = args;
+    args.referrer_fee_bps = pending.referrer_fee_bps;
+    args.referee_fee_bps = pending.referee_fee_bps;
+    args.priority_fee_reimbursement = pending.priority_fee_reimbursement;
+
+    ctx.cash.release(pending.amount)?;
+    let mut plan = plan_redemption(
+        &ctx.cash,
+        &args,
+        0,
+        ctx.vault_amount,
+        ctx.is_native,
+        ctx.recipient_token_exists,
+        min_payout_for_mint(ctx.decimals, config.max_dust_decimals),
+        Some(pending.amount),
+    )?;
+    assert_vault_invariant(&ctx.cash, &plan, ctx.vault_amount)?;
+    // flag the link before the first cpi, commit_state clears it with the rest of the state
+    let mut busy = ctx.cash.clone();
+    busy.in_progress = true;
+    store_account(busy, ctx.cash_info)?;
+    let rent_paid = execute_plan(accounts, &ctx, &mut plan, &args, program_id)?;
+    let rent_payer_info =
+        find_optional_account(accounts, &pending.rent_payer).ok_or(CashError::InvalidFeePayer)?;
+    close_pending(pending_info, rent_payer_info)?;
+    commit_state(accounts, program_id, ctx, &plan, rent_paid, args.voucher_hash)
+}
+
+/// Release the payout of a pending redemption back to the link and close the request
+pub fn process_reject_redemption(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process reject redemption");
+    let account_info_iter = &mut accounts.iter();
+    let signer_info = next_account_info(account_info_iter)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let pending_info = next_account_info(account_info_iter)?;
+    let rent_payer_info = next_account_info(account_info_iter)?;
+
+    let pending = load_pending(pending_info, program_id)?;
+    assert_account_key(
+        rent_payer_info,
+        &pending.rent_payer,
+        Some(CashError::InvalidFeePayer),
+        Some("rent payer"),
+    )?;
+    assert_account_key(cash_info, &pending.cash, Some(CashError::InvalidCashKey), Some("cash"))?;
+    // nothing is left to release on a closed link
+    if !cmp_pubkeys(cash_info.owner, program_id) || cash_info.data_is_empty() {
+        return close_pending(pending_info, rent_payer_info);
+    }
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let expired = pending.expired(cash.approval_ttl, now);
+    let approver = signer_info.is_signer
+        && (cmp_pubkeys(signer_info.key, &cash.authority)
+            || cmp_pubkeys(signer_info.key, &cash.owner));
+    if !approver && !expired && !cash.canceled() {
+        return Err(CashError::InvalidAuthorityId.into());
+    }
+    cash.release(pending.amount)?;
+    msg!("Released {} reserved for {}", pending.amount, pending.wallet);
+    CashEvent::ApprovalRejected(ApprovalRejectedEvent {
+        cash: *cash_info.key,
+        wallet: pending.wallet,
+        amount: pending.amount,
+        rejected_by: *signer_info.key,
+        expired,
+        timestamp: now,
+    })
+    .emit();
+    store_account(cash, cash_info)?;
+    close_pending(pending_info, rent_payer_info)
+}
+
+/// The pending redemption pda of a wallet among the accounts
+pub fn find_pending_redemption<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+    cash_key: &Pubkey,
+    wallet_key: &Pubkey,
+) -> Option<&'b AccountInfo<'a>> {
+    let (pending_key, _) = find_pending_redemption_address(program_id, cash_key, wallet_key);
+    find_optional_account(accounts, &pending_key)
+}
+
+fn load_pending(
+    pending_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<PendingRedemption, ProgramError> {
+    if pending_info.data_is_empty() {
+        return Err(CashError::NoPendingApproval.into());
+    }
+    assert_owned_by(pending_info, program_id, Some("pending"))?;
+    let pending = PendingRedemption::unpack_unchecked(&pending_info.try_borrow_data()?)?;
+    if !pending.is_initialized() {
+        return Err(CashError::NoPendingApproval.into());
+    }
+    Ok(pending)
+}
+
+fn close_pending(pending_info: &AccountInfo, rent_payer_info: &AccountInfo) -> ProgramResult {
+    empty_account_balance(pending_info, rent_payer_info)?;
+    pending_info.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! Scheduled cancels of cash links
+//!
+//! An owner opting into auto-cancel at init queues its link with the time it falls due. Once
+//! due anyone may crank the queue: each live link is canceled as its authority would, the
+//! cranker is tipped out of the fee reserve of the link and the entry is removed. Links that
+//! were canceled or fully redeemed in the meantime are dequeued without a tip.
+use crate::{
+    error::CashError,
+    find_auto_cancel_queue_program_address,
+    instruction::{ProcessAutoCancelQueueArgs, MAX_AUTO_CANCEL_BATCH},
+    processor::{
+        cash::{cancel_link, CancelAccounts},
+        config::assert_features_enabled,
+    },
+    state::{auto_cancel::AutoCancelQueue, cash::Cash, config::Feature},
+    utils::{
+        assert_account_key, assert_distinct_token_accounts, assert_owned_by, assert_signer,
+        assert_valid_token_program, create_new_account_raw, find_optional_account, load_account,
+        load_token_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+/// Cancel the due links of a batch, see `CashInstruction::ProcessAutoCancelQueue`
+pub fn process_auto_cancel_queue(
+    accounts: &[AccountInfo],
+    args: ProcessAutoCancelQueueArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process auto-cancel queue");
+    assert_features_enabled(accounts, program_id, &[Feature::Cancel])?;
+    if args.links.len() > MAX_AUTO_CANCEL_BATCH as usize {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let account_info_iter = &mut accounts.iter();
+    let cranker_info = next_account_info(account_info_iter)?;
+    assert_signer(cranker_info)?;
+    let queue_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(token_program_info.key)?;
+
+    let mut queue = load_auto_cancel_queue(queue_info, program_id)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let mut canceled: u8 = 0;
+    for link_args in args.links.iter() {
+        let cash_info = next_account_info(account_info_iter)?;
+        let owner_info = next_account_info(account_info_iter)?;
+        let owner_token_info = next_account_info(account_info_iter)?;
+        let fee_payer_info = next_account_info(account_info_iter)?;
+        let vault_token_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let cranker_token_info = next_account_info(account_info_iter)?;
+
+        let position = match queue.position(cash_info.key) {
+            Some(position) => position,
+            None => {
+                msg!("Link {} is not queued, skipped", cash_info.key);
+                continue;
+            }
+        };
+        assert_owned_by(cash_info, program_id, Some("cash"))?;
+        let signer_seeds = [
+            Cash::PREFIX.as_bytes(),
+            link_args.cash_reference.as_bytes(),
+            &[link_args.cash_bump],
+        ];
+        let cash_key = Pubkey::create_program_address(&signer_seeds, program_id)?;
+        assert_account_key(
+            cash_info,
+            &cash_key,
+            Some(CashError::InvalidCashKey),
+            Some("cash"),
+        )?;
+        let cash = load_account::<Cash>(cash_info)?;
+        // a link canceled or redeemed out of band is done, its entry only has to go
+        if cash.canceled() || cash.redeemed() {
+            msg!("Link {} is terminal, dequeued", cash_info.key);
+            queue.remove(position);
+            continue;
+        }
+        let due_at = queue.get(position).map_or(u64::MAX, |entry| entry.due_at);
+        if due_at > now {
+            msg!("Link {} is due at {}, skipped", cash_info.key, due_at);
+            continue;
+        }
+        if cash.in_progress || cash.halted || cash.cancel_pending() {
+            msg!("Link {} can not be canceled now, skipped", cash_info.key);
+            continue;
+        }
+        assert_account_key(
+            mint_info,
+            &cash.mint,
+            Some(CashError::InvalidMint),
+            Some("mint"),
+        )?;
+        assert_distinct_token_accounts(&[
+            (vault_token_info.key, cash_info.key),
+            (owner_token_info.key, owner_info.key),
+            (cranker_token_info.key, cranker_info.key),
+        ])?;
+        let tip = if cash.auto_cancel_tip > 0 {
+            load_token_account(
+                cranker_token_info,
+                cranker_info.key,
+                &cash.mint,
+                token_program_info.key,
+                "cranker token",
+            )?;
+            Some((cranker_token_info, cash.auto_cancel_tip))
+        } else {
+            None
+        };
+        let link = CancelAccounts {
+            cash_info,
+            owner_info,
+            owner_token_info,
+            fee_payer_info,
+            vault_token_info,
+            mint_info,
+            token_program_info,
+        };
+        cancel_link(accounts, program_id, &link, cash, &signer_seeds, false, tip)?;
+        queue.remove(position);
+        canceled += 1;
+    }
+    msg!("Canceled {} of {} links", canceled, args.links.len());
+    AutoCancelQueue::pack(queue, &mut queue_info.data.borrow_mut())?;
+    set_return_data(&[canceled]);
+    Ok(())
+}
+
+/// Queue a link being initialized for the crank, the queue is created by the first link
+/// opting in
+pub fn enqueue_auto_cancel<'a>(
+    accounts: &[AccountInfo<'a>],
+    program_id: &Pubkey,
+    cash_key: &Pubkey,
+    due_at: u64,
+    payer_info: &AccountInfo<'a>,
+    rent_sysvar_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (queue_key, queue_bump) = find_auto_cancel_queue_program_address(program_id);
+    let queue_info =
+        find_optional_account(accounts, &queue_key).ok_or(CashError::MissingAutoCancelQueue)?;
+    let mut queue = if queue_info.lamports() > 0 && !queue_info.data_is_empty() {
+        load_auto_cancel_queue(queue_info, program_id)?
+    } else {
+        create_new_account_raw(
+            program_id,
+            queue_info,
+            rent_sysvar_info,
+            payer_info,
+            system_program_info,
+            AutoCancelQueue::LEN,
+            &[AutoCancelQueue::PREFIX.as_bytes(), &[queue_bump]],
+        )?;
+        AutoCancelQueue::new()
+    };
+    queue.push(*cash_key, due_at)?;
+    msg!("Link {} is queued for auto-cancel at {}", cash_key, due_at);
+    AutoCancelQueue::pack(queue, &mut queue_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Load the initialized auto-cancel queue owned by the program
+pub fn load_auto_cancel_queue(
+    queue_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<AutoCancelQueue, ProgramError> {
+    assert_owned_by(queue_info, program_id, Some("auto-cancel queue"))?;
+    let queue = AutoCancelQueue::unpack_unchecked(&queue_info.data.borrow())?;
+    if !queue.is_initialized() {
+        return Err(CashError::MissingAutoCancelQueue.into());
+    }
+    Ok(queue)
+}
@@ -0,0 +1,212 @@
+use crate::{
+    error::CashError,
+    event::ParamsHashEvent,
+    instruction::{CreateBufferArgs, FinalizeBufferArgs, WriteBufferChunkArgs},
+    state::{
+        buffer::{Buffer, BUFFER_HEADER_SIZE, MAX_BUFFER_DATA_SIZE},
+        cash::{Cash, CashState},
+        AccountType, AuxiliaryHeader,
+    },
+    utils::{
+        assert_account_key, assert_owned_by, assert_signer, create_new_account_raw, load_account,
+        store_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    hash::hashv,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+pub fn process_create_buffer(
+    accounts: &[AccountInfo],
+    args: CreateBufferArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process create buffer");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let buffer_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if args.size == 0 || args.size as usize > MAX_BUFFER_DATA_SIZE {
+        return Err(CashError::BufferOutOfBounds.into());
+    }
+    if buffer_info.lamports() > 0 && !buffer_info.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_new_account_raw(
+        program_id,
+        buffer_info,
+        rent_info,
+        fee_payer_info,
+        system_program_info,
+        BUFFER_HEADER_SIZE + args.size as usize,
+        &[
+            Buffer::PREFIX.as_bytes(),
+            args.cash_reference.as_bytes(),
+            &[args.buffer_bump],
+        ],
+    )?;
+    let buffer = Buffer {
+        header: AuxiliaryHeader::new(fee_payer_info.key),
+        account_type: AccountType::Buffer,
+        authority: *authority_info.key,
+        data_len: args.size,
+        ..Default::default()
+    };
+    Buffer::pack(buffer, &mut buffer_info.data.borrow_mut()[..BUFFER_HEADER_SIZE])?;
+    Ok(())
+}
+
+pub fn process_write_buffer_chunk(
+    accounts: &[AccountInfo],
+    args: WriteBufferChunkArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process write buffer chunk");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let buffer_info = next_account_info(account_info_iter)?;
+    let buffer = load_buffer(buffer_info, program_id)?;
+    assert_buffer_authority(&buffer, authority_info)?;
+    if buffer.finalized {
+        return Err(CashError::BufferFinalized.into());
+    }
+    let mut data = buffer_info.data.borrow_mut();
+    let start = BUFFER_HEADER_SIZE
+        .checked_add(args.offset as usize)
+        .ok_or(CashError::Overflow)?;
+    let end = start
+        .checked_add(args.data.len())
+        .ok_or(CashError::Overflow)?;
+    if end > BUFFER_HEADER_SIZE + buffer.data_len as usize {
+        return Err(CashError::BufferOutOfBounds.into());
+    }
+    data[start..end].copy_from_slice(&args.data);
+    Ok(())
+}
+
+pub fn process_finalize_buffer(
+    accounts: &[AccountInfo],
+    args: FinalizeBufferArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process finalize buffer");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    let buffer_info = next_account_info(account_info_iter)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let mut buffer = load_buffer(buffer_info, program_id)?;
+    assert_buffer_authority(&buffer, authority_info)?;
+    if buffer.finalized {
+        return Err(CashError::BufferFinalized.into());
+    }
+
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+    let cash_key = Pubkey::create_program_address(
+        &[
+            Cash::PREFIX.as_bytes(),
+            args.cash_reference.as_bytes(),
+            &[args.cash_bump],
+        ],
+        program_id,
+    )?;
+    assert_account_key(cash_info, &cash_key, Some(CashError::InvalidBufferKey), Some("cash"))?;
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    // the schedule can not change once the link started paying out
+    if cash.state != CashState::Initialized || cash.total_redemptions > 0 {
+        return Err(CashError::AccountAlreadyRedeemed.into());
+    }
+
+    let hash = {
+        let data = buffer_info.data.borrow();
+        hashv(&[&Buffer::data(&data)[..buffer.data_len as usize]]).to_bytes()
+    };
+    match cash.buffer_hash {
+        Some(expected) if expected != hash => return Err(CashError::BufferHashMismatch.into()),
+        _ => cash.buffer_hash = Some(hash),
+    }
+    if cash.params_hash.is_some() {
+        cash.update_params_hash();
+        let now = Clock::get()?.unix_timestamp as u64;
+        ParamsHashEvent::emit_for(cash_info.key, &cash, now);
+    }
+    buffer.finalized = true;
+    buffer.cash = *cash_info.key;
+    buffer.hash = hash;
+    Buffer::pack(buffer, &mut buffer_info.data.borrow_mut()[..BUFFER_HEADER_SIZE])?;
+    store_account(cash, cash_info)?;
+    Ok(())
+}
+
+/// Load the header of a buffer account
+pub fn load_buffer(buffer_info: &AccountInfo, program_id: &Pubkey) -> Result<Buffer, ProgramError> {
+    assert_owned_by(buffer_info, program_id, Some("buffer"))?;
+    let data = buffer_info.data.borrow();
+    if data.len() < BUFFER_HEADER_SIZE {
+        return Err(CashError::InvalidBufferKey.into());
+    }
+    let buffer = Buffer::unpack_unchecked(&data[..BUFFER_HEADER_SIZE])?;
+    if !buffer.is_initialized() {
+        return Err(CashError::InvalidBufferKey.into());
+    }
+    Ok(buffer)
+}
+
+/// Find the finalized buffer of a cash link among the accounts, its content must match the
+/// hash recorded on the link
+pub fn find_cash_buffer<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+    cash_key: &Pubkey,
+    cash: &Cash,
+) -> Result<Option<&'b AccountInfo<'a>>, ProgramError> {
+    let expected_hash = match cash.buffer_hash {
+        Some(hash) => hash,
+        None => return Ok(None),
+    };
+    for account in accounts.iter().filter(|account| account.owner == program_id) {
+        let buffer = match load_buffer(account, program_id) {
+            Ok(buffer) => buffer,
+            Err(_) => continue,
+        };
+        if buffer.cash != *cash_key {
+            continue;
+        }
+        if !buffer.finalized {
+            return Err(CashError::BufferNotFinalized.into());
+        }
+        if buffer.hash != expected_hash {
+            return Err(CashError::BufferHashMismatch.into());
+        }
+        return Ok(Some(account));
+    }
+    Ok(None)
+}
+
+fn assert_buffer_authority(buffer: &Buffer, authority_info: &AccountInfo) -> ProgramResult {
+    assert_signer(authority_info)?;
+    assert_account_key(
+        authority_info,
+        &buffer.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )
+}
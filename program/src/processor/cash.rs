@@ -1,31 +1,59 @@
 use crate::{
+    core_math,
     error::CashError::{
         self, AccountAlreadyCanceled, AccountAlreadyRedeemed, AccountNotCanceled,
-        InsufficientSettlementFunds,
     },
-    instruction::{CancelCashRedemptionArgs, InitCashArgs, InitCashRedemptionArgs},
+    event::{CompletionEvent, LedgerEvent, LegKind, ParamsHashEvent},
+    fees::{compute_priority_reserve, compute_total_reserve, validate_schedule, FeeSchedule},
+    instruction::{CancelBeginArgs, CancelCashRedemptionArgs, InitCashArgs, RotatePassKeyArgs},
+    find_config_program_address,
     math::SafeMath,
+    rounding::{floor_div, floor_share},
+    processor::{
+        auto_cancel::enqueue_auto_cancel,
+        buffer::{find_cash_buffer, load_buffer},
+        config::{
+            assert_features_enabled, find_config, init_limits, load_config, max_dust_decimals,
+        },
+        fee_wallet::find_latest_fee_wallet,
+        metadata::{create_cash_metadata, find_cash_metadata, load_cash_metadata},
+        mint_registry::assert_mint_allowed,
+        stats::update_stats,
+        swap::validate_swap_config,
+    },
     state::{
-        cash::{Cash, CashState, DistributionType},
-        AccountType, FLAG_ACCOUNT_SIZE, REDEMPTION_PREFIX,
+        cash::{
+            Cash, CashState, CompletionReason, DistributionType, EntropySource,
+            MAX_CLAWBACK_WINDOW,
+        },
+        commit::MAX_COMMIT_REVEAL_DELAY,
+        config::{min_payout_for_mint, Feature},
+        AccountType, AuxiliaryHeader,
     },
     utils::{
         assert_account_key, assert_initialized, assert_owned_by, assert_signer,
-        assert_token_owned_by, assert_valid_token_program, calculate_fee, cmp_pubkeys,
-        create_associated_token_account_raw, create_new_account_raw, empty_account_balance, exists,
-        get_random_value, native_transfer, spl_token_close, spl_token_transfer,
-        sync_native,
+        assert_token_account, assert_valid_token_program, cmp_pubkeys,
+        load_token_account, create_associated_token_account_raw, create_new_account_raw, empty_account_balance, exists,
+        native_transfer, spl_token_close, spl_token_transfer,
+        sync_native, transfer_account_lamports, find_optional_account, unwrap_native_vault,
+        transfer_if_nonzero,
+        mint_supports_burn, load_account, store_account, assert_distinct_token_accounts,
+        is_native_mint, assert_not_frozen, assert_mint_unchanged, err_ctx,
     },
+    PROGRAM_VERSION,
 };
 
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     msg,
+    program::set_return_data,
     program_error::ProgramError,
-    program_pack::Pack,
+    program_pack::{IsInitialized, Pack},
     pubkey::Pubkey,
-    sysvar::{clock::Clock, slot_hashes, Sysvar},
+    system_program,
+    sysvar::{clock::Clock, Sysvar},
 };
 use spl_associated_token_account::get_associated_token_address_with_program_id;
 use spl_token_2022::state::{Account as TokenAccount, Mint};
@@ -34,13 +62,16 @@ pub struct Processor;
 
 pub fn process_init(
     accounts: &[AccountInfo],
-    args: InitCashArgs,
+    mut args: InitCashArgs,
     program_id: &Pubkey,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let authority_info = next_account_info(account_info_iter)?;
     assert_signer(authority_info)?;
     let owner_info = next_account_info(account_info_iter)?;
+    // every link is funded by its owner in this instruction, the refunds of the link go to the
+    // owner so its claim is checked up front rather than left to the funding transfer
+    assert_signer(owner_info)?;
     let fee_payer_info = next_account_info(account_info_iter)?;
     let fee_payer_token_info = next_account_info(account_info_iter)?;
     let cash_info = next_account_info(account_info_iter)?;
@@ -57,6 +88,56 @@ pub fn process_init(
     let system_account_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
     assert_valid_token_program(&token_program_info.key)?;
+    // an owner acting as its own authority is a permissionless init, the fees come from the config
+    let permissionless = cmp_pubkeys(authority_info.key, owner_info.key);
+    let (fee_wallet, fee_wallet_epoch) = if permissionless {
+        let (config_key, _) = find_config_program_address(program_id);
+        let config = find_optional_account(accounts, &config_key)
+            .map(|config_info| load_config(config_info, program_id))
+            .transpose()?
+            .filter(|config| config.permissionless_init)
+            .ok_or(CashError::PermissionlessInitDisabled)?;
+        msg!("Permissionless init, fee args are replaced by the config defaults");
+        config.fees.apply(&mut args);
+        // links of the registry era follow the epoch, a rotated wallet reaches them
+        match find_latest_fee_wallet(accounts, program_id)? {
+            Some(entry) => (Some(entry.wallet), Some(entry.epoch)),
+            None => (Some(config.fee_wallet), None),
+        }
+    } else {
+        (None, None)
+    };
+    let mut features = vec![Feature::Init];
+    if args.distribution_type == DistributionType::Random {
+        features.push(Feature::RandomDistribution);
+    }
+    if is_native_mint(mint_info.key) {
+        features.push(Feature::NativeMint);
+    }
+    if args.swap.is_some() {
+        features.push(Feature::Swap);
+    }
+    assert_features_enabled(accounts, program_id, &features)?;
+    validate_schedule(&FeeSchedule::from_args(&args), args.burn_bps)?;
+    if args.burn_bps > 0 && !mint_supports_burn(mint_info)? {
+        return err_ctx!(CashError::BurnNotSupportedForMint, "mint", mint_info);
+    }
+    if let Some(swap) = args.swap.as_ref() {
+        validate_swap_config(swap, mint_info.key, find_config(accounts, program_id)?.as_ref())?;
+    }
+    assert_mint_allowed(accounts, program_id, mint_info, permissionless)?;
+    // a backend retrying an init that already landed gets an acknowledgment, not an error
+    if acknowledge_init_retry(
+        program_id,
+        &args,
+        cash_info,
+        owner_info.key,
+        mint_info.key,
+        pass_info.map(|pass| pass.key),
+        vault_token_info,
+    )? {
+        return Ok(());
+    }
     let mut cash = create_cash_link(
         program_id,
         cash_info,
@@ -70,54 +151,118 @@ pub fn process_init(
         ],
     )?;
     if args.amount == 0 {
-        return Err(CashError::InvalidAmount.into());
+        return err_ctx!(CashError::InvalidAmount, "cash", cash_info);
     }
     if args.max_num_redemptions == 0 {
-        return Err(CashError::InvalidNumberOfRedemptions.into());
+        return err_ctx!(CashError::InvalidNumberOfRedemptions, "cash", cash_info);
+    }
+    let (max_redemptions, max_fee_reserve_bps) = init_limits(accounts, program_id)?;
+    if args.max_num_redemptions > max_redemptions {
+        msg!("A link can have at most {} redemptions", max_redemptions);
+        return err_ctx!(CashError::TooManyRedemptions, "cash", cash_info);
     }
-    let total_platform_fee = calculate_fee(args.amount, args.fee_bps as u64)?;
-
-    // let total_platform_fee = fee_from_bps
-    //     .checked_add(args.network_fee)
-    //     .ok_or::<ProgramError>(CashError::Overflow.into())?;
-
-    let total_redemption_fee = args
-        .base_fee_to_redeem
-        .checked_add(args.rent_fee_to_redeem)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?
-        .checked_mul(args.max_num_redemptions as u64)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?;
-
     let total_amount = match args.distribution_type {
         DistributionType::Fixed => {
             if args.amount % args.max_num_redemptions as u64 != 0 {
-                return Err(CashError::InvalidAmount.into());
+                return err_ctx!(CashError::InvalidAmount, "cash", cash_info);
             }
             args.amount
         }
         _ => args.amount,
     };
+    let mint: Mint = assert_initialized(mint_info, Some("mint"))?;
+    if mint.freeze_authority.is_some() && !args.allow_freezable_mint {
+        return err_ctx!(CashError::FreezableMintNotAllowed, "mint", mint_info);
+    }
     if args.distribution_type == DistributionType::Random {
         if args.min_amount.is_none() {
-            return Err(CashError::MinAmountNotSet.into());
+            return err_ctx!(CashError::MinAmountNotSet, "cash", cash_info);
         }
         if let Some(min_amount) = args.min_amount {
             if min_amount > total_amount {
-                return Err(CashError::MinAmountMustBeLessThanAmount.into());
+                return err_ctx!(CashError::MinAmountMustBeLessThanAmount, "cash", cash_info);
+            }
+            let min_payout =
+                min_payout_for_mint(mint.decimals, max_dust_decimals(accounts, program_id)?);
+            if min_amount < min_payout {
+                msg!("The min amount must be at least {} for this mint", min_payout);
+                return err_ctx!(CashError::MinAmountTooSmallForMint, "mint", mint_info);
             }
         }
     }
+    // a flash slot pays a fixed share of the amount, it cannot follow random draws or weights
+    if let Some(flash) = args.flash.as_ref() {
+        if flash.interval == 0
+            || !matches!(
+                args.distribution_type,
+                DistributionType::Fixed | DistributionType::Equal
+            )
+        {
+            return err_ctx!(CashError::InvalidFlashSchedule, "cash", cash_info);
+        }
+    }
+    // commit-reveal only hardens slot hash draws, a vrf draw can not be simulated ahead
+    if let Some(delay) = args.commit_reveal_delay {
+        if delay == 0
+            || delay > MAX_COMMIT_REVEAL_DELAY
+            || args.distribution_type != DistributionType::Random
+            || args.entropy_source != EntropySource::SlotHashes
+        {
+            return err_ctx!(CashError::InvalidCommitRevealDelay, "cash", cash_info);
+        }
+    }
+    // a locked payout can not follow weights nor the clock of flash slots
+    if args.approval_threshold.is_some()
+        && (args.distribution_type == DistributionType::Weighted || args.flash.is_some())
+    {
+        return err_ctx!(CashError::InvalidApprovalThreshold, "cash", cash_info);
+    }
+    // a clawback spends a delegation of the payout in the link mint, approved by the wallet
+    // when it redeems: a native or swapped payout is not held in it, an approved one is not
+    // signed by the wallet
+    if let Some(window) = args.clawback_window_seconds {
+        if window == 0
+            || window > MAX_CLAWBACK_WINDOW
+            || is_native_mint(mint_info.key)
+            || args.swap.is_some()
+            || args.approval_threshold.is_some()
+        {
+            return err_ctx!(CashError::InvalidClawbackWindow, "cash", cash_info);
+        }
+    }
+    // an invoice style link pays its single recipient in one go
+    if args.intended_recipient.is_some() && args.max_num_redemptions != 1 {
+        return err_ctx!(CashError::InvalidNumberOfRedemptions, "cash", cash_info);
+    }
     // if args.num_days_to_expire == 0 {
     //     return Err(CashError::InvalidExpiryInDays.into());
     // }
     //let now = clock.unix_timestamp as u64;
-    let total = total_amount
-        .checked_add(total_platform_fee)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?
-        .checked_add(total_redemption_fee)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?;
+    dry_run_schedule(&args, mint.decimals, max_dust_decimals(accounts, program_id)?)?;
+    let totals = compute_init_totals(&args)?;
+    msg!(
+        "Init totals: principal {}, platform fee {}, network fee {}, redemption fee reserve {}, total {}",
+        totals.principal,
+        totals.platform_fee,
+        totals.network_fee,
+        totals.redemption_fee_reserve,
+        totals.total
+    );
+    if totals.redemption_fee_reserve as u128 * 10000
+        > totals.principal as u128 * max_fee_reserve_bps as u128
+    {
+        msg!(
+            "The redemption fee reserve can be at most {} bps of the amount",
+            max_fee_reserve_bps
+        );
+        return err_ctx!(CashError::FeeReserveTooLarge, "cash", cash_info);
+    }
+    let total = totals.vault_total()?;
+    let required_total = totals.total;
     cash.account_type = AccountType::Cash;
     cash.state = CashState::Initialized;
+    cash.created_at = Clock::get()?.unix_timestamp as u64;
+    cash.created_by_version = PROGRAM_VERSION;
     cash.amount = total_amount;
     cash.fee_bps = args.fee_bps;
     cash.base_fee_to_redeem = args.base_fee_to_redeem;
@@ -132,12 +277,62 @@ pub fn process_init(
     //cash.expires_at = now + (args.num_days_to_expire as u64 * 86400);
     cash.min_amount = match args.min_amount {
         Some(amount) if amount > total_amount => {
-            return Err(CashError::MinAmountMustBeLessThanAmount.into())
+            return err_ctx!(CashError::MinAmountMustBeLessThanAmount, "cash", cash_info)
         }
         Some(amount) => amount,
         None => 1,
     };
+    if args.kyc_required_above.is_some() != args.kyc_authority.is_some() {
+        return err_ctx!(CashError::InvalidKycConfig, "cash", cash_info);
+    }
+    cash.kyc_required_above = args.kyc_required_above;
+    cash.kyc_authority = args.kyc_authority;
+    if args.referral_ceilings_enabled
+        && (args.max_referrer_fee_bps as u32 + args.max_referee_fee_bps as u32) > 10000
+    {
+        return err_ctx!(CashError::InvalidReferralFees, "cash", cash_info);
+    }
+    cash.referral_ceilings_enabled = args.referral_ceilings_enabled;
+    cash.max_referrer_fee_bps = args.max_referrer_fee_bps;
+    cash.max_referee_fee_bps = args.max_referee_fee_bps;
+    cash.burn_bps = args.burn_bps;
+    cash.claim_requires_wallet_signature = args.claim_requires_wallet_signature;
+    cash.swap = args.swap;
+    cash.entropy_source = args.entropy_source;
+    cash.flash = args.flash;
+    cash.intended_recipient = args.intended_recipient;
+    cash.max_priority_fee_per_redeem = args.max_priority_fee_per_redeem;
+    cash.allow_freezable_mint = args.allow_freezable_mint;
+    cash.commit_reveal_delay = args.commit_reveal_delay;
+    cash.refund_destination = args.refund_destination;
+    cash.approval_threshold = args.approval_threshold;
+    cash.approval_ttl = args.approval_ttl;
+    cash.require_voucher = args.require_voucher;
+    if let Some(auto_cancel_at) = args.auto_cancel_at {
+        if auto_cancel_at <= cash.created_at {
+            return err_ctx!(CashError::InvalidAutoCancelSchedule, "cash", cash_info);
+        }
+        enqueue_auto_cancel(
+            accounts,
+            program_id,
+            cash_info.key,
+            auto_cancel_at,
+            fee_payer_info,
+            rent_info,
+            system_account_info,
+        )?;
+    }
+    cash.auto_cancel_at = args.auto_cancel_at;
+    cash.auto_cancel_tip = args.auto_cancel_tip;
+    cash.max_signature_age_slots = args.max_signature_age_slots;
+    cash.clawback_window_seconds = args.clawback_window_seconds;
+    cash.decimals = Some(mint.decimals);
+    cash.mint_owner = Some(*mint_info.owner);
+    cash.mint_supply_at_init = Some(mint.supply);
     cash.mint = *mint_info.key;
+    cash.fee_wallet = fee_wallet;
+    cash.fee_wallet_epoch = fee_wallet_epoch;
+    cash.token_program = *token_program_info.key;
     let associated_token_account = get_associated_token_address_with_program_id(
         &cash_info.key,
         &mint_info.key,
@@ -147,12 +342,13 @@ pub fn process_init(
         vault_token_info,
         &associated_token_account,
         Some(CashError::InvalidVaultTokenOwner),
+        Some("vault token"),
     )?;
+    cash.vault = associated_token_account;
     if exists(vault_token_info)? {
-        let vault_token: TokenAccount = assert_initialized(vault_token_info)?;
-        assert_owned_by(vault_token_info, &token_program_info.key)?;
-        assert_token_owned_by(&vault_token, cash_info.key)?;
-        assert_account_key(mint_info, &vault_token.mint, Some(CashError::InvalidMint))?;
+        let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+        assert_owned_by(vault_token_info, &token_program_info.key, Some("vault token"))?;
+        assert_token_account(&vault_token, cash_info.key, Some(mint_info.key), "vault token")?;
     } else {
         create_associated_token_account_raw(
             fee_payer_info,
@@ -163,52 +359,324 @@ pub fn process_init(
             &token_program_info.key,
         )?;
     }
-    let mint: Mint = assert_initialized(mint_info)?;
     let total_network_fee = args.network_fee;
+    let mut ledger = LedgerEvent::new(cash_info.key);
+    let (mint_key, vault) = (mint_info.key, vault_token_info.key);
     if cmp_pubkeys(&mint_info.key, &spl_token::native_mint::id())
         || cmp_pubkeys(&mint_info.key, &spl_token_2022::native_mint::id())
     {
-        native_transfer(owner_info, vault_token_info, total, &[])?;
+        assert_sufficient_funds(required_total, owner_info.lamports())?;
+        transfer_if_nonzero("vault", total, |amount| {
+            native_transfer(owner_info, vault_token_info, amount, &[])
+        })?;
         sync_native(vault_token_info, &token_program_info.key)?;
-        if total_network_fee > 0 {
-            native_transfer(owner_info, fee_payer_info, total_network_fee, &[])?;
-        }
+        transfer_if_nonzero("network fee", total_network_fee, |amount| {
+            native_transfer(owner_info, fee_payer_info, amount, &[])
+        })?;
+        ledger.post(LegKind::Principal, mint_key, owner_info.key, vault, total);
+        let fee_payer = fee_payer_info.key;
+        ledger.post(LegKind::NetworkFee, mint_key, owner_info.key, fee_payer, total_network_fee);
     } else {
-        assert_owned_by(owner_token_info, &token_program_info.key)?;
-        let owner_token: TokenAccount = assert_initialized(owner_token_info)?;
-        assert_token_owned_by(&owner_token, owner_info.key)?;
-        spl_token_transfer(
-            owner_token_info,
-            vault_token_info,
-            owner_info,
-            &mint_info,
-            &token_program_info.key,
-            total,
-            mint.decimals,
-            &[],
-        )?;
-        if total_network_fee > 0 {
-            assert_owned_by(fee_payer_token_info, &token_program_info.key)?;
-            let fee_token: TokenAccount = assert_initialized(fee_payer_token_info)?;
-            assert_token_owned_by(&fee_token, fee_payer_info.key)?;
+        assert_owned_by(owner_token_info, &token_program_info.key, Some("owner token"))?;
+        let owner_token: TokenAccount = assert_initialized(owner_token_info, Some("owner token"))?;
+        assert_token_account(&owner_token, owner_info.key, Some(mint_info.key), "owner token")?;
+        assert_sufficient_funds(required_total, owner_token.amount)?;
+        transfer_if_nonzero("vault", total, |amount| {
             spl_token_transfer(
                 owner_token_info,
-                fee_payer_token_info,
+                vault_token_info,
                 owner_info,
                 mint_info,
-                &token_program_info.key,
-                total_network_fee,
+                token_program_info.key,
+                amount,
                 mint.decimals,
                 &[],
+            )
+        })?;
+        ledger.post(LegKind::Principal, mint_key, owner_token_info.key, vault, total);
+        if total_network_fee > 0 {
+            assert_owned_by(
+                fee_payer_token_info,
+                &token_program_info.key,
+                Some("fee payer token"),
+            )?;
+            let fee_token: TokenAccount = assert_initialized(
+                fee_payer_token_info,
+                Some("fee payer token"),
             )?;
+            assert_token_account(
+                &fee_token,
+                fee_payer_info.key,
+                Some(mint_info.key),
+                "fee payer token",
+            )?;
+            transfer_if_nonzero("network fee", total_network_fee, |amount| {
+                spl_token_transfer(
+                    owner_token_info,
+                    fee_payer_token_info,
+                    owner_info,
+                    mint_info,
+                    token_program_info.key,
+                    amount,
+                    mint.decimals,
+                    &[],
+                )
+            })?;
+            let (owner_token, fee_payer_token) = (owner_token_info.key, fee_payer_token_info.key);
+            let fee = total_network_fee;
+            ledger.post(LegKind::NetworkFee, mint_key, owner_token, fee_payer_token, fee);
         }
     }
+    ledger.emit();
     //spl_token_transfer(owner_token_info, fee_token_info, owner_info, total_platform_fee, &[])?;
-    Cash::pack(cash, &mut cash_info.data.borrow_mut())?;
+    if let Some(uri) = args.metadata_uri.take() {
+        create_cash_metadata(
+            accounts,
+            program_id,
+            cash_info,
+            fee_payer_info,
+            rent_info,
+            system_account_info,
+            uri,
+        )?;
+    }
+    update_stats(accounts, program_id, |stats| {
+        stats.record_init(mint_info.key, total_amount)
+    })?;
+    cash.update_params_hash();
+    ParamsHashEvent::emit_for(cash_info.key, &cash, cash.created_at);
+    store_account(cash, cash_info)?;
+    // set last, the cpis above clear any return data
+    if let Ok(data) = borsh::to_vec(&totals) {
+        set_return_data(&data);
+    }
+    Ok(())
+}
+
+/// Check the vault and token program passed to an instruction against the ones stored at init,
+/// the vault is only derived again for links created before it was stored
+pub fn assert_vault(
+    cash: &Cash,
+    cash_key: &Pubkey,
+    vault_token_info: &AccountInfo,
+    token_program_id: &Pubkey,
+) -> ProgramResult {
+    let vault = if cash.vault == Pubkey::default() {
+        get_associated_token_address_with_program_id(cash_key, &cash.mint, token_program_id)
+    } else {
+        if !cmp_pubkeys(&cash.token_program, token_program_id) {
+            return err_ctx!(ProgramError::IncorrectProgramId, "vault token", vault_token_info);
+        }
+        cash.vault
+    };
+    assert_account_key(
+        vault_token_info,
+        &vault,
+        Some(CashError::InvalidVaultTokenOwner),
+        Some("vault token"),
+    )
+}
+
+/// Check the fee payer refunded the rent it paid for a link, links of the first release did not
+/// record their fee payer and refund the one passed
+pub fn assert_fee_payer(cash: &Cash, fee_payer_info: &AccountInfo) -> ProgramResult {
+    if cash.fee_payer == Pubkey::default() {
+        return Ok(());
+    }
+    assert_account_key(
+        fee_payer_info,
+        &cash.fee_payer,
+        Some(CashError::InvalidFeePayer),
+        Some("fee payer"),
+    )
+}
+
+/// Decimals of the mint of a link, the caller checked the mint key. The mint must still have
+/// the owner program and decimals of init, only links created before the decimals were stored
+/// unpack the mint
+pub fn mint_decimals(cash: &Cash, mint_info: &AccountInfo) -> Result<u8, ProgramError> {
+    let mint_owner = cash.mint_owner.as_ref().unwrap_or(&cash.token_program);
+    match cash.decimals {
+        Some(decimals) => {
+            assert_mint_unchanged(mint_info, mint_owner, decimals)?;
+            Ok(decimals)
+        }
+        None => {
+            if !cmp_pubkeys(mint_info.owner, mint_owner) {
+                return err_ctx!(CashError::MintConfigurationChanged, "mint", mint_info);
+            }
+            Ok(assert_initialized::<Mint>(mint_info, Some("mint"))?.decimals)
+        }
+    }
+}
+
+/// Breakdown of what the owner is debited at init, returned to simulating wallets
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Default)]
+pub struct InitTotals {
+    pub principal: u64,
+    pub platform_fee: u64,
+    pub network_fee: u64,
+    pub redemption_fee_reserve: u64,
+    pub total: u64,
+}
+
+impl InitTotals {
+    /// Amount deposited in the vault: everything but the network fee
+    pub fn vault_total(&self) -> Result<u64, ProgramError> {
+        self.total.error_sub(self.network_fee)
+    }
+}
+
+/// The init debit breakdown, usable off-chain to preview the cost of a cash link
+pub fn compute_init_totals(args: &InitCashArgs) -> Result<InitTotals, ProgramError> {
+    let platform_fee = core_math::platform_fee(args.amount, args.fee_bps)?;
+    let redemption_fee_reserve =
+        compute_total_reserve(&FeeSchedule::from_args(args), args.max_num_redemptions)?
+            .error_add(compute_priority_reserve(
+                args.max_priority_fee_per_redeem,
+                args.max_num_redemptions,
+            )?)?;
+    let total = args
+        .amount
+        .error_add(platform_fee)?
+        .error_add(redemption_fee_reserve)?
+        .error_add(args.network_fee)?;
+    Ok(InitTotals {
+        principal: args.amount,
+        platform_fee,
+        network_fee: args.network_fee,
+        redemption_fee_reserve,
+        total,
+    })
+}
+
+/// Total the owner must hold to create a cash link: the vault deposit plus the network fee
+pub fn compute_required_total(args: &InitCashArgs) -> Result<u64, ProgramError> {
+    Ok(compute_init_totals(args)?.total)
+}
+
+/// Replay the worst case redemption sequence of a new link arithmetically, so a schedule that
+/// can not be paid out fails at init rather than in front of a redeeming user
+///
+/// The smallest payout must cover the costs every redemption pays whether or not the
+/// recipient token account exists, and the vault must cover every payout with the rent fee
+/// charged on each of them.
+pub fn dry_run_schedule(args: &InitCashArgs, decimals: u8, max_dust_decimals: u8) -> ProgramResult {
+    let fees = FeeSchedule::from_args(args);
+    let platform_fee_per_redeem =
+        core_math::platform_fee_per_redeem(args.amount, args.fee_bps, args.max_num_redemptions)?;
+    // the rent fee is waived when the recipient token account exists, the rest never is
+    let fixed_cost = platform_fee_per_redeem.error_add(fees.base_fee_to_redeem)?;
+    let min_payout = match args.distribution_type {
+        DistributionType::Fixed | DistributionType::Equal => {
+            Some(floor_div(args.amount, args.max_num_redemptions as u64)?)
+        }
+        DistributionType::Random if args.max_num_redemptions == 1 => Some(args.amount),
+        DistributionType::Random => args.min_amount.map(|min_amount| {
+            min_amount.max(min_payout_for_mint(decimals, max_dust_decimals))
+        }),
+        // the weights are only known at redeem time, a min amount is the only lower bound
+        DistributionType::Weighted => args.min_amount,
+    };
+    match min_payout {
+        Some(min_payout) => {
+            let burn = floor_share(min_payout, args.burn_bps as u64, 10000)?;
+            let net_payout = min_payout.error_sub(burn)?;
+            if net_payout < fixed_cost {
+                msg!(
+                    "The smallest payout {} nets {} after the burn, below the {} fees of a redemption",
+                    min_payout,
+                    net_payout,
+                    fixed_cost
+                );
+                return Err(CashError::PayoutCannotCoverFees.into());
+            }
+        }
+        None if fixed_cost > 0 => msg!(
+            "Weights below {} ppm cannot cover the {} fees of a redemption",
+            (fixed_cost as u128 * 1_000_000 + args.amount as u128 - 1) / args.amount as u128,
+            fixed_cost
+        ),
+        None => {}
+    }
+    // every redemption charged the rent fee and the capped priority fee, the payouts adding up
+    // to the whole amount
+    let worst_case = args
+        .amount
+        .error_add(platform_fee_per_redeem.error_mul(args.max_num_redemptions as u64)?)?
+        .error_add(compute_total_reserve(&fees, args.max_num_redemptions)?)?
+        .error_add(compute_priority_reserve(
+            args.max_priority_fee_per_redeem,
+            args.max_num_redemptions,
+        )?)?;
+    let vault_total = compute_init_totals(args)?.vault_total()?;
+    if vault_total < worst_case {
+        msg!(
+            "The vault total {} is below the worst case redemption total {}",
+            vault_total,
+            worst_case
+        );
+        return Err(CashError::ReserveInsufficientForWorstCase.into());
+    }
+    Ok(())
+}
+
+fn assert_sufficient_funds(required: u64, available: u64) -> ProgramResult {
+    if available < required {
+        msg!(
+            "Insufficient funds for cash link: required {}, available {}",
+            required,
+            available
+        );
+        return Err(CashError::InsufficientFundsForCashLink.into());
+    }
     Ok(())
 }
 
-fn create_cash_link<'a>(
+/// Whether the link is already initialized exactly as these args would and its vault holds
+/// the total, any other initialized link fails with the first field that differs
+fn acknowledge_init_retry(
+    program_id: &Pubkey,
+    args: &InitCashArgs,
+    cash_info: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    pass_key: Option<&Pubkey>,
+    vault_token_info: &AccountInfo,
+) -> Result<bool, ProgramError> {
+    if cash_info.data_is_empty() || !cmp_pubkeys(cash_info.owner, program_id) {
+        return Ok(false);
+    }
+    let cash = Cash::unpack_unchecked(&cash_info.try_borrow_data()?)?;
+    if !cash.is_initialized() {
+        return Ok(false);
+    }
+    if let Some(field) = cash.first_init_mismatch(args, owner, mint, pass_key) {
+        msg!("The link differs from the init args in {}", field);
+        return err_ctx!(CashError::InitArgsMismatch, "cash", cash_info);
+    }
+    assert_account_key(
+        vault_token_info,
+        &cash.vault,
+        Some(CashError::InvalidVaultTokenOwner),
+        Some("vault token"),
+    )?;
+    let vault_total = compute_init_totals(args)?.vault_total()?;
+    let vault_amount = if exists(vault_token_info)? {
+        assert_initialized::<TokenAccount>(vault_token_info, Some("vault token"))?.amount
+    } else {
+        0
+    };
+    if vault_amount < vault_total {
+        msg!("The vault holds {} of {}", vault_amount, vault_total);
+        return err_ctx!(CashError::InitArgsMismatch, "vault token", vault_token_info);
+    }
+    msg!("idempotent init acknowledged");
+    Ok(true)
+}
+
+pub fn create_cash_link<'a>(
     program_id: &Pubkey,
     cash_info: &AccountInfo<'a>,
     payer_info: &AccountInfo<'a>,
@@ -217,14 +685,14 @@ fn create_cash_link<'a>(
     signer_seeds: &[&[u8]],
 ) -> Result<Cash, ProgramError> {
     if cash_info.lamports() > 0 && !cash_info.data_is_empty() {
-        return Err(ProgramError::AccountAlreadyInitialized);
+        return err_ctx!(ProgramError::AccountAlreadyInitialized, "cash", cash_info);
     }
     // set up cash account
-    let unpack = Cash::unpack(&cash_info.data.borrow_mut());
+    let unpack = load_account::<Cash>(cash_info);
     let proving_process = match unpack {
         Ok(data) => Ok(data),
         Err(_) => {
-            create_new_account_raw(
+            let rent_paid = create_new_account_raw(
                 program_id,
                 cash_info,
                 rent_sysvar_info,
@@ -233,7 +701,10 @@ fn create_cash_link<'a>(
                 Cash::LEN,
                 signer_seeds,
             )?;
-            Ok(Cash::unpack_unchecked(&cash_info.data.borrow_mut())?)
+            let mut cash = Cash::unpack_unchecked(&cash_info.try_borrow_data()?)?;
+            cash.fee_payer = *payer_info.key;
+            cash.rent_paid_by_fee_payer = rent_paid;
+            Ok(cash)
         }
     };
 
@@ -246,33 +717,55 @@ pub fn process_cancel(
     args: CancelCashRedemptionArgs,
 ) -> ProgramResult {
     msg!("Process cancel");
+    // cancel only answers to its own bit, disabling redemptions never locks the funds in
+    assert_features_enabled(accounts, program_id, &[Feature::Cancel])?;
     let account_info_iter = &mut accounts.iter();
     let authority_info = next_account_info(account_info_iter)?;
 
     assert_signer(authority_info)?;
 
     let cash_info = next_account_info(account_info_iter)?;
-    let mut cash = Cash::unpack(&cash_info.data.borrow())?;
-    assert_owned_by(cash_info, program_id)?;
-    assert_account_key(
-        authority_info,
-        &cash.authority,
-        Some(CashError::InvalidAuthorityId),
-    )?;
+    let cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+    // the holder of the pass key of a locked link may void it as long as nothing was redeemed
+    let pass_key_cancel = !cmp_pubkeys(authority_info.key, &cash.authority)
+        && matches!(cash.pass_key, Some(pass_key) if cmp_pubkeys(&pass_key, authority_info.key));
+    if pass_key_cancel {
+        if cash.total_redemptions > 0 {
+            return err_ctx!(CashError::PassKeyCancelNotAllowed, "authority", authority_info);
+        }
+    } else {
+        assert_account_key(
+            authority_info,
+            &cash.authority,
+            Some(CashError::InvalidAuthorityId),
+            Some("authority"),
+        )?;
+    }
     let owner_info = next_account_info(account_info_iter)?;
     let owner_token_info = next_account_info(account_info_iter)?;
     let fee_payer_info = next_account_info(account_info_iter)?;
     let vault_token_info = next_account_info(account_info_iter)?;
     let mint_info = next_account_info(account_info_iter)?;
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
 
     let token_program_info = next_account_info(account_info_iter)?;
     assert_valid_token_program(&token_program_info.key)?;
+    assert_distinct_token_accounts(&[
+        (vault_token_info.key, cash_info.key),
+        (owner_token_info.key, owner_info.key),
+    ])?;
 
     if cash.canceled() {
-        return Err(AccountAlreadyCanceled.into());
+        return err_ctx!(AccountAlreadyCanceled, "cash", cash_info);
+    }
+    if cash.cancel_pending() {
+        return err_ctx!(CashError::CancelPending, "cash", cash_info);
     }
     if cash.redeemed() {
-        return Err(AccountAlreadyRedeemed.into());
+        return err_ctx!(AccountAlreadyRedeemed, "cash", cash_info);
     }
 
     // if (clock.unix_timestamp as u64) <= cash.expires_at {
@@ -285,20 +778,75 @@ pub fn process_cancel(
         &[args.cash_bump],
     ];
 
-    let vault_token: TokenAccount = assert_initialized(vault_token_info)?;
-    let mint: Mint = assert_initialized(mint_info)?;
-    // assert_account_key(vault_token.mint, mint, Some(CashError::InvalidMint))?;
-    let associated_token_account = get_associated_token_address_with_program_id(
-        &cash_info.key,
-        &cash.mint,
-        &token_program_info.key,
-    );
-    assert_account_key(
+    let link = CancelAccounts {
+        cash_info,
+        owner_info,
+        owner_token_info,
+        fee_payer_info,
         vault_token_info,
-        &associated_token_account,
-        Some(CashError::InvalidVaultTokenOwner),
-    )?;
-    if vault_token.amount > 0 {
+        mint_info,
+        token_program_info,
+    };
+    cancel_link(accounts, program_id, &link, cash, &signer_seeds, pass_key_cancel, None)
+}
+
+/// The accounts of a link its cancel moves the vault between
+pub struct CancelAccounts<'a, 'b> {
+    pub cash_info: &'b AccountInfo<'a>,
+    pub owner_info: &'b AccountInfo<'a>,
+    pub owner_token_info: &'b AccountInfo<'a>,
+    pub fee_payer_info: &'b AccountInfo<'a>,
+    pub vault_token_info: &'b AccountInfo<'a>,
+    pub mint_info: &'b AccountInfo<'a>,
+    pub token_program_info: &'b AccountInfo<'a>,
+}
+
+/// Refund the vault of a live link to its owner and mark it canceled, the caller checked the
+/// link may be canceled. A tip goes to the token account first, out of the fee reserve only
+pub fn cancel_link<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+    link: &CancelAccounts<'a, 'b>,
+    mut cash: Cash,
+    signer_seeds: &[&[u8]],
+    pass_key_cancel: bool,
+    tip: Option<(&'b AccountInfo<'a>, u64)>,
+) -> ProgramResult {
+    let CancelAccounts {
+        cash_info,
+        owner_info,
+        owner_token_info,
+        fee_payer_info,
+        vault_token_info,
+        mint_info,
+        token_program_info,
+    } = *link;
+    let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+    let decimals = mint_decimals(&cash, mint_info)?;
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    assert_not_frozen(&vault_token, CashError::VaultFrozen)?;
+    let mut refund = vault_token.amount;
+    let mut ledger = LedgerEvent::new(cash_info.key);
+    if let Some((tip_token_info, tip)) = tip {
+        // the principal still goes back whole, only the fee reserve beyond it pays the tip
+        let tip = tip.min(vault_token.amount.saturating_sub(cash.remaining_amount));
+        if tip > 0 {
+            spl_token_transfer(
+                vault_token_info,
+                tip_token_info,
+                cash_info,
+                mint_info,
+                token_program_info.key,
+                tip,
+                decimals,
+                &[signer_seeds],
+            )?;
+            let vault = vault_token_info.key;
+            ledger.post(LegKind::Tip, mint_info.key, vault, tip_token_info.key, tip);
+            refund -= tip;
+        }
+    }
+    if refund > 0 {
         if cmp_pubkeys(&mint_info.key, &spl_token::native_mint::id())
             || cmp_pubkeys(&mint_info.key, &spl_token_2022::native_mint::id())
         {
@@ -306,22 +854,49 @@ pub fn process_cancel(
                 owner_info,
                 &cash.owner,
                 Some(CashError::InvalidOwner),
+                Some("owner"),
             )?;
-            spl_token_close(
+            // the owner receives the vault amount and any unsynced lamports, the rent goes to the fee payer
+            let receiver_info = lamport_refund_receiver(accounts, &cash, owner_info)?;
+            let vault = vault_token_info.key;
+            ledger.post(LegKind::Refund, mint_info.key, vault, receiver_info.key, refund);
+            unwrap_native_vault(
                 vault_token_info,
-                fee_payer_info,
                 cash_info,
+                receiver_info,
+                fee_payer_info,
                 &token_program_info.key,
-                &[&signer_seeds],
+                refund,
+                &[signer_seeds],
             )?;
-            native_transfer(fee_payer_info, owner_info, vault_token.amount, &[])?;
         } else {
-            let owner_token: TokenAccount = assert_initialized(owner_token_info)?;
-            assert_token_owned_by(&owner_token, &cash.owner)?;
+            if pass_key_cancel {
+                // the refund can only go to the canonical token account of the owner
+                let owner_ata = get_associated_token_address_with_program_id(
+                    &cash.owner,
+                    &cash.mint,
+                    &token_program_info.key,
+                );
+                assert_account_key(
+                    owner_token_info,
+                    &owner_ata,
+                    Some(CashError::InvalidOwner),
+                    Some("owner token"),
+                )?;
+            }
+            let owner_token = load_token_account(
+                owner_token_info,
+                &cash.owner,
+                &cash.mint,
+                token_program_info.key,
+                "owner token",
+            )?;
+            assert_not_frozen(&owner_token, CashError::RecipientAccountFrozen)?;
             assert_account_key(
                 owner_info,
                 &cash.owner,
                 Some(CashError::InvalidOwner),
+                Some("owner"),
             )?;
             spl_token_transfer(
                 vault_token_info,
@@ -329,16 +904,18 @@ pub fn process_cancel(
                 cash_info,
                 mint_info,
                 &token_program_info.key,
-                vault_token.amount,
-                mint.decimals,
-                &[&signer_seeds],
+                refund,
+                decimals,
+                &[signer_seeds],
             )?;
+            let vault = vault_token_info.key;
+            ledger.post(LegKind::Refund, mint_info.key, vault, owner_token_info.key, refund);
             spl_token_close(
                 vault_token_info,
                 fee_payer_info,
                 cash_info,
                 &token_program_info.key,
-                &[&signer_seeds],
+                &[signer_seeds],
             )?;
         }
     } else {
@@ -347,529 +924,663 @@ pub fn process_cancel(
             fee_payer_info,
             cash_info,
             &token_program_info.key,
-            &[&signer_seeds],
+            &[signer_seeds],
         )?;
     }
+    update_stats(accounts, program_id, |stats| {
+        stats.record_refund(&cash.mint, refund)
+    })?;
+    let now = Clock::get()?.unix_timestamp as u64;
     cash.state = CashState::Canceled;
-    Cash::pack(cash, &mut cash_info.data.borrow_mut())?;
+    cash.completion_reason = CompletionReason::Canceled;
+    cash.canceled_at = Some(now);
+    ledger.emit();
+    CompletionEvent {
+        cash: *cash_info.key,
+        reason: cash.completion_reason,
+        total_redemptions: cash.total_redemptions,
+        remaining_amount: cash.remaining_amount,
+        timestamp: now,
+        created_by_version: cash.created_by_version,
+        last_touched_version: PROGRAM_VERSION,
+    }
+    .emit();
+    store_account(cash, cash_info)?;
     Ok(())
 }
 
-//inside: impl Processor {}
-pub fn process_redemption(
+pub fn process_cancel_begin(
     accounts: &[AccountInfo],
-    args: InitCashRedemptionArgs,
     program_id: &Pubkey,
+    args: CancelBeginArgs,
 ) -> ProgramResult {
-    msg!("Process redemption");
+    msg!("Process cancel begin");
+    assert_features_enabled(accounts, program_id, &[Feature::Cancel])?;
     let account_info_iter = &mut accounts.iter();
     let authority_info = next_account_info(account_info_iter)?;
-
     assert_signer(authority_info)?;
-
-    let wallet_info = next_account_info(account_info_iter)?;
-    let platform_wallet_info = next_account_info(account_info_iter)?;
-    let platform_token_info = next_account_info(account_info_iter)?;
     let cash_info = next_account_info(account_info_iter)?;
-    assert_owned_by(cash_info, program_id)?;
-    let mut cash = Cash::unpack(&cash_info.data.borrow())?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let owner_token_info = next_account_info(account_info_iter)?;
+    let vault_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(&token_program_info.key)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
     assert_account_key(
         authority_info,
         &cash.authority,
         Some(CashError::InvalidAuthorityId),
+        Some("authority"),
     )?;
-    let pass_info = cash
-        .pass_key
-        .map(|_| next_account_info(account_info_iter))
-        .transpose()?;
-
-    if let Some((pass_info, pass_key)) = pass_info.zip(cash.pass_key.as_ref()) {
-        assert_account_key(pass_info, pass_key, Some(CashError::InvalidPassKey))?;
-        assert_signer(pass_info)?;
-    } else if pass_info.is_some() || cash.pass_key.is_some() {
-        return Err(CashError::InvalidPassKey.into());
-    }
     if cash.canceled() {
-        return Err(AccountAlreadyCanceled.into());
+        return err_ctx!(AccountAlreadyCanceled, "cash", cash_info);
     }
     if cash.redeemed() {
-        return Err(AccountAlreadyRedeemed.into());
+        return err_ctx!(AccountAlreadyRedeemed, "cash", cash_info);
+    }
+    if args.max_amount == 0 {
+        return err_ctx!(CashError::InvalidAmount, "cash", cash_info);
     }
-    let owner_wallet_info = next_account_info(account_info_iter)?;
-    let owner_token_info = next_account_info(account_info_iter)?; //owner_token_info
+    // wrapped SOL has no extensions to process, its vault is unwrapped in one go by Cancel
+    if cmp_pubkeys(&mint_info.key, &spl_token::native_mint::id())
+        || cmp_pubkeys(&mint_info.key, &spl_token_2022::native_mint::id())
+    {
+        return err_ctx!(CashError::InvalidMint, "mint", mint_info);
+    }
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    assert_account_key(owner_info, &cash.owner, Some(CashError::InvalidOwner), Some("owner"))?;
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+    let owner_token: TokenAccount = assert_initialized(owner_token_info, Some("owner token"))?;
+    assert_token_account(&owner_token, &cash.owner, Some(&cash.mint), "owner token")?;
+    assert_not_frozen(&vault_token, CashError::VaultFrozen)?;
+    assert_not_frozen(&owner_token, CashError::RecipientAccountFrozen)?;
+    let decimals = mint_decimals(&cash, mint_info)?;
+
+    let amount = vault_token.amount.min(args.max_amount);
+    if amount > 0 {
+        let signer_seeds = [
+            Cash::PREFIX.as_bytes(),
+            args.cash_reference.as_bytes(),
+            &[args.cash_bump],
+        ];
+        spl_token_transfer(
+            vault_token_info,
+            owner_token_info,
+            cash_info,
+            mint_info,
+            &token_program_info.key,
+            amount,
+            decimals,
+            &[&signer_seeds],
+        )?;
+    }
+    msg!(
+        "Refunded {}, {} left in the vault",
+        amount,
+        vault_token.amount.error_sub(amount)?
+    );
+    update_stats(accounts, program_id, |stats| {
+        stats.record_refund(&cash.mint, amount)
+    })?;
+    cash.state = CashState::CancelPending;
+    cash.clear_recovery();
+    store_account(cash, cash_info)?;
+    Ok(())
+}
+
+pub fn process_cancel_finalize(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    args: CancelCashRedemptionArgs,
+) -> ProgramResult {
+    msg!("Process cancel finalize");
+    assert_features_enabled(accounts, program_id, &[Feature::Cancel])?;
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let cash_info = next_account_info(account_info_iter)?;
     let fee_payer_info = next_account_info(account_info_iter)?;
-    let fee_payer_token_info = next_account_info(account_info_iter)?;
     let vault_token_info = next_account_info(account_info_iter)?;
-    let recipient_token_info = next_account_info(account_info_iter)?;
-    let mint_info = next_account_info(account_info_iter)?;
-    let redemption_info = next_account_info(account_info_iter)?;
-    let clock_info = next_account_info(account_info_iter)?;
-    let clock = &Clock::from_account_info(clock_info)?;
-    let rent_info = next_account_info(account_info_iter)?;
-    let recent_slothashes_info = next_account_info(account_info_iter)?;
     let token_program_info = next_account_info(account_info_iter)?;
-    let system_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(&token_program_info.key)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
     assert_account_key(
-        recent_slothashes_info,
-        &slot_hashes::id(),
-        Some(CashError::InvalidSlotHashProgram.into()),
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    if !cash.cancel_pending() {
+        return err_ctx!(CashError::CancelNotPending, "cash", cash_info);
+    }
+    assert_fee_payer(&cash, fee_payer_info)?;
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+    if vault_token.amount > 0 {
+        return err_ctx!(CashError::VaultNotEmpty, "vault token", vault_token_info);
+    }
+    spl_token_close(
+        vault_token_info,
+        fee_payer_info,
+        cash_info,
+        &token_program_info.key,
+        &[&[
+            Cash::PREFIX.as_bytes(),
+            args.cash_reference.as_bytes(),
+            &[args.cash_bump],
+        ]],
     )?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    cash.state = CashState::Canceled;
+    cash.completion_reason = CompletionReason::Canceled;
+    cash.canceled_at = Some(now);
+    CompletionEvent {
+        cash: *cash_info.key,
+        reason: cash.completion_reason,
+        total_redemptions: cash.total_redemptions,
+        remaining_amount: cash.remaining_amount,
+        timestamp: now,
+        created_by_version: cash.created_by_version,
+        last_touched_version: PROGRAM_VERSION,
+    }
+    .emit();
+    store_account(cash, cash_info)?;
+    Ok(())
+}
 
-    assert_valid_token_program(&token_program_info.key)?;
+//inside: impl Processor {}
+pub fn process_close(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    assert_features_enabled(accounts, program_id, &[Feature::Close])?;
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
 
-    let signer_seeds = [
-        Cash::PREFIX.as_bytes(),
-        args.cash_reference.as_ref(),
-        &[args.cash_bump],
-    ];
+    let cash = load_account::<Cash>(cash_info)?;
 
-    if cash.total_redemptions >= cash.max_num_redemptions {
-        return Err(CashError::MaxRedemptionsReached.into());
-    }
-    if cash.remaining_amount == 0 {
-        return Err(CashError::NoRemainingAmount.into());
-    }
-
-    let amount_to_redeem = match cash.distribution_type {
-        DistributionType::Fixed => cash
-            .amount
-            .checked_div(cash.max_num_redemptions as u64)
-            .ok_or(CashError::Overflow)?,
-        DistributionType::Random => {
-            if cash.max_num_redemptions == 1
-                || cash.total_redemptions == (cash.max_num_redemptions - 1)
-            {
-                cash.remaining_amount
-            } else {
-                let remaining_redemptions = cash.max_num_redemptions - cash.total_redemptions;
-                let average_possible = cash.remaining_amount / remaining_redemptions as u64;
-                let max_possible = average_possible * 2;
-
-                let min_possible = cash.min_amount.min(cash.remaining_amount);
-                let max_possible = max_possible.min(cash.remaining_amount);
-
-                if max_possible > min_possible {
-                    let rand = get_random_value(recent_slothashes_info, clock)?;
-                    let range = max_possible - min_possible + 1;
-                    let random_amount = min_possible + (rand % range);
-                    random_amount
-                } else {
-                    min_possible
-                }
-            }
-        }
-        DistributionType::Weighted => {
-            let weight_ppm = args.weight_ppm.ok_or(CashError::WeightNotProvided)?;
-            // Validate that weight_ppm is within acceptable range (0 to 1,000,000)
-            if weight_ppm == 0 || weight_ppm > 1_000_000 {
-                return Err(CashError::InvalidWeight.into());
-            }
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    if !cash.canceled() {
+        return err_ctx!(AccountNotCanceled, "cash", cash_info);
+    }
+    if cash.total_redemptions > 0 {
+        return err_ctx!(AccountAlreadyRedeemed, "cash", cash_info);
+    }
+    assert_fee_payer(&cash, fee_payer_info)?;
+    close_cash_account(
+        accounts,
+        program_id,
+        &cash,
+        cash_info,
+        fee_payer_info,
+        destination_info,
+    )
+}
 
-            // Calculate new total weight
-            let new_total_weight_ppm = cash
-                .total_weight_ppm
-                .checked_add(weight_ppm)
-                .ok_or(CashError::Overflow)?;
+/// Close a canceled link along with its buffer and metadata
+pub fn close_cash_account<'a>(
+    accounts: &[AccountInfo<'a>],
+    program_id: &Pubkey,
+    cash: &Cash,
+    cash_info: &AccountInfo<'a>,
+    fee_payer_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    // the fee payer is reimbursed first, whatever is left goes to the destination
+    let fee_payer_refund = cash.rent_paid_by_fee_payer.min(cash_info.lamports());
+    if fee_payer_refund > 0 {
+        transfer_account_lamports(cash_info, fee_payer_info, fee_payer_refund)?;
+    }
+    // the buffer and the metadata of the link are closed with it, each refunds its rent payer
+    let destination_info = lamport_refund_receiver(accounts, cash, destination_info)?;
+    if let Some(buffer_info) = find_cash_buffer(accounts, program_id, cash_info.key, cash)? {
+        let buffer = load_buffer(buffer_info, program_id)?;
+        close_auxiliary_account(accounts, buffer_info, &buffer.header)?;
+    }
+    if let Some(metadata_info) = find_cash_metadata(accounts, program_id, cash_info.key)? {
+        let metadata = load_cash_metadata(metadata_info, program_id, cash_info.key)?;
+        close_auxiliary_account(accounts, metadata_info, &metadata.header)?;
+    }
+    empty_account_balance(cash_info, destination_info)?;
+    Ok(())
+}
 
-            // Ensure that total weight does not exceed 1,000,000 PPM (100%)
-            if new_total_weight_ppm > 1_000_000 {
-                return Err(CashError::TotalWeightExceeded.into());
-            }
+/// The account receiving lamports refunded to `wallet_info`: the wallet itself when it can
+/// spend them, the refund destination of the link otherwise. Lamports pushed into an account
+/// owned by another program, e.g. a pda owner, are stranded unless it is a token account
+pub fn lamport_refund_receiver<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    cash: &Cash,
+    wallet_info: &'b AccountInfo<'a>,
+) -> Result<&'b AccountInfo<'a>, ProgramError> {
+    if cmp_pubkeys(wallet_info.owner, &system_program::id())
+        || assert_valid_token_program(wallet_info.owner).is_ok()
+    {
+        return Ok(wallet_info);
+    }
+    let destination_info = cash
+        .refund_destination
+        .and_then(|destination| find_optional_account(accounts, &destination))
+        .ok_or(CashError::RefundDestinationUnspendable)?;
+    msg!(
+        "{} can not spend lamports, refunding {}",
+        wallet_info.key,
+        destination_info.key
+    );
+    Ok(destination_info)
+}
 
-            // Calculate amount to redeem based on the total amount
-            let amount_to_redeem = cash
-                .amount
-                .checked_mul(weight_ppm as u64)
-                .ok_or(CashError::Overflow)?
-                .checked_div(1_000_000)
-                .ok_or(CashError::Overflow)?;
+/// Close an auxiliary account of a link, its lamports go to the rent payer recorded in its
+/// header
+fn close_auxiliary_account<'a>(
+    accounts: &[AccountInfo<'a>],
+    account_info: &AccountInfo<'a>,
+    header: &AuxiliaryHeader,
+) -> ProgramResult {
+    let receiver_info =
+        find_optional_account(accounts, &header.rent_payer).ok_or(CashError::InvalidFeePayer)?;
+    msg!("Refund rent of {} to {}", account_info.key, receiver_info.key);
+    empty_account_balance(account_info, receiver_info)?;
+    account_info.data.borrow_mut().fill(0);
+    Ok(())
+}
 
-            // Ensure amount_to_redeem does not exceed remaining_amount
-            let amount_to_redeem = amount_to_redeem.min(cash.remaining_amount);
+pub fn process_transfer_authority(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process transfer authority");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let new_authority_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
 
-            // Update cumulative weight
-            cash.total_weight_ppm = new_total_weight_ppm;
+    let mut cash = load_account::<Cash>(cash_info)?;
 
-            amount_to_redeem
-        }
-        DistributionType::Equal => {
-            // Calculate the equal amount per redemption
-            let amount_to_redeem = cash
-                .amount
-                .checked_div(cash.max_num_redemptions as u64)
-                .ok_or(CashError::Overflow)?;
-            amount_to_redeem
-        }
-    };
+    cash.assert_not_in_progress()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    if cash.redeemed() {
+        return err_ctx!(AccountAlreadyRedeemed, "cash", cash_info);
+    }
+    // the authority is only ever checked as a signer, a PDA signing through a CPI works as well
+    cash.authority = *new_authority_info.key;
+    cash.clear_recovery();
+    store_account(cash, cash_info)?;
+    Ok(())
+}
 
-    let mut fee_to_redeem = cash.max_fee_to_redeem()?;
+pub fn process_rotate_pass_key(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    args: RotatePassKeyArgs,
+) -> ProgramResult {
+    msg!("Process rotate pass key");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let new_pass_key_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
 
-    cash.remaining_amount = cash
-        .remaining_amount
-        .checked_sub(amount_to_redeem)
-        .ok_or(CashError::Overflow)?;
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    if cash.canceled() || cash.cancel_pending() {
+        return err_ctx!(AccountAlreadyCanceled, "cash", cash_info);
+    }
+    if cash.redeemed() {
+        return err_ctx!(AccountAlreadyRedeemed, "cash", cash_info);
+    }
+    let current_pass_key = cash.pass_key.ok_or(CashError::NotALockedLink)?;
+    if args.force {
+        msg!("Forced rotation of pass key {} without its signature", current_pass_key);
+    } else {
+        let current_pass_key_info = next_account_info(account_info_iter)?;
+        assert_account_key(
+            current_pass_key_info,
+            &current_pass_key,
+            Some(CashError::InvalidPassKey),
+            Some("current pass key"),
+        )?;
+        assert_signer(current_pass_key_info)?;
+    }
+    // redemptions signed by the old pass key fail the pass key check from here on
+    cash.pass_key = Some(*new_pass_key_info.key);
+    cash.pass_rotations = cash.pass_rotations.checked_add(1).ok_or(CashError::Overflow)?;
+    cash.clear_recovery();
+    store_account(cash, cash_info)?;
+    Ok(())
+}
 
-    cash.total_redemptions = cash.total_redemptions.error_increment()?;
+/// Forfeit the oldest unclaimed flash slot, the owner gets its amount back with the vault
+/// leftover, swept by the final redemption or refunded by Cancel
+pub fn process_expire_slot(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process expire slot");
+    let account_info_iter = &mut accounts.iter();
+    let cash_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
 
-    let platform_fee_per_redeem: u64 = calculate_fee(cash.amount, cash.fee_bps as u64)?
-        .checked_div(cash.max_num_redemptions as u64)
-        .ok_or(CashError::Overflow)?;
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
+    if cash.canceled() || cash.cancel_pending() {
+        return err_ctx!(AccountAlreadyCanceled, "cash", cash_info);
+    }
+    if cash.redeemed() {
+        return err_ctx!(AccountAlreadyRedeemed, "cash", cash_info);
+    }
+    if cash.flash.is_none() {
+        return err_ctx!(CashError::InvalidFlashSchedule, "cash", cash_info);
+    }
+    cash.assert_redemption_counters()?;
+    let slot = cash.slots_used()?;
+    if slot >= cash.max_num_redemptions {
+        return err_ctx!(CashError::MaxRedemptionsReached, "cash", cash_info);
+    }
+    // a slot expires once the next one opened
+    let now = Clock::get()?.unix_timestamp as u64;
+    match cash.open_flash_slot(now) {
+        Some(open_slot) if open_slot > slot as u64 => {}
+        _ => return err_ctx!(CashError::SlotNotExpired, "cash", cash_info),
+    }
+    let forfeited = cash.per_redemption(cash.amount)?.min(cash.remaining_amount);
+    cash.remaining_amount = cash.remaining_amount.error_sub(forfeited)?;
+    cash.forfeited_amount = cash.forfeited_amount.error_add(forfeited)?;
+    cash.expired_slots = cash.expired_slots.error_increment()?;
+    msg!("Slot {} expired, {} forfeited", slot, forfeited);
+    store_account(cash, cash_info)?;
+    Ok(())
+}
 
-    let mut total_fee_to_redeem = if cash.total_redemptions == 1 {
-        platform_fee_per_redeem
-            .checked_add(fee_to_redeem)
-            .ok_or::<ProgramError>(CashError::Overflow.into())?
-    } else {
-        platform_fee_per_redeem
-            .checked_add(fee_to_redeem)
-            .ok_or::<ProgramError>(CashError::Overflow.into())?
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::{
+            config::Config,
+            mint_registry::{MintMode, MintRegistry},
+        },
+        test_utils::TestAccount,
+        find_mint_registry_program_address,
     };
 
-    let mut total = amount_to_redeem
-        .checked_add(total_fee_to_redeem)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?;
-    assert_owned_by(vault_token_info, &token_program_info.key)?;
-    let associated_token_account = get_associated_token_address_with_program_id(
-        &cash_info.key,
-        &cash.mint,
-        &token_program_info.key,
-    );
-    assert_account_key(
-        vault_token_info,
-        &associated_token_account,
-        Some(CashError::InvalidVaultTokenOwner),
-    )?;
-    let vault_token: TokenAccount = assert_initialized(vault_token_info)?;
-    let mint: Mint = assert_initialized(mint_info)?;
+    fn args(
+        distribution_type: DistributionType,
+        amount: u64,
+        max_num_redemptions: u16,
+    ) -> InitCashArgs {
+        InitCashArgs {
+            amount,
+            fee_bps: 100,
+            network_fee: 5_000,
+            base_fee_to_redeem: 5_000,
+            rent_fee_to_redeem: 2_000,
+            cash_bump: 255,
+            distribution_type,
+            max_num_redemptions,
+            min_amount: None,
+            cash_reference: "reference".to_string(),
+            is_locked: false,
+            kyc_required_above: None,
+            kyc_authority: None,
+            referral_ceilings_enabled: false,
+            max_referrer_fee_bps: 0,
+            max_referee_fee_bps: 0,
+            burn_bps: 0,
+            metadata_uri: None,
+            claim_requires_wallet_signature: false,
+            swap: None,
+            entropy_source: EntropySource::SlotHashes,
+            flash: None,
+            intended_recipient: None,
+            max_priority_fee_per_redeem: 1_000,
+            allow_freezable_mint: false,
+            commit_reveal_delay: None,
+            refund_destination: None,
+            approval_threshold: None,
+            approval_ttl: None,
+            require_voucher: false,
+            auto_cancel_at: None,
+            auto_cancel_tip: 0,
+            max_signature_age_slots: None,
+            clawback_window_seconds: None,
+        }
+    }
 
-    let is_native = cmp_pubkeys(&mint_info.key, &spl_token::native_mint::id())
-    || cmp_pubkeys(&mint_info.key, &spl_token_2022::native_mint::id());
+    #[test]
+    fn breaks_the_init_debit_down() {
+        let totals = compute_init_totals(&args(DistributionType::Fixed, 1_000_000, 4)).unwrap();
+        assert_eq!(
+            totals,
+            InitTotals {
+                principal: 1_000_000,
+                platform_fee: 10_000,
+                network_fee: 5_000,
+                // 4 redemptions of 7_000 fees and 1_000 priority fees
+                redemption_fee_reserve: 32_000,
+                total: 1_047_000,
+            }
+        );
+        assert_eq!(totals.vault_total(), Ok(1_042_000));
+    }
 
-    if vault_token.amount < total {
-        return Err(InsufficientSettlementFunds.into());
+    #[test]
+    fn refuses_init_totals_that_overflow() {
+        let mut args = args(DistributionType::Fixed, u64::MAX, 1);
+        args.fee_bps = 0;
+        args.network_fee = 0;
+        args.base_fee_to_redeem = 0;
+        args.rent_fee_to_redeem = 0;
+        args.max_priority_fee_per_redeem = 0;
+        assert_eq!(compute_init_totals(&args).unwrap().total, u64::MAX);
+        args.network_fee = 1;
+        assert_eq!(compute_init_totals(&args), Err(CashError::Overflow.into()));
     }
 
-    if is_native {
-        assert_account_key(
-            owner_wallet_info,
-            &cash.owner,
-            Some(CashError::InvalidOwner),
-        )?;
-        if exists(fee_payer_token_info)? {
-            let fee_payer_token: TokenAccount = assert_initialized(fee_payer_token_info)?;
-            assert_token_owned_by(&fee_payer_token, &fee_payer_info.key)?;
-            assert_owned_by(fee_payer_token_info, &token_program_info.key)?;
-        } else {
-            create_associated_token_account_raw(
-                fee_payer_info,
-                fee_payer_token_info,
-                fee_payer_info,
-                mint_info,
-                rent_info,
-                &token_program_info.key,
-            )?;
-        }
-        spl_token_transfer(
-            vault_token_info,
-            fee_payer_token_info,
-            cash_info,
-            mint_info,
-            &token_program_info.key,
-            amount_to_redeem
-                .checked_add(total_fee_to_redeem)
-                .ok_or::<ProgramError>(CashError::Overflow.into())?,
-            mint.decimals,
-            &[&signer_seeds],
-        )?;
-        spl_token_close(
-            fee_payer_token_info,
-            fee_payer_info,
-            fee_payer_info,
-            &token_program_info.key,
-            &[&signer_seeds],
-        )?;
-        native_transfer(fee_payer_info, wallet_info, amount_to_redeem, &[])?;
-    } else {
-        let owner_token: TokenAccount = assert_initialized(owner_token_info)?;
-        assert_token_owned_by(&owner_token, &owner_wallet_info.key)?;
-        assert_account_key(
-            owner_wallet_info,
-            &cash.owner,
-            Some(CashError::InvalidOwner),
-        )?;
-        if exists(recipient_token_info)? {
-            let recipient_token: TokenAccount = assert_initialized(recipient_token_info)?;
-            assert_token_owned_by(&recipient_token, &wallet_info.key)?;
-            assert_owned_by(recipient_token_info, &token_program_info.key)?;
-            //subtract rent_fee
-            total_fee_to_redeem = total_fee_to_redeem
-                .checked_sub(cash.rent_fee_to_redeem)
-                .ok_or::<ProgramError>(CashError::Overflow.into())?;
-            fee_to_redeem = fee_to_redeem
-            .checked_sub(cash.rent_fee_to_redeem)
-            .ok_or::<ProgramError>(CashError::Overflow.into())?;
-            total = amount_to_redeem
-                .checked_add(total_fee_to_redeem)
-                .ok_or::<ProgramError>(CashError::Overflow.into())?;
-        } else {
-            create_associated_token_account_raw(
-                fee_payer_info,
-                recipient_token_info,
-                wallet_info,
-                mint_info,
-                rent_info,
-                &token_program_info.key,
-            )?;
+    #[test]
+    fn accepts_a_schedule_every_payout_covers() {
+        for distribution_type in [DistributionType::Fixed, DistributionType::Equal] {
+            let args = args(distribution_type, 1_000_000, 4);
+            assert_eq!(dry_run_schedule(&args, 6, 2), Ok(()));
         }
-    
-        if exists(platform_token_info)? {
-            let platform_token: TokenAccount = assert_initialized(platform_token_info)?;
-            assert_token_owned_by(&platform_token, &platform_wallet_info.key)?;
-            assert_owned_by(platform_token_info, &token_program_info.key)?;
-        } else {
-            create_associated_token_account_raw(
-                fee_payer_info,
-                platform_token_info,
-                platform_wallet_info,
-                mint_info,
-                rent_info,
-                &token_program_info.key,
-            )?;
-        }
-        if exists(fee_payer_token_info)? {
-            let fee_payer_token: TokenAccount = assert_initialized(fee_payer_token_info)?;
-            assert_token_owned_by(&fee_payer_token, &fee_payer_info.key)?;
-            assert_owned_by(fee_payer_token_info, &token_program_info.key)?;
-        } else {
-            create_associated_token_account_raw(
-                fee_payer_info,
-                fee_payer_token_info,
-                fee_payer_info,
-                mint_info,
-                rent_info,
-                &token_program_info.key,
-            )?;
-        }
-        spl_token_transfer(
-            vault_token_info,
-            recipient_token_info,
-            cash_info,
-            mint_info,
-            &token_program_info.key,
-            amount_to_redeem,
-            mint.decimals,
-            &[&signer_seeds],
-        )?;
+        // the weights are only known at redeem time, the schedule is accepted
+        assert_eq!(dry_run_schedule(&args(DistributionType::Weighted, 1_000, 4), 6, 2), Ok(()));
+        // a single claim random link pays the whole amount
+        assert_eq!(dry_run_schedule(&args(DistributionType::Random, 100_000, 1), 6, 2), Ok(()));
     }
-    if fee_to_redeem > 0 && !is_native{
-        spl_token_transfer(
-            vault_token_info,
-            fee_payer_token_info,
-            cash_info,
-            mint_info,
-            &token_program_info.key,
-            fee_to_redeem,
-            mint.decimals,
-            &[&signer_seeds],
-        )?;
+
+    #[test]
+    fn refuses_a_payout_below_the_fees_of_a_redemption() {
+        let cannot_cover = Err(CashError::PayoutCannotCoverFees.into());
+        // 1_000 per redemption against 5_000 base fee and 2 platform fee
+        assert_eq!(dry_run_schedule(&args(DistributionType::Fixed, 4_000, 4), 6, 2), cannot_cover);
+
+        let mut random = args(DistributionType::Random, 1_000_000, 4);
+        random.min_amount = Some(1);
+        // the dust floor of 10_000 units lifts the smallest draw over the fees
+        assert_eq!(dry_run_schedule(&random, 6, 2), Ok(()));
+        assert_eq!(dry_run_schedule(&random, 6, 6), cannot_cover);
+
+        let mut weighted = args(DistributionType::Weighted, 1_000_000, 4);
+        weighted.min_amount = Some(4_999);
+        assert_eq!(dry_run_schedule(&weighted, 6, 2), cannot_cover);
     }
-    if platform_fee_per_redeem > 0 {
-        if let Some(referrer_fee_bps) = args.referrer_fee_bps {
-            let referral_wallet_info = next_account_info(account_info_iter)?;
-            let referral_account_info = next_account_info(account_info_iter)?;
-            if exists(referral_account_info)? {
-                let referral_token: TokenAccount = assert_initialized(referral_account_info)?;
-                assert_token_owned_by(&referral_token, &referral_wallet_info.key)?;
-                assert_owned_by(referral_account_info, &token_program_info.key)?;
-            } else {
-                create_associated_token_account_raw(
-                    fee_payer_info,
-                    referral_account_info,
-                    referral_wallet_info,
-                    mint_info,
-                    rent_info,
-                    &token_program_info.key,
-                )?;
-            }
-            let referee_fee_bps = match args.referee_fee_bps {
-                Some(fee) => fee,
-                None => 0,
-            };
 
-            let commission_bps = referrer_fee_bps
-                .checked_add(referee_fee_bps)
-                .ok_or(CashError::Overflow)?;
+    #[test]
+    fn counts_the_burn_against_the_smallest_payout() {
+        let mut args = args(DistributionType::Fixed, 40_000, 4);
+        args.fee_bps = 0;
+        // 10_000 per redemption, half burned, nets exactly the 5_000 base fee
+        args.burn_bps = 5_000;
+        assert_eq!(dry_run_schedule(&args, 6, 2), Ok(()));
+        args.burn_bps = 5_001;
+        assert_eq!(
+            dry_run_schedule(&args, 6, 2),
+            Err(CashError::PayoutCannotCoverFees.into())
+        );
+    }
 
-            if commission_bps > 10000 {
-                return Err(CashError::InvalidReferralFees.into());
-            }
-            let referrer_fee: u64 =
-                calculate_fee(platform_fee_per_redeem, referrer_fee_bps as u64)?;
-
-            let referee_fee: u64 = calculate_fee(platform_fee_per_redeem, referee_fee_bps as u64)?;
-
-            let platform_fee = platform_fee_per_redeem
-                .checked_sub(referrer_fee)
-                .ok_or(CashError::Overflow)?
-                .checked_sub(referee_fee)
-                .ok_or(CashError::Overflow)?;
-
-            if platform_fee > 0 {
-                if is_native {
-                    native_transfer(fee_payer_info, platform_wallet_info, platform_fee, &[])?;
-                } else {
-                    spl_token_transfer(
-                        vault_token_info,
-                        platform_token_info,
-                        cash_info,
-                        mint_info,
-                        &token_program_info.key,
-                        platform_fee,
-                        mint.decimals,
-                        &[&signer_seeds],
-                    )?;
-                }
-            }
-            if referrer_fee > 0 {
-                if is_native {
-                    native_transfer(fee_payer_info, referral_wallet_info, referrer_fee, &[])?;
-                } else {
-                    spl_token_transfer(
-                        vault_token_info,
-                        referral_account_info,
-                        cash_info,
-                        mint_info,
-                        &token_program_info.key,
-                        referrer_fee,
-                        mint.decimals,
-                        &[&signer_seeds],
-                    )?;
-                }
-            }
-            if referee_fee > 0 {
-                if is_native {
-                    native_transfer(fee_payer_info, owner_wallet_info, referee_fee, &[])?;
-                } else {
-                    spl_token_transfer(
-                        vault_token_info,
-                        owner_token_info,
-                        cash_info,
-                        mint_info,
-                        &token_program_info.key,
-                        referee_fee,
-                        mint.decimals,
-                        &[&signer_seeds],
-                    )?;
-                }
-            }
-        } else {
-            if is_native {
-                native_transfer(
-                    fee_payer_info,
-                    platform_wallet_info,
-                    platform_fee_per_redeem,
-                    &[],
-                )?;
-            } else {
-                spl_token_transfer(
-                    vault_token_info,
-                    platform_token_info,
-                    cash_info,
-                    mint_info,
-                    &token_program_info.key,
-                    platform_fee_per_redeem,
-                    mint.decimals,
-                    &[&signer_seeds],
-                )?;
+    #[test]
+    fn the_vault_covers_the_worst_case_of_any_schedule() {
+        let mut seed: u64 = 0x0dd;
+        let mut next = |bound: u64| {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            (seed >> 33) % bound
+        };
+        for _ in 0..1_000 {
+            let max_num_redemptions = next(100) as u16 + 1;
+            let mut args = args(
+                DistributionType::Fixed,
+                (next(1_000_000_000) + 1) * max_num_redemptions as u64,
+                max_num_redemptions,
+            );
+            args.fee_bps = next(10_001) as u16;
+            args.base_fee_to_redeem = next(10_000);
+            args.rent_fee_to_redeem = next(3_000_000);
+            args.max_priority_fee_per_redeem = next(100_000);
+            // the floored fees per redemption never add up to more than the deposited fee
+            match dry_run_schedule(&args, 9, 2) {
+                Ok(()) => {}
+                Err(error) => assert_eq!(error, CashError::PayoutCannotCoverFees.into()),
             }
         }
     }
-    let remaining = vault_token
-        .amount
-        .checked_sub(total)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?;
-    if cash.is_fully_redeemed()? {
-        if is_native
-        {
-            spl_token_close(
-                vault_token_info,
-                fee_payer_info,
-                cash_info,
-                &token_program_info.key,
-                &[&signer_seeds],
-            )?;
-            if remaining > 0 {
-                native_transfer(fee_payer_info, owner_wallet_info, remaining, &[])?;
-            }
-        } else {
-            if remaining > 0 {
-                spl_token_transfer(
-                    vault_token_info,
-                    owner_token_info,
-                    cash_info,
-                    mint_info,
-                    &token_program_info.key,
-                    remaining,
-                    mint.decimals,
-                    &[&signer_seeds],
-                )?;
-            }
-            spl_token_close(
-                vault_token_info,
-                fee_payer_info,
-                cash_info,
-                &token_program_info.key,
-                &[&signer_seeds],
-            )?;
-        }
+
+    /// A packed account of the program at the address of its pda
+    fn program_account<T: Pack>(key: Pubkey, program_id: &Pubkey, state: T) -> TestAccount {
+        let mut data = vec![0; T::LEN];
+        T::pack(state, &mut data).unwrap();
+        TestAccount::new(key, *program_id, data)
     }
-    if redemption_info.lamports() > 0 && !redemption_info.data_is_empty() {
-        return Err(ProgramError::AccountAlreadyInitialized);
+
+    fn permissionless_config(program_id: &Pubkey) -> TestAccount {
+        let config = Config {
+            account_type: AccountType::Config,
+            permissionless_init: true,
+            ..Config::default()
+        };
+        program_account(find_config_program_address(program_id).0, program_id, config)
     }
-    create_new_account_raw(
-        program_id,
-        redemption_info,
-        rent_info,
-        fee_payer_info,
-        system_program_info,
-        FLAG_ACCOUNT_SIZE,
-        &[
-            REDEMPTION_PREFIX.as_bytes(),
-            cash_info.key.as_ref(),
-            wallet_info.key.as_ref(),
-            &[args.redemption_bump],
-        ],
-    )?;
-    cash.state = if cash.is_fully_redeemed()? {
-        CashState::Redeemed
-    } else {
-        CashState::Redeeming
-    };
-    Cash::pack(cash, &mut cash_info.data.borrow_mut())?;
-    Ok(())
-}
 
-//inside: impl Processor {}
-pub fn process_close(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    let authority_info = next_account_info(account_info_iter)?;
-    assert_signer(authority_info)?;
-    let cash_info = next_account_info(account_info_iter)?;
-    let destination_info = next_account_info(account_info_iter)?;
-    assert_owned_by(cash_info, program_id)?;
+    /// Run a permissionless init of a link with the trailing accounts
+    fn init_permissionless(
+        program_id: &Pubkey,
+        mint: &Pubkey,
+        trailing: &mut [TestAccount],
+    ) -> ProgramResult {
+        let owner = Pubkey::new_unique();
+        let token_program = spl_token_2022::id();
+        let mut leading = vec![
+            TestAccount::signer(owner),
+            TestAccount::signer(owner),
+            TestAccount::signer(Pubkey::new_unique()),
+            TestAccount::new(Pubkey::new_unique(), token_program, vec![]),
+            TestAccount::new(Pubkey::new_unique(), Pubkey::default(), vec![]),
+            TestAccount::new(*mint, token_program, vec![]),
+            TestAccount::new(Pubkey::new_unique(), token_program, vec![]),
+            TestAccount::new(Pubkey::new_unique(), token_program, vec![]),
+            TestAccount::new(solana_program::sysvar::rent::id(), Pubkey::default(), vec![]),
+            TestAccount::new(system_program::id(), Pubkey::default(), vec![]),
+            TestAccount::new(token_program, Pubkey::default(), vec![]),
+        ];
+        let accounts: Vec<AccountInfo> = leading
+            .iter_mut()
+            .chain(trailing.iter_mut())
+            .map(|account| account.info())
+            .collect();
+        process_init(&accounts, args(DistributionType::Fixed, 1_000_000, 4), program_id)
+    }
 
-    let cash = Cash::unpack(&cash_info.data.borrow())?;
-    assert_account_key(
-        authority_info,
-        &cash.authority,
-        Some(CashError::InvalidAuthorityId),
-    )?;
-    if !cash.canceled() {
-        return Err(AccountNotCanceled.into());
+    fn mint_registry(program_id: &Pubkey, mode: MintMode, mints: Vec<Pubkey>) -> TestAccount {
+        let registry = MintRegistry {
+            account_type: AccountType::MintRegistry,
+            mode,
+            mints,
+            caps: vec![],
+        };
+        program_account(find_mint_registry_program_address(program_id).0, program_id, registry)
     }
-    if cash.total_redemptions > 0 {
-        return Err(AccountAlreadyRedeemed.into());
+
+    #[test]
+    fn a_permissionless_init_must_pass_the_mint_registry() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut trailing = [permissionless_config(&program_id)];
+        assert_eq!(
+            init_permissionless(&program_id, &mint, &mut trailing),
+            Err(CashError::MintNotAllowed.into())
+        );
+    }
+
+    #[test]
+    fn a_permissionless_init_is_checked_against_the_mint_registry() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut trailing = [
+            permissionless_config(&program_id),
+            mint_registry(&program_id, MintMode::Denylist, vec![mint]),
+        ];
+        assert_eq!(
+            init_permissionless(&program_id, &mint, &mut trailing),
+            Err(CashError::MintNotAllowed.into())
+        );
+    }
+
+    #[test]
+    fn checks_the_mint_against_the_registry_passed() {
+        let program_id = Pubkey::new_unique();
+        let mut mint = TestAccount::new(Pubkey::new_unique(), spl_token::id(), vec![]);
+        let mut allowlist = mint_registry(&program_id, MintMode::Allowlist, vec![mint.key]);
+        let mut denylist = mint_registry(&program_id, MintMode::Denylist, vec![mint.key]);
+        let mut uncreated = TestAccount::new(
+            find_mint_registry_program_address(&program_id).0,
+            Pubkey::default(),
+            vec![],
+        );
+        uncreated.lamports = 0;
+        for permissionless in [false, true] {
+            let check = |registry: &mut TestAccount, mint: &mut TestAccount| {
+                assert_mint_allowed(&[registry.info()], &program_id, &mint.info(), permissionless)
+            };
+            assert_eq!(check(&mut allowlist, &mut mint), Ok(()));
+            assert_eq!(check(&mut denylist, &mut mint), Err(CashError::MintNotAllowed.into()));
+            // a registry never created allows every mint
+            assert_eq!(check(&mut uncreated, &mut mint), Ok(()));
+        }
+        // a link of the platform authority is only checked against a registry it passes
+        assert_eq!(assert_mint_allowed(&[], &program_id, &mint.info(), false), Ok(()));
+        assert_eq!(
+            assert_mint_allowed(&[], &program_id, &mint.info(), true),
+            Err(CashError::MintNotAllowed.into())
+        );
     }
-    empty_account_balance(cash_info, destination_info)?;
-    Ok(())
 }
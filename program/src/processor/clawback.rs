@@ -0,0 +1,497 @@
+//! Clawback of fresh payouts
+//!
+//! A link created with a clawback window has each redeeming wallet sign its redemption and
+//! approve the link as delegate of the payout on its token account. Until the window ends the
+//! authority and the config admin may together spend that delegation to take the payout back,
+//! as long as the recipient still holds it. Once the window ended anyone may spend what is left
+//! of the delegation, the token program only lets the wallet revoke it.
+use crate::{
+    error::CashError,
+    event::{CashEvent, ClawbackEvent, LedgerEvent, LegKind},
+    find_redemption_address,
+    instruction::{CancelCashRedemptionArgs, ClawbackRedemptionArgs},
+    math::SafeMath,
+    processor::{cash::assert_vault, config::assert_config_admin},
+    state::{cash::Cash, redemption::RedemptionReceipt},
+    utils::{
+        assert_account_key, assert_initialized, assert_owned_by, assert_signer,
+        assert_valid_token_program, cmp_pubkeys, find_optional_account, load_account,
+        load_token_account, requires_memo, spl_memo, spl_token_transfer, store_account,
+        MEMO_PROGRAM_ID,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_token_2022::state::Mint;
+
+/// Take part of a payout back within the clawback window of its link
+pub fn process_clawback_redemption(
+    accounts: &[AccountInfo],
+    args: ClawbackRedemptionArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process clawback redemption");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let receipt_info = next_account_info(account_info_iter)?;
+    let recipient_token_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(token_program_info.key)?;
+
+    let mut cash = load_link(cash_info, program_id, args.cash_bump, &args.cash_reference)?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    let mut receipt = load_redemption_receipt(receipt_info, program_id, cash_info.key)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if !cash.clawback_open(receipt.redeemed_at, now) {
+        msg!(
+            "The payout of {} at {} is past the clawback window",
+            receipt.wallet,
+            receipt.redeemed_at
+        );
+        return Err(CashError::ClawbackWindowExpired.into());
+    }
+    if args.amount == 0 {
+        return Err(CashError::InvalidAmount.into());
+    }
+    let clawed_back = receipt.clawed_back.error_add(args.amount)?;
+    if clawed_back > receipt.amount {
+        msg!(
+            "Clawed back {} of {}, {} more is refused",
+            receipt.clawed_back,
+            receipt.amount,
+            args.amount
+        );
+        return Err(CashError::ClawbackExceedsRedeemed.into());
+    }
+    load_token_account(
+        recipient_token_info,
+        &receipt.wallet,
+        &cash.mint,
+        token_program_info.key,
+        "recipient token",
+    )?;
+    // the vault of a canceled or redeemed link was swept to the owner, so is the clawback
+    let live = !cash.canceled() && !cash.redeemed();
+    if live {
+        assert_vault(&cash, cash_info.key, destination_info, token_program_info.key)?;
+    } else {
+        load_token_account(
+            destination_info,
+            &cash.owner,
+            &cash.mint,
+            token_program_info.key,
+            "owner token",
+        )?;
+    }
+    let mint: Mint = assert_initialized(mint_info, Some("mint"))?;
+
+    let signer_seeds = [
+        Cash::PREFIX.as_bytes(),
+        args.cash_reference.as_bytes(),
+        &[args.cash_bump],
+    ];
+    spl_token_transfer(
+        recipient_token_info,
+        destination_info,
+        cash_info,
+        mint_info,
+        token_program_info.key,
+        args.amount,
+        mint.decimals,
+        &[&signer_seeds],
+    )?;
+    if live {
+        cash.remaining_amount = cash.remaining_amount.error_add(args.amount)?;
+    }
+    let mut ledger = LedgerEvent::new(cash_info.key);
+    ledger.post(
+        LegKind::Clawback,
+        mint_info.key,
+        recipient_token_info.key,
+        destination_info.key,
+        args.amount,
+    );
+    ledger.emit();
+    CashEvent::Clawback(ClawbackEvent {
+        cash: *cash_info.key,
+        wallet: receipt.wallet,
+        authority: *authority_info.key,
+        admin: *admin_info.key,
+        destination: *destination_info.key,
+        amount: args.amount,
+        clawed_back,
+        timestamp: now,
+    })
+    .emit();
+    receipt.clawed_back = clawed_back;
+    store_account(receipt, receipt_info)?;
+    store_account(cash, cash_info)?;
+    Ok(())
+}
+
+/// Spend what is left of the clawback delegation of a payout once its window ended
+pub fn process_revoke_clawback(
+    accounts: &[AccountInfo],
+    args: CancelCashRedemptionArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process revoke clawback");
+    let account_info_iter = &mut accounts.iter();
+    let cranker_info = next_account_info(account_info_iter)?;
+    assert_signer(cranker_info)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let receipt_info = next_account_info(account_info_iter)?;
+    let recipient_token_info = next_account_info(account_info_iter)?;
+    let vault_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(token_program_info.key)?;
+
+    let cash = load_link(cash_info, program_id, args.cash_bump, &args.cash_reference)?;
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    let receipt = load_redemption_receipt(receipt_info, program_id, cash_info.key)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if cash.clawback_open(receipt.redeemed_at, now) {
+        return Err(CashError::ClawbackWindowOpen.into());
+    }
+    if cash.canceled() || cash.redeemed() {
+        msg!("The vault is closed, the wallet revokes the delegation itself");
+        return Err(CashError::AccountAlreadyClosed.into());
+    }
+    let recipient_token = load_token_account(
+        recipient_token_info,
+        &receipt.wallet,
+        &cash.mint,
+        token_program_info.key,
+        "recipient token",
+    )?;
+    let delegated = match recipient_token.delegate {
+        COption::Some(delegate) if cmp_pubkeys(&delegate, cash_info.key) => {
+            recipient_token.delegated_amount
+        }
+        _ => 0,
+    };
+    if delegated == 0 {
+        msg!("No delegation of the link left on {}", recipient_token_info.key);
+        return Ok(());
+    }
+    // the wallet may have moved funds since, only what it still holds can be spent
+    let spent = delegated.min(recipient_token.amount);
+    if spent == 0 {
+        msg!(
+            "Nothing left to spend on {}, the wallet revokes the delegation itself",
+            recipient_token_info.key
+        );
+        return Ok(());
+    }
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    let mint: Mint = assert_initialized(mint_info, Some("mint"))?;
+
+    let signer_seeds = [
+        Cash::PREFIX.as_bytes(),
+        args.cash_reference.as_bytes(),
+        &[args.cash_bump],
+    ];
+    // spending the whole delegation clears it, the same amount is paid straight back
+    spl_token_transfer(
+        recipient_token_info,
+        vault_token_info,
+        cash_info,
+        mint_info,
+        token_program_info.key,
+        spent,
+        mint.decimals,
+        &[&signer_seeds],
+    )?;
+    if requires_memo(recipient_token_info)? {
+        let memo_program_info = find_optional_account(accounts, &MEMO_PROGRAM_ID)
+            .ok_or(CashError::RecipientRequiresMemo)?;
+        spl_memo(
+            memo_program_info,
+            &format!("cash clawback revoke {}", args.cash_reference),
+        )?;
+    }
+    spl_token_transfer(
+        vault_token_info,
+        recipient_token_info,
+        cash_info,
+        mint_info,
+        token_program_info.key,
+        spent,
+        mint.decimals,
+        &[&signer_seeds],
+    )?;
+    if spent < delegated {
+        msg!(
+            "Spent {} of the delegation of {} on {}, the wallet revokes the rest itself",
+            spent,
+            delegated,
+            recipient_token_info.key
+        );
+        return Ok(());
+    }
+    msg!("Revoked the delegation of {} on {}", delegated, recipient_token_info.key);
+    Ok(())
+}
+
+/// Load a link that is not in the middle of a redemption, its key derived from its seeds
+fn load_link(
+    cash_info: &AccountInfo,
+    program_id: &Pubkey,
+    cash_bump: u8,
+    cash_reference: &str,
+) -> Result<Cash, ProgramError> {
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+    let cash_key = Pubkey::create_program_address(
+        &[Cash::PREFIX.as_bytes(), cash_reference.as_bytes(), &[cash_bump]],
+        program_id,
+    )?;
+    assert_account_key(
+        cash_info,
+        &cash_key,
+        Some(CashError::InvalidCashKey),
+        Some("cash"),
+    )?;
+    let cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    Ok(cash)
+}
+
+/// Load the receipt of a payout of the link, its key derived from the wallet it records
+fn load_redemption_receipt(
+    receipt_info: &AccountInfo,
+    program_id: &Pubkey,
+    cash_key: &Pubkey,
+) -> Result<RedemptionReceipt, ProgramError> {
+    assert_owned_by(receipt_info, program_id, Some("redemption receipt"))?;
+    let receipt = RedemptionReceipt::unpack_unchecked(&receipt_info.data.borrow())?;
+    let (receipt_key, _) = find_redemption_address(program_id, cash_key, &receipt.wallet);
+    assert_account_key(receipt_info, &receipt_key, None, Some("redemption receipt"))?;
+    Ok(receipt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        state::AuxiliaryHeader,
+        test_utils::{invoked, runtime, TestAccount, TestLink},
+        utils::spl_token_approve,
+    };
+    use spl_token_2022::state::Account;
+
+    const REDEEMED_AT: i64 = 1_700_000_000;
+    const WINDOW: u64 = 600;
+
+    /// A live link that paid 1_000 to a wallet, the payout delegated back to the link
+    struct Payout {
+        link: TestLink,
+        receipt: TestAccount,
+        wallet: TestAccount,
+        recipient: TestAccount,
+    }
+
+    impl Payout {
+        fn new() -> Self {
+            let mut link = TestLink::new(4_000, 4);
+            link.update(|cash| {
+                cash.remaining_amount = 3_000;
+                cash.total_redemptions = 1;
+                cash.clawback_window_seconds = Some(WINDOW);
+            });
+            let mut vault = link.vault.unpack::<Account>();
+            vault.amount = 3_000;
+            Account::pack(vault, &mut link.vault.data).unwrap();
+            let (program_id, cash_key) = (link.program_id, link.cash.key);
+            let wallet = Pubkey::new_unique();
+            let receipt = RedemptionReceipt {
+                header: AuxiliaryHeader::new(&cash_key),
+                authority: link.authority.key,
+                voucher_hash: None,
+                wallet,
+                amount: 1_000,
+                redeemed_at: REDEEMED_AT as u64,
+                clawed_back: 0,
+            };
+            let mut payout = Self {
+                receipt: TestAccount::packed(
+                    find_redemption_address(&program_id, &cash_key, &wallet).0,
+                    program_id,
+                    receipt,
+                ),
+                wallet: TestAccount::signer(wallet),
+                recipient: TestAccount::token(Pubkey::new_unique(), link.mint.key, wallet, 1_000),
+                link,
+            };
+            runtime(REDEEMED_AT);
+            // the approve of the redemption, see `execute_plan`
+            spl_token_approve(
+                &payout.recipient.info(),
+                &payout.link.mint.info(),
+                &payout.link.cash.info(),
+                &payout.wallet.info(),
+                &spl_token_2022::id(),
+                1_000,
+                6,
+            )
+            .unwrap();
+            payout
+        }
+
+        fn claw_back(&mut self, amount: u64) -> ProgramResult {
+            let link = &mut self.link;
+            let args = ClawbackRedemptionArgs {
+                cash_bump: link.cash_bump,
+                cash_reference: TestLink::REFERENCE.to_string(),
+                amount,
+            };
+            let accounts = [
+                link.authority.info(),
+                link.admin.info(),
+                link.config.info(),
+                link.cash.info(),
+                self.receipt.info(),
+                self.recipient.info(),
+                link.vault.info(),
+                link.mint.info(),
+                link.token_program.info(),
+            ];
+            process_clawback_redemption(&accounts, args, &link.program_id)
+        }
+
+        fn revoke(&mut self) -> ProgramResult {
+            let args = self.link.args();
+            let link = &mut self.link;
+            let mut cranker = TestAccount::signer(Pubkey::new_unique());
+            let accounts = [
+                cranker.info(),
+                link.cash.info(),
+                self.receipt.info(),
+                self.recipient.info(),
+                link.vault.info(),
+                link.mint.info(),
+                link.token_program.info(),
+            ];
+            process_revoke_clawback(&accounts, args, &link.program_id)
+        }
+
+        fn recipient(&self) -> Account {
+            self.recipient.unpack()
+        }
+    }
+
+    #[test]
+    fn claws_a_payout_back_within_the_window() {
+        let mut payout = Payout::new();
+        runtime(REDEEMED_AT + WINDOW as i64);
+        payout.claw_back(400).unwrap();
+        assert_eq!(invoked(), vec![spl_token_2022::id()]);
+        assert_eq!(payout.recipient().amount, 600);
+        assert_eq!(payout.recipient().delegated_amount, 600);
+        assert_eq!(TestLink::amount(&payout.link.vault), 3_400);
+        assert_eq!(payout.receipt.unpack::<RedemptionReceipt>().clawed_back, 400);
+        // the clawed back amount is redeemable again
+        assert_eq!(payout.link.cash().remaining_amount, 3_400);
+
+        // the rest of the payout spends the whole delegation
+        payout.claw_back(600).unwrap();
+        assert_eq!(payout.recipient().amount, 0);
+        assert_eq!(payout.recipient().delegate, COption::None);
+    }
+
+    #[test]
+    fn refuses_a_clawback_after_the_window() {
+        let mut payout = Payout::new();
+        runtime(REDEEMED_AT + WINDOW as i64 + 1);
+        assert_eq!(payout.claw_back(400), Err(CashError::ClawbackWindowExpired.into()));
+        assert_eq!(invoked(), vec![]);
+        assert_eq!(payout.recipient().amount, 1_000);
+    }
+
+    #[test]
+    fn refuses_to_claw_back_more_than_was_redeemed() {
+        let mut payout = Payout::new();
+        payout.claw_back(900).unwrap();
+        runtime(REDEEMED_AT);
+        assert_eq!(payout.claw_back(101), Err(CashError::ClawbackExceedsRedeemed.into()));
+        assert_eq!(payout.claw_back(0), Err(CashError::InvalidAmount.into()));
+        assert_eq!(invoked(), vec![]);
+        payout.claw_back(100).unwrap();
+        assert_eq!(payout.receipt.unpack::<RedemptionReceipt>().clawed_back, 1_000);
+    }
+
+    #[test]
+    fn only_the_link_authority_claws_back() {
+        let mut payout = Payout::new();
+        payout.link.authority = TestAccount::signer(Pubkey::new_unique());
+        assert_eq!(payout.claw_back(400), Err(CashError::InvalidAuthorityId.into()));
+        let mut payout = Payout::new();
+        payout.link.admin = TestAccount::signer(Pubkey::new_unique());
+        assert_eq!(payout.claw_back(400), Err(CashError::InvalidAdmin.into()));
+    }
+
+    #[test]
+    fn revokes_the_delegation_once_the_window_ended() {
+        let mut payout = Payout::new();
+        payout.claw_back(400).unwrap();
+        assert_eq!(payout.revoke(), Err(CashError::ClawbackWindowOpen.into()));
+
+        runtime(REDEEMED_AT + WINDOW as i64 + 1);
+        payout.revoke().unwrap();
+        // spent and paid straight back, the wallet keeps its balance
+        assert_eq!(invoked(), vec![spl_token_2022::id(); 2]);
+        assert_eq!(payout.recipient().amount, 600);
+        assert_eq!(payout.recipient().delegate, COption::None);
+        assert_eq!(TestLink::amount(&payout.link.vault), 3_400);
+
+        // nothing left to revoke
+        runtime(REDEEMED_AT + WINDOW as i64 + 1);
+        payout.revoke().unwrap();
+        assert_eq!(invoked(), vec![]);
+    }
+
+    #[test]
+    fn revokes_what_the_wallet_still_holds() {
+        let mut payout = Payout::new();
+        let mut recipient = payout.recipient();
+        recipient.amount = 300;
+        Account::pack(recipient, &mut payout.recipient.data).unwrap();
+
+        runtime(REDEEMED_AT + WINDOW as i64 + 1);
+        payout.revoke().unwrap();
+        assert_eq!(payout.recipient().amount, 300);
+        assert_eq!(payout.recipient().delegated_amount, 700);
+        assert_eq!(TestLink::amount(&payout.link.vault), 3_000);
+
+        // with nothing left to spend the wallet revokes the delegation itself
+        let mut recipient = payout.recipient();
+        recipient.amount = 0;
+        Account::pack(recipient, &mut payout.recipient.data).unwrap();
+        runtime(REDEEMED_AT + WINDOW as i64 + 1);
+        payout.revoke().unwrap();
+        assert_eq!(invoked(), vec![]);
+        assert_eq!(payout.recipient().delegated_amount, 700);
+    }
+}
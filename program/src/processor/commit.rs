@@ -0,0 +1,215 @@
+//! Commit-reveal redemptions of Random links
+//!
+//! A searcher simulating a redemption sees the draw before sending it and aborts the bad ones.
+//! A link created with a commit-reveal delay only pays a wallet that committed first: the wallet
+//! stores `keccak(wallet || nonce)` in a commit pda, then redeems with the nonce once the delay
+//! elapsed. The draw comes from the hash of the first slot at or after the commit slot plus the
+//! delay, which did not exist when the commit was sent.
+use arrayref::array_ref;
+
+use crate::{
+    error::CashError::{self, AccountAlreadyCanceled, AccountAlreadyRedeemed},
+    find_redemption_commit_address,
+    instruction::CommitRedemptionArgs,
+    processor::entropy::Entropy,
+    state::{
+        cash::Cash,
+        commit::{redemption_commitment, RedemptionCommit},
+        AccountType,
+    },
+    utils::{
+        assert_account_key, assert_owned_by, assert_signer, cmp_pubkeys, create_new_account_raw,
+        empty_account_balance, find_optional_account, load_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::Clock,
+    entrypoint::ProgramResult,
+    keccak,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    slot_hashes::MAX_ENTRIES,
+    sysvar::Sysvar,
+};
+
+const SLOT_HASH_ENTRY_SIZE: usize = 8 + 32;
+
+pub fn process_commit_redemption(
+    accounts: &[AccountInfo],
+    args: CommitRedemptionArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process commit redemption");
+    let account_info_iter = &mut accounts.iter();
+    let wallet_info = next_account_info(account_info_iter)?;
+    assert_signer(wallet_info)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let commit_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_halted()?;
+    if cash.canceled() || cash.cancel_pending() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    if cash.commit_reveal_delay.is_none() {
+        return Err(CashError::CommitRevealDisabled.into());
+    }
+    // a pending commit is revealed or swept before the wallet may commit again
+    if commit_info.lamports() > 0 && !commit_info.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_new_account_raw(
+        program_id,
+        commit_info,
+        rent_info,
+        fee_payer_info,
+        system_program_info,
+        RedemptionCommit::LEN,
+        &[
+            RedemptionCommit::PREFIX.as_bytes(),
+            cash_info.key.as_ref(),
+            wallet_info.key.as_ref(),
+            &[args.commit_bump],
+        ],
+    )?;
+    let commit = RedemptionCommit {
+        account_type: AccountType::RedemptionCommit,
+        cash: *cash_info.key,
+        wallet: *wallet_info.key,
+        rent_payer: *fee_payer_info.key,
+        commitment: args.commitment,
+        slot: Clock::get()?.slot,
+    };
+    msg!("Commit of wallet {} at slot {}", commit.wallet, commit.slot);
+    RedemptionCommit::pack(commit, &mut commit_info.try_borrow_mut_data()?)?;
+    Ok(())
+}
+
+/// Close a commit that can no longer be revealed, its rent goes back to its rent payer
+pub fn process_sweep_commit(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process sweep commit");
+    let account_info_iter = &mut accounts.iter();
+    let commit_info = next_account_info(account_info_iter)?;
+    let rent_payer_info = next_account_info(account_info_iter)?;
+    let cash_info = next_account_info(account_info_iter)?;
+
+    let commit = load_commit(commit_info, program_id)?;
+    assert_account_key(
+        rent_payer_info,
+        &commit.rent_payer,
+        Some(CashError::InvalidFeePayer),
+        Some("rent payer"),
+    )?;
+    assert_account_key(cash_info, &commit.cash, None, Some("cash"))?;
+    // a closed or finished link is never redeemed again, a live one once the slot hash of
+    // the commit fell out of the slot hashes sysvar
+    let stale = if !cmp_pubkeys(cash_info.owner, program_id) || cash_info.data_is_empty() {
+        true
+    } else {
+        let cash = load_account::<Cash>(cash_info)?;
+        let delay = cash.commit_reveal_delay.unwrap_or(0);
+        cash.canceled()
+            || cash.redeemed()
+            || Clock::get()?.slot > commit.target_slot(delay).saturating_add(MAX_ENTRIES as u64)
+    };
+    if !stale {
+        return Err(CashError::CommitNotStale.into());
+    }
+    close_commit(commit_info, rent_payer_info)
+}
+
+/// Check the revealed nonce against the commit of the wallet, consume the commit and draw
+/// from the slot hash that followed it
+pub fn reveal_commit(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    cash: &Cash,
+    cash_key: &Pubkey,
+    wallet_key: &Pubkey,
+    nonce: Option<&[u8; 32]>,
+    recent_slothashes_info: &AccountInfo,
+    clock: &Clock,
+) -> Result<Entropy, ProgramError> {
+    let delay = cash
+        .commit_reveal_delay
+        .ok_or(CashError::CommitRevealDisabled)?;
+    let (commit_key, _) = find_redemption_commit_address(program_id, cash_key, wallet_key);
+    let commit_info =
+        find_optional_account(accounts, &commit_key).ok_or(CashError::CommitMissing)?;
+    let commit = load_commit(commit_info, program_id)?;
+    let nonce = nonce.ok_or(CashError::NonceMismatch)?;
+    if redemption_commitment(wallet_key, nonce) != commit.commitment {
+        return Err(CashError::NonceMismatch.into());
+    }
+    let target_slot = commit.target_slot(delay);
+    if clock.slot <= target_slot {
+        msg!("The commit can be revealed after slot {}", target_slot);
+        return Err(CashError::CommitTooRecent.into());
+    }
+    let slot_hash = first_slot_hash_from(recent_slothashes_info, target_slot)?;
+    let seed = keccak::hashv(&[&slot_hash, nonce, cash_key.as_ref()]).to_bytes();
+    let value = u64::from_le_bytes(*array_ref![seed, 0, 8]);
+    let rent_payer_info =
+        find_optional_account(accounts, &commit.rent_payer).ok_or(CashError::InvalidFeePayer)?;
+    close_commit(commit_info, rent_payer_info)?;
+    Ok(Entropy { value, round: None })
+}
+
+/// The hash of the oldest slot at or after `target_slot` in the slot hashes sysvar, newest
+/// entries come first
+fn first_slot_hash_from(
+    recent_slothashes_info: &AccountInfo,
+    target_slot: u64,
+) -> Result<[u8; 32], ProgramError> {
+    let data = recent_slothashes_info.try_borrow_data()?;
+    if data.len() < 8 {
+        return Err(CashError::CommitTooRecent.into());
+    }
+    let len = (u64::from_le_bytes(*array_ref![data, 0, 8]) as usize)
+        .min((data.len() - 8) / SLOT_HASH_ENTRY_SIZE);
+    let mut found = None;
+    let mut oldest_slot = u64::MAX;
+    for i in 0..len {
+        let offset = 8 + i * SLOT_HASH_ENTRY_SIZE;
+        let slot = u64::from_le_bytes(*array_ref![data, offset, 8]);
+        oldest_slot = slot;
+        if slot < target_slot {
+            break;
+        }
+        found = Some(*array_ref![data, offset + 8, 32]);
+    }
+    // the first slot after the target may have aged out, the draw would be up for grabs
+    if oldest_slot > target_slot && len >= MAX_ENTRIES {
+        return Err(CashError::CommitExpired.into());
+    }
+    found.ok_or_else(|| CashError::CommitTooRecent.into())
+}
+
+fn load_commit(
+    commit_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<RedemptionCommit, ProgramError> {
+    assert_owned_by(commit_info, program_id, Some("commit"))?;
+    let commit = RedemptionCommit::unpack_unchecked(&commit_info.try_borrow_data()?)?;
+    if !commit.is_initialized() {
+        return Err(CashError::CommitMissing.into());
+    }
+    Ok(commit)
+}
+
+fn close_commit(commit_info: &AccountInfo, rent_payer_info: &AccountInfo) -> ProgramResult {
+    empty_account_balance(commit_info, rent_payer_info)?;
+    commit_info.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
@@ -0,0 +1,425 @@
+use crate::{
+    error::CashError,
+    event::{CashEvent, FeaturesUpdatedEvent},
+    fees::{validate_schedule, FeeSchedule},
+    instruction::{InitConfigArgs, SetDisabledFeaturesArgs, UpdateConfigArgs},
+    find_config_program_address, find_program_data_address,
+    state::{
+        config::{
+            Config, Feature, DEFAULT_MAX_DUST_DECIMALS, DEFAULT_MAX_FEE_RESERVE_BPS,
+            MAX_REDEMPTIONS_CAP,
+        },
+        AccountType,
+    },
+    utils::{
+        assert_account_key, assert_owned_by, assert_signer, cmp_pubkeys, create_new_account_raw,
+        find_optional_account, UPGRADEABLE_LOADER_ID,
+    },
+};
+
+use arrayref::{array_ref, array_refs};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+/// Variant of `UpgradeableLoaderState::ProgramData`
+const PROGRAM_DATA_VARIANT: u32 = 3;
+/// Variant, deployment slot and optional upgrade authority ahead of the program bytes
+const PROGRAM_DATA_METADATA_SIZE: usize = 4 + 8 + 1 + 32;
+
+pub fn process_init_config(
+    accounts: &[AccountInfo],
+    args: InitConfigArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process init config");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    assert_signer(admin_info)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let program_data_info = next_account_info(account_info_iter)?;
+    // the first init would otherwise hand the config to whoever lands it first
+    assert_upgrade_authority(program_data_info, admin_info, program_id)?;
+
+    if config_info.lamports() > 0 && !config_info.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_new_account_raw(
+        program_id,
+        config_info,
+        rent_info,
+        fee_payer_info,
+        system_program_info,
+        Config::LEN,
+        &[Config::PREFIX.as_bytes(), &[args.config_bump]],
+    )?;
+    let config = Config {
+        account_type: AccountType::Config,
+        admin: *admin_info.key,
+        bump: args.config_bump,
+        max_dust_decimals: DEFAULT_MAX_DUST_DECIMALS,
+        ..Default::default()
+    };
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_update_config(
+    accounts: &[AccountInfo],
+    args: UpdateConfigArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process update config");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let mut config = assert_config_admin(config_info, admin_info, program_id)?;
+    let fees = FeeSchedule {
+        fee_bps: args.fee_bps,
+        network_fee: args.network_fee,
+        base_fee_to_redeem: args.base_fee_to_redeem,
+        rent_fee_to_redeem: args.rent_fee_to_redeem,
+    };
+    validate_schedule(&fees, 0)?;
+    if matches!(args.max_redemptions, Some(max) if max == 0 || max > MAX_REDEMPTIONS_CAP) {
+        return Err(CashError::InvalidNumberOfRedemptions.into());
+    }
+    config.permissionless_init = args.permissionless_init;
+    config.fee_wallet = args.fee_wallet;
+    config.fees = fees;
+    config.max_dust_decimals = args.max_dust_decimals;
+    config.max_redemptions = args.max_redemptions;
+    config.max_fee_reserve_bps = args.max_fee_reserve_bps;
+    config.swap_amm_program = args.swap_amm_program;
+    config.recovery_wallet = args.recovery_wallet;
+    config.recovery_timelock = args.recovery_timelock;
+    config.referral_vesting_delay = args.referral_vesting_delay;
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_set_disabled_features(
+    accounts: &[AccountInfo],
+    args: SetDisabledFeaturesArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process set disabled features");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let mut config = assert_config_admin(config_info, admin_info, program_id)?;
+    msg!(
+        "Disabled features {:#x} -> {:#x}",
+        config.disabled_features,
+        args.disabled_features
+    );
+    CashEvent::FeaturesUpdated(FeaturesUpdatedEvent {
+        admin: *admin_info.key,
+        previous_disabled_features: config.disabled_features,
+        disabled_features: args.disabled_features,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    })
+    .emit();
+    config.disabled_features = args.disabled_features;
+    Config::pack(config, &mut config_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Assert the admin is the upgrade authority recorded in the program data account of the
+/// program, an immutable program has none and can not init its config
+fn assert_upgrade_authority(
+    program_data_info: &AccountInfo,
+    admin_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let (program_data_key, _) = find_program_data_address(program_id);
+    assert_account_key(program_data_info, &program_data_key, None, Some("program data"))?;
+    assert_owned_by(program_data_info, &UPGRADEABLE_LOADER_ID, Some("program data"))?;
+    let data = program_data_info.try_borrow_data()?;
+    if data.len() < PROGRAM_DATA_METADATA_SIZE {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    // bincode of `UpgradeableLoaderState::ProgramData`: u32 variant, u64 slot, option tag and
+    // the upgrade authority
+    let (variant, _, authority_tag, authority) =
+        array_refs![array_ref![data, 0, PROGRAM_DATA_METADATA_SIZE], 4, 8, 1, 32];
+    if u32::from_le_bytes(*variant) != PROGRAM_DATA_VARIANT {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if authority_tag[0] == 0 || !cmp_pubkeys(&Pubkey::new_from_array(*authority), admin_info.key) {
+        msg!("The admin {} is not the upgrade authority of the program", admin_info.key);
+        return Err(CashError::InvalidAdmin.into());
+    }
+    Ok(())
+}
+
+/// Load an initialized config account
+pub fn load_config(config_info: &AccountInfo, program_id: &Pubkey) -> Result<Config, ProgramError> {
+    assert_owned_by(config_info, program_id, Some("config"))?;
+    let config = Config::unpack_unchecked(&config_info.data.borrow())?;
+    if !config.is_initialized() {
+        return Err(CashError::InvalidConfigKey.into());
+    }
+    Ok(config)
+}
+
+/// Load the config when it was passed to the instruction
+pub fn find_config(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<Option<Config>, ProgramError> {
+    let (config_key, _) = find_config_program_address(program_id);
+    find_optional_account(accounts, &config_key)
+        .map(|config_info| load_config(config_info, program_id))
+        .transpose()
+}
+
+/// Fail when one of the features is disabled by the config
+pub fn assert_config_features(config: &Config, features: &[Feature]) -> ProgramResult {
+    if let Some(feature) = features.iter().find(|feature| !config.feature_enabled(**feature)) {
+        msg!("Feature {:?} is disabled", feature);
+        return Err(CashError::FeatureDisabled.into());
+    }
+    Ok(())
+}
+
+/// Fail when the config was passed to the instruction and disables one of the features
+pub fn assert_features_enabled(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    features: &[Feature],
+) -> ProgramResult {
+    match find_config(accounts, program_id)? {
+        Some(config) => assert_config_features(&config, features),
+        None => Ok(()),
+    }
+}
+
+/// The dust decimals of the config when it was passed to the instruction, the default otherwise
+pub fn max_dust_decimals(accounts: &[AccountInfo], program_id: &Pubkey) -> Result<u8, ProgramError> {
+    Ok(find_config(accounts, program_id)?
+        .map_or(DEFAULT_MAX_DUST_DECIMALS, |config| config.max_dust_decimals))
+}
+
+/// The redemption cap and the fee reserve cap in bps of the principal a new link must respect
+pub fn init_limits(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<(u16, u16), ProgramError> {
+    let config = find_config(accounts, program_id)?;
+    let max_redemptions = config
+        .as_ref()
+        .and_then(|config| config.max_redemptions)
+        .map_or(MAX_REDEMPTIONS_CAP, |max| max.min(MAX_REDEMPTIONS_CAP));
+    let max_fee_reserve_bps = config
+        .and_then(|config| config.max_fee_reserve_bps)
+        .unwrap_or(DEFAULT_MAX_FEE_RESERVE_BPS);
+    Ok((max_redemptions, max_fee_reserve_bps))
+}
+
+/// Load the config and assert that the admin account signed for it
+pub fn assert_config_admin(
+    config_info: &AccountInfo,
+    admin_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Config, ProgramError> {
+    assert_signer(admin_info)?;
+    let config = load_config(config_info, program_id)?;
+    assert_account_key(admin_info, &config.admin, Some(CashError::InvalidAdmin), Some("admin"))?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        processor::cash::process_cancel,
+        test_utils::{runtime, TestAccount, TestLink},
+    };
+    use solana_program::sysvar;
+
+    /// The program data account of the program recording the upgrade authority
+    fn program_data(program_id: &Pubkey, upgrade_authority: Option<&Pubkey>) -> TestAccount {
+        let mut data = vec![0; PROGRAM_DATA_METADATA_SIZE];
+        data[..4].copy_from_slice(&PROGRAM_DATA_VARIANT.to_le_bytes());
+        if let Some(upgrade_authority) = upgrade_authority {
+            data[12] = 1;
+            data[13..].copy_from_slice(upgrade_authority.as_ref());
+        }
+        let (key, _) = find_program_data_address(program_id);
+        TestAccount::new(key, UPGRADEABLE_LOADER_ID, data)
+    }
+
+    fn check(
+        program_data: &mut TestAccount,
+        admin: &mut TestAccount,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        assert_upgrade_authority(&program_data.info(), &admin.info(), program_id)
+    }
+
+    #[test]
+    fn the_upgrade_authority_inits_the_config() {
+        let program_id = Pubkey::new_unique();
+        let mut admin = TestAccount::signer(Pubkey::new_unique());
+        let mut program_data = program_data(&program_id, Some(&admin.key));
+        assert_eq!(check(&mut program_data, &mut admin, &program_id), Ok(()));
+    }
+
+    #[test]
+    fn refuses_a_config_init_by_another_signer() {
+        let program_id = Pubkey::new_unique();
+        let mut admin = TestAccount::signer(Pubkey::new_unique());
+        let mut program_data = program_data(&program_id, Some(&Pubkey::new_unique()));
+        let mut fee_payer = TestAccount::signer(admin.key);
+        let (config_key, _) = find_config_program_address(&program_id);
+        let mut config = TestAccount::new(config_key, Pubkey::default(), vec![]);
+        let mut rent = TestAccount::new(sysvar::rent::id(), Pubkey::default(), vec![]);
+        // the system program id is the default pubkey
+        let mut system_program = TestAccount::new(Pubkey::default(), Pubkey::default(), vec![]);
+        let accounts = [
+            admin.info(),
+            fee_payer.info(),
+            config.info(),
+            rent.info(),
+            system_program.info(),
+            program_data.info(),
+        ];
+        assert_eq!(
+            process_init_config(&accounts, InitConfigArgs { config_bump: 255 }, &program_id),
+            Err(CashError::InvalidAdmin.into())
+        );
+    }
+
+    #[test]
+    fn refuses_an_immutable_program() {
+        let program_id = Pubkey::new_unique();
+        let mut admin = TestAccount::signer(Pubkey::new_unique());
+        let mut program_data = program_data(&program_id, None);
+        assert_eq!(
+            check(&mut program_data, &mut admin, &program_id),
+            Err(CashError::InvalidAdmin.into())
+        );
+    }
+
+    #[test]
+    fn refuses_a_program_data_account_of_another_program() {
+        let program_id = Pubkey::new_unique();
+        let mut admin = TestAccount::signer(Pubkey::new_unique());
+        // the program data of another program naming the signer
+        let mut other = program_data(&Pubkey::new_unique(), Some(&admin.key));
+        assert_eq!(
+            check(&mut other, &mut admin, &program_id),
+            Err(ProgramError::InvalidArgument)
+        );
+        // an account at the address not owned by the loader
+        let mut forged = program_data(&program_id, Some(&admin.key));
+        forged.owner = Pubkey::new_unique();
+        assert_eq!(
+            check(&mut forged, &mut admin, &program_id),
+            Err(CashError::InvalidOwner.into())
+        );
+        // the loader state of another variant
+        let mut buffer = program_data(&program_id, Some(&admin.key));
+        buffer.data[0] = 1;
+        assert_eq!(
+            check(&mut buffer, &mut admin, &program_id),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    /// Every feature, the match fails to compile when one is added without being listed
+    const FEATURES: [Feature; 9] = [
+        Feature::Init,
+        Feature::Redeem,
+        Feature::Cancel,
+        Feature::Close,
+        Feature::ReferralSplit,
+        Feature::RandomDistribution,
+        Feature::NativeMint,
+        Feature::Swap,
+        Feature::FeeWalletRecipientGuard,
+    ];
+
+    fn listed(feature: Feature) -> bool {
+        match feature {
+            Feature::Init
+            | Feature::Redeem
+            | Feature::Cancel
+            | Feature::Close
+            | Feature::ReferralSplit
+            | Feature::RandomDistribution
+            | Feature::NativeMint
+            | Feature::Swap
+            | Feature::FeeWalletRecipientGuard => FEATURES.contains(&feature),
+        }
+    }
+
+    fn disabling(features: &[Feature]) -> Config {
+        Config {
+            account_type: AccountType::Config,
+            disabled_features: features.iter().fold(0, |bits, feature| bits | feature.bit()),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn gates_each_feature_on_its_own_bit() {
+        let disabled = Err(CashError::FeatureDisabled.into());
+        assert_eq!(assert_config_features(&disabling(&[]), &FEATURES), Ok(()));
+        for feature in FEATURES {
+            assert!(listed(feature));
+            let config = disabling(&[feature]);
+            assert_eq!(assert_config_features(&config, &[feature]), disabled);
+            assert_eq!(assert_config_features(&config, &FEATURES), disabled);
+            for other in FEATURES.iter().filter(|other| **other != feature) {
+                assert_eq!(assert_config_features(&config, &[*other]), Ok(()));
+            }
+        }
+    }
+
+    /// Cancel the link with its config passed
+    fn cancel(link: &mut TestLink) -> ProgramResult {
+        let args = link.args();
+        let accounts = [
+            link.authority.info(),
+            link.cash.info(),
+            link.owner.info(),
+            link.owner_token.info(),
+            link.fee_payer.info(),
+            link.vault.info(),
+            link.mint.info(),
+            link.token_program.info(),
+            link.config.info(),
+        ];
+        process_cancel(&accounts, &link.program_id, args)
+    }
+
+    #[test]
+    fn cancels_while_redemptions_are_disabled() {
+        let mut link = TestLink::new(3_000, 3);
+        runtime(1_700_000_000);
+        Config::pack(disabling(&[Feature::Redeem]), &mut link.config.data).unwrap();
+        assert_eq!(
+            assert_features_enabled(&[link.config.info()], &link.program_id, &[Feature::Redeem]),
+            Err(CashError::FeatureDisabled.into())
+        );
+        cancel(&mut link).unwrap();
+        assert_eq!(TestLink::amount(&link.owner_token), 3_000);
+
+        // the cancel bit alone stops it
+        let mut link = TestLink::new(3_000, 3);
+        Config::pack(disabling(&[Feature::Cancel]), &mut link.config.data).unwrap();
+        assert_eq!(cancel(&mut link), Err(CashError::FeatureDisabled.into()));
+    }
+}
@@ -0,0 +1,400 @@
+//! Emergency controls
+//!
+//! Every instruction here needs both the cash authority and the config admin to sign, so a
+//! single compromised key can neither freeze a link nor move its funds.
+use crate::{
+    error::CashError::{self, AccountAlreadyCanceled, AccountAlreadyRedeemed},
+    event::{CashEvent, CompletionEvent, EmergencyWithdrawEvent, HaltEvent},
+    instruction::CancelCashRedemptionArgs,
+    processor::{
+        cash::{assert_fee_payer, assert_vault},
+        config::assert_config_admin,
+    },
+    state::cash::{Cash, CashState, CompletionReason},
+    utils::{
+        assert_account_key, assert_initialized, assert_owned_by, assert_signer,
+        assert_valid_token_program, load_account, spl_token_close, spl_token_transfer,
+        store_account,
+    },
+    PROGRAM_VERSION,
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::state::{Account as TokenAccount, Mint};
+
+/// Halt a live link, redeem, cancel and close are refused until it is released
+pub fn process_emergency_halt(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process emergency halt");
+    let (authority_info, admin_info, cash_info, mut cash) =
+        load_controlled_link(accounts, program_id)?;
+    cash.assert_not_halted()?;
+    if cash.canceled() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    cash.halted = true;
+    store_halt(authority_info, admin_info, cash_info, cash)
+}
+
+/// Release a halted link
+pub fn process_emergency_release(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process emergency release");
+    let (authority_info, admin_info, cash_info, mut cash) =
+        load_controlled_link(accounts, program_id)?;
+    if !cash.halted {
+        return Err(CashError::CashNotHalted.into());
+    }
+    cash.halted = false;
+    store_halt(authority_info, admin_info, cash_info, cash)
+}
+
+/// The authority, the admin and the link of a halt or release, both keys signed
+fn load_controlled_link<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+) -> Result<(&'b AccountInfo<'a>, &'b AccountInfo<'a>, &'b AccountInfo<'a>, Cash), ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    Ok((authority_info, admin_info, cash_info, cash))
+}
+
+fn store_halt(
+    authority_info: &AccountInfo,
+    admin_info: &AccountInfo,
+    cash_info: &AccountInfo,
+    mut cash: Cash,
+) -> ProgramResult {
+    cash.clear_recovery();
+    CashEvent::Halt(HaltEvent {
+        cash: *cash_info.key,
+        authority: *authority_info.key,
+        admin: *admin_info.key,
+        halted: cash.halted,
+        timestamp: Clock::get()?.unix_timestamp as u64,
+    })
+    .emit();
+    store_account(cash, cash_info)
+}
+
+/// Move the whole vault to the recovery wallet and cancel the link. A canceled or redeemed
+/// link is refused, its vault was already swept to the owner and closed, and recording it as
+/// an emergency would rewrite how it completed
+pub fn process_emergency_withdraw(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    args: CancelCashRedemptionArgs,
+) -> ProgramResult {
+    msg!("Process emergency withdraw");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let config = assert_config_admin(config_info, admin_info, program_id)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let vault_token_info = next_account_info(account_info_iter)?;
+    let recovery_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(token_program_info.key)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    if cash.canceled() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    let recovery_wallet = config
+        .recovery_wallet
+        .ok_or(CashError::RecoveryWalletNotSet)?;
+    let recovery_token = get_associated_token_address_with_program_id(
+        &recovery_wallet,
+        &cash.mint,
+        token_program_info.key,
+    );
+    assert_account_key(
+        recovery_token_info,
+        &recovery_token,
+        Some(CashError::InvalidRecoveryToken),
+        Some("recovery token"),
+    )?;
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    assert_fee_payer(&cash, fee_payer_info)?;
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+    let mint: Mint = assert_initialized(mint_info, Some("mint"))?;
+
+    let signer_seeds = [
+        Cash::PREFIX.as_bytes(),
+        args.cash_reference.as_bytes(),
+        &[args.cash_bump],
+    ];
+    if vault_token.amount > 0 {
+        spl_token_transfer(
+            vault_token_info,
+            recovery_token_info,
+            cash_info,
+            mint_info,
+            token_program_info.key,
+            vault_token.amount,
+            mint.decimals,
+            &[&signer_seeds],
+        )?;
+    }
+    spl_token_close(
+        vault_token_info,
+        fee_payer_info,
+        cash_info,
+        token_program_info.key,
+        &[&signer_seeds],
+    )?;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    cash.state = CashState::Canceled;
+    cash.completion_reason = CompletionReason::Emergency;
+    cash.canceled_at = Some(now);
+    // the funds are gone, the canceled link may be closed like any other
+    cash.halted = false;
+    CashEvent::EmergencyWithdraw(EmergencyWithdrawEvent {
+        cash: *cash_info.key,
+        authority: *authority_info.key,
+        admin: *admin_info.key,
+        recovery_token,
+        amount: vault_token.amount,
+        remaining_amount: cash.remaining_amount,
+        timestamp: now,
+    })
+    .emit();
+    CompletionEvent {
+        cash: *cash_info.key,
+        reason: cash.completion_reason,
+        total_redemptions: cash.total_redemptions,
+        remaining_amount: cash.remaining_amount,
+        timestamp: now,
+        created_by_version: cash.created_by_version,
+        last_touched_version: PROGRAM_VERSION,
+    }
+    .emit();
+    store_account(cash, cash_info)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        processor::{
+            cash::{process_cancel, process_close},
+            redemption::process_redemption,
+        },
+        test_utils::{runtime, TestAccount, TestLink},
+    };
+
+    const NOW: i64 = 1_700_000_000;
+
+    /// A live link holding 3_000 under the two person control of its authority and the admin
+    struct Incident {
+        link: TestLink,
+        recovery: TestAccount,
+    }
+
+    impl Incident {
+        fn new() -> Self {
+            let link = TestLink::new(3_000, 3);
+            let recovery_wallet = link.recovery_wallet();
+            let recovery = TestAccount::token(
+                link.ata(&recovery_wallet),
+                link.mint.key,
+                recovery_wallet,
+                0,
+            );
+            runtime(NOW);
+            Self { link, recovery }
+        }
+
+        fn halt(&mut self) -> ProgramResult {
+            let link = &mut self.link;
+            let accounts = [
+                link.authority.info(),
+                link.admin.info(),
+                link.config.info(),
+                link.cash.info(),
+            ];
+            process_emergency_halt(&accounts, &link.program_id)
+        }
+
+        fn release(&mut self) -> ProgramResult {
+            let link = &mut self.link;
+            let accounts = [
+                link.authority.info(),
+                link.admin.info(),
+                link.config.info(),
+                link.cash.info(),
+            ];
+            process_emergency_release(&accounts, &link.program_id)
+        }
+
+        fn withdraw(&mut self) -> ProgramResult {
+            let args = self.link.args();
+            let link = &mut self.link;
+            let accounts = [
+                link.authority.info(),
+                link.admin.info(),
+                link.config.info(),
+                link.cash.info(),
+                link.vault.info(),
+                self.recovery.info(),
+                link.mint.info(),
+                link.fee_payer.info(),
+                link.token_program.info(),
+            ];
+            process_emergency_withdraw(&accounts, &link.program_id, args)
+        }
+
+        fn redeem(&mut self) -> ProgramResult {
+            let link = &mut self.link;
+            let args = link.redeem_args();
+            let mut wallet = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), vec![]);
+            let accounts = [
+                link.authority.info(),
+                wallet.info(),
+                link.fee_payer.info(),
+                link.vault.info(),
+                link.cash.info(),
+            ];
+            process_redemption(&accounts, args, &link.program_id)
+        }
+
+        fn cancel(&mut self) -> ProgramResult {
+            let args = self.link.args();
+            let link = &mut self.link;
+            let accounts = [
+                link.authority.info(),
+                link.cash.info(),
+                link.owner.info(),
+                link.owner_token.info(),
+                link.fee_payer.info(),
+                link.vault.info(),
+                link.mint.info(),
+                link.token_program.info(),
+            ];
+            process_cancel(&accounts, &link.program_id, args)
+        }
+
+        fn close(&mut self) -> ProgramResult {
+            let link = &mut self.link;
+            let accounts = [
+                link.authority.info(),
+                link.cash.info(),
+                link.owner.info(),
+                link.fee_payer.info(),
+            ];
+            process_close(&accounts, &link.program_id)
+        }
+    }
+
+    #[test]
+    fn a_halt_blocks_the_link_until_it_is_released() {
+        let halted = Err(CashError::CashHalted.into());
+        let mut incident = Incident::new();
+        incident.halt().unwrap();
+        assert!(incident.link.cash().halted);
+        assert_eq!(incident.halt(), halted);
+        assert_eq!(incident.redeem(), halted);
+        assert_eq!(incident.cancel(), halted);
+        assert_eq!(TestLink::amount(&incident.link.vault), 3_000);
+
+        incident.release().unwrap();
+        assert!(!incident.link.cash().halted);
+        assert_eq!(incident.release(), Err(CashError::CashNotHalted.into()));
+        // past the halt the redeem fails on the accounts it was not given
+        assert_eq!(incident.redeem(), Err(ProgramError::NotEnoughAccountKeys));
+        incident.cancel().unwrap();
+        assert_eq!(incident.link.cash().state, CashState::Canceled);
+        assert_eq!(TestLink::amount(&incident.link.owner_token), 3_000);
+    }
+
+    #[test]
+    fn a_halted_link_is_not_closed() {
+        let mut incident = Incident::new();
+        incident.link.update(|cash| {
+            cash.state = CashState::Canceled;
+            cash.halted = true;
+        });
+        assert_eq!(incident.close(), Err(CashError::CashHalted.into()));
+        // a canceled link can not be halted in the first place
+        incident.release().unwrap();
+        assert_eq!(incident.halt(), Err(AccountAlreadyCanceled.into()));
+    }
+
+    #[test]
+    fn needs_both_signatures() {
+        let mut incident = Incident::new();
+        incident.link.admin = TestAccount::signer(Pubkey::new_unique());
+        assert_eq!(incident.halt(), Err(CashError::InvalidAdmin.into()));
+        assert_eq!(incident.withdraw(), Err(CashError::InvalidAdmin.into()));
+        let mut incident = Incident::new();
+        incident.link.authority.is_signer = false;
+        assert_eq!(incident.halt(), Err(ProgramError::MissingRequiredSignature));
+        assert_eq!(incident.withdraw(), Err(ProgramError::MissingRequiredSignature));
+    }
+
+    #[test]
+    fn withdraws_the_vault_to_the_recovery_wallet() {
+        let mut incident = Incident::new();
+        incident.halt().unwrap();
+        incident.withdraw().unwrap();
+        assert_eq!(TestLink::amount(&incident.recovery), 3_000);
+        assert_eq!(TestLink::amount(&incident.link.vault), 0);
+        let cash = incident.link.cash();
+        assert_eq!(cash.state, CashState::Canceled);
+        assert_eq!(cash.completion_reason, CompletionReason::Emergency);
+        assert_eq!(cash.canceled_at, Some(NOW as u64));
+        assert!(!cash.halted);
+        // the vault was swept and closed already
+        assert_eq!(incident.withdraw(), Err(AccountAlreadyCanceled.into()));
+    }
+
+    #[test]
+    fn withdraws_only_to_the_recovery_token_account() {
+        let mut incident = Incident::new();
+        incident.recovery.key = Pubkey::new_unique();
+        assert_eq!(incident.withdraw(), Err(CashError::InvalidRecoveryToken.into()));
+        assert_eq!(TestLink::amount(&incident.link.vault), 3_000);
+    }
+}
@@ -0,0 +1,68 @@
+//! Entropy of the random draws
+//!
+//! Random links draw from the most recent slot hash by default. A link created with a
+//! `VrfAccount` source reads the randomness of that account instead, its data must hold:
+//!
+//! - at `VRF_ROUND_OFFSET` the little endian u64 round of the randomness
+//! - at `VRF_RANDOMNESS_OFFSET` the 32 bytes of randomness of that round
+//!
+//! Every draw must use a newer round than the previous draw of the link.
+use std::convert::TryInto;
+
+use crate::{
+    error::CashError,
+    state::cash::{Cash, EntropySource},
+    utils::{find_optional_account, get_random_value},
+};
+
+use solana_program::{account_info::AccountInfo, clock::Clock, program_error::ProgramError};
+
+pub const VRF_ROUND_OFFSET: usize = 8;
+pub const VRF_RANDOMNESS_OFFSET: usize = 16;
+pub const VRF_ACCOUNT_MIN_LEN: usize = VRF_RANDOMNESS_OFFSET + 32;
+
+/// A random value and the vrf round it was read from
+pub struct Entropy {
+    pub value: u64,
+    pub round: Option<u64>,
+}
+
+/// Read the entropy of a draw from the source configured on the link
+pub fn get_entropy(
+    cash: &Cash,
+    accounts: &[AccountInfo],
+    recent_slothashes_info: &AccountInfo,
+    clock: &Clock,
+) -> Result<Entropy, ProgramError> {
+    match cash.entropy_source {
+        EntropySource::SlotHashes => Ok(Entropy {
+            value: get_random_value(recent_slothashes_info, clock)?,
+            round: None,
+        }),
+        EntropySource::VrfAccount(vrf_key) => {
+            let vrf_info = find_optional_account(accounts, &vrf_key)
+                .ok_or(CashError::InvalidEntropyAccount)?;
+            let data = vrf_info.try_borrow_data()?;
+            if data.len() < VRF_ACCOUNT_MIN_LEN {
+                return Err(CashError::InvalidEntropyAccount.into());
+            }
+            let round = u64::from_le_bytes(
+                data[VRF_ROUND_OFFSET..VRF_ROUND_OFFSET + 8]
+                    .try_into()
+                    .map_err(|_| CashError::InvalidEntropyAccount)?,
+            );
+            if round <= cash.last_entropy_round {
+                return Err(CashError::StaleVrf.into());
+            }
+            let value = u64::from_le_bytes(
+                data[VRF_RANDOMNESS_OFFSET..VRF_RANDOMNESS_OFFSET + 8]
+                    .try_into()
+                    .map_err(|_| CashError::InvalidEntropyAccount)?,
+            );
+            Ok(Entropy {
+                value,
+                round: Some(round),
+            })
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Platform fee wallets per deployment epoch
+//!
+//! Permissionless links store the epoch of the latest fee wallet at init rather than locking
+//! the wallet itself. When the treasury rotates its wallet the admin appends the new one and
+//! supersedes the old epoch, redemptions of the links of that epoch then pay their platform
+//! fees to the latest live wallet. Entries are never removed so every routing stays auditable.
+use crate::{
+    error::CashError,
+    find_fee_wallet_registry_program_address,
+    instruction::{FeeWalletRegistryArgs, SupersedeFeeWalletArgs},
+    processor::config::assert_config_admin,
+    state::{
+        cash::Cash,
+        fee_wallet::{FeeWalletEntry, FeeWalletRegistry, MAX_FEE_WALLET_EPOCHS},
+        AccountType,
+    },
+    utils::{assert_owned_by, create_new_account_raw, find_optional_account},
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+
+pub fn process_add_fee_wallet(
+    accounts: &[AccountInfo],
+    args: FeeWalletRegistryArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process add fee wallet");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let registry_info = next_account_info(account_info_iter)?;
+    let wallet_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let mut registry = get_or_create_fee_wallet_registry(
+        program_id,
+        registry_info,
+        fee_payer_info,
+        rent_info,
+        system_program_info,
+        args.registry_bump,
+    )?;
+    if registry.entries.len() >= MAX_FEE_WALLET_EPOCHS {
+        return Err(CashError::FeeWalletRegistryFull.into());
+    }
+    let epoch = registry
+        .next_epoch()
+        .ok_or(CashError::FeeWalletRegistryFull)?;
+    registry.entries.push(FeeWalletEntry {
+        epoch,
+        wallet: *wallet_info.key,
+        superseded: false,
+    });
+    msg!("Fee wallet epoch {} is {}", epoch, wallet_info.key);
+    FeeWalletRegistry::pack(registry, &mut registry_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_supersede_fee_wallet(
+    accounts: &[AccountInfo],
+    args: SupersedeFeeWalletArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process supersede fee wallet");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let registry_info = next_account_info(account_info_iter)?;
+
+    let mut registry = load_fee_wallet_registry(registry_info, program_id)?;
+    let entry = registry
+        .entries
+        .iter_mut()
+        .find(|entry| entry.epoch == args.epoch)
+        .ok_or(CashError::MissingFeeWalletEpoch)?;
+    entry.superseded = true;
+    msg!("Fee wallet epoch {} is superseded", args.epoch);
+    FeeWalletRegistry::pack(registry, &mut registry_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Load the fee wallet registry when it was passed to the instruction
+pub fn find_fee_wallet_registry(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<Option<FeeWalletRegistry>, ProgramError> {
+    let (registry_key, _) = find_fee_wallet_registry_program_address(program_id);
+    find_optional_account(accounts, &registry_key)
+        .map(|registry_info| load_fee_wallet_registry(registry_info, program_id))
+        .transpose()
+}
+
+/// The latest live fee wallet entry when the registry was passed to the instruction
+pub fn find_latest_fee_wallet(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> Result<Option<FeeWalletEntry>, ProgramError> {
+    let registry = find_fee_wallet_registry(accounts, program_id)?;
+    Ok(registry.and_then(|registry| registry.latest().copied()))
+}
+
+/// The wallet collecting the platform fees of a link, resolved through the registry when the
+/// link stores a fee wallet epoch
+pub fn resolve_fee_wallet(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    cash: &Cash,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let epoch = match cash.fee_wallet_epoch {
+        Some(epoch) => epoch,
+        None => return Ok(cash.fee_wallet),
+    };
+    let (registry_key, _) = find_fee_wallet_registry_program_address(program_id);
+    let registry_info =
+        find_optional_account(accounts, &registry_key).ok_or(CashError::MissingFeeWalletEpoch)?;
+    let registry = load_fee_wallet_registry(registry_info, program_id)?;
+    let entry = registry
+        .resolve(epoch)
+        .ok_or(CashError::MissingFeeWalletEpoch)?;
+    if entry.epoch != epoch {
+        msg!(
+            "Fee wallet epoch {} is superseded, fees go to epoch {} wallet {}",
+            epoch,
+            entry.epoch,
+            entry.wallet
+        );
+    }
+    Ok(Some(entry.wallet))
+}
+
+/// Load an initialized fee wallet registry owned by the program
+pub fn load_fee_wallet_registry(
+    registry_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<FeeWalletRegistry, ProgramError> {
+    assert_owned_by(registry_info, program_id, Some("registry"))?;
+    let registry = FeeWalletRegistry::unpack_unchecked(&registry_info.data.borrow())?;
+    if !registry.is_initialized() {
+        return Err(CashError::MissingFeeWalletEpoch.into());
+    }
+    Ok(registry)
+}
+
+fn get_or_create_fee_wallet_registry<'a>(
+    program_id: &Pubkey,
+    registry_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    rent_sysvar_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    registry_bump: u8,
+) -> Result<FeeWalletRegistry, ProgramError> {
+    if registry_info.lamports() > 0 && !registry_info.data_is_empty() {
+        return load_fee_wallet_registry(registry_info, program_id);
+    }
+    create_new_account_raw(
+        program_id,
+        registry_info,
+        rent_sysvar_info,
+        payer_info,
+        system_program_info,
+        FeeWalletRegistry::LEN,
+        &[FeeWalletRegistry::PREFIX.as_bytes(), &[registry_bump]],
+    )?;
+    Ok(FeeWalletRegistry {
+        account_type: AccountType::FeeWalletRegistry,
+        ..Default::default()
+    })
+}
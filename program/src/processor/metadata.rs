@@ -0,0 +1,133 @@
+use crate::{
+    error::CashError::{self, AccountAlreadyCanceled, AccountAlreadyRedeemed},
+    find_cash_metadata_address,
+    instruction::UpdateMetadataArgs,
+    state::{
+        cash::Cash,
+        metadata::CashMetadata,
+        AccountType, AuxiliaryHeader,
+    },
+    utils::{
+        assert_account_key, assert_owned_by, assert_signer, create_new_account_raw,
+        find_optional_account, load_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+
+/// Create the metadata pda of a new cash link, the fee payer funds its rent
+pub fn create_cash_metadata<'a>(
+    accounts: &[AccountInfo<'a>],
+    program_id: &Pubkey,
+    cash_info: &AccountInfo<'a>,
+    fee_payer_info: &AccountInfo<'a>,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    uri: String,
+) -> ProgramResult {
+    if !CashMetadata::is_valid_uri(&uri) {
+        return Err(CashError::InvalidMetadataUri.into());
+    }
+    let (metadata_key, metadata_bump) = find_cash_metadata_address(program_id, cash_info.key);
+    let metadata_info =
+        find_optional_account(accounts, &metadata_key).ok_or(CashError::InvalidMetadataKey)?;
+    if metadata_info.lamports() > 0 && !metadata_info.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    create_new_account_raw(
+        program_id,
+        metadata_info,
+        rent_info,
+        fee_payer_info,
+        system_program_info,
+        CashMetadata::LEN,
+        &[
+            CashMetadata::PREFIX.as_bytes(),
+            cash_info.key.as_ref(),
+            &[metadata_bump],
+        ],
+    )?;
+    let metadata = CashMetadata {
+        header: AuxiliaryHeader::new(fee_payer_info.key),
+        account_type: AccountType::Metadata,
+        cash: *cash_info.key,
+        uri,
+    };
+    CashMetadata::pack(metadata, &mut metadata_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_update_metadata(
+    accounts: &[AccountInfo],
+    args: UpdateMetadataArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process update metadata");
+    let account_info_iter = &mut accounts.iter();
+    let owner_info = next_account_info(account_info_iter)?;
+    assert_signer(owner_info)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    assert_account_key(owner_info, &cash.owner, Some(CashError::InvalidOwner), Some("owner"))?;
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    if cash.canceled() || cash.cancel_pending() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if !CashMetadata::is_valid_uri(&args.uri) {
+        return Err(CashError::InvalidMetadataUri.into());
+    }
+    let mut metadata = load_cash_metadata(metadata_info, program_id, cash_info.key)?;
+    metadata.uri = args.uri;
+    CashMetadata::pack(metadata, &mut metadata_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Load the metadata of a cash link, the account must be the metadata pda of the link
+pub fn load_cash_metadata(
+    metadata_info: &AccountInfo,
+    program_id: &Pubkey,
+    cash_key: &Pubkey,
+) -> Result<CashMetadata, ProgramError> {
+    assert_owned_by(metadata_info, program_id, Some("metadata"))?;
+    let (metadata_key, _) = find_cash_metadata_address(program_id, cash_key);
+    assert_account_key(
+        metadata_info,
+        &metadata_key,
+        Some(CashError::InvalidMetadataKey),
+        Some("metadata"),
+    )?;
+    let metadata = CashMetadata::unpack_unchecked(&metadata_info.data.borrow())?;
+    if !metadata.is_initialized() {
+        return Err(CashError::InvalidMetadataKey.into());
+    }
+    Ok(metadata)
+}
+
+/// Find the metadata pda of a cash link among the accounts
+pub fn find_cash_metadata<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+    cash_key: &Pubkey,
+) -> Result<Option<&'b AccountInfo<'a>>, ProgramError> {
+    let (metadata_key, _) = find_cash_metadata_address(program_id, cash_key);
+    match find_optional_account(accounts, &metadata_key) {
+        Some(metadata_info) => {
+            load_cash_metadata(metadata_info, program_id, cash_key)?;
+            Ok(Some(metadata_info))
+        }
+        None => Ok(None),
+    }
+}
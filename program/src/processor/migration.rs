@@ -0,0 +1,221 @@
+//! Maintenance of links created by earlier program versions
+use crate::{
+    error::CashError,
+    instruction::{BackfillVersionArgs, MigrateBatchArgs, MAX_MIGRATE_BATCH},
+    processor::config::assert_config_admin,
+    state::{
+        cash::{Cash, LEGACY_CASH_DATA_SIZE},
+        AccountType,
+    },
+    utils::{assert_owned_by, assert_signer, load_account, native_transfer, store_account},
+    PROGRAM_VERSION,
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::Sysvar,
+};
+
+/// Record the version that created a legacy link and grow it to the current layout, the admin
+/// pays the rent of the extra bytes
+pub fn process_backfill_version(
+    accounts: &[AccountInfo],
+    args: BackfillVersionArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process backfill version");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    if cash.created_by_version != 0 {
+        return Err(CashError::VersionAlreadyRecorded.into());
+    }
+    if args.created_by_version == 0 || args.created_by_version > PROGRAM_VERSION {
+        return Err(CashError::InvalidProgramVersion.into());
+    }
+    grow_to_current_layout(cash_info, admin_info, &Rent::get()?)?;
+    msg!(
+        "Link {} created by version {}",
+        cash_info.key,
+        args.created_by_version
+    );
+    cash.created_by_version = args.created_by_version;
+    store_account(cash, cash_info)
+}
+
+/// Grow a batch of legacy links to the current layout, see `CashInstruction::MigrateBatch`
+pub fn process_migrate_batch(
+    accounts: &[AccountInfo],
+    args: MigrateBatchArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process migrate batch");
+    if args.count > MAX_MIGRATE_BATCH {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let account_info_iter = &mut accounts.iter();
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    assert_signer(fee_payer_info)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    let rent = Rent::get()?;
+    let mut migrated: u8 = 0;
+    for index in 0..args.count {
+        let cash_info = next_account_info(account_info_iter)?;
+        if cash_info.data_len() == Cash::LEN {
+            msg!("Link {} is at the current layout, skipped", cash_info.key);
+            continue;
+        }
+        // a bad account fails the batch, the caller must not take it for migrated
+        let cash = match unpack_legacy_cash(cash_info, program_id) {
+            Ok(cash) => cash,
+            Err(error) => {
+                msg!(
+                    "Account {} at index {} not migrated: {}",
+                    cash_info.key,
+                    index,
+                    error
+                );
+                return Err(error.into());
+            }
+        };
+        grow_to_current_layout(cash_info, fee_payer_info, &rent)?;
+        store_account(cash, cash_info)?;
+        migrated += 1;
+    }
+    msg!("Migrated {} of {} links", migrated, args.count);
+    set_return_data(&[migrated]);
+    Ok(())
+}
+
+/// Borsh size of a link of the first release without a pass key, the only optional field
+const FIRST_RELEASE_MIN_LENGTH: usize = 158;
+
+/// Unpack a link of the first release, the data must hold a cash account of that release that
+/// round trips, with nothing but zeros after it
+fn unpack_legacy_cash(cash_info: &AccountInfo, program_id: &Pubkey) -> Result<Cash, CashError> {
+    if cash_info.owner != program_id {
+        return Err(CashError::UnrecognizedLegacyLayout);
+    }
+    let data = cash_info
+        .try_borrow_data()
+        .map_err(|_| CashError::UnrecognizedLegacyLayout)?;
+    if data.len() != LEGACY_CASH_DATA_SIZE {
+        return Err(CashError::UnrecognizedLegacyLayout);
+    }
+    let cash = Cash::unpack_unchecked(&data).map_err(|_| CashError::UnrecognizedLegacyLayout)?;
+    if cash.account_type != AccountType::Cash || cash.in_progress {
+        return Err(CashError::UnrecognizedLegacyLayout);
+    }
+    let length = FIRST_RELEASE_MIN_LENGTH + cash.pass_key.map_or(0, |_| 32);
+    let serialized = borsh::to_vec(&cash).map_err(|_| CashError::UnrecognizedLegacyLayout)?;
+    if data[..length] != serialized[..length] || data[length..].iter().any(|byte| *byte != 0) {
+        return Err(CashError::UnrecognizedLegacyLayout);
+    }
+    Ok(cash)
+}
+
+/// Realloc a legacy link to `Cash::LEN`, the payer tops up its rent
+fn grow_to_current_layout<'a>(
+    cash_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    rent: &Rent,
+) -> ProgramResult {
+    if cash_info.data_len() >= Cash::LEN {
+        return Ok(());
+    }
+    let required_lamports = rent.minimum_balance(Cash::LEN);
+    if cash_info.lamports() < required_lamports {
+        native_transfer(
+            payer_info,
+            cash_info,
+            required_lamports - cash_info.lamports(),
+            &[],
+        )?;
+    }
+    cash_info.realloc(Cash::LEN, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::cash::CashState;
+
+    /// A link as the first release stored it, the fields appended since then left unset
+    fn legacy_data() -> Vec<u8> {
+        legacy_data_with(None)
+    }
+
+    fn legacy_data_with(pass_key: Option<Pubkey>) -> Vec<u8> {
+        let cash = Cash {
+            account_type: AccountType::Cash,
+            authority: Pubkey::new_unique(),
+            state: CashState::Initialized,
+            amount: 1_000,
+            remaining_amount: 1_000,
+            owner: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            max_num_redemptions: 1,
+            pass_key,
+            ..Cash::default()
+        };
+        let mut data = borsh::to_vec(&cash).unwrap();
+        data.truncate(LEGACY_CASH_DATA_SIZE);
+        data
+    }
+
+    fn unpack(mut data: Vec<u8>, owner: &Pubkey) -> Result<Cash, CashError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, owner, false, 0);
+        unpack_legacy_cash(&info, &crate::id())
+    }
+
+    fn rejected(data: Vec<u8>, owner: &Pubkey) -> bool {
+        matches!(unpack(data, owner), Err(CashError::UnrecognizedLegacyLayout))
+    }
+
+    #[test]
+    fn migrates_links_of_the_first_release() {
+        let cash = unpack(legacy_data(), &crate::id()).unwrap();
+        assert_eq!(cash.account_type, AccountType::Cash);
+        assert_eq!(cash.amount, 1_000);
+        assert_eq!(cash.fee_payer, Pubkey::default());
+
+        let pass_key = Pubkey::new_unique();
+        let cash = unpack(legacy_data_with(Some(pass_key)), &crate::id()).unwrap();
+        assert_eq!(cash.pass_key, Some(pass_key));
+    }
+
+    #[test]
+    fn rejects_accounts_that_are_not_legacy_links() {
+        let foreign = Pubkey::new_unique();
+        assert!(rejected(legacy_data(), &foreign));
+        let mut short = legacy_data();
+        short.pop();
+        assert!(rejected(short, &crate::id()));
+        let mut current = legacy_data();
+        current.resize(Cash::LEN, 0);
+        assert!(rejected(current, &crate::id()));
+
+        let mut trailing = legacy_data();
+        *trailing.last_mut().unwrap() = 1;
+        assert!(rejected(trailing, &crate::id()));
+        let mut config = legacy_data();
+        config[0] = AccountType::Config as u8;
+        assert!(rejected(config, &crate::id()));
+    }
+}
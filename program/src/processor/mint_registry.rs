@@ -0,0 +1,399 @@
+use crate::{
+    error::CashError,
+    find_mint_registry_program_address,
+    instruction::{MintRegistryArgs, SetMintCapArgs, SetMintModeArgs},
+    processor::config::assert_config_admin,
+    state::{
+        mint_registry::{MintRegistry, MAX_REGISTERED_MINTS},
+        AccountType,
+    },
+    utils::{
+        assert_owned_by, cmp_pubkeys, create_new_account_raw, err_ctx, exists,
+        find_optional_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+
+pub fn process_add_mint(
+    accounts: &[AccountInfo],
+    args: MintRegistryArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process add mint");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let registry_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let mut registry = get_or_create_mint_registry(
+        program_id,
+        registry_info,
+        fee_payer_info,
+        rent_info,
+        system_program_info,
+        args.registry_bump,
+    )?;
+    if registry.contains(mint_info.key) {
+        return Err(CashError::MintAlreadyRegistered.into());
+    }
+    if registry.mints.len() >= MAX_REGISTERED_MINTS {
+        return Err(CashError::MintRegistryFull.into());
+    }
+    registry.mints.push(*mint_info.key);
+    MintRegistry::pack(registry, &mut registry_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_remove_mint(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process remove mint");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let registry_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    let mut registry = load_mint_registry(registry_info, program_id)?;
+    let position = registry
+        .mints
+        .iter()
+        .position(|mint| cmp_pubkeys(mint, mint_info.key))
+        .ok_or(CashError::MintNotRegistered)?;
+    registry.mints.swap_remove(position);
+    MintRegistry::pack(registry, &mut registry_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_set_mint_mode(
+    accounts: &[AccountInfo],
+    args: SetMintModeArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process set mint mode");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let registry_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let mut registry = get_or_create_mint_registry(
+        program_id,
+        registry_info,
+        fee_payer_info,
+        rent_info,
+        system_program_info,
+        args.registry_bump,
+    )?;
+    registry.mode = args.mode;
+    MintRegistry::pack(registry, &mut registry_info.data.borrow_mut())?;
+    Ok(())
+}
+
+pub fn process_set_mint_cap(
+    accounts: &[AccountInfo],
+    args: SetMintCapArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process set mint cap");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let registry_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    let mut registry = get_or_create_mint_registry(
+        program_id,
+        registry_info,
+        fee_payer_info,
+        rent_info,
+        system_program_info,
+        args.registry_bump,
+    )?;
+    registry.set_daily_cap(mint_info.key, args.daily_cap)?;
+    match args.daily_cap {
+        Some(daily_cap) => msg!("Daily cap of mint {} set to {}", mint_info.key, daily_cap),
+        None => msg!("Daily cap of mint {} cleared", mint_info.key),
+    }
+    MintRegistry::pack(registry, &mut registry_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Fail when the mint registry refuses the mint. A link initialized by the platform authority
+/// is only checked against a registry it passes, a permissionless one must pass the registry
+/// pda so leaving it out can not skip the check. A registry that was never created allows
+/// every mint
+pub fn assert_mint_allowed(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    mint_info: &AccountInfo,
+    permissionless: bool,
+) -> ProgramResult {
+    let (mint_registry_key, _) = find_mint_registry_program_address(program_id);
+    let mint_registry_info = match find_optional_account(accounts, &mint_registry_key) {
+        Some(mint_registry_info) => mint_registry_info,
+        None if permissionless => {
+            msg!("A permissionless init must pass the mint registry {}", mint_registry_key);
+            return err_ctx!(CashError::MintNotAllowed, "mint", mint_info);
+        }
+        None => return Ok(()),
+    };
+    if !exists(mint_registry_info)? || mint_registry_info.data_is_empty() {
+        return Ok(());
+    }
+    let mint_registry = load_mint_registry(mint_registry_info, program_id)?;
+    if !mint_registry.is_allowed(mint_info.key) {
+        return err_ctx!(CashError::MintNotAllowed, "mint", mint_info);
+    }
+    Ok(())
+}
+
+/// Load an initialized mint registry owned by the program
+pub fn load_mint_registry(
+    registry_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<MintRegistry, ProgramError> {
+    assert_owned_by(registry_info, program_id, Some("registry"))?;
+    let registry = MintRegistry::unpack_unchecked(&registry_info.data.borrow())?;
+    if !registry.is_initialized() {
+        return Err(CashError::InvalidMintRegistryKey.into());
+    }
+    Ok(registry)
+}
+
+fn get_or_create_mint_registry<'a>(
+    program_id: &Pubkey,
+    registry_info: &AccountInfo<'a>,
+    payer_info: &AccountInfo<'a>,
+    rent_sysvar_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    registry_bump: u8,
+) -> Result<MintRegistry, ProgramError> {
+    if registry_info.lamports() > 0 && !registry_info.data_is_empty() {
+        return load_mint_registry(registry_info, program_id);
+    }
+    create_new_account_raw(
+        program_id,
+        registry_info,
+        rent_sysvar_info,
+        payer_info,
+        system_program_info,
+        MintRegistry::LEN,
+        &[MintRegistry::PREFIX.as_bytes(), &[registry_bump]],
+    )?;
+    Ok(MintRegistry {
+        account_type: AccountType::MintRegistry,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        find_config_program_address,
+        state::{config::Config, mint_registry::MintMode},
+        test_utils::TestAccount,
+    };
+
+    /// The config, its admin and a created registry in `mode` listing one mint
+    struct Registry {
+        program_id: Pubkey,
+        admin: TestAccount,
+        config: TestAccount,
+        registry: TestAccount,
+        mint: TestAccount,
+        other: TestAccount,
+    }
+
+    impl Registry {
+        fn new(mode: MintMode) -> Self {
+            let program_id = Pubkey::new_unique();
+            let admin = Pubkey::new_unique();
+            let config = Config {
+                account_type: AccountType::Config,
+                admin,
+                ..Config::default()
+            };
+            let mint = Pubkey::new_unique();
+            let registry = MintRegistry {
+                account_type: AccountType::MintRegistry,
+                mode,
+                mints: vec![mint],
+                caps: vec![],
+            };
+            Self {
+                program_id,
+                admin: TestAccount::signer(admin),
+                config: TestAccount::packed(
+                    find_config_program_address(&program_id).0,
+                    program_id,
+                    config,
+                ),
+                registry: TestAccount::packed(
+                    find_mint_registry_program_address(&program_id).0,
+                    program_id,
+                    registry,
+                ),
+                mint: TestAccount::new(mint, spl_token::id(), vec![]),
+                other: TestAccount::new(Pubkey::new_unique(), spl_token::id(), vec![]),
+            }
+        }
+
+        fn add_mint(&mut self) -> ProgramResult {
+            let (mut rent, mut system) = unused_accounts();
+            let admin = self.admin.info();
+            let accounts = [
+                admin.clone(),
+                self.config.info(),
+                admin,
+                self.registry.info(),
+                self.other.info(),
+                rent.info(),
+                system.info(),
+            ];
+            process_add_mint(&accounts, MintRegistryArgs { registry_bump: 255 }, &self.program_id)
+        }
+
+        fn remove_mint(&mut self) -> ProgramResult {
+            let accounts = [
+                self.admin.info(),
+                self.config.info(),
+                self.registry.info(),
+                self.mint.info(),
+            ];
+            process_remove_mint(&accounts, &self.program_id)
+        }
+
+        fn set_mode(&mut self, mode: MintMode) -> ProgramResult {
+            let (mut rent, mut system) = unused_accounts();
+            let admin = self.admin.info();
+            let accounts = [
+                admin.clone(),
+                self.config.info(),
+                admin,
+                self.registry.info(),
+                rent.info(),
+                system.info(),
+            ];
+            let args = SetMintModeArgs {
+                registry_bump: 255,
+                mode,
+            };
+            process_set_mint_mode(&accounts, args, &self.program_id)
+        }
+
+        fn set_cap(&mut self, daily_cap: Option<u64>) -> ProgramResult {
+            let (mut rent, mut system) = unused_accounts();
+            let admin = self.admin.info();
+            let accounts = [
+                admin.clone(),
+                self.config.info(),
+                admin,
+                self.registry.info(),
+                self.mint.info(),
+                rent.info(),
+                system.info(),
+            ];
+            let args = SetMintCapArgs {
+                registry_bump: 255,
+                daily_cap,
+            };
+            process_set_mint_cap(&accounts, args, &self.program_id)
+        }
+
+        fn check(&mut self, permissionless: bool) -> (ProgramResult, ProgramResult) {
+            let accounts = [self.registry.info()];
+            let program_id = &self.program_id;
+            (
+                assert_mint_allowed(&accounts, program_id, &self.mint.info(), permissionless),
+                assert_mint_allowed(&accounts, program_id, &self.other.info(), permissionless),
+            )
+        }
+
+        fn stored(&self) -> MintRegistry {
+            self.registry.unpack()
+        }
+    }
+
+    /// The rent sysvar and system program, only read when the registry is created
+    fn unused_accounts() -> (TestAccount, TestAccount) {
+        (
+            TestAccount::new(solana_program::sysvar::rent::id(), Pubkey::default(), vec![]),
+            TestAccount::new(Pubkey::default(), Pubkey::default(), vec![]),
+        )
+    }
+
+    #[test]
+    fn checks_mints_against_the_mode_of_the_registry() {
+        let refused = Err(CashError::MintNotAllowed.into());
+        let mut registry = Registry::new(MintMode::Disabled);
+        assert_eq!(registry.check(true), (Ok(()), Ok(())));
+        let mut registry = Registry::new(MintMode::Allowlist);
+        assert_eq!(registry.check(true), (Ok(()), refused.clone()));
+        let mut registry = Registry::new(MintMode::Denylist);
+        assert_eq!(registry.check(false), (refused, Ok(())));
+    }
+
+    #[test]
+    fn allows_every_mint_without_a_registry() {
+        let mut registry = Registry::new(MintMode::Allowlist);
+        registry.registry = TestAccount::new(registry.registry.key, Pubkey::default(), vec![]);
+        registry.registry.lamports = 0;
+        assert_eq!(registry.check(true), (Ok(()), Ok(())));
+        // nothing to remove a mint from
+        assert_eq!(registry.remove_mint(), Err(CashError::InvalidOwner.into()));
+    }
+
+    #[test]
+    fn the_admin_manages_the_registry() {
+        let mut registry = Registry::new(MintMode::Disabled);
+        registry.add_mint().unwrap();
+        assert_eq!(registry.add_mint(), Err(CashError::MintAlreadyRegistered.into()));
+        registry.remove_mint().unwrap();
+        assert_eq!(registry.remove_mint(), Err(CashError::MintNotRegistered.into()));
+        registry.set_mode(MintMode::Allowlist).unwrap();
+        registry.set_cap(Some(1_000)).unwrap();
+        let stored = registry.stored();
+        assert_eq!(stored.mints, vec![registry.other.key]);
+        assert_eq!(stored.mode, MintMode::Allowlist);
+        assert_eq!(stored.daily_cap(&registry.mint.key), Some(1_000));
+    }
+
+    #[test]
+    fn refuses_a_signer_other_than_the_admin() {
+        let mut registry = Registry::new(MintMode::Denylist);
+        registry.admin = TestAccount::signer(Pubkey::new_unique());
+        let refused = Err(CashError::InvalidAdmin.into());
+        assert_eq!(registry.add_mint(), refused);
+        assert_eq!(registry.remove_mint(), refused);
+        assert_eq!(registry.set_mode(MintMode::Disabled), refused);
+        assert_eq!(registry.set_cap(Some(1_000)), refused);
+
+        // the admin key without its signature
+        let mut registry = Registry::new(MintMode::Denylist);
+        registry.admin.is_signer = false;
+        let unsigned = Err(ProgramError::MissingRequiredSignature);
+        assert_eq!(registry.set_mode(MintMode::Disabled), unsigned);
+        assert_eq!(registry.stored().mode, MintMode::Denylist);
+    }
+}
@@ -0,0 +1,368 @@
+//! Timelocked recovery of abandoned links
+//!
+//! When both the owner and the authority keys of a link are lost its funds are stuck. A config
+//! admin may request a recovery and, once the timelock of the config elapsed without any sign
+//! of life from the link, refund the vault to the owner and close the link. The admin never
+//! receives any of the funds.
+use crate::{
+    error::CashError::{self, AccountAlreadyCanceled, AccountAlreadyRedeemed},
+    event::{CashEvent, CompletionEvent, RecoveryRequestedEvent},
+    instruction::CancelCashRedemptionArgs,
+    math::SafeMath,
+    processor::{
+        cash::{assert_fee_payer, assert_vault, close_cash_account, lamport_refund_receiver},
+        config::assert_config_admin,
+        stats::update_stats,
+    },
+    state::cash::{Cash, CashState, CompletionReason},
+    utils::{
+        assert_account_key, assert_initialized, assert_owned_by, assert_valid_token_program,
+        create_associated_token_account_idempotent_raw, is_native_mint, load_account,
+        spl_token_close, spl_token_transfer, store_account, unwrap_native_vault,
+    },
+    PROGRAM_VERSION,
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::state::{Account as TokenAccount, Mint};
+
+pub fn process_initiate_recovery(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+    msg!("Process initiate recovery");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let config = assert_config_admin(config_info, admin_info, program_id)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    if cash.canceled() || cash.cancel_pending() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    if cash.recovery_requested_at.is_some() {
+        return Err(CashError::RecoveryTimelockActive.into());
+    }
+    let now = Clock::get()?.unix_timestamp as u64;
+    let executable_at = now.error_add(config.recovery_timelock())?;
+    msg!(
+        "RECOVERY REQUESTED for cash {} by admin {}, executable at {}",
+        cash_info.key,
+        admin_info.key,
+        executable_at
+    );
+    cash.recovery_requested_at = Some(now);
+    CashEvent::RecoveryRequested(RecoveryRequestedEvent {
+        cash: *cash_info.key,
+        admin: *admin_info.key,
+        requested_at: now,
+        executable_at,
+    })
+    .emit();
+    store_account(cash, cash_info)?;
+    Ok(())
+}
+
+pub fn process_execute_recovery(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    args: CancelCashRedemptionArgs,
+) -> ProgramResult {
+    msg!("Process execute recovery");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let config = assert_config_admin(config_info, admin_info, program_id)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let owner_token_info = next_account_info(account_info_iter)?;
+    let vault_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(token_program_info.key)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    if cash.canceled() || cash.cancel_pending() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    let requested_at = cash
+        .recovery_requested_at
+        .ok_or(CashError::RecoveryNotRequested)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    if now < requested_at.error_add(config.recovery_timelock())? {
+        return Err(CashError::RecoveryTimelockActive.into());
+    }
+    // the refund only ever goes to the owner, never to the admin running the recovery
+    assert_account_key(owner_info, &cash.owner, Some(CashError::InvalidOwner), Some("owner"))?;
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    assert_fee_payer(&cash, fee_payer_info)?;
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+    let mint: Mint = assert_initialized(mint_info, Some("mint"))?;
+
+    let signer_seeds = [
+        Cash::PREFIX.as_bytes(),
+        args.cash_reference.as_bytes(),
+        &[args.cash_bump],
+    ];
+    if vault_token.amount > 0 && is_native_mint(mint_info.key) {
+        unwrap_native_vault(
+            vault_token_info,
+            cash_info,
+            lamport_refund_receiver(accounts, &cash, owner_info)?,
+            fee_payer_info,
+            token_program_info.key,
+            vault_token.amount,
+            &[&signer_seeds],
+        )?;
+    } else {
+        if vault_token.amount > 0 {
+            let owner_token = get_associated_token_address_with_program_id(
+                &cash.owner,
+                &cash.mint,
+                token_program_info.key,
+            );
+            assert_account_key(
+                owner_token_info,
+                &owner_token,
+                Some(CashError::InvalidOwner),
+                Some("owner token"),
+            )?;
+            create_associated_token_account_idempotent_raw(
+                admin_info,
+                owner_token_info,
+                owner_info,
+                mint_info,
+                token_program_info.key,
+            )?;
+            spl_token_transfer(
+                vault_token_info,
+                owner_token_info,
+                cash_info,
+                mint_info,
+                token_program_info.key,
+                vault_token.amount,
+                mint.decimals,
+                &[&signer_seeds],
+            )?;
+        }
+        spl_token_close(
+            vault_token_info,
+            fee_payer_info,
+            cash_info,
+            token_program_info.key,
+            &[&signer_seeds],
+        )?;
+    }
+    update_stats(accounts, program_id, |stats| {
+        stats.record_refund(&cash.mint, vault_token.amount)
+    })?;
+
+    cash.state = CashState::Canceled;
+    cash.completion_reason = CompletionReason::Recovered;
+    cash.canceled_at = Some(now);
+    cash.halted = false;
+    msg!("Recovered {} to owner {}", vault_token.amount, cash.owner);
+    CompletionEvent {
+        cash: *cash_info.key,
+        reason: cash.completion_reason,
+        total_redemptions: cash.total_redemptions,
+        remaining_amount: cash.remaining_amount,
+        timestamp: now,
+        created_by_version: cash.created_by_version,
+        last_touched_version: PROGRAM_VERSION,
+    }
+    .emit();
+    close_cash_account(
+        accounts,
+        program_id,
+        &cash,
+        cash_info,
+        fee_payer_info,
+        owner_info,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        find_pending_redemption_address,
+        instruction::{CancelBeginArgs, SplitCashArgs},
+        processor::{
+            approval::request_approval,
+            cash::process_cancel_begin,
+            redemption::{commit_state, plan_redemption},
+            split::process_split_cash,
+        },
+        state::{config::DEFAULT_RECOVERY_TIMELOCK, pending::PendingRedemption},
+        test_utils::{runtime, TestAccount, TestLink, TestRedemption},
+    };
+    use solana_program::program_pack::Pack;
+
+    const REQUESTED_AT: i64 = 1_700_000_000;
+
+    /// A live link of 4_000 over 4 redemptions whose recovery the admin requested
+    fn abandoned() -> TestLink {
+        let mut link = TestLink::new(4_000, 4);
+        link.update(|cash| cash.recovery_requested_at = Some(REQUESTED_AT as u64));
+        runtime(REQUESTED_AT + 60);
+        link
+    }
+
+    fn execute(link: &mut TestLink) -> ProgramResult {
+        let args = link.args();
+        let accounts = [
+            link.admin.info(),
+            link.config.info(),
+            link.cash.info(),
+            link.owner.info(),
+            link.owner_token.info(),
+            link.vault.info(),
+            link.mint.info(),
+            link.fee_payer.info(),
+            link.token_program.info(),
+        ];
+        process_execute_recovery(&accounts, &link.program_id, args)
+    }
+
+    #[test]
+    fn executes_the_recovery_once_the_timelock_elapsed() {
+        let mut link = abandoned();
+        let executable_at = REQUESTED_AT + DEFAULT_RECOVERY_TIMELOCK as i64;
+        runtime(executable_at - 1);
+        assert_eq!(execute(&mut link), Err(CashError::RecoveryTimelockActive.into()));
+        assert_eq!(TestLink::amount(&link.vault), 4_000);
+
+        runtime(executable_at);
+        execute(&mut link).unwrap();
+        assert_eq!(TestLink::amount(&link.owner_token), 4_000);
+        assert_eq!(TestLink::amount(&link.vault), 0);
+        // the link is closed with the refund, its rent goes to the owner
+        assert_eq!(link.cash.lamports, 0);
+        assert_eq!(link.owner.lamports, 2_000_000);
+    }
+
+    #[test]
+    fn only_a_requested_recovery_is_executed() {
+        let mut link = TestLink::new(4_000, 4);
+        runtime(REQUESTED_AT + DEFAULT_RECOVERY_TIMELOCK as i64);
+        assert_eq!(execute(&mut link), Err(CashError::RecoveryNotRequested.into()));
+    }
+
+    #[test]
+    fn a_redemption_clears_the_recovery() {
+        let mut redemption = TestRedemption::new(abandoned());
+        let args = redemption.args();
+        let program_id = redemption.link.program_id;
+        redemption
+            .with_context(|accounts, ctx| {
+                let plan =
+                    plan_redemption(&ctx.cash, &args, 0, ctx.vault_amount, false, true, 1, None)?;
+                commit_state(accounts, &program_id, ctx, &plan, 0, None)
+            })
+            .unwrap();
+        let cash = redemption.link.cash();
+        assert_eq!(cash.recovery_requested_at, None);
+        assert_eq!(cash.total_redemptions, 1);
+    }
+
+    #[test]
+    fn an_approval_request_clears_the_recovery() {
+        let mut redemption = TestRedemption::new(abandoned());
+        let (cash_key, wallet) = (redemption.link.cash.key, redemption.wallet.key);
+        let program_id = redemption.link.program_id;
+        let (pending, _) = find_pending_redemption_address(&program_id, &cash_key, &wallet);
+        redemption.remaining = vec![TestAccount::uncreated(pending, PendingRedemption::LEN)];
+        let args = redemption.args();
+        redemption
+            .with_context(|accounts, ctx| {
+                let plan =
+                    plan_redemption(&ctx.cash, &args, 0, ctx.vault_amount, false, true, 1, None)?;
+                request_approval(&program_id, ctx, &accounts[17], &args, &plan)
+            })
+            .unwrap();
+        let cash = redemption.link.cash();
+        assert_eq!(cash.recovery_requested_at, None);
+        assert_eq!(cash.pending_approvals, 1);
+    }
+
+    #[test]
+    fn a_cancel_clears_the_recovery() {
+        let mut link = abandoned();
+        let args = CancelBeginArgs {
+            cash_bump: link.cash_bump,
+            cash_reference: TestLink::REFERENCE.to_string(),
+            max_amount: 1_000,
+        };
+        let accounts = [
+            link.authority.info(),
+            link.cash.info(),
+            link.owner.info(),
+            link.owner_token.info(),
+            link.vault.info(),
+            link.mint.info(),
+            link.token_program.info(),
+        ];
+        process_cancel_begin(&accounts, &link.program_id, args).unwrap();
+        let cash = link.cash();
+        assert_eq!(cash.recovery_requested_at, None);
+        assert_eq!(cash.state, CashState::CancelPending);
+    }
+
+    #[test]
+    fn a_split_clears_the_recovery() {
+        let mut link = abandoned();
+        let program_id = link.program_id;
+        let (child_key, child_bump) = Pubkey::find_program_address(
+            &[Cash::PREFIX.as_bytes(), b"child"],
+            &program_id,
+        );
+        let mut child = TestAccount::uncreated(child_key, Cash::LEN);
+        let mut child_vault = TestAccount::token(link.ata(&child_key), link.mint.key, child_key, 0);
+        let mut rent = TestAccount::rent();
+        let mut system = TestAccount::new(Pubkey::default(), Pubkey::default(), vec![]);
+        let args = SplitCashArgs {
+            cash_bump: link.cash_bump,
+            cash_reference: TestLink::REFERENCE.to_string(),
+            child_bump,
+            child_reference: "child".to_string(),
+            amount: 1_000,
+            max_num_redemptions: 1,
+        };
+        let accounts = [
+            link.authority.info(),
+            link.owner.info(),
+            link.fee_payer.info(),
+            link.cash.info(),
+            link.vault.info(),
+            child.info(),
+            child_vault.info(),
+            link.mint.info(),
+            rent.info(),
+            system.info(),
+            link.token_program.info(),
+        ];
+        process_split_cash(&accounts, args, &program_id).unwrap();
+        let cash = link.cash();
+        assert_eq!(cash.recovery_requested_at, None);
+        assert_eq!(cash.remaining_amount, 3_000);
+        assert_eq!(TestLink::amount(&child_vault), 1_000);
+    }
+}
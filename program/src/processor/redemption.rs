@@ -0,0 +1,2080 @@
+//! Redemption processor
+//!
+//! A redemption runs in four stages: the accounts are validated into a typed
+//! [`RedemptionContext`], the amounts are planned by the pure [`plan_redemption`],
+//! the plan is executed with the token and system program CPIs, and finally the
+//! new state is committed to the cash account. Every check runs before the first CPI, a
+//! redemption bound to fail never has the fee payer fund an account creation first.
+//!
+//! The cash account is flagged as in progress before the first CPI and cleared when the state
+//! is committed. The runtime currently only allows direct self recursion, so a hook program
+//! calling back into this program is rejected before it gets here; the flag keeps a nested
+//! Cancel, Close or Redeem from acting on stale state should that restriction ever be lifted.
+use crate::{
+    core_math::{
+        compute_redemption, savings_split, RedemptionInput, RedemptionOutput, BPS_DENOMINATOR,
+    },
+    error::CashError::{self, AccountAlreadyCanceled, AccountAlreadyRedeemed},
+    event::{CashEvent, CompletionEvent, LedgerEvent, LegKind, RedemptionEvent},
+    find_kyc_attestation_address, find_redemption_address, find_referral_stats_address,
+    redemption_receipt_seeds,
+    instruction::{
+        InitCashRedemptionArgs, RedemptionEntry, ValidateRedemptionsArgs,
+        MAX_VALIDATE_REDEMPTIONS,
+    },
+    math::SafeMath,
+    permit::verify_recipient_permit,
+    processor::{
+        approval::{find_pending_redemption, request_approval},
+        buffer::find_cash_buffer,
+        cash::{assert_vault, lamport_refund_receiver, mint_decimals},
+        commit::reveal_commit,
+        config::{assert_config_features, find_config},
+        stats::update_stats,
+        entropy::get_entropy,
+        fee_wallet::{find_fee_wallet_registry, resolve_fee_wallet},
+        referral::{get_or_create_referral_stats, load_referral_stats, update_referral_stats},
+        swap::{find_swap_accounts, SwapAccounts},
+        wallet_limit::record_wallet_limit,
+    },
+    state::{
+        buffer::Buffer,
+        config::{min_payout_for_mint, Config, Feature},
+        cash::{Cash, CashState, CompletionReason, DistributionType},
+        redemption::{RedemptionIndex, RedemptionReceipt},
+        AuxiliaryHeader,
+    },
+    utils::{
+        assert_account_key, assert_initialized, assert_owned_by, assert_signer,
+        load_token_account, assert_token_account, assert_valid_token_program,
+        cmp_pubkeys, create_associated_token_account_idempotent_raw,
+        create_associated_token_account_raw, create_new_account_raw, exists,
+        find_optional_account, is_native_mint, native_transfer, requires_memo, transfer_if_nonzero,
+        spl_memo, spl_token_burn, spl_token_close, spl_token_transfer, unwrap_native_vault, MEMO_PROGRAM_ID,
+        load_account, store_account, assert_distinct_token_accounts, assert_not_frozen,
+        spl_token_approve,
+    },
+    PROGRAM_VERSION,
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    sysvar::{self, clock::Clock, slot_hashes, Sysvar},
+};
+use arrayref::array_ref;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::state::Account as TokenAccount;
+
+/// Wallet pubkey followed by the expiry timestamp
+const KYC_ATTESTATION_MIN_LEN: usize = 40;
+
+/// The optional referral accounts of a redemption
+pub struct ReferralAccounts<'a, 'b> {
+    pub wallet_info: &'b AccountInfo<'a>,
+    pub token_info: &'b AccountInfo<'a>,
+    pub token_exists: bool,
+    /// Set when the config vests referrer fees
+    pub escrow: Option<ReferralEscrow<'a, 'b>>,
+}
+
+/// The escrow holding a referrer fee until it vests, owned by the referral stats of the referrer
+pub struct ReferralEscrow<'a, 'b> {
+    pub referral_stats_info: &'b AccountInfo<'a>,
+    pub referral_stats_bump: u8,
+    /// The associated token account of the referral stats, the referral stats itself holds
+    /// native fees as lamports
+    pub token_info: &'b AccountInfo<'a>,
+    pub token_exists: bool,
+}
+
+/// The validated accounts of a redemption
+pub struct RedemptionContext<'a, 'b> {
+    pub wallet_info: &'b AccountInfo<'a>,
+    pub platform_wallet_info: &'b AccountInfo<'a>,
+    pub platform_token_info: &'b AccountInfo<'a>,
+    pub cash_info: &'b AccountInfo<'a>,
+    pub owner_wallet_info: &'b AccountInfo<'a>,
+    pub owner_token_info: &'b AccountInfo<'a>,
+    pub fee_payer_info: &'b AccountInfo<'a>,
+    pub fee_payer_token_info: &'b AccountInfo<'a>,
+    pub vault_token_info: &'b AccountInfo<'a>,
+    pub recipient_token_info: &'b AccountInfo<'a>,
+    pub mint_info: &'b AccountInfo<'a>,
+    pub redemption_info: &'b AccountInfo<'a>,
+    pub redemption_index_info: &'b AccountInfo<'a>,
+    pub rent_info: &'b AccountInfo<'a>,
+    pub recent_slothashes_info: &'b AccountInfo<'a>,
+    pub token_program_info: &'b AccountInfo<'a>,
+    pub system_program_info: &'b AccountInfo<'a>,
+    pub referral: Option<ReferralAccounts<'a, 'b>>,
+    /// Receives the saved share of the payout, set when the redemption saves a share
+    pub savings_token_info: Option<&'b AccountInfo<'a>>,
+    /// Set when the link swaps its payouts, the recipient token account is then in the target mint
+    pub swap: Option<SwapAccounts<'a, 'b>>,
+    /// Set when the recipient token account requires a memo on incoming transfers
+    pub memo_program_info: Option<&'b AccountInfo<'a>>,
+    pub cash: Cash,
+    pub clock: Clock,
+    pub vault_amount: u64,
+    pub decimals: u8,
+    pub is_native: bool,
+    pub recipient_token_exists: bool,
+    pub platform_token_exists: bool,
+    pub fee_payer_token_exists: bool,
+    /// Owner of the account the payout goes to, the wallet for native links and new accounts
+    pub recipient_owner: Pubkey,
+}
+
+/// Every amount moved by a redemption and the resulting cash state
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RedemptionPlan {
+    /// Amount taken from the link for the redeeming wallet, burn included
+    pub payout: u64,
+    /// Part of the payout burned instead of paid to the wallet
+    pub burn_amount: u64,
+    /// Platform fee share of this redemption, before the referral split
+    pub platform_fee_per_redeem: u64,
+    /// Platform fee paid to the platform wallet after the referral split
+    pub platform_fee: u64,
+    pub referrer_fee: u64,
+    pub referee_fee: u64,
+    /// Network and rent fees reimbursed to the fee payer
+    pub network_fee: u64,
+    /// Priority fees reimbursed to the fee payer, within the cap of the link
+    pub priority_fee: u64,
+    /// The rent fee is not reimbursed when the recipient token account already exists
+    pub rent_fee_waived: bool,
+    /// Fees left unpaid because the vault fell short on the final slot
+    pub fee_shortfall: u64,
+    /// Lamports spent creating the recipient token account, measured at execution
+    pub recipient_token_rent: u64,
+    /// Total debited from the vault by this redemption
+    pub vault_debit: u64,
+    /// Vault leftover returned to the owner when the link is fully redeemed
+    pub sweep: Option<u64>,
+    pub unclaimed_weight_refund: u64,
+    pub remaining_amount: u64,
+    pub total_redemptions: u16,
+    pub total_weight_ppm: u32,
+    pub state: CashState,
+    pub completion_reason: CompletionReason,
+    /// Vrf round consumed by the random draw, recorded on the link
+    pub entropy_round: Option<u64>,
+}
+
+impl RedemptionPlan {
+    /// Keep the rent fee in the vault, it is swept to the owner with the rest of the leftover
+    fn waive_rent_fee(&mut self, rent_fee: u64) -> ProgramResult {
+        if self.rent_fee_waived {
+            return Ok(());
+        }
+        self.rent_fee_waived = true;
+        // an underfunded final slot may already have cut the rent fee out of the network fee
+        let rent_fee = rent_fee.min(self.network_fee);
+        self.network_fee = self.network_fee.error_sub(rent_fee)?;
+        self.vault_debit = self.vault_debit.error_sub(rent_fee)?;
+        if let Some(sweep) = self.sweep.as_mut() {
+            *sweep = sweep.error_add(rent_fee)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn process_redemption(
+    accounts: &[AccountInfo],
+    args: InitCashRedemptionArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process redemption");
+    let mut ctx = validate_accounts(accounts, &args, program_id)?;
+    assert_wallet_consent(accounts, &ctx, &args)?;
+    let config = find_config(accounts, program_id)?.ok_or(CashError::InvalidConfigKey)?;
+    assert_redemption_features(&config, &ctx, &args)?;
+    assert_recipient_not_fee_wallet(accounts, program_id, &config, &ctx)?;
+    ctx.cash.assert_signature_fresh(args.signed_at_slot, ctx.clock.slot)?;
+    attach_referral_escrow(accounts, program_id, &config, &mut ctx)?;
+    ctx.cash.assert_flash_slot_open(ctx.clock.unix_timestamp as u64)?;
+    // a wallet waits for its pending redemption to be approved or rejected before redeeming again
+    let pending_info = if ctx.cash.approval_threshold.is_some() {
+        let pending_info =
+            find_pending_redemption(accounts, program_id, ctx.cash_info.key, ctx.wallet_info.key)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if pending_info.lamports() > 0 && !pending_info.data_is_empty() {
+            return Err(CashError::PendingApprovalExists.into());
+        }
+        Some(pending_info)
+    } else {
+        None
+    };
+    let mut args = args;
+    if ctx.cash.distribution_type == DistributionType::Weighted && ctx.cash.buffer_hash.is_some() {
+        // the weights schedule of the link overrides the weight supplied in the args
+        let buffer_info = find_cash_buffer(accounts, program_id, ctx.cash_info.key, &ctx.cash)?
+            .ok_or(CashError::InvalidBufferKey)?;
+        let weight_ppm = Buffer::read_u32(
+            &buffer_info.data.borrow(),
+            ctx.cash.total_redemptions as usize,
+        )
+        .ok_or(CashError::WeightNotProvided)?;
+        args.weight_ppm = Some(weight_ppm);
+    }
+    let entropy = if ctx.cash.commit_reveal_delay.is_some() {
+        Some(reveal_commit(
+            accounts,
+            program_id,
+            &ctx.cash,
+            ctx.cash_info.key,
+            ctx.wallet_info.key,
+            args.reveal_nonce.as_ref(),
+            ctx.recent_slothashes_info,
+            &ctx.clock,
+        )?)
+    } else if ctx.cash.distribution_type == DistributionType::Random {
+        Some(get_entropy(
+            &ctx.cash,
+            accounts,
+            ctx.recent_slothashes_info,
+            &ctx.clock,
+        )?)
+    } else {
+        None
+    };
+    let mut plan = plan_redemption(
+        &ctx.cash,
+        &args,
+        entropy.as_ref().map_or(0, |entropy| entropy.value),
+        ctx.vault_amount,
+        ctx.is_native,
+        ctx.recipient_token_exists,
+        min_payout_for_mint(ctx.decimals, config.max_dust_decimals),
+        None,
+    )?;
+    if ctx.cash.kyc_required(plan.payout) {
+        assert_kyc_attestation(accounts, &ctx.cash, ctx.wallet_info.key, &ctx.clock)?;
+    }
+    plan.entropy_round = entropy.as_ref().and_then(|entropy| entropy.round);
+    if let Some(pending_info) = pending_info.filter(|_| ctx.cash.approval_required(plan.payout)) {
+        return request_approval(program_id, ctx, pending_info, &args, &plan);
+    }
+    assert_vault_invariant(&ctx.cash, &plan, ctx.vault_amount)?;
+    // flag the link before the first cpi, commit_state clears it with the rest of the state
+    let mut busy = ctx.cash.clone();
+    busy.in_progress = true;
+    store_account(busy, ctx.cash_info)?;
+    let rent_paid = execute_plan(accounts, &ctx, &mut plan, &args, program_id)?;
+    commit_state(accounts, program_id, ctx, &plan, rent_paid, args.voucher_hash)
+}
+
+pub fn process_validate_redemptions(
+    accounts: &[AccountInfo],
+    args: ValidateRedemptionsArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process validate redemptions");
+    if args.entries.len() > MAX_VALIDATE_REDEMPTIONS {
+        return Err(CashError::TooManyRedemptions.into());
+    }
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+
+    let mut valid: u16 = 0;
+    for (index, entry) in args.entries.iter().enumerate() {
+        let cash_info = next_account_info(account_info_iter)?;
+        match validate_redemption_entry(accounts, authority_info, cash_info, entry, program_id) {
+            Ok(()) => valid |= 1 << index,
+            Err(error) => msg!("Redemption {} would fail: {:?}", index, error),
+        }
+    }
+    set_return_data(&valid.to_le_bytes());
+    Ok(())
+}
+
+/// Check a planned redemption against the current cash state, nothing is written
+fn validate_redemption_entry(
+    accounts: &[AccountInfo],
+    authority_info: &AccountInfo,
+    cash_info: &AccountInfo,
+    entry: &RedemptionEntry,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    let (cash_key, _) = Pubkey::find_program_address(
+        &[Cash::PREFIX.as_bytes(), entry.cash_reference.as_bytes()],
+        program_id,
+    );
+    assert_account_key(cash_info, &cash_key, Some(CashError::InvalidCashKey), Some("cash"))?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+    let cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
+    cash.assert_redemption_counters()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    if cash.canceled() || cash.cancel_pending() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    if let Some(intended_recipient) = cash.intended_recipient.as_ref() {
+        if !cmp_pubkeys(&entry.wallet, intended_recipient) {
+            return Err(CashError::NotIntendedRecipient.into());
+        }
+    }
+    if cash.slots_used()? >= cash.max_num_redemptions {
+        return Err(CashError::MaxRedemptionsReached.into());
+    }
+    if cash.remaining_amount == 0 {
+        return Err(CashError::NoRemainingAmount.into());
+    }
+    if let Some(pass_key) = cash.pass_key.as_ref() {
+        match find_optional_account(accounts, pass_key) {
+            Some(pass_info) if pass_info.is_signer => {}
+            _ => return Err(CashError::InvalidPassKey.into()),
+        }
+    }
+    let (receipt_key, _) = find_redemption_address(program_id, cash_info.key, &entry.wallet);
+    if let Some(receipt_info) = find_optional_account(accounts, &receipt_key) {
+        if receipt_info.lamports() > 0 {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+    }
+    Ok(())
+}
+
+/// Read the redemption accounts in order and validate everything that does not move funds
+pub fn validate_accounts<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    args: &InitCashRedemptionArgs,
+    program_id: &Pubkey,
+) -> Result<RedemptionContext<'a, 'b>, ProgramError> {
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+
+    assert_signer(authority_info)?;
+
+    let wallet_info = next_account_info(account_info_iter)?;
+    let platform_wallet_info = next_account_info(account_info_iter)?;
+    let platform_token_info = next_account_info(account_info_iter)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+    let cash = load_account::<Cash>(cash_info)?;
+    // an earlier redeem of the same transaction may have completed the link and closed its
+    // vault, the terminal state is named before any other account is looked at
+    if cash.canceled() || cash.cancel_pending() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
+    cash.assert_redemption_counters()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    if let Some(intended_recipient) = cash.intended_recipient.as_ref() {
+        assert_account_key(
+            wallet_info,
+            intended_recipient,
+            Some(CashError::NotIntendedRecipient),
+            Some("wallet"),
+        )?;
+    }
+    if let Some(fee_wallet) = resolve_fee_wallet(accounts, program_id, &cash)? {
+        assert_account_key(
+            platform_wallet_info,
+            &fee_wallet,
+            Some(CashError::InvalidPlatformWallet),
+            Some("platform wallet"),
+        )?;
+    }
+    if cash.require_voucher && args.voucher_hash.is_none() {
+        return Err(CashError::VoucherRequired.into());
+    }
+    let owner_wallet_info = next_account_info(account_info_iter)?;
+    let owner_token_info = next_account_info(account_info_iter)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let fee_payer_token_info = next_account_info(account_info_iter)?;
+    let vault_token_info = next_account_info(account_info_iter)?;
+    let recipient_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    // checked before anything is derived from or created for the mint
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    let redemption_info = next_account_info(account_info_iter)?;
+    let redemption_index_info = next_account_info(account_info_iter)?;
+    let clock_info = next_account_info(account_info_iter)?;
+    // timestamps and the random draw rely on the clock, fail loudly on anything else
+    assert_account_key(
+        clock_info,
+        &sysvar::clock::id(),
+        Some(CashError::InvalidClockSysvar),
+        Some("clock"),
+    )?;
+    let clock = Clock::from_account_info(clock_info)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    assert_account_key(
+        rent_info,
+        &sysvar::rent::id(),
+        Some(CashError::InvalidRentSysvar),
+        Some("rent"),
+    )?;
+    let recent_slothashes_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    assert_account_key(
+        recent_slothashes_info,
+        &slot_hashes::id(),
+        Some(CashError::InvalidSlotHashProgram),
+        Some("recent slothashes"),
+    )?;
+    // only invoked through the ata creation cpis, it is consumed to reach the trailing accounts
+    let _associated_token_program_info = next_account_info(account_info_iter)?;
+
+    // the trailing accounts follow in a fixed order: the pass key, then the referral accounts
+    if let Some(pass_key) = cash.pass_key.as_ref() {
+        let pass_info = next_account_info(account_info_iter)?;
+        assert_account_key(pass_info, pass_key, Some(CashError::InvalidPassKey), Some("pass"))?;
+        assert_signer(pass_info)?;
+    }
+
+    assert_valid_token_program(token_program_info.key)?;
+
+    assert_owned_by(vault_token_info, token_program_info.key, Some("vault token"))?;
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+    // a frozen account fails the transfer cpi with a generic token error, name it instead
+    assert_not_frozen(&vault_token, CashError::VaultFrozen)?;
+    let decimals = mint_decimals(&cash, mint_info)?;
+    let is_native = is_native_mint(mint_info.key);
+
+    // referral accounts are validated up front so a bad account fails before any transfer
+    let referral = if cash.platform_fee_per_redeem()? > 0 && args.referrer_fee_bps.is_some() {
+        let referral_wallet_info = next_account_info(account_info_iter)?;
+        let referral_token_info = next_account_info(account_info_iter)?;
+        let token_exists = check_token_account(
+            referral_token_info,
+            referral_wallet_info.key,
+            &cash.mint,
+            token_program_info.key,
+            "referral token",
+        )?;
+        Some(ReferralAccounts {
+            wallet_info: referral_wallet_info,
+            token_info: referral_token_info,
+            token_exists,
+            escrow: None,
+        })
+    } else {
+        None
+    };
+    let savings_token_info = match args.save_bps {
+        Some(save_bps) => Some(load_savings_token(
+            account_info_iter.next().ok_or(ProgramError::NotEnoughAccountKeys)?,
+            &cash,
+            wallet_info.key,
+            save_bps,
+            is_native,
+            token_program_info.key,
+        )?),
+        None => None,
+    };
+
+    // the owner token receives the referee fee and the sweep
+    if !is_native {
+        load_token_account(
+            owner_token_info,
+            owner_wallet_info.key,
+            &cash.mint,
+            token_program_info.key,
+            "owner token",
+        )?;
+    }
+    assert_account_key(
+        owner_wallet_info,
+        &cash.owner,
+        Some(CashError::InvalidOwner),
+        Some("owner wallet"),
+    )?;
+    let mut legs = vec![
+        (vault_token_info.key, cash_info.key),
+        (recipient_token_info.key, wallet_info.key),
+        (platform_token_info.key, platform_wallet_info.key),
+        (fee_payer_token_info.key, fee_payer_info.key),
+        (owner_token_info.key, owner_wallet_info.key),
+    ];
+    if let Some(referral) = referral.as_ref() {
+        legs.push((referral.token_info.key, referral.wallet_info.key));
+    }
+    if let Some(savings_token_info) = savings_token_info {
+        legs.push((savings_token_info.key, wallet_info.key));
+    }
+    assert_distinct_token_accounts(&legs)?;
+    let swap = cash
+        .swap
+        .map(|config| find_swap_accounts(accounts, config, &cash.mint, token_program_info.key))
+        .transpose()?;
+    let recipient_mint = cash.swap.map_or(cash.mint, |swap| swap.target_mint);
+    let (recipient_token_exists, platform_token_exists) = if is_native {
+        (false, false)
+    } else {
+        (
+            check_token_account(
+                recipient_token_info,
+                wallet_info.key,
+                &recipient_mint,
+                token_program_info.key,
+                "recipient token",
+            )?,
+            check_token_account(
+                platform_token_info,
+                platform_wallet_info.key,
+                &cash.mint,
+                token_program_info.key,
+                "platform fee token",
+            )?,
+        )
+    };
+    // a missing recipient token account is created for the wallet
+    let mut recipient_owner = *wallet_info.key;
+    if recipient_token_exists {
+        let recipient_token: TokenAccount = assert_initialized(
+            recipient_token_info,
+            Some("recipient token"),
+        )?;
+        assert_not_frozen(&recipient_token, CashError::RecipientAccountFrozen)?;
+        assert_no_foreign_delegate(&cash, cash_info.key, &recipient_token)?;
+        recipient_owner = recipient_token.owner;
+    }
+    let memo_program_info = if recipient_token_exists && requires_memo(recipient_token_info)? {
+        Some(
+            find_optional_account(accounts, &MEMO_PROGRAM_ID)
+                .ok_or(CashError::RecipientRequiresMemo)?,
+        )
+    } else {
+        None
+    };
+    let fee_payer_token_exists = check_token_account(
+        fee_payer_token_info,
+        fee_payer_info.key,
+        &cash.mint,
+        token_program_info.key,
+        "fee payer token",
+    )?;
+
+    Ok(RedemptionContext {
+        wallet_info,
+        platform_wallet_info,
+        platform_token_info,
+        cash_info,
+        owner_wallet_info,
+        owner_token_info,
+        fee_payer_info,
+        fee_payer_token_info,
+        vault_token_info,
+        recipient_token_info,
+        mint_info,
+        redemption_info,
+        redemption_index_info,
+        rent_info,
+        recent_slothashes_info,
+        token_program_info,
+        system_program_info,
+        referral,
+        savings_token_info,
+        swap,
+        memo_program_info,
+        cash,
+        clock,
+        vault_amount: vault_token.amount,
+        decimals,
+        is_native,
+        recipient_token_exists,
+        platform_token_exists,
+        fee_payer_token_exists,
+        recipient_owner,
+    })
+}
+
+/// Refuse a recipient that is the fee wallet of the link, of the config or of any registry
+/// epoch, a payout there would pass stolen principal off as fee revenue. The recipient token
+/// account was checked to be owned by the wallet
+fn assert_recipient_not_fee_wallet(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    config: &Config,
+    ctx: &RedemptionContext,
+) -> ProgramResult {
+    if !config.feature_enabled(Feature::FeeWalletRecipientGuard) {
+        return Ok(());
+    }
+    let recipient = ctx.recipient_owner;
+    let mut fee_wallet = cmp_pubkeys(&recipient, ctx.platform_wallet_info.key)
+        || cmp_pubkeys(&recipient, &config.fee_wallet);
+    if let Some(registry) = find_fee_wallet_registry(accounts, program_id)? {
+        fee_wallet |= registry.entries.iter().any(|entry| cmp_pubkeys(&recipient, &entry.wallet));
+    }
+    if fee_wallet {
+        msg!("Recipient {} is a platform fee wallet", recipient);
+        return Err(CashError::RecipientIsFeeWallet.into());
+    }
+    Ok(())
+}
+
+/// Every feature the redemption relies on must be enabled in the config
+pub fn assert_redemption_features(
+    config: &Config,
+    ctx: &RedemptionContext,
+    args: &InitCashRedemptionArgs,
+) -> ProgramResult {
+    let mut features = vec![Feature::Redeem];
+    if args.referrer_fee_bps.is_some() {
+        features.push(Feature::ReferralSplit);
+    }
+    if ctx.cash.distribution_type == DistributionType::Random {
+        features.push(Feature::RandomDistribution);
+    }
+    if ctx.is_native {
+        features.push(Feature::NativeMint);
+    }
+    if ctx.cash.swap.is_some() {
+        features.push(Feature::Swap);
+    }
+    assert_config_features(config, &features)
+}
+
+/// Route the referrer fee into the escrow of the referral stats when the config vests it, a
+/// flagged referrer earns no more fees
+pub fn attach_referral_escrow<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+    config: &Config,
+    ctx: &mut RedemptionContext<'a, 'b>,
+) -> ProgramResult {
+    let referrer = match ctx.referral.as_ref() {
+        Some(referral) if config.referral_vesting_delay.is_some() => *referral.wallet_info.key,
+        _ => return Ok(()),
+    };
+    let (referral_stats_key, referral_stats_bump) =
+        find_referral_stats_address(program_id, &referrer);
+    let referral_stats_info = find_optional_account(accounts, &referral_stats_key)
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if referral_stats_info.owner == program_id && !referral_stats_info.data_is_empty() {
+        let referral_stats = load_referral_stats(referral_stats_info, program_id)?;
+        if referral_stats.flagged_at.is_some() {
+            return Err(CashError::ReferrerFlagged.into());
+        }
+    }
+    let (token_info, token_exists) = if ctx.is_native {
+        (referral_stats_info, true)
+    } else {
+        let escrow_key = get_associated_token_address_with_program_id(
+            &referral_stats_key,
+            &ctx.cash.mint,
+            ctx.token_program_info.key,
+        );
+        let token_info = find_optional_account(accounts, &escrow_key)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        let token_exists = check_token_account(
+            token_info,
+            &referral_stats_key,
+            &ctx.cash.mint,
+            ctx.token_program_info.key,
+            "referral escrow token",
+        )?;
+        (token_info, token_exists)
+    };
+    if let Some(referral) = ctx.referral.as_mut() {
+        referral.escrow = Some(ReferralEscrow {
+            referral_stats_info,
+            referral_stats_bump,
+            token_info,
+            token_exists,
+        });
+    }
+    Ok(())
+}
+
+/// The payment can not be pushed to a recipient that did not ask for it, a recipient consents
+/// by signing the transaction or an off chain permit
+pub fn assert_wallet_consent(
+    accounts: &[AccountInfo],
+    ctx: &RedemptionContext,
+    args: &InitCashRedemptionArgs,
+) -> ProgramResult {
+    let wallet_must_consent =
+        ctx.cash.intended_recipient.is_some() || ctx.cash.claim_requires_wallet_signature;
+    if wallet_must_consent && args.recipient_permit.is_none() {
+        assert_signer(ctx.wallet_info)?;
+    }
+    // the wallet approves the link as delegate of the payout, a permit can not sign that
+    if ctx.cash.clawback_window_seconds.is_some() {
+        assert_signer(ctx.wallet_info)?;
+    }
+    if let Some(permit) = args.recipient_permit.as_ref() {
+        verify_recipient_permit(
+            accounts,
+            permit,
+            ctx.cash_info.key,
+            ctx.wallet_info.key,
+            ctx.cash.total_redemptions,
+            &ctx.clock,
+        )?;
+    }
+    Ok(())
+}
+
+/// Compute every amount of a redemption without touching any account
+///
+/// The payouts waiting for approval and their slots are set aside, the redemption draws from
+/// what is left. An approved redemption passes the payout locked at request time
+pub fn plan_redemption(
+    cash: &Cash,
+    args: &InitCashRedemptionArgs,
+    entropy: u64,
+    vault_amount: u64,
+    is_native: bool,
+    recipient_token_exists: bool,
+    min_payout: u64,
+    locked_payout: Option<u64>,
+) -> Result<RedemptionPlan, ProgramError> {
+    let (referrer_fee_bps, referee_fee_bps, clamped) =
+        cash.clamp_referral_fee_bps(args.referrer_fee_bps, args.referee_fee_bps)?;
+    if clamped {
+        msg!(
+            "Referral fees clamped to {} and {} bps",
+            referrer_fee_bps.unwrap_or(0),
+            referee_fee_bps.unwrap_or(0)
+        );
+    }
+    let RedemptionOutput {
+        payout,
+        burn_amount,
+        platform_fee_per_redeem,
+        platform_fee,
+        referrer_fee,
+        referee_fee,
+        network_fee,
+        priority_fee,
+        rent_fee_waived,
+        fee_shortfall,
+        vault_debit,
+        unclaimed_weight_refund,
+        remaining_amount,
+        total_redemptions,
+        total_weight_ppm,
+    } = compute_redemption(&RedemptionInput {
+        distribution: cash.distribution_type.clone() as u8,
+        amount: cash.amount,
+        remaining_amount: cash.remaining_amount.error_sub(cash.reserved_amount)?,
+        max_num_redemptions: cash.max_num_redemptions,
+        total_redemptions: cash.total_redemptions.error_add(cash.pending_approvals)?,
+        slots_used: cash.slots_used()?.error_add(cash.pending_approvals)?,
+        total_weight_ppm: cash.total_weight_ppm,
+        min_amount: cash.min_amount,
+        min_payout,
+        has_weight: args.weight_ppm.is_some(),
+        weight_ppm: args.weight_ppm.unwrap_or(0),
+        entropy,
+        vault_amount,
+        fee_bps: cash.fee_bps,
+        base_fee_to_redeem: cash.base_fee_to_redeem,
+        rent_fee_to_redeem: cash.rent_fee_to_redeem,
+        requested_priority_fee: args.priority_fee_reimbursement.unwrap_or(0),
+        max_priority_fee_per_redeem: cash.max_priority_fee_per_redeem,
+        is_native,
+        recipient_token_exists,
+        has_referrer: referrer_fee_bps.is_some(),
+        referrer_fee_bps: referrer_fee_bps.unwrap_or(0),
+        referee_fee_bps: referee_fee_bps.unwrap_or(0),
+        burn_bps: cash.burn_bps,
+        has_locked_payout: locked_payout.is_some(),
+        locked_payout: locked_payout.unwrap_or(0),
+    })?;
+    let remaining_amount = remaining_amount.error_add(cash.reserved_amount)?;
+    let total_redemptions = total_redemptions.error_sub(cash.pending_approvals)?;
+    if args.priority_fee_reimbursement.unwrap_or(0) > priority_fee {
+        msg!("Priority fee reimbursement clamped to {}", priority_fee);
+    }
+    if fee_shortfall > 0 {
+        msg!(
+            "Final slot underfunded, fees cut by {} to platform {}, network {}, priority {}",
+            fee_shortfall,
+            platform_fee_per_redeem,
+            network_fee,
+            priority_fee
+        );
+    }
+    if unclaimed_weight_refund > 0 {
+        msg!(
+            "Weights sum to {} ppm, refunding {} unclaimed to the owner",
+            total_weight_ppm,
+            unclaimed_weight_refund
+        );
+    }
+
+    let next = Cash {
+        remaining_amount,
+        total_redemptions,
+        total_weight_ppm,
+        ..cash.clone()
+    };
+    let completion_reason = next.redemption_completion_reason()?;
+    let fully_redeemed = completion_reason != CompletionReason::None;
+    let sweep = if fully_redeemed {
+        Some(vault_amount.error_sub(vault_debit)?)
+    } else {
+        None
+    };
+
+    Ok(RedemptionPlan {
+        payout,
+        burn_amount,
+        platform_fee_per_redeem,
+        platform_fee,
+        referrer_fee,
+        referee_fee,
+        network_fee,
+        priority_fee,
+        rent_fee_waived,
+        fee_shortfall,
+        recipient_token_rent: 0,
+        vault_debit,
+        sweep,
+        unclaimed_weight_refund,
+        remaining_amount,
+        total_redemptions,
+        total_weight_ppm,
+        state: if fully_redeemed {
+            CashState::Redeemed
+        } else {
+            CashState::Redeeming
+        },
+        completion_reason,
+        entropy_round: None,
+    })
+}
+
+/// Perform the transfers of a plan and create the receipts, returns the rent paid by the fee payer
+///
+/// The rent fee is only kept when creating the recipient token account actually cost the
+/// fee payer lamports, otherwise it is left in the vault and the plan is adjusted
+pub fn execute_plan<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    ctx: &RedemptionContext<'a, 'b>,
+    plan: &mut RedemptionPlan,
+    args: &InitCashRedemptionArgs,
+    program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let sweep_receiver_info = assert_plan_executable(accounts, ctx, plan, args, program_id)?;
+    let signer_seeds = [
+        Cash::PREFIX.as_bytes(),
+        args.cash_reference.as_ref(),
+        &[args.cash_bump],
+    ];
+    let mut ledger = LedgerEvent::new(ctx.cash_info.key);
+    let mint = ctx.mint_info.key;
+    let vault = ctx.vault_token_info.key;
+
+    if let Some(referral) = &ctx.referral {
+        if !referral.token_exists {
+            ctx.create_token_account(referral.token_info, referral.wallet_info)?;
+        }
+        if let Some(escrow) = referral.escrow.as_ref().filter(|_| plan.referrer_fee > 0) {
+            // the escrow belongs to the referral stats, they exist before the first fee lands
+            get_or_create_referral_stats(
+                escrow.referral_stats_info,
+                program_id,
+                referral.wallet_info.key,
+                escrow.referral_stats_bump,
+                ctx.fee_payer_info,
+                ctx.rent_info,
+                ctx.system_program_info,
+            )?;
+            if !escrow.token_exists {
+                ctx.create_token_account(escrow.token_info, escrow.referral_stats_info)?;
+            }
+        }
+    }
+
+    if ctx.is_native {
+        if !ctx.fee_payer_token_exists {
+            ctx.create_token_account(ctx.fee_payer_token_info, ctx.fee_payer_info)?;
+        }
+        // unwrap everything through the fee payer, fees are then paid in lamports
+        transfer_if_nonzero("vault debit", plan.vault_debit, |amount| {
+            ctx.transfer_from_vault(ctx.fee_payer_token_info, amount, &signer_seeds)
+        })?;
+        spl_token_close(
+            ctx.fee_payer_token_info,
+            ctx.fee_payer_info,
+            ctx.fee_payer_info,
+            ctx.token_program_info.key,
+            &[&signer_seeds],
+        )?;
+        transfer_if_nonzero("payout", plan.payout, |amount| {
+            native_transfer(ctx.fee_payer_info, ctx.wallet_info, amount, &[])
+        })?;
+        ledger.post(LegKind::Principal, mint, vault, ctx.wallet_info.key, plan.payout);
+        if plan.priority_fee > 0 {
+            // kept by the fee payer out of the unwrapped vault debit
+            msg!("Priority fee {}", plan.priority_fee);
+        }
+        ctx.post_fee_payer_legs(&mut ledger, plan, ctx.fee_payer_info.key);
+    } else {
+        if !ctx.recipient_token_exists {
+            let recipient_mint_info = ctx
+                .swap
+                .as_ref()
+                .map_or(ctx.mint_info, |swap| swap.target_mint_info);
+            plan.recipient_token_rent = create_associated_token_account_idempotent_raw(
+                ctx.fee_payer_info,
+                ctx.recipient_token_info,
+                ctx.wallet_info,
+                recipient_mint_info,
+                ctx.token_program_info.key,
+            )?;
+            if plan.recipient_token_rent == 0 {
+                msg!("Recipient token account already existed, the rent fee is not charged");
+                plan.waive_rent_fee(ctx.cash.rent_fee_to_redeem)?;
+            }
+        }
+        if !ctx.platform_token_exists {
+            ctx.create_token_account(ctx.platform_token_info, ctx.platform_wallet_info)?;
+        }
+        if !ctx.fee_payer_token_exists {
+            ctx.create_token_account(ctx.fee_payer_token_info, ctx.fee_payer_info)?;
+        }
+        if let Some(memo_program_info) = ctx.memo_program_info {
+            spl_memo(
+                memo_program_info,
+                &format!("cash redemption {}", args.cash_reference),
+            )?;
+        }
+        if plan.burn_amount > 0 {
+            msg!("Burning {} of the payout", plan.burn_amount);
+            spl_token_burn(
+                ctx.vault_token_info,
+                ctx.mint_info,
+                ctx.cash_info,
+                ctx.token_program_info.key,
+                plan.burn_amount,
+                ctx.decimals,
+                &[&signer_seeds],
+            )?;
+            ledger.post(LegKind::Burn, mint, vault, mint, plan.burn_amount);
+        }
+        let (recipient_amount, saved_amount) = savings_split(
+            plan.payout.error_sub(plan.burn_amount)?,
+            args.save_bps.unwrap_or(0),
+        )?;
+        let recipient = ctx.recipient_token_info.key;
+        transfer_if_nonzero("payout", recipient_amount, |amount| match &ctx.swap {
+            Some(swap) => {
+                let received = swap.swap_from_vault(
+                    ctx.vault_token_info,
+                    ctx.recipient_token_info,
+                    ctx.cash_info,
+                    ctx.mint_info,
+                    ctx.token_program_info,
+                    amount,
+                    &signer_seeds,
+                )?;
+                let (pool_source, pool_target) =
+                    (swap.pool_source_info.key, swap.pool_target_info.key);
+                ledger.post(LegKind::Swap, mint, vault, pool_source, amount);
+                let target_mint = swap.target_mint_info.key;
+                ledger.post(LegKind::Swap, target_mint, pool_target, recipient, received);
+                Ok(())
+            }
+            None => {
+                ctx.transfer_from_vault(ctx.recipient_token_info, amount, &signer_seeds)?;
+                ledger.post(LegKind::Principal, mint, vault, recipient, amount);
+                Ok(())
+            }
+        })?;
+        if ctx.cash.clawback_window_seconds.is_some() && recipient_amount > 0 {
+            // lets the link take the payout back while the window is open, see
+            // `CashInstruction::ClawbackRedemption`
+            spl_token_approve(
+                ctx.recipient_token_info,
+                ctx.mint_info,
+                ctx.cash_info,
+                ctx.wallet_info,
+                ctx.token_program_info.key,
+                recipient_amount,
+                ctx.decimals,
+            )?;
+        }
+        if let Some(savings_token_info) = ctx.savings_token_info {
+            transfer_if_nonzero("savings", saved_amount, |amount| {
+                ctx.transfer_from_vault(savings_token_info, amount, &signer_seeds)
+            })?;
+            let savings = savings_token_info.key;
+            ledger.post(LegKind::Principal, mint, vault, savings, saved_amount);
+        }
+        transfer_if_nonzero("network fee", plan.network_fee, |amount| {
+            ctx.transfer_from_vault(ctx.fee_payer_token_info, amount, &signer_seeds)
+        })?;
+        transfer_if_nonzero("priority fee", plan.priority_fee, |amount| {
+            ctx.transfer_from_vault(ctx.fee_payer_token_info, amount, &signer_seeds)
+        })?;
+        ctx.post_fee_payer_legs(&mut ledger, plan, ctx.fee_payer_token_info.key);
+    }
+
+    transfer_if_nonzero("platform fee", plan.platform_fee, |amount| {
+        ctx.pay_fee(ctx.platform_wallet_info, ctx.platform_token_info, amount, &signer_seeds)
+    })?;
+    let platform = ctx.fee_account(ctx.platform_wallet_info, ctx.platform_token_info);
+    ledger.post(LegKind::PlatformFee, mint, vault, platform, plan.platform_fee);
+    transfer_if_nonzero("referrer fee", plan.referrer_fee, |amount| {
+        let referral = ctx.referral.as_ref().ok_or(CashError::InvalidReferralFees)?;
+        let (wallet_info, token_info) = match &referral.escrow {
+            Some(escrow) => (escrow.referral_stats_info, escrow.token_info),
+            None => (referral.wallet_info, referral.token_info),
+        };
+        ctx.pay_fee(wallet_info, token_info, amount, &signer_seeds)?;
+        let referrer = ctx.fee_account(wallet_info, token_info);
+        ledger.post(LegKind::Referral, mint, vault, referrer, amount);
+        Ok(())
+    })?;
+    transfer_if_nonzero("referee fee", plan.referee_fee, |amount| {
+        ctx.pay_fee(ctx.owner_wallet_info, ctx.owner_token_info, amount, &signer_seeds)
+    })?;
+    let referee = ctx.fee_account(ctx.owner_wallet_info, ctx.owner_token_info);
+    ledger.post(LegKind::Referral, mint, vault, referee, plan.referee_fee);
+
+    if let Some(remaining) = plan.sweep {
+        if ctx.is_native {
+            unwrap_native_vault(
+                ctx.vault_token_info,
+                ctx.cash_info,
+                sweep_receiver_info,
+                ctx.fee_payer_info,
+                ctx.token_program_info.key,
+                remaining,
+                &[&signer_seeds],
+            )?;
+            ledger.post(LegKind::Sweep, mint, vault, sweep_receiver_info.key, remaining);
+        } else {
+            transfer_if_nonzero("sweep", remaining, |amount| {
+                ctx.transfer_from_vault(ctx.owner_token_info, amount, &signer_seeds)
+            })?;
+            ledger.post(LegKind::Sweep, mint, vault, ctx.owner_token_info.key, remaining);
+            spl_token_close(
+                ctx.vault_token_info,
+                ctx.fee_payer_info,
+                ctx.cash_info,
+                ctx.token_program_info.key,
+                &[&signer_seeds],
+            )?;
+        }
+    }
+    ledger.emit();
+
+    let receipt_rent = if creates_receipt(ctx, args) {
+        create_redemption_receipt(ctx, args, plan.payout, program_id)?
+    } else {
+        0
+    };
+    let index_rent = create_new_account_raw(
+        program_id,
+        ctx.redemption_index_info,
+        ctx.rent_info,
+        ctx.fee_payer_info,
+        ctx.system_program_info,
+        RedemptionIndex::LEN,
+        &[
+            RedemptionIndex::PREFIX.as_bytes(),
+            ctx.cash_info.key.as_ref(),
+            &plan.total_redemptions.to_le_bytes(),
+            &[args.redemption_index_bump],
+        ],
+    )?;
+    RedemptionIndex::pack(
+        RedemptionIndex {
+            header: AuxiliaryHeader::new(ctx.fee_payer_info.key),
+            wallet: *ctx.wallet_info.key,
+            amount: plan.payout,
+        },
+        &mut ctx.redemption_index_info.data.borrow_mut(),
+    )?;
+    receipt_rent.error_add(index_rent)
+}
+
+/// Everything the execution of a plan can still fail on, checked before its first cpi. A
+/// redemption bound to fail then fails before the fee payer funds any account creation.
+/// Returns the receiver of a native sweep
+fn assert_plan_executable<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    ctx: &RedemptionContext<'a, 'b>,
+    plan: &RedemptionPlan,
+    args: &InitCashRedemptionArgs,
+    program_id: &Pubkey,
+) -> Result<&'b AccountInfo<'a>, ProgramError> {
+    if let Some(referral) = ctx.referral.as_ref().filter(|referral| !referral.token_exists) {
+        ctx.assert_associated_token(referral.token_info, referral.wallet_info, ctx.mint_info)?;
+    }
+    if plan.referrer_fee > 0 && ctx.referral.is_none() {
+        return Err(CashError::InvalidReferralFees.into());
+    }
+    if !ctx.fee_payer_token_exists {
+        ctx.assert_associated_token(ctx.fee_payer_token_info, ctx.fee_payer_info, ctx.mint_info)?;
+    }
+    if !ctx.is_native {
+        if !ctx.recipient_token_exists {
+            let recipient_mint_info = ctx
+                .swap
+                .as_ref()
+                .map_or(ctx.mint_info, |swap| swap.target_mint_info);
+            ctx.assert_associated_token(
+                ctx.recipient_token_info,
+                ctx.wallet_info,
+                recipient_mint_info,
+            )?;
+        }
+        if !ctx.platform_token_exists {
+            ctx.assert_associated_token(
+                ctx.platform_token_info,
+                ctx.platform_wallet_info,
+                ctx.mint_info,
+            )?;
+        }
+    }
+    // the index follows the counter as read by this instruction, a redeem built from the same
+    // snapshot as an earlier redeem of the link in the transaction passes a stale one
+    let index_key = Pubkey::create_program_address(
+        &[
+            RedemptionIndex::PREFIX.as_bytes(),
+            ctx.cash_info.key.as_ref(),
+            &plan.total_redemptions.to_le_bytes(),
+            &[args.redemption_index_bump],
+        ],
+        program_id,
+    )
+    .map_err(|_| CashError::InvalidRedemptionIndexKey)?;
+    if !cmp_pubkeys(&index_key, ctx.redemption_index_info.key) {
+        msg!(
+            "Redemption index {} is not the pda of redemption {}",
+            ctx.redemption_index_info.key,
+            plan.total_redemptions
+        );
+        return Err(CashError::InvalidRedemptionIndexKey.into());
+    }
+    let receipt_taken =
+        ctx.redemption_info.lamports() > 0 && !ctx.redemption_info.data_is_empty();
+    let index_taken =
+        ctx.redemption_index_info.lamports() > 0 && !ctx.redemption_index_info.data_is_empty();
+    if (receipt_taken && creates_receipt(ctx, args)) || index_taken {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let sweep_receiver_info = if plan.sweep.is_some() && ctx.is_native {
+        lamport_refund_receiver(accounts, &ctx.cash, ctx.owner_wallet_info)?
+    } else {
+        ctx.owner_wallet_info
+    };
+    #[cfg(test)]
+    crate::test_utils::record_validation();
+    Ok(sweep_receiver_info)
+}
+
+/// The payout of a link with a clawback window is delegated back to the link, an approve would
+/// silently replace a delegation the wallet gave to another account
+fn assert_no_foreign_delegate(
+    cash: &Cash,
+    cash_key: &Pubkey,
+    recipient_token: &TokenAccount,
+) -> ProgramResult {
+    if cash.clawback_window_seconds.is_none() {
+        return Ok(());
+    }
+    match recipient_token.delegate {
+        COption::Some(delegate) if !cmp_pubkeys(&delegate, cash_key) => {
+            msg!("The recipient token account is delegated to {}", delegate);
+            Err(CashError::RecipientDelegated.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The receipt keeps a wallet from claiming twice, a single claim link is done after one. A
+/// voucher hash is only auditable from a receipt, a clawback is bounded by it
+fn creates_receipt(ctx: &RedemptionContext, args: &InitCashRedemptionArgs) -> bool {
+    !ctx.cash.is_single_claim()
+        || args.create_receipt
+        || args.voucher_hash.is_some()
+        || ctx.cash.clawback_window_seconds.is_some()
+}
+
+fn create_redemption_receipt(
+    ctx: &RedemptionContext,
+    args: &InitCashRedemptionArgs,
+    amount: u64,
+    program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let [prefix, cash_seed, wallet_seed] =
+        redemption_receipt_seeds(ctx.cash_info.key, ctx.wallet_info.key);
+    let receipt_rent = create_new_account_raw(
+        program_id,
+        ctx.redemption_info,
+        ctx.rent_info,
+        ctx.fee_payer_info,
+        ctx.system_program_info,
+        RedemptionReceipt::LEN,
+        &[prefix, cash_seed, wallet_seed, &[args.redemption_bump]],
+    )?;
+    store_account(
+        RedemptionReceipt {
+            header: AuxiliaryHeader::new(ctx.fee_payer_info.key),
+            authority: ctx.cash.authority,
+            voucher_hash: args.voucher_hash,
+            wallet: *ctx.wallet_info.key,
+            amount,
+            redeemed_at: ctx.clock.unix_timestamp as u64,
+            clawed_back: 0,
+        },
+        ctx.redemption_info,
+    )?;
+    Ok(receipt_rent)
+}
+
+/// Write the planned state to the cash account and report the redemption
+pub fn commit_state<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+    ctx: RedemptionContext<'a, 'b>,
+    plan: &RedemptionPlan,
+    rent_paid: u64,
+    voucher_hash: Option<[u8; 32]>,
+) -> ProgramResult {
+    record_wallet_limit(accounts, program_id, &ctx, plan.payout)?;
+    let mut cash = ctx.cash;
+    cash.in_progress = false;
+    cash.total_burned = cash.total_burned.error_add(plan.burn_amount)?;
+    if plan.burn_amount > 0 {
+        msg!("Total burned {}", cash.total_burned);
+    }
+    cash.remaining_amount = plan.remaining_amount;
+    cash.total_redemptions = plan.total_redemptions;
+    cash.total_weight_ppm = plan.total_weight_ppm;
+    cash.state = plan.state.clone();
+    cash.completion_reason = plan.completion_reason;
+    if let Some(round) = plan.entropy_round {
+        cash.last_entropy_round = round;
+    }
+    cash.clear_recovery();
+    if cash.redeemed() {
+        cash.completed_at = Some(ctx.clock.unix_timestamp as u64);
+    }
+    if cmp_pubkeys(ctx.fee_payer_info.key, &cash.fee_payer) {
+        cash.rent_paid_by_fee_payer = cash.rent_paid_by_fee_payer.error_add(rent_paid)?;
+    }
+    CashEvent::Redemption(RedemptionEvent {
+        cash: *ctx.cash_info.key,
+        wallet: *ctx.wallet_info.key,
+        amount: plan.payout,
+        total_redemptions: cash.total_redemptions,
+        remaining_amount: cash.remaining_amount,
+        unclaimed_weight_refund: plan.unclaimed_weight_refund,
+        rent_fee_charged: !plan.rent_fee_waived,
+        recipient_token_rent: plan.recipient_token_rent,
+        recipient_owner: ctx.recipient_owner,
+        created_by_version: cash.created_by_version,
+        last_touched_version: PROGRAM_VERSION,
+        voucher_hash,
+    })
+    .emit();
+    if cash.completion_reason != CompletionReason::None {
+        CompletionEvent {
+            cash: *ctx.cash_info.key,
+            reason: cash.completion_reason,
+            total_redemptions: cash.total_redemptions,
+            remaining_amount: cash.remaining_amount,
+            timestamp: ctx.clock.unix_timestamp as u64,
+            created_by_version: cash.created_by_version,
+            last_touched_version: PROGRAM_VERSION,
+        }
+        .emit();
+    }
+    update_stats(accounts, program_id, |stats| {
+        stats.record_redemption(&cash.mint, plan.payout, plan.platform_fee_per_redeem)
+    })?;
+    if let Some(referral) = ctx.referral.as_ref().filter(|_| plan.referrer_fee > 0) {
+        let now = ctx.clock.unix_timestamp as u64;
+        update_referral_stats(
+            accounts,
+            program_id,
+            referral.wallet_info.key,
+            &cash.mint,
+            plan.referrer_fee,
+            referral.escrow.as_ref().map(|_| now),
+            ctx.fee_payer_info,
+            ctx.rent_info,
+            ctx.system_program_info,
+        )?;
+    }
+    store_account(cash, ctx.cash_info)?;
+    Ok(())
+}
+
+impl<'a, 'b> RedemptionContext<'a, 'b> {
+    fn transfer_from_vault(
+        &self,
+        destination: &AccountInfo<'a>,
+        amount: u64,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        spl_token_transfer(
+            self.vault_token_info,
+            destination,
+            self.cash_info,
+            self.mint_info,
+            self.token_program_info.key,
+            amount,
+            self.decimals,
+            &[signer_seeds],
+        )
+    }
+
+    /// The account credited by `pay_fee`
+    fn fee_account<'c>(
+        &self,
+        wallet: &'c AccountInfo<'a>,
+        token: &'c AccountInfo<'a>,
+    ) -> &'c Pubkey {
+        if self.is_native {
+            wallet.key
+        } else {
+            token.key
+        }
+    }
+
+    /// Post the network, rent and priority fees reimbursed to the fee payer out of the vault
+    fn post_fee_payer_legs(&self, ledger: &mut LedgerEvent, plan: &RedemptionPlan, to: &Pubkey) {
+        let (mint, vault) = (self.mint_info.key, self.vault_token_info.key);
+        let rent_fee = if plan.rent_fee_waived {
+            0
+        } else {
+            self.cash.rent_fee_to_redeem.min(plan.network_fee)
+        };
+        ledger.post(LegKind::NetworkFee, mint, vault, to, plan.network_fee - rent_fee);
+        ledger.post(LegKind::Rent, mint, vault, to, rent_fee);
+        ledger.post(LegKind::PriorityFee, mint, vault, to, plan.priority_fee);
+    }
+
+    /// Native fees are paid from the unwrapped lamports of the fee payer, token fees from the vault
+    fn pay_fee(
+        &self,
+        wallet: &AccountInfo<'a>,
+        token: &AccountInfo<'a>,
+        amount: u64,
+        signer_seeds: &[&[u8]],
+    ) -> ProgramResult {
+        if self.is_native {
+            native_transfer(self.fee_payer_info, wallet, amount, &[])
+        } else {
+            self.transfer_from_vault(token, amount, signer_seeds)
+        }
+    }
+
+    /// A missing token account passed for a wallet must be its associated token account, it is
+    /// created by the redemption
+    fn assert_associated_token(
+        &self,
+        token: &AccountInfo<'a>,
+        wallet: &AccountInfo<'a>,
+        mint: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        let associated_token = get_associated_token_address_with_program_id(
+            wallet.key,
+            mint.key,
+            self.token_program_info.key,
+        );
+        assert_account_key(
+            token,
+            &associated_token,
+            Some(CashError::InvalidDstTokenOwner),
+            Some("associated token"),
+        )
+    }
+
+    /// Create the associated token account of a fee recipient, checked by
+    /// `assert_plan_executable`
+    fn create_token_account(
+        &self,
+        token: &AccountInfo<'a>,
+        wallet: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        create_associated_token_account_raw(
+            self.fee_payer_info,
+            token,
+            wallet,
+            self.mint_info,
+            self.rent_info,
+            self.token_program_info.key,
+        )
+    }
+}
+
+/// Find the attestation of the wallet among the accounts at its address derived under the kyc
+/// program of the link, owned by that program, and check it
+///
+/// The attestation data starts with the attested wallet followed by the expiry unix timestamp
+pub fn assert_kyc_attestation(
+    accounts: &[AccountInfo],
+    cash: &Cash,
+    wallet: &Pubkey,
+    clock: &Clock,
+) -> ProgramResult {
+    let kyc_authority = cash.kyc_authority.ok_or(CashError::KycRequired)?;
+    let (attestation_key, _) = find_kyc_attestation_address(&kyc_authority, wallet);
+    let attestation_info =
+        find_optional_account(accounts, &attestation_key).ok_or(CashError::KycRequired)?;
+    if !cmp_pubkeys(attestation_info.owner, &kyc_authority) {
+        msg!("Kyc attestation {} is not owned by the kyc authority", attestation_info.key);
+        return Err(CashError::KycRequired.into());
+    }
+    let data = attestation_info.try_borrow_data()?;
+    if data.len() < KYC_ATTESTATION_MIN_LEN {
+        msg!("Kyc attestation {} is too short", attestation_info.key);
+        return Err(CashError::KycRequired.into());
+    }
+    let attested_wallet = Pubkey::new_from_array(*array_ref![data, 0, 32]);
+    if !cmp_pubkeys(&attested_wallet, wallet) {
+        msg!("Kyc attestation {} is for another wallet", attestation_info.key);
+        return Err(CashError::KycRequired.into());
+    }
+    let expires_at = i64::from_le_bytes(*array_ref![data, 32, 8]);
+    if clock.unix_timestamp >= expires_at {
+        return Err(CashError::KycExpired.into());
+    }
+    Ok(())
+}
+
+/// The vault left by a plan must still cover the remaining amount and the worst case fees of
+/// every remaining redemption, a shortfall is caught on the redemption causing it rather than
+/// on a later claim failing with insufficient funds
+pub fn assert_vault_invariant(cash: &Cash, plan: &RedemptionPlan, vault_amount: u64) -> ProgramResult {
+    // the leftover of a fully redeemed link is swept to the owner, nothing is left to cover
+    if plan.sweep.is_some() {
+        return Ok(());
+    }
+    let next = Cash {
+        remaining_amount: plan.remaining_amount,
+        total_redemptions: plan.total_redemptions,
+        total_weight_ppm: plan.total_weight_ppm,
+        ..cash.clone()
+    };
+    let required_reserve = next.required_reserve()?;
+    let vault_after = vault_amount.error_sub(plan.vault_debit)?;
+    if vault_after < required_reserve {
+        msg!(
+            "Vault would hold {} after the redemption, {} is required",
+            vault_after,
+            required_reserve
+        );
+        return Err(CashError::WouldBreakVaultInvariant.into());
+    }
+    Ok(())
+}
+
+/// Check the savings share and load the savings token account, an existing token account of
+/// the link mint owned by the wallet
+fn load_savings_token<'a, 'b>(
+    savings_token_info: &'b AccountInfo<'a>,
+    cash: &Cash,
+    wallet: &Pubkey,
+    save_bps: u16,
+    is_native: bool,
+    token_program_id: &Pubkey,
+) -> Result<&'b AccountInfo<'a>, ProgramError> {
+    if save_bps as u64 > BPS_DENOMINATOR {
+        msg!("Save bps {} exceeds {}", save_bps, BPS_DENOMINATOR);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if is_native || cash.swap.is_some() {
+        msg!("Only token links without a swap can save a share of the payout");
+        return Err(ProgramError::InvalidArgument);
+    }
+    assert_owned_by(savings_token_info, token_program_id, Some("savings token"))?;
+    let savings_token: TokenAccount =
+        assert_initialized(savings_token_info, Some("savings token"))?;
+    if !cmp_pubkeys(&savings_token.owner, wallet) {
+        msg!("Savings token is owned by {}, not {}", savings_token.owner, wallet);
+        return Err(CashError::SecondaryRecipientOwnerMismatch.into());
+    }
+    assert_token_account(&savings_token, wallet, Some(&cash.mint), "savings token")?;
+    assert_not_frozen(&savings_token, CashError::RecipientAccountFrozen)?;
+    Ok(savings_token_info)
+}
+
+/// Validate a token account the cash pda pays into when it exists, returns whether it exists.
+/// An existing account must be owned by the token program of the link and hold the mint for
+/// the wallet, a missing one is created for the wallet by the redemption. Frozen accounts and
+/// accounts passed for two roles are refused by the callers
+fn check_token_account(
+    token_info: &AccountInfo,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+    role: &str,
+) -> Result<bool, ProgramError> {
+    if !exists(token_info)? {
+        return Ok(false);
+    }
+    load_token_account(token_info, owner, mint, token_program_id, role)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        kyc_stub,
+        test_utils::{runtime, trace, Step, TestAccount, TestLink, TestRedemption},
+    };
+    use spl_token_2022::state::AccountState;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn clock() -> Clock {
+        Clock {
+            unix_timestamp: NOW,
+            ..Clock::default()
+        }
+    }
+
+    fn kyc_link(kyc_program: &Pubkey) -> Cash {
+        Cash {
+            kyc_required_above: Some(1_000),
+            kyc_authority: Some(*kyc_program),
+            ..Cash::default()
+        }
+    }
+
+    /// The attestation of the wallet written by the stub attestation program
+    fn attest(kyc_program: &Pubkey, wallet: &Pubkey, expires_at: i64) -> TestAccount {
+        let (key, _) = find_kyc_attestation_address(kyc_program, wallet);
+        let data = vec![0; kyc_stub::ATTESTATION_LEN];
+        let mut attestation = TestAccount::new(key, *kyc_program, data);
+        let mut wallet = TestAccount::new(*wallet, Pubkey::new_unique(), vec![]);
+        kyc_stub::process_instruction(
+            kyc_program,
+            &[attestation.info(), wallet.info()],
+            &expires_at.to_le_bytes(),
+        )
+        .unwrap();
+        attestation
+    }
+
+    /// A live fixed link of 1_000_000 over 4 redemptions, 1% platform fee
+    fn fixed_link() -> Cash {
+        Cash {
+            state: CashState::Initialized,
+            distribution_type: DistributionType::Fixed,
+            amount: 1_000_000,
+            remaining_amount: 1_000_000,
+            max_num_redemptions: 4,
+            fee_bps: 100,
+            base_fee_to_redeem: 5_000,
+            rent_fee_to_redeem: 2_000,
+            max_priority_fee_per_redeem: 1_000,
+            ..Cash::default()
+        }
+    }
+
+    fn redeem_args() -> InitCashRedemptionArgs {
+        InitCashRedemptionArgs {
+            cash_bump: 255,
+            cash_reference: "reference".to_string(),
+            referrer_fee_bps: None,
+            referee_fee_bps: None,
+            weight_ppm: None,
+            rate_usd: None,
+            redemption_bump: 255,
+            redemption_index_bump: 255,
+            priority_fee_reimbursement: None,
+            recipient_permit: None,
+            reveal_nonce: None,
+            create_receipt: true,
+            voucher_hash: None,
+            save_bps: None,
+            signed_at_slot: None,
+        }
+    }
+
+    /// Plan a token redemption into a new recipient token account
+    fn plan(cash: &Cash, args: &InitCashRedemptionArgs, vault_amount: u64) -> RedemptionPlan {
+        plan_redemption(cash, args, 0, vault_amount, false, false, 1, None).unwrap()
+    }
+
+    #[test]
+    fn plans_a_redemption_of_a_live_link() {
+        let mut args = redeem_args();
+        args.priority_fee_reimbursement = Some(5_000);
+        let plan = plan(&fixed_link(), &args, 1_042_000);
+        assert_eq!(
+            plan,
+            RedemptionPlan {
+                payout: 250_000,
+                platform_fee_per_redeem: 2_500,
+                platform_fee: 2_500,
+                network_fee: 7_000,
+                // clamped to the cap of the link
+                priority_fee: 1_000,
+                vault_debit: 260_500,
+                remaining_amount: 750_000,
+                total_redemptions: 1,
+                state: CashState::Redeeming,
+                ..RedemptionPlan::default()
+            }
+        );
+    }
+
+    #[test]
+    fn waives_the_rent_fee_of_an_existing_recipient_token_account() {
+        let plan =
+            plan_redemption(&fixed_link(), &redeem_args(), 0, 1_042_000, false, true, 1, None)
+                .unwrap();
+        assert!(plan.rent_fee_waived);
+        assert_eq!(plan.network_fee, 5_000);
+        // a native recipient is the wallet itself, the rent fee is always charged
+        let plan =
+            plan_redemption(&fixed_link(), &redeem_args(), 0, 1_042_000, true, true, 1, None)
+                .unwrap();
+        assert!(!plan.rent_fee_waived);
+        assert_eq!(plan.network_fee, 7_000);
+    }
+
+    #[test]
+    fn sweeps_the_vault_on_the_final_redemption() {
+        let cash = Cash {
+            remaining_amount: 250_000,
+            total_redemptions: 3,
+            ..fixed_link()
+        };
+        let plan = plan(&cash, &redeem_args(), 265_000);
+        assert_eq!(plan.payout, 250_000);
+        assert_eq!(plan.vault_debit, 259_500);
+        assert_eq!(plan.sweep, Some(5_500));
+        assert_eq!(plan.state, CashState::Redeemed);
+        assert_eq!(plan.completion_reason, CompletionReason::SlotsExhausted);
+    }
+
+    #[test]
+    fn sets_the_payouts_waiting_for_approval_aside() {
+        let cash = Cash {
+            pending_approvals: 2,
+            reserved_amount: 500_000,
+            ..fixed_link()
+        };
+        let plan = plan(&cash, &redeem_args(), 1_042_000);
+        assert_eq!(plan.remaining_amount, 750_000);
+        assert_eq!(plan.total_redemptions, 1);
+        // the approvals still owe their payouts, the link stays live
+        assert_eq!(plan.state, CashState::Redeeming);
+        assert_eq!(plan.sweep, None);
+
+        // an approved redemption pays the payout locked at request time
+        let plan = plan_redemption(&cash, &redeem_args(), 0, 1_042_000, false, false, 1, Some(7))
+            .unwrap();
+        assert_eq!(plan.payout, 7);
+    }
+
+    #[test]
+    fn clamps_the_referral_fees_to_the_ceilings_of_the_link() {
+        let cash = Cash {
+            referral_ceilings_enabled: true,
+            max_referrer_fee_bps: 1_000,
+            max_referee_fee_bps: 500,
+            ..fixed_link()
+        };
+        let mut args = redeem_args();
+        args.referrer_fee_bps = Some(5_000);
+        args.referee_fee_bps = Some(5_000);
+        let plan = plan(&cash, &args, 1_042_000);
+        assert_eq!(plan.platform_fee_per_redeem, 2_500);
+        assert_eq!(plan.referrer_fee, 250);
+        assert_eq!(plan.referee_fee, 125);
+        assert_eq!(plan.platform_fee, 2_125);
+    }
+
+    #[test]
+    fn refuses_a_redemption_the_vault_can_not_cover() {
+        assert_eq!(
+            plan_redemption(&fixed_link(), &redeem_args(), 0, 259_499, false, false, 1, None),
+            Err(CashError::InsufficientSettlementFunds.into())
+        );
+        let exhausted = Cash {
+            total_redemptions: 4,
+            ..fixed_link()
+        };
+        assert_eq!(
+            plan_redemption(&exhausted, &redeem_args(), 0, 1_042_000, false, false, 1, None),
+            Err(CashError::MaxRedemptionsReached.into())
+        );
+    }
+
+    /// A token account of the token program holding the mint for the wallet
+    fn token_account(
+        token_program: &Pubkey,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        state: AccountState,
+    ) -> TestAccount {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint: *mint,
+            owner: *wallet,
+            amount: 0,
+            state,
+            ..TokenAccount::default()
+        }
+        .pack_into_slice(&mut data);
+        TestAccount::new(Pubkey::new_unique(), *token_program, data)
+    }
+
+    fn check(
+        token: &mut TestAccount,
+        wallet: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Result<bool, ProgramError> {
+        check_token_account(&token.info(), wallet, mint, token_program, "recipient token")
+    }
+
+    #[test]
+    fn checks_the_token_accounts_the_pda_pays_into() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        for token_program in [spl_token::id(), spl_token_2022::id()].iter() {
+            let mut token = token_account(token_program, &wallet, &mint, AccountState::Initialized);
+            assert_eq!(check(&mut token, &wallet, &mint, token_program), Ok(true));
+
+            let mut missing = TestAccount::new(Pubkey::new_unique(), Pubkey::default(), vec![]);
+            missing.lamports = 0;
+            assert_eq!(check(&mut missing, &wallet, &mint, token_program), Ok(false));
+        }
+    }
+
+    #[test]
+    fn refuses_a_token_account_of_another_owner() {
+        let wallet = Pubkey::new_unique();
+        let attacker = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_program = spl_token::id();
+        let mut token = token_account(&token_program, &attacker, &mint, AccountState::Initialized);
+        assert_eq!(
+            check(&mut token, &wallet, &mint, &token_program),
+            Err(CashError::InvalidOwner.into())
+        );
+    }
+
+    #[test]
+    fn refuses_a_token_account_of_another_mint() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_program = spl_token::id();
+        let mut token = token_account(
+            &token_program,
+            &wallet,
+            &Pubkey::new_unique(),
+            AccountState::Initialized,
+        );
+        assert_eq!(
+            check(&mut token, &wallet, &mint, &token_program),
+            Err(CashError::InvalidMint.into())
+        );
+    }
+
+    #[test]
+    fn refuses_a_token_account_of_the_other_token_program() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut token = token_account(&spl_token::id(), &wallet, &mint, AccountState::Initialized);
+        assert_eq!(
+            check(&mut token, &wallet, &mint, &spl_token_2022::id()),
+            Err(CashError::InvalidOwner.into())
+        );
+        let mut token =
+            token_account(&spl_token_2022::id(), &wallet, &mint, AccountState::Initialized);
+        assert_eq!(
+            check(&mut token, &wallet, &mint, &spl_token::id()),
+            Err(CashError::InvalidOwner.into())
+        );
+    }
+
+    #[test]
+    fn refuses_the_cash_pda_as_a_token_account() {
+        let program_id = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut cash = TestAccount::new(
+            Pubkey::new_unique(),
+            program_id,
+            vec![0; TokenAccount::LEN],
+        );
+        assert_eq!(
+            check(&mut cash, &wallet, &mint, &spl_token::id()),
+            Err(CashError::InvalidOwner.into())
+        );
+    }
+
+    #[test]
+    fn refuses_a_frozen_recipient() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_program = spl_token::id();
+        let mut token = token_account(&token_program, &wallet, &mint, AccountState::Frozen);
+        // the account plays its role, the redemption refuses it before the transfer fails
+        assert_eq!(check(&mut token, &wallet, &mint, &token_program), Ok(true));
+        let recipient_token: TokenAccount =
+            assert_initialized(&token.info(), Some("recipient token")).unwrap();
+        assert_eq!(
+            assert_not_frozen(&recipient_token, CashError::RecipientAccountFrozen),
+            Err(CashError::RecipientAccountFrozen.into())
+        );
+    }
+
+    #[test]
+    fn refuses_the_vault_as_a_destination() {
+        let cash = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let token_program = spl_token::id();
+        let mut vault = token_account(&token_program, &cash, &mint, AccountState::Initialized);
+        assert_eq!(
+            check(&mut vault, &wallet, &mint, &token_program),
+            Err(CashError::InvalidOwner.into())
+        );
+        // a role whose owner is the link itself still can not reuse the vault
+        let platform_token = Pubkey::new_unique();
+        let legs = [
+            (&vault.key, &cash),
+            (&vault.key, &wallet),
+            (&platform_token, &Pubkey::new_unique()),
+        ];
+        assert_eq!(
+            assert_distinct_token_accounts(&legs),
+            Err(CashError::DuplicateAccount.into())
+        );
+    }
+
+    #[test]
+    fn kyc_is_only_required_above_the_threshold() {
+        let cash = kyc_link(&Pubkey::new_unique());
+        assert!(!cash.kyc_required(999));
+        assert!(!cash.kyc_required(1_000));
+        assert!(cash.kyc_required(1_001));
+        assert!(!Cash::default().kyc_required(u64::MAX));
+    }
+
+    #[test]
+    fn accepts_a_valid_attestation() {
+        let kyc_program = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let mut attestation = attest(&kyc_program, &wallet, NOW + 60);
+        let accounts = [attestation.info()];
+        let cash = kyc_link(&kyc_program);
+        assert_eq!(assert_kyc_attestation(&accounts, &cash, &wallet, &clock()), Ok(()));
+    }
+
+    #[test]
+    fn rejects_an_expired_attestation() {
+        let kyc_program = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let cash = kyc_link(&kyc_program);
+        for expires_at in [NOW, NOW - 1, 0] {
+            let mut attestation = attest(&kyc_program, &wallet, expires_at);
+            let accounts = [attestation.info()];
+            assert_eq!(
+                assert_kyc_attestation(&accounts, &cash, &wallet, &clock()),
+                Err(CashError::KycExpired.into())
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_the_attestation_of_another_wallet() {
+        let kyc_program = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let mut attestation = attest(&kyc_program, &other, NOW + 60);
+        let accounts = [attestation.info()];
+        let cash = kyc_link(&kyc_program);
+        assert_eq!(
+            assert_kyc_attestation(&accounts, &cash, &wallet, &clock()),
+            Err(CashError::KycRequired.into())
+        );
+    }
+
+    #[test]
+    fn rejects_an_attestation_away_from_its_address() {
+        let kyc_program = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let cash = kyc_link(&kyc_program);
+        // an account of the kyc program holding the wallet, at any other address
+        let mut data = wallet.to_bytes().to_vec();
+        data.extend_from_slice(&(NOW + 60).to_le_bytes());
+        let mut forged = TestAccount::new(Pubkey::new_unique(), kyc_program, data.clone());
+        let accounts = [forged.info()];
+        assert_eq!(
+            assert_kyc_attestation(&accounts, &cash, &wallet, &clock()),
+            Err(CashError::KycRequired.into())
+        );
+        // the right address owned by another program
+        let (key, _) = find_kyc_attestation_address(&kyc_program, &wallet);
+        let mut foreign = TestAccount::new(key, Pubkey::new_unique(), data);
+        let accounts = [foreign.info()];
+        assert_eq!(
+            assert_kyc_attestation(&accounts, &cash, &wallet, &clock()),
+            Err(CashError::KycRequired.into())
+        );
+        assert_eq!(
+            assert_kyc_attestation(&[], &cash, &wallet, &clock()),
+            Err(CashError::KycRequired.into())
+        );
+    }
+
+    #[test]
+    fn the_stub_only_writes_the_attestation_address() {
+        let kyc_program = Pubkey::new_unique();
+        let wallet = Pubkey::new_unique();
+        let mut attestation = TestAccount::new(
+            Pubkey::new_unique(),
+            kyc_program,
+            vec![0; kyc_stub::ATTESTATION_LEN],
+        );
+        let mut wallet = TestAccount::new(wallet, Pubkey::new_unique(), vec![]);
+        assert_eq!(
+            kyc_stub::process_instruction(
+                &kyc_program,
+                &[attestation.info(), wallet.info()],
+                &NOW.to_le_bytes(),
+            ),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn refuses_a_recipient_delegated_to_another_account() {
+        let cash_key = Pubkey::new_unique();
+        let delegated_to = |delegate: Option<Pubkey>| TokenAccount {
+            delegate: delegate.into(),
+            delegated_amount: 1,
+            ..TokenAccount::default()
+        };
+        let cash = Cash {
+            clawback_window_seconds: Some(600),
+            ..fixed_link()
+        };
+        let foreign = delegated_to(Some(Pubkey::new_unique()));
+        assert_eq!(
+            assert_no_foreign_delegate(&cash, &cash_key, &foreign),
+            Err(CashError::RecipientDelegated.into())
+        );
+        // a delegation left by an earlier payout of the link is replaced
+        let earlier = delegated_to(Some(cash_key));
+        assert_eq!(assert_no_foreign_delegate(&cash, &cash_key, &earlier), Ok(()));
+        assert_eq!(assert_no_foreign_delegate(&cash, &cash_key, &delegated_to(None)), Ok(()));
+        // a link without a clawback window approves nothing
+        assert_eq!(assert_no_foreign_delegate(&fixed_link(), &cash_key, &foreign), Ok(()));
+    }
+
+    /// A redemption of a live link of 4_000 over 4 into a recipient token account it creates
+    fn late_redemption() -> TestRedemption {
+        let mut redemption = TestRedemption::new(TestLink::new(4_000, 4));
+        let recipient_token = redemption.recipient_token.key;
+        redemption.recipient_token = TestAccount::uncreated(recipient_token, TokenAccount::LEN);
+        runtime(NOW);
+        redemption
+    }
+
+    /// Plan and execute the redemption, `late` changes the plan before it is executed
+    fn execute(
+        redemption: &mut TestRedemption,
+        late: impl FnOnce(&mut RedemptionPlan),
+    ) -> Result<u64, ProgramError> {
+        let args = redemption.args();
+        let program_id = redemption.link.program_id;
+        redemption.with_context(|accounts, ctx| {
+            let mut plan = plan_redemption(
+                &ctx.cash,
+                &args,
+                0,
+                ctx.vault_amount,
+                false,
+                ctx.recipient_token_exists,
+                1,
+                None,
+            )?;
+            late(&mut plan);
+            execute_plan(accounts, &ctx, &mut plan, &args, &program_id)
+        })
+    }
+
+    #[test]
+    fn validates_everything_before_the_first_cpi() {
+        let mut redemption = late_redemption();
+        execute(&mut redemption, |_| {}).unwrap();
+        let steps = trace();
+        assert_eq!(steps[0], Step::Validated);
+        // the recipient token account is created first
+        assert_eq!(steps[1], Step::Invoked(spl_associated_token_account::id()));
+        assert!(steps[1..].iter().all(|step| *step != Step::Validated));
+        assert_eq!(TestLink::amount(&redemption.link.vault), 3_000);
+    }
+
+    #[test]
+    fn fails_a_recipient_token_account_off_its_address_before_any_cpi() {
+        let mut redemption = late_redemption();
+        let elsewhere = Pubkey::new_unique();
+        redemption.recipient_token = TestAccount::uncreated(elsewhere, TokenAccount::LEN);
+        assert_eq!(
+            execute(&mut redemption, |_| {}),
+            Err(CashError::InvalidDstTokenOwner.into())
+        );
+        assert_eq!(trace(), vec![]);
+    }
+
+    #[test]
+    fn fails_a_taken_receipt_or_index_before_any_cpi() {
+        let mut redemption = late_redemption();
+        let program_id = redemption.link.program_id;
+        let receipt = redemption.redemption.key;
+        redemption.redemption = TestAccount::new(receipt, program_id, vec![1; 8]);
+        let taken = Err(ProgramError::AccountAlreadyInitialized);
+        assert_eq!(execute(&mut redemption, |_| {}), taken);
+        assert_eq!(trace(), vec![]);
+
+        let mut redemption = late_redemption();
+        let index = redemption.redemption_index.key;
+        redemption.redemption_index = TestAccount::new(index, program_id, vec![1; 8]);
+        assert_eq!(execute(&mut redemption, |_| {}), taken);
+        assert_eq!(trace(), vec![]);
+    }
+
+    #[test]
+    fn fails_a_referrer_fee_without_referral_accounts_before_any_cpi() {
+        let mut redemption = late_redemption();
+        assert_eq!(
+            execute(&mut redemption, |plan| plan.referrer_fee = 1),
+            Err(CashError::InvalidReferralFees.into())
+        );
+        assert_eq!(trace(), vec![]);
+    }
+}
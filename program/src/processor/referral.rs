@@ -0,0 +1,345 @@
+use crate::{
+    error::CashError,
+    find_referral_stats_address,
+    instruction::ReferralFeesArgs,
+    processor::config::{assert_config_admin, load_config},
+    state::{referral::ReferralStats, AccountType},
+    utils::{
+        assert_account_key, assert_initialized, assert_owned_by, assert_signer,
+        load_token_account, assert_valid_token_program,
+        create_associated_token_account_idempotent_raw, create_new_account_raw,
+        empty_account_balance, find_optional_account, is_native_mint,
+        spl_token_transfer, transfer_account_lamports,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::state::Mint;
+
+/// Close a referral stats account, its rent goes to the destination
+pub fn process_close_referral_stats(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process close referral stats");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let referral_stats_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+
+    let referral_stats = load_referral_stats(referral_stats_info, program_id)?;
+    // native escrowed fees are lamports of the account, they would leave with its rent
+    if !referral_stats.vesting.is_empty() {
+        return Err(CashError::ReferralFeesEscrowed.into());
+    }
+    msg!(
+        "Closing referral stats of {} after {} redemptions",
+        referral_stats.referrer,
+        referral_stats.redemption_count
+    );
+    empty_account_balance(referral_stats_info, destination_info)?;
+    referral_stats_info.data.borrow_mut().fill(0);
+    Ok(())
+}
+
+/// Pay the referrer its escrowed fees of a mint that have vested
+pub fn process_claim_referral_fees<'a>(
+    accounts: &[AccountInfo<'a>],
+    args: ReferralFeesArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process claim referral fees");
+    let account_info_iter = &mut accounts.iter();
+    let referrer_info = next_account_info(account_info_iter)?;
+    assert_signer(referrer_info)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let config = load_config(config_info, program_id)?;
+    let referral_stats_info = next_account_info(account_info_iter)?;
+    let escrow_info = next_account_info(account_info_iter)?;
+    let referrer_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+    let _associated_token_program_info = next_account_info(account_info_iter)?;
+
+    let (referral_stats_key, referral_stats_bump) =
+        find_referral_stats_address(program_id, referrer_info.key);
+    assert_account_key(
+        referral_stats_info,
+        &referral_stats_key,
+        Some(CashError::InvalidReferralStatsKey),
+        Some("referral stats"),
+    )?;
+    assert_account_key(mint_info, &args.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    let mut referral_stats = load_referral_stats(referral_stats_info, program_id)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    // the fees vested when the referrer was flagged stay claimable, the others were forfeited
+    let vested_at = referral_stats
+        .flagged_at
+        .map_or(now, |flagged_at| flagged_at.min(now));
+    let amount = referral_stats.take_vested(
+        mint_info.key,
+        config.referral_vesting_delay.unwrap_or(0),
+        vested_at,
+    )?;
+    if amount == 0 {
+        return Err(CashError::ReferralFeesNotVested.into());
+    }
+
+    if is_native_mint(mint_info.key) {
+        assert_account_key(
+            referrer_token_info,
+            referrer_info.key,
+            Some(CashError::InvalidDstTokenOwner),
+            Some("referrer token"),
+        )?;
+    } else {
+        create_associated_token_account_idempotent_raw(
+            referrer_info,
+            referrer_token_info,
+            referrer_info,
+            mint_info,
+            token_program_info.key,
+        )?;
+    }
+    release_escrow(
+        referral_stats_info,
+        &[
+            ReferralStats::PREFIX.as_bytes(),
+            referrer_info.key.as_ref(),
+            &[referral_stats_bump],
+        ],
+        escrow_info,
+        referrer_token_info,
+        mint_info,
+        token_program_info,
+        amount,
+    )?;
+    msg!(
+        "Claimed {} vested referrer fees of mint {}",
+        amount,
+        mint_info.key
+    );
+    ReferralStats::pack(
+        referral_stats,
+        &mut referral_stats_info.try_borrow_mut_data()?,
+    )?;
+    Ok(())
+}
+
+/// Flag a referrer farming referrals, its fees of the mint unvested at the first flag go to
+/// the config fee wallet. Flag again for each mint the referrer holds escrowed fees in
+pub fn process_flag_referrer<'a>(
+    accounts: &[AccountInfo<'a>],
+    args: ReferralFeesArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process flag referrer");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    let config = assert_config_admin(config_info, admin_info, program_id)?;
+    let referral_stats_info = next_account_info(account_info_iter)?;
+    let escrow_info = next_account_info(account_info_iter)?;
+    let fee_wallet_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    assert_account_key(mint_info, &args.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    let mut referral_stats = load_referral_stats(referral_stats_info, program_id)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+    let flagged_at = *referral_stats.flagged_at.get_or_insert(now);
+    let amount = referral_stats.take_unvested(
+        mint_info.key,
+        config.referral_vesting_delay.unwrap_or(0),
+        flagged_at,
+    )?;
+    msg!(
+        "Referrer {} flagged, {} unvested fees of mint {} forfeited",
+        referral_stats.referrer,
+        amount,
+        mint_info.key
+    );
+    if amount > 0 {
+        if is_native_mint(mint_info.key) {
+            assert_account_key(
+                fee_wallet_token_info,
+                &config.fee_wallet,
+                Some(CashError::InvalidFeeTokenOwner),
+                Some("fee wallet token"),
+            )?;
+        } else {
+            load_token_account(
+                fee_wallet_token_info,
+                &config.fee_wallet,
+                mint_info.key,
+                token_program_info.key,
+                "fee wallet token",
+            )?;
+        }
+        let (_, referral_stats_bump) =
+            find_referral_stats_address(program_id, &referral_stats.referrer);
+        release_escrow(
+            referral_stats_info,
+            &[
+                ReferralStats::PREFIX.as_bytes(),
+                referral_stats.referrer.as_ref(),
+                &[referral_stats_bump],
+            ],
+            escrow_info,
+            fee_wallet_token_info,
+            mint_info,
+            token_program_info,
+            amount,
+        )?;
+    }
+    ReferralStats::pack(
+        referral_stats,
+        &mut referral_stats_info.try_borrow_mut_data()?,
+    )?;
+    Ok(())
+}
+
+/// Move escrowed fees out of the referral stats, native fees are lamports of the account itself
+fn release_escrow<'a>(
+    referral_stats_info: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+    escrow_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    if is_native_mint(mint_info.key) {
+        return transfer_account_lamports(referral_stats_info, destination_info, amount);
+    }
+    assert_valid_token_program(token_program_info.key)?;
+    let escrow_key = get_associated_token_address_with_program_id(
+        referral_stats_info.key,
+        mint_info.key,
+        token_program_info.key,
+    );
+    assert_account_key(
+        escrow_info,
+        &escrow_key,
+        Some(CashError::InvalidSrcTokenOwner),
+        Some("escrow"),
+    )?;
+    let mint: Mint = assert_initialized(mint_info, Some("mint"))?;
+    spl_token_transfer(
+        escrow_info,
+        destination_info,
+        referral_stats_info,
+        mint_info,
+        token_program_info.key,
+        amount,
+        mint.decimals,
+        &[signer_seeds],
+    )
+}
+
+/// Load an initialized referral stats account
+pub fn load_referral_stats(
+    referral_stats_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<ReferralStats, ProgramError> {
+    assert_owned_by(referral_stats_info, program_id, Some("referral stats"))?;
+    let referral_stats = ReferralStats::unpack_unchecked(&referral_stats_info.try_borrow_data()?)?;
+    if !referral_stats.is_initialized() {
+        return Err(CashError::InvalidReferralStatsKey.into());
+    }
+    Ok(referral_stats)
+}
+
+/// Create the referral stats of a referrer the first time it earns a fee
+pub fn get_or_create_referral_stats<'a>(
+    referral_stats_info: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    referrer: &Pubkey,
+    referral_stats_bump: u8,
+    fee_payer_info: &AccountInfo<'a>,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+) -> Result<ReferralStats, ProgramError> {
+    if referral_stats_info.owner == program_id && !referral_stats_info.data_is_empty() {
+        return load_referral_stats(referral_stats_info, program_id);
+    }
+    create_new_account_raw(
+        program_id,
+        referral_stats_info,
+        rent_info,
+        fee_payer_info,
+        system_program_info,
+        ReferralStats::LEN,
+        &[
+            ReferralStats::PREFIX.as_bytes(),
+            referrer.as_ref(),
+            &[referral_stats_bump],
+        ],
+    )?;
+    let referral_stats = ReferralStats {
+        account_type: AccountType::ReferralStats,
+        referrer: *referrer,
+        ..Default::default()
+    };
+    ReferralStats::pack(
+        referral_stats.clone(),
+        &mut referral_stats_info.try_borrow_mut_data()?,
+    )?;
+    Ok(referral_stats)
+}
+
+/// Accrue a referrer fee on the referral stats of the referrer when they were passed to the
+/// instruction, the fee payer funds the account the first time the referrer earns a fee.
+/// `escrowed_at` is set when the fee was paid into the escrow of the referral stats
+pub fn update_referral_stats<'a>(
+    accounts: &[AccountInfo<'a>],
+    program_id: &Pubkey,
+    referrer: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    escrowed_at: Option<u64>,
+    fee_payer_info: &AccountInfo<'a>,
+    rent_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+    let (referral_stats_key, referral_stats_bump) =
+        find_referral_stats_address(program_id, referrer);
+    let referral_stats_info = match find_optional_account(accounts, &referral_stats_key) {
+        Some(referral_stats_info) => referral_stats_info,
+        None if escrowed_at.is_some() => return Err(CashError::InvalidReferralStatsKey.into()),
+        None => {
+            msg!("No referral stats for referrer {}, skipped", referrer);
+            return Ok(());
+        }
+    };
+    let mut referral_stats = get_or_create_referral_stats(
+        referral_stats_info,
+        program_id,
+        referrer,
+        referral_stats_bump,
+        fee_payer_info,
+        rent_info,
+        system_program_info,
+    )?;
+    referral_stats.record_earning(mint, amount);
+    if let Some(escrowed_at) = escrowed_at {
+        referral_stats.escrow(mint, escrowed_at, amount)?;
+    }
+    ReferralStats::pack(
+        referral_stats,
+        &mut referral_stats_info.try_borrow_mut_data()?,
+    )?;
+    Ok(())
+}
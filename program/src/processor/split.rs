@@ -0,0 +1,240 @@
+//! Split a funded link into child links
+//!
+//! A creator may carve part of the unredeemed amount and redemptions of a link out into a new
+//! child link, e.g. for another audience, without withdrawing and funding again. The child
+//! inherits the configuration of its parent and receives the principal it was given along with
+//! the fee reserves of its redemptions straight from the parent vault. Both links must still be
+//! able to pay out every redemption they have left.
+use crate::{
+    error::CashError::{self, AccountAlreadyCanceled, AccountAlreadyRedeemed},
+    event::ParamsHashEvent,
+    instruction::SplitCashArgs,
+    math::SafeMath,
+    processor::{
+        cash::{assert_vault, create_cash_link, mint_decimals},
+        config::assert_features_enabled,
+    },
+    state::{
+        cash::{Cash, CashState, CompletionReason, DistributionType},
+        config::Feature,
+        AccountType,
+    },
+    utils::{
+        assert_account_key, assert_initialized, assert_owned_by, assert_signer,
+        assert_valid_token_program, create_associated_token_account_raw, load_account,
+        spl_token_transfer, store_account,
+    },
+    PROGRAM_VERSION,
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::state::Account as TokenAccount;
+
+pub fn process_split_cash(
+    accounts: &[AccountInfo],
+    args: SplitCashArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process split cash");
+    let account_info_iter = &mut accounts.iter();
+    let authority_info = next_account_info(account_info_iter)?;
+    assert_signer(authority_info)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    assert_signer(owner_info)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let cash_info = next_account_info(account_info_iter)?;
+    let vault_token_info = next_account_info(account_info_iter)?;
+    let child_info = next_account_info(account_info_iter)?;
+    let child_vault_token_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    assert_valid_token_program(token_program_info.key)?;
+    assert_owned_by(cash_info, program_id, Some("cash"))?;
+    assert_features_enabled(accounts, program_id, &[Feature::Init])?;
+
+    let mut cash = load_account::<Cash>(cash_info)?;
+    cash.assert_not_in_progress()?;
+    cash.assert_not_halted()?;
+    assert_account_key(
+        authority_info,
+        &cash.authority,
+        Some(CashError::InvalidAuthorityId),
+        Some("authority"),
+    )?;
+    assert_account_key(owner_info, &cash.owner, Some(CashError::InvalidOwner), Some("owner"))?;
+    assert_account_key(mint_info, &cash.mint, Some(CashError::InvalidMint), Some("mint"))?;
+    if cash.canceled() || cash.cancel_pending() {
+        return Err(AccountAlreadyCanceled.into());
+    }
+    if cash.redeemed() {
+        return Err(AccountAlreadyRedeemed.into());
+    }
+    // weights are shares of the whole amount and flash slots follow the clock, neither can be
+    // cut in two
+    if cash.distribution_type == DistributionType::Weighted || cash.flash.is_some() {
+        return Err(CashError::SplitBreaksDistribution.into());
+    }
+    cash.assert_redemption_counters()?;
+    assert_vault(&cash, cash_info.key, vault_token_info, token_program_info.key)?;
+    let vault_token: TokenAccount = assert_initialized(vault_token_info, Some("vault token"))?;
+
+    // the parent keeps at least one redemption and some of its amount, besides what is
+    // reserved for the redemptions waiting for approval
+    if args.amount == 0
+        || args.max_num_redemptions == 0
+        || args.amount >= cash.remaining_amount.error_sub(cash.reserved_amount)?
+        || args.max_num_redemptions
+            >= cash
+                .max_num_redemptions_remaining()?
+                .error_sub(cash.pending_approvals)?
+    {
+        return Err(CashError::SplitExceedsRemaining.into());
+    }
+    let now = Clock::get()?.unix_timestamp as u64;
+    let parent = Cash {
+        amount: cash.amount.error_sub(args.amount)?,
+        remaining_amount: cash.remaining_amount.error_sub(args.amount)?,
+        max_num_redemptions: cash.max_num_redemptions.error_sub(args.max_num_redemptions)?,
+        ..cash.clone()
+    };
+    let mut child = Cash {
+        account_type: AccountType::Cash,
+        state: CashState::Initialized,
+        amount: args.amount,
+        remaining_amount: args.amount,
+        max_num_redemptions: args.max_num_redemptions,
+        total_redemptions: 0,
+        total_weight_ppm: 0,
+        // the network fee was paid once by the parent
+        network_fee: 0,
+        completion_reason: CompletionReason::None,
+        buffer_hash: None,
+        params_hash: None,
+        in_progress: false,
+        total_burned: 0,
+        created_at: now,
+        created_by_version: PROGRAM_VERSION,
+        canceled_at: None,
+        completed_at: None,
+        expired_slots: 0,
+        forfeited_amount: 0,
+        pass_rotations: 0,
+        recovery_requested_at: None,
+        reserved_amount: 0,
+        pending_approvals: 0,
+        ..cash.clone()
+    };
+    assert_split_distribution(&parent)?;
+    assert_split_distribution(&child)?;
+
+    let child_total = child.required_reserve()?;
+    let parent_required = parent.required_reserve()?.error_add(parent.forfeited_amount)?;
+    let vault_after = vault_token.amount.error_sub(child_total)?;
+    if vault_after < parent_required {
+        msg!(
+            "The vault would hold {} after the split, {} is required",
+            vault_after,
+            parent_required
+        );
+        return Err(CashError::SplitExceedsRemaining.into());
+    }
+
+    let created = create_cash_link(
+        program_id,
+        child_info,
+        fee_payer_info,
+        rent_info,
+        system_program_info,
+        &[
+            Cash::PREFIX.as_bytes(),
+            args.child_reference.as_bytes(),
+            &[args.child_bump],
+        ],
+    )?;
+    child.fee_payer = created.fee_payer;
+    child.rent_paid_by_fee_payer = created.rent_paid_by_fee_payer;
+    child.vault = get_associated_token_address_with_program_id(
+        child_info.key,
+        mint_info.key,
+        token_program_info.key,
+    );
+    child.token_program = *token_program_info.key;
+    child.update_params_hash();
+    assert_account_key(
+        child_vault_token_info,
+        &child.vault,
+        Some(CashError::InvalidVaultTokenOwner),
+        Some("child vault token"),
+    )?;
+    create_associated_token_account_raw(
+        fee_payer_info,
+        child_vault_token_info,
+        child_info,
+        mint_info,
+        rent_info,
+        token_program_info.key,
+    )?;
+    spl_token_transfer(
+        vault_token_info,
+        child_vault_token_info,
+        cash_info,
+        mint_info,
+        token_program_info.key,
+        child_total,
+        mint_decimals(&cash, mint_info)?,
+        &[&[
+            Cash::PREFIX.as_bytes(),
+            args.cash_reference.as_bytes(),
+            &[args.cash_bump],
+        ]],
+    )?;
+    msg!(
+        "Split {} and {} redemptions into {}",
+        args.amount,
+        args.max_num_redemptions,
+        child_info.key
+    );
+
+    cash.amount = parent.amount;
+    cash.remaining_amount = parent.remaining_amount;
+    cash.max_num_redemptions = parent.max_num_redemptions;
+    cash.clear_recovery();
+    // links too small to hold a hash were created before it was stored and keep none
+    if cash.params_hash.is_some() {
+        cash.update_params_hash();
+    }
+    ParamsHashEvent::emit_for(cash_info.key, &cash, now);
+    ParamsHashEvent::emit_for(child_info.key, &child, now);
+    store_account(cash, cash_info)?;
+    store_account(child, child_info)?;
+    Ok(())
+}
+
+/// Every redemption left on a link after a split must still be payable by its distribution
+fn assert_split_distribution(cash: &Cash) -> ProgramResult {
+    let slots_left = cash.max_num_redemptions_remaining()? as u64;
+    // slots_left never exceeds the redemptions, neither product overflows the amount
+    let per_redemption = cash.per_redemption(cash.amount)?;
+    let payable = match cash.distribution_type {
+        // a fixed link pays the same amount per redemption on both sides of the split
+        DistributionType::Fixed => {
+            per_redemption * cash.max_num_redemptions as u64 == cash.amount
+                && cash.remaining_amount == per_redemption * slots_left
+        }
+        DistributionType::Equal => cash.remaining_amount >= per_redemption * slots_left,
+        _ => cash.remaining_amount >= cash.min_total_required()?,
+    };
+    if !payable {
+        return Err(CashError::SplitBreaksDistribution.into());
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use crate::{
+    error::CashError,
+    find_stats_program_address,
+    instruction::InitStatsArgs,
+    processor::{config::assert_config_admin, mint_registry::load_mint_registry},
+    state::{
+        stats::{MintStats, Stats, MAX_STATS_MINTS},
+        AccountType,
+    },
+    utils::{assert_owned_by, create_new_account_raw, find_optional_account},
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+};
+
+pub fn process_init_stats(
+    accounts: &[AccountInfo],
+    args: InitStatsArgs,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process init stats");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let fee_payer_info = next_account_info(account_info_iter)?;
+    let stats_info = next_account_info(account_info_iter)?;
+    let rent_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let mint_registry_info = next_account_info(account_info_iter).ok();
+
+    if stats_info.lamports() > 0 && !stats_info.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    // the per mint breakdown follows the first configured mints of the registry
+    let mints = match mint_registry_info {
+        Some(mint_registry_info) => load_mint_registry(mint_registry_info, program_id)?
+            .mints
+            .into_iter()
+            .take(MAX_STATS_MINTS)
+            .map(|mint| MintStats {
+                mint,
+                ..Default::default()
+            })
+            .collect(),
+        None => vec![],
+    };
+    create_new_account_raw(
+        program_id,
+        stats_info,
+        rent_info,
+        fee_payer_info,
+        system_program_info,
+        Stats::LEN,
+        &[Stats::PREFIX.as_bytes(), &[args.stats_bump]],
+    )?;
+    let stats = Stats {
+        account_type: AccountType::Stats,
+        mints,
+        ..Default::default()
+    };
+    Stats::pack(stats, &mut stats_info.data.borrow_mut())?;
+    Ok(())
+}
+
+/// Update the stats account when it was passed to the instruction, a no-op otherwise
+pub fn update_stats<F>(accounts: &[AccountInfo], program_id: &Pubkey, update: F) -> ProgramResult
+where
+    F: FnOnce(&mut Stats),
+{
+    let (stats_key, _) = find_stats_program_address(program_id);
+    let stats_info = match find_optional_account(accounts, &stats_key) {
+        Some(stats_info) => stats_info,
+        None => return Ok(()),
+    };
+    assert_owned_by(stats_info, program_id, Some("stats"))?;
+    let mut stats = Stats::unpack_unchecked(&stats_info.data.borrow())?;
+    if !stats.is_initialized() {
+        return Err(CashError::InvalidStatsKey.into());
+    }
+    update(&mut stats);
+    Stats::pack(stats, &mut stats_info.data.borrow_mut())?;
+    Ok(())
+}
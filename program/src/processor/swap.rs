@@ -0,0 +1,197 @@
+//! Swap on redeem
+//!
+//! A link with a swap config pays the recipient in the target mint. The payout is swapped
+//! out of the vault through a single constant product pool of the allow listed amm, which
+//! must implement this instruction:
+//!
+//! - data: `SWAP_INSTRUCTION_TAG` followed by the little endian u64 amount in and minimum out
+//! - accounts: the pool, the source authority as signer, the source token account, the
+//!   destination token account, the pool source and target reserves, the source and target
+//!   mints and the token program
+//!
+//! The minimum out is the pool quote less the slippage bound of the link, the received
+//! amount is checked again after the cpi.
+use crate::{
+    error::CashError,
+    math::SafeMath,
+    rounding::{constant_product_out, floor_share},
+    state::{cash::SwapConfig, config::Config},
+    utils::{
+        assert_account_key, assert_initialized, assert_owned_by, assert_token_mint, cmp_pubkeys,
+        is_native_mint,
+    },
+};
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use spl_token_2022::state::Account as TokenAccount;
+
+pub const SWAP_INSTRUCTION_TAG: u8 = 1;
+
+/// The accounts of the pool a payout is swapped through, they follow the amm program
+pub struct SwapAccounts<'a, 'b> {
+    pub config: SwapConfig,
+    pub amm_program_info: &'b AccountInfo<'a>,
+    pub pool_info: &'b AccountInfo<'a>,
+    pub pool_source_info: &'b AccountInfo<'a>,
+    pub pool_target_info: &'b AccountInfo<'a>,
+    pub target_mint_info: &'b AccountInfo<'a>,
+}
+
+/// Check a swap config at init, the amm must be the one allow listed by the config
+pub fn validate_swap_config(
+    swap: &SwapConfig,
+    mint: &Pubkey,
+    config: Option<&Config>,
+) -> ProgramResult {
+    // the native vault is unwrapped through the fee payer, there are no tokens to swap
+    if is_native_mint(mint) || cmp_pubkeys(&swap.target_mint, mint) {
+        return Err(CashError::InvalidSwapConfig.into());
+    }
+    if swap.max_slippage_bps > 10000 {
+        return Err(CashError::InvalidSwapConfig.into());
+    }
+    match config.and_then(|config| config.swap_amm_program) {
+        Some(amm_program) if cmp_pubkeys(&amm_program, &swap.amm_program) => Ok(()),
+        _ => Err(CashError::AmmProgramNotAllowed.into()),
+    }
+}
+
+/// Find the amm program of the swap config among the accounts and the pool accounts
+/// following it: the pool, the pool source reserve, the pool target reserve and the target mint
+pub fn find_swap_accounts<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    config: SwapConfig,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Result<SwapAccounts<'a, 'b>, ProgramError> {
+    let position = accounts
+        .iter()
+        .position(|account| cmp_pubkeys(account.key, &config.amm_program))
+        .ok_or(CashError::InvalidSwapAccounts)?;
+    let swap_accounts = accounts
+        .get(position..position + 5)
+        .ok_or(CashError::InvalidSwapAccounts)?;
+    let swap = SwapAccounts {
+        config,
+        amm_program_info: &swap_accounts[0],
+        pool_info: &swap_accounts[1],
+        pool_source_info: &swap_accounts[2],
+        pool_target_info: &swap_accounts[3],
+        target_mint_info: &swap_accounts[4],
+    };
+    if !swap.amm_program_info.executable {
+        return Err(CashError::InvalidSwapAccounts.into());
+    }
+    // both sides of the pool use the token program of the link
+    assert_owned_by(swap.pool_source_info, token_program_id, Some("pool source"))?;
+    assert_owned_by(swap.pool_target_info, token_program_id, Some("pool target"))?;
+    assert_owned_by(swap.target_mint_info, token_program_id, Some("target mint"))?;
+    assert_account_key(
+        swap.target_mint_info,
+        &config.target_mint,
+        Some(CashError::InvalidSwapAccounts),
+        Some("target mint"),
+    )?;
+    let pool_source: TokenAccount = assert_initialized(swap.pool_source_info, Some("pool source"))?;
+    assert_token_mint(&pool_source, mint, "pool source")?;
+    let pool_target: TokenAccount = assert_initialized(swap.pool_target_info, Some("pool target"))?;
+    assert_token_mint(&pool_target, &config.target_mint, "pool target")?;
+    Ok(swap)
+}
+
+impl<'a, 'b> SwapAccounts<'a, 'b> {
+    /// Smallest amount of the target mint accepted for `amount_in`
+    pub fn min_out(&self, amount_in: u64) -> Result<u64, ProgramError> {
+        let reserve_in = assert_initialized::<TokenAccount>(
+            self.pool_source_info,
+            Some("pool source"),
+        )?.amount;
+        let reserve_out = assert_initialized::<TokenAccount>(
+            self.pool_target_info,
+            Some("pool target"),
+        )?.amount;
+        let quote = constant_product_out(amount_in, reserve_in, reserve_out)?;
+        let min_out = floor_share(
+            quote,
+            10000u64.error_sub(self.config.max_slippage_bps as u64)?,
+            10000,
+        )?;
+        if min_out == 0 {
+            return Err(CashError::SlippageExceeded.into());
+        }
+        Ok(min_out)
+    }
+
+    /// Swap `amount_in` out of the vault straight into the recipient target token account
+    pub fn swap_from_vault(
+        &self,
+        vault_token_info: &AccountInfo<'a>,
+        recipient_token_info: &AccountInfo<'a>,
+        cash_info: &AccountInfo<'a>,
+        mint_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        amount_in: u64,
+        signer_seeds: &[&[u8]],
+    ) -> Result<u64, ProgramError> {
+        let min_out = self.min_out(amount_in)?;
+        let balance_before = assert_initialized::<TokenAccount>(
+            recipient_token_info,
+            Some("recipient token"),
+        )?.amount;
+        let mut data = Vec::with_capacity(17);
+        data.push(SWAP_INSTRUCTION_TAG);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&min_out.to_le_bytes());
+        let ix = Instruction {
+            program_id: *self.amm_program_info.key,
+            accounts: vec![
+                AccountMeta::new(*self.pool_info.key, false),
+                AccountMeta::new_readonly(*cash_info.key, true),
+                AccountMeta::new(*vault_token_info.key, false),
+                AccountMeta::new(*recipient_token_info.key, false),
+                AccountMeta::new(*self.pool_source_info.key, false),
+                AccountMeta::new(*self.pool_target_info.key, false),
+                AccountMeta::new_readonly(*mint_info.key, false),
+                AccountMeta::new_readonly(*self.target_mint_info.key, false),
+                AccountMeta::new_readonly(*token_program_info.key, false),
+            ],
+            data,
+        };
+        invoke_signed(
+            &ix,
+            &[
+                self.pool_info.clone(),
+                cash_info.clone(),
+                vault_token_info.clone(),
+                recipient_token_info.clone(),
+                self.pool_source_info.clone(),
+                self.pool_target_info.clone(),
+                mint_info.clone(),
+                self.target_mint_info.clone(),
+                token_program_info.clone(),
+                self.amm_program_info.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+        let received = assert_initialized::<TokenAccount>(
+            recipient_token_info,
+            Some("recipient token"),
+        )?
+            .amount
+            .error_sub(balance_before)?;
+        if received < min_out {
+            msg!("Swap returned {}, at least {} expected", received, min_out);
+            return Err(CashError::SlippageExceeded.into());
+        }
+        msg!("Swapped {} for {}", amount_in, received);
+        Ok(received)
+    }
+}
@@ -0,0 +1,129 @@
+//! Daily caps per wallet across every link of a mint
+//!
+//! The mint registry holds the cap of each capped mint. Every payout of a capped mint is added
+//! to the `WalletLimit` of the redeeming wallet, created on its first capped redemption, and a
+//! payout that would take the wallet past the cap within the window is refused. Windows roll
+//! over on the next redemption after they end, the admin sweeps the accounts left stale.
+use crate::{
+    error::CashError,
+    find_mint_registry_program_address, find_wallet_limit_address,
+    processor::{
+        config::assert_config_admin, mint_registry::load_mint_registry,
+        redemption::RedemptionContext,
+    },
+    state::{wallet_limit::WalletLimit, AccountType},
+    utils::{
+        assert_owned_by, create_new_account_raw, empty_account_balance, find_optional_account,
+    },
+};
+
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+/// Add a payout to the window of the wallet when the mint registry caps the mint of the link,
+/// the fee payer funds the wallet limit on the first capped redemption of the wallet
+pub fn record_wallet_limit<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    program_id: &Pubkey,
+    ctx: &RedemptionContext<'a, 'b>,
+    amount: u64,
+) -> ProgramResult {
+    let (registry_key, _) = find_mint_registry_program_address(program_id);
+    let daily_cap = match find_optional_account(accounts, &registry_key) {
+        Some(registry_info) => {
+            load_mint_registry(registry_info, program_id)?.daily_cap(&ctx.cash.mint)
+        }
+        None => None,
+    };
+    let daily_cap = match daily_cap {
+        Some(daily_cap) => daily_cap,
+        None => return Ok(()),
+    };
+    let (limit_key, limit_bump) =
+        find_wallet_limit_address(program_id, ctx.wallet_info.key, &ctx.cash.mint);
+    let limit_info =
+        find_optional_account(accounts, &limit_key).ok_or(CashError::InvalidWalletLimitKey)?;
+    let mut limit = if limit_info.owner == program_id && !limit_info.data_is_empty() {
+        load_wallet_limit(limit_info, program_id)?
+    } else {
+        create_new_account_raw(
+            program_id,
+            limit_info,
+            ctx.rent_info,
+            ctx.fee_payer_info,
+            ctx.system_program_info,
+            WalletLimit::LEN,
+            &[
+                WalletLimit::PREFIX.as_bytes(),
+                ctx.wallet_info.key.as_ref(),
+                ctx.cash.mint.as_ref(),
+                &[limit_bump],
+            ],
+        )?;
+        WalletLimit {
+            account_type: AccountType::WalletLimit,
+            wallet: *ctx.wallet_info.key,
+            mint: ctx.cash.mint,
+            rent_payer: *ctx.fee_payer_info.key,
+            ..Default::default()
+        }
+    };
+    limit.record(daily_cap, ctx.clock.unix_timestamp as u64, amount)?;
+    msg!("Wallet redeemed {} of its daily cap {}", limit.amount, daily_cap);
+    WalletLimit::pack(limit, &mut limit_info.try_borrow_mut_data()?)
+}
+
+/// Close the wallet limits whose window ended, each refunds the rent to its payer. Returns the
+/// number of closed accounts as a `u8`
+pub fn process_close_stale_wallet_limits(
+    accounts: &[AccountInfo],
+    program_id: &Pubkey,
+) -> ProgramResult {
+    msg!("Process close stale wallet limits");
+    let account_info_iter = &mut accounts.iter();
+    let admin_info = next_account_info(account_info_iter)?;
+    let config_info = next_account_info(account_info_iter)?;
+    assert_config_admin(config_info, admin_info, program_id)?;
+    let now = Clock::get()?.unix_timestamp as u64;
+
+    let mut closed: u8 = 0;
+    for limit_info in account_info_iter {
+        // rent payers are passed after the wallet limits, found by key
+        if limit_info.owner != program_id {
+            continue;
+        }
+        let limit = load_wallet_limit(limit_info, program_id)?;
+        if !limit.stale(now) {
+            return Err(CashError::WalletLimitNotStale.into());
+        }
+        let rent_payer_info =
+            find_optional_account(accounts, &limit.rent_payer).ok_or(CashError::InvalidFeePayer)?;
+        msg!("Refund rent of {} to {}", limit_info.key, rent_payer_info.key);
+        empty_account_balance(limit_info, rent_payer_info)?;
+        limit_info.try_borrow_mut_data()?.fill(0);
+        closed = closed.saturating_add(1);
+    }
+    set_return_data(&[closed]);
+    Ok(())
+}
+
+/// Load an initialized wallet limit owned by the program
+pub fn load_wallet_limit(
+    limit_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<WalletLimit, ProgramError> {
+    assert_owned_by(limit_info, program_id, Some("wallet limit"))?;
+    let limit = WalletLimit::unpack_unchecked(&limit_info.data.borrow())?;
+    if !limit.is_initialized() {
+        return Err(CashError::InvalidWalletLimitKey.into());
+    }
+    Ok(limit)
+}
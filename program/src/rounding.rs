@@ -0,0 +1,201 @@
+//! Rounding policy shared by every fee and split computation
+//!
+//! All divisions round down, the caller decides where the dust goes:
+//!
+//! - platform fee at init: `floor(amount * fee_bps / 10000)`, the dust is never deposited
+//!   and stays with the owner
+//! - platform fee per redemption: the total platform fee floored over the redemptions,
+//!   the dust stays in the vault and is swept to the owner when the link completes
+//! - referral split: the referrer and referee shares are floored, the dust goes to the platform
+//! - savings split: the saved share of a payout is floored, the dust goes to the recipient
+//! - fixed and equal payouts: the amount floored over the redemptions, the dust is swept
+//!   to the owner when the link completes
+//! - weighted payouts: every share is floored, the final redemption takes the accumulated dust
+//! - random payouts: the average is floored, the last redemption takes whatever remains
+//! - swap quotes: the constant product output and its slippage bound are floored, the
+//!   minimum out never exceeds what the pool pays
+use core::convert::TryFrom;
+
+use crate::core_math::CoreError;
+
+/// `floor(amount * numerator / denominator)` computed without intermediate overflow
+pub fn floor_share(amount: u64, numerator: u64, denominator: u64) -> Result<u64, CoreError> {
+    if denominator == 0 {
+        return Err(CoreError::Overflow);
+    }
+    let share = (amount as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(CoreError::Overflow)?
+        / denominator as u128;
+    u64::try_from(share).map_err(|_| CoreError::Overflow)
+}
+
+/// Output of a constant product pool for `amount_in`, `floor(reserve_out * in / (reserve_in + in))`
+pub fn constant_product_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+) -> Result<u64, CoreError> {
+    let denominator = reserve_in.checked_add(amount_in).ok_or(CoreError::Overflow)?;
+    floor_share(reserve_out, amount_in, denominator)
+}
+
+/// `floor(amount / divisor)`
+pub fn floor_div(amount: u64, divisor: u64) -> Result<u64, CoreError> {
+    amount.checked_div(divisor).ok_or(CoreError::Overflow)
+}
+
+/// Split `total` by `parts` out of `denominator`, every part is floored and the remainder
+/// goes to the part at `remainder_index` so the returned parts always sum to `total`
+pub fn distribute_with_remainder<const N: usize>(
+    total: u64,
+    parts: &[u64; N],
+    denominator: u64,
+    remainder_index: usize,
+) -> Result<[u64; N], CoreError> {
+    if remainder_index >= N {
+        return Err(CoreError::Overflow);
+    }
+    let mut shares = [0u64; N];
+    for (share, part) in shares.iter_mut().zip(parts.iter()) {
+        *share = floor_share(total, *part, denominator)?;
+    }
+    let distributed = shares
+        .iter()
+        .try_fold(0u64, |sum, share| sum.checked_add(*share))
+        .ok_or(CoreError::Overflow)?;
+    let remainder = total.checked_sub(distributed).ok_or(CoreError::Underflow)?;
+    shares[remainder_index] = shares[remainder_index]
+        .checked_add(remainder)
+        .ok_or(CoreError::Overflow)?;
+    Ok(shares)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic xorshift draws, so a failing case reproduces
+    struct Draws(u64);
+
+    impl Draws {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        /// A draw biased toward the edges of the u64 range
+        fn amount(&mut self) -> u64 {
+            match self.next() % 4 {
+                0 => u64::MAX - self.next() % 1_000,
+                1 => self.next() % 1_000,
+                _ => self.next(),
+            }
+        }
+    }
+
+    const EXTREMES: [u64; 6] = [0, 1, 2, u64::MAX / 2, u64::MAX - 1, u64::MAX];
+
+    #[test]
+    fn a_share_never_overshoots() {
+        let mut draws = Draws(0x5eed);
+        for _ in 0..10_000 {
+            let amount = draws.amount();
+            let denominator = draws.next() % 1_000_000 + 1;
+            let numerator = draws.next() % (denominator + 1);
+            let share = floor_share(amount, numerator, denominator).unwrap();
+            let exact = amount as u128 * numerator as u128;
+            assert!(share as u128 * denominator as u128 <= exact);
+            assert!((share as u128 + 1) * denominator as u128 > exact);
+            assert!(share <= amount);
+        }
+    }
+
+    #[test]
+    fn shares_sum_to_the_total() {
+        let mut draws = Draws(0xd15);
+        for _ in 0..10_000 {
+            let total = draws.amount();
+            let first = draws.next() % 10_001;
+            let second = draws.next() % (10_001 - first);
+            let parts = [10_000 - first - second, first, second];
+            let remainder_index = (draws.next() % 3) as usize;
+            let shares = distribute_with_remainder(total, &parts, 10_000, remainder_index).unwrap();
+            let sum = shares.iter().map(|share| *share as u128).sum::<u128>();
+            assert_eq!(sum, total as u128);
+            // only the remainder part is above its floored share, by less than one per part
+            for (i, (share, part)) in shares.iter().zip(parts.iter()).enumerate() {
+                let floored = floor_share(total, *part, 10_000).unwrap();
+                if i == remainder_index {
+                    assert!(*share - floored < parts.len() as u64);
+                } else {
+                    assert_eq!(*share, floored);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn parts_below_the_denominator_leave_the_rest_to_the_remainder() {
+        let mut draws = Draws(0xbeef);
+        for _ in 0..10_000 {
+            let total = draws.amount();
+            let part = draws.next() % 10_001;
+            let shares = distribute_with_remainder(total, &[0, part], 10_000, 0).unwrap();
+            assert_eq!(shares[1], floor_share(total, part, 10_000).unwrap());
+            assert_eq!(shares[0], total - shares[1]);
+        }
+    }
+
+    #[test]
+    fn parts_above_the_denominator_are_refused() {
+        assert_eq!(
+            distribute_with_remainder(100, &[6_000, 5_000], 10_000, 0),
+            Err(CoreError::Underflow)
+        );
+        assert_eq!(
+            distribute_with_remainder(100, &[10_000], 10_000, 1),
+            Err(CoreError::Overflow)
+        );
+    }
+
+    #[test]
+    fn is_stable_at_the_extremes() {
+        for amount in EXTREMES.iter() {
+            assert_eq!(floor_share(*amount, 10_000, 10_000), Ok(*amount));
+            assert_eq!(floor_share(*amount, 0, 10_000), Ok(0));
+            assert_eq!(floor_share(*amount, 1, 0), Err(CoreError::Overflow));
+            assert_eq!(floor_div(*amount, 1), Ok(*amount));
+            assert_eq!(floor_div(*amount, 0), Err(CoreError::Overflow));
+            let shares = distribute_with_remainder(*amount, &[5_000, 5_000], 10_000, 1).unwrap();
+            assert_eq!(shares[0], amount / 2);
+            assert_eq!(shares[1], amount - amount / 2);
+        }
+        assert_eq!(floor_share(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+        assert_eq!(floor_share(u64::MAX, u64::MAX, u64::MAX - 1), Err(CoreError::Overflow));
+        assert_eq!(floor_share(u64::MAX, u64::MAX - 1, u64::MAX), Ok(u64::MAX - 1));
+    }
+
+    #[test]
+    fn a_pool_never_pays_out_its_reserve() {
+        let mut draws = Draws(0xa11);
+        for _ in 0..10_000 {
+            let amount_in = draws.amount();
+            let reserve_in = draws.amount();
+            let reserve_out = draws.amount();
+            match constant_product_out(amount_in, reserve_in, reserve_out) {
+                Ok(out) => {
+                    assert!(out <= reserve_out);
+                    // only an empty input reserve hands over the whole output reserve
+                    assert!(out < reserve_out || reserve_in == 0 || reserve_out == 0);
+                }
+                Err(error) => {
+                    assert_eq!(error, CoreError::Overflow);
+                    assert!(!matches!(reserve_in.checked_add(amount_in), Some(d) if d > 0));
+                }
+            }
+        }
+    }
+}
@@ -1,9 +1,29 @@
 use borsh::{BorshDeserialize, BorshSerialize, BorshSchema};
+use solana_program::pubkey::Pubkey;
 
+pub mod auto_cancel;
+pub mod buffer;
 pub mod cash;
+pub mod commit;
+pub mod config;
+pub mod fee_wallet;
+pub mod metadata;
+pub mod mint_registry;
+pub mod pending;
+pub mod redemption;
+pub mod referral;
+pub mod stats;
+pub mod wallet_limit;
 
-pub const FLAG_ACCOUNT_SIZE: usize = 1;
+pub const AUXILIARY_HEADER_SIZE: usize = 32;
+/// The existence of a redemption receipt marks the wallet as redeemed, see `RedemptionReceipt`
+pub const REDEMPTION_RECEIPT_SIZE: usize = AUXILIARY_HEADER_SIZE + 32 + 1 + 32 + 32 + 8 * 3;
+/// Size of the receipts of the first release, a single zero byte flagging the wallet as
+/// redeemed. They unpack with every field unset
+pub const LEGACY_REDEMPTION_RECEIPT_SIZE: usize = 1;
 pub const REDEMPTION_PREFIX: &'static str = "redemption";
+/// Seed prefix of the attestation of a wallet, derived under the kyc authority of a link
+pub const KYC_ATTESTATION_PREFIX: &str = "attestation";
 
 #[derive(Clone, Debug, PartialEq, Eq, BorshDeserialize, BorshSerialize, BorshSchema)]
 pub enum AccountType {
@@ -11,10 +31,50 @@ pub enum AccountType {
     Uninitialized,
     /// A cashlink account type
     Cash,
+    /// The program config account type
+    Config,
+    /// The mint registry account type
+    MintRegistry,
+    /// The program statistics account type
+    Stats,
+    /// A chunked write buffer account type
+    Buffer,
+    /// The display metadata of a cash link
+    Metadata,
+    /// The lifetime referral earnings of a referrer
+    ReferralStats,
+    /// A wallet's pending commitment to redeem a commit-reveal link
+    RedemptionCommit,
+    /// A redemption waiting for approval
+    PendingRedemption,
+    /// The platform fee wallets of each epoch
+    FeeWalletRegistry,
+    /// The links waiting for the auto-cancel crank
+    AutoCancelQueue,
+    /// The redemptions of a wallet in a capped mint during the current window
+    WalletLimit,
 }
 
 impl Default for AccountType {
     fn default() -> Self {
         AccountType::Uninitialized
     }
-}
\ No newline at end of file
+}
+
+/// Leading 32 bytes of the auxiliary pdas of a cash link: receipts, redemption indices,
+/// metadata and buffers. Each is funded by whoever paid the fees of the transaction creating
+/// it, the header records that wallet so closing the account refunds it
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct AuxiliaryHeader {
+    pub rent_payer: Pubkey,
+}
+
+impl AuxiliaryHeader {
+    pub fn new(rent_payer: &Pubkey) -> Self {
+        Self {
+            rent_payer: *rent_payer,
+        }
+    }
+}
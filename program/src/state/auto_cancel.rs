@@ -0,0 +1,177 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::error::CashError;
+
+use super::AccountType;
+
+pub const MAX_AUTO_CANCEL_ENTRIES: usize = 64;
+// account_type + head + len + vec length prefix + 64 entries of cash and due_at
+pub const AUTO_CANCEL_QUEUE_DATA_SIZE: usize = 1 + 2 + 2 + 4 + (32 + 8) * MAX_AUTO_CANCEL_ENTRIES;
+
+/// A link its owner asked to be canceled once `due_at` passed
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct AutoCancelEntry {
+    pub cash: Pubkey,
+    /// Unix timestamp from which anyone may cancel the link
+    pub due_at: u64,
+}
+
+/// The links waiting to be canceled by the crank, a ring buffer of `len` entries starting at
+/// `head`. Entries are appended at init and removed once their link is canceled or terminal.
+/// The slots live on the heap, `entries` always holds `MAX_AUTO_CANCEL_ENTRIES` of them
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct AutoCancelQueue {
+    pub account_type: AccountType,
+    pub head: u16,
+    pub len: u16,
+    pub entries: Vec<AutoCancelEntry>,
+}
+
+impl AutoCancelQueue {
+    pub const PREFIX: &'static str = "auto_cancel";
+
+    pub fn new() -> Self {
+        Self {
+            account_type: AccountType::AutoCancelQueue,
+            entries: vec![AutoCancelEntry::default(); MAX_AUTO_CANCEL_ENTRIES],
+            ..Default::default()
+        }
+    }
+
+    /// Slot of the entry at `position` counted from the head
+    fn slot(&self, position: usize) -> usize {
+        (self.head as usize + position) % MAX_AUTO_CANCEL_ENTRIES
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &AutoCancelEntry> {
+        (0..self.len as usize).map(move |position| &self.entries[self.slot(position)])
+    }
+
+    /// The entry at `position` counted from the head
+    pub fn get(&self, position: usize) -> Option<&AutoCancelEntry> {
+        if position >= self.len as usize {
+            return None;
+        }
+        self.entries.get(self.slot(position))
+    }
+
+    /// Position from the head of the entry of a link
+    pub fn position(&self, cash: &Pubkey) -> Option<usize> {
+        self.iter().position(|entry| entry.cash == *cash)
+    }
+
+    pub fn push(&mut self, cash: Pubkey, due_at: u64) -> Result<(), CashError> {
+        if self.len as usize >= MAX_AUTO_CANCEL_ENTRIES {
+            return Err(CashError::AutoCancelQueueFull);
+        }
+        let slot = self.slot(self.len as usize);
+        self.entries[slot] = AutoCancelEntry { cash, due_at };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Remove the entry at `position`, the head advances when it is the oldest entry and the
+    /// younger entries shift down otherwise
+    pub fn remove(&mut self, position: usize) -> Option<AutoCancelEntry> {
+        let len = self.len as usize;
+        if position >= len {
+            return None;
+        }
+        let entry = self.entries[self.slot(position)];
+        if position == 0 {
+            let slot = self.slot(0);
+            self.entries[slot] = AutoCancelEntry::default();
+            self.head = ((self.head as usize + 1) % MAX_AUTO_CANCEL_ENTRIES) as u16;
+        } else {
+            for from in position + 1..len {
+                let (to, from) = (self.slot(from - 1), self.slot(from));
+                self.entries[to] = self.entries[from];
+            }
+            let last = self.slot(len - 1);
+            self.entries[last] = AutoCancelEntry::default();
+        }
+        self.len -= 1;
+        Some(entry)
+    }
+}
+
+impl IsInitialized for AutoCancelQueue {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::AutoCancelQueue
+    }
+}
+
+impl Sealed for AutoCancelQueue {}
+
+impl Pack for AutoCancelQueue {
+    const LEN: usize = AUTO_CANCEL_QUEUE_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+        if result.is_initialized() && result.entries.len() != MAX_AUTO_CANCEL_ENTRIES {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    #[test]
+    fn round_trips_an_empty_and_a_full_queue() {
+        round_trip(AutoCancelQueue::new());
+        let mut queue = AutoCancelQueue::new();
+        for due_at in 0..MAX_AUTO_CANCEL_ENTRIES as u64 {
+            queue.push(Pubkey::new_unique(), due_at).unwrap();
+        }
+        queue.remove(0);
+        queue.push(Pubkey::new_unique(), u64::MAX).unwrap();
+        round_trip(queue);
+    }
+
+    #[test]
+    fn refuses_a_queue_missing_slots() {
+        let mut queue = AutoCancelQueue::new();
+        queue.entries.pop();
+        let data = packed(queue);
+        assert_eq!(AutoCancelQueue::unpack(&data), Err(ProgramError::InvalidAccountData));
+        // an account yet to be initialized holds no slots
+        let blank = AutoCancelQueue::unpack_unchecked(&[0; AUTO_CANCEL_QUEUE_DATA_SIZE]);
+        assert_eq!(blank, Ok(AutoCancelQueue::default()));
+    }
+
+    #[test]
+    fn refuses_a_truncated_queue() {
+        let data = packed(AutoCancelQueue::new());
+        assert_eq!(
+            AutoCancelQueue::unpack(&data[..AutoCancelQueue::LEN - 40]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
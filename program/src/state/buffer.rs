@@ -0,0 +1,118 @@
+use std::convert::TryInto;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use super::{AccountType, AuxiliaryHeader, AUXILIARY_HEADER_SIZE};
+
+// auxiliary header + account_type + authority + cash + finalized + hash + data length
+pub const BUFFER_HEADER_SIZE: usize = AUXILIARY_HEADER_SIZE + 1 + 32 + 32 + 1 + 32 + 4;
+// a pda created through a cpi can not be larger than 10KB
+pub const MAX_BUFFER_DATA_SIZE: usize = 10240 - BUFFER_HEADER_SIZE;
+
+/// Header of a buffer account, the raw content follows it
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct Buffer {
+    pub header: AuxiliaryHeader,
+    pub account_type: AccountType,
+    pub authority: Pubkey,
+    /// The cash link the buffer was finalized against
+    pub cash: Pubkey,
+    pub finalized: bool,
+    pub hash: [u8; 32],
+    pub data_len: u32,
+}
+
+impl Buffer {
+    pub const PREFIX: &'static str = "buffer";
+
+    /// The content of a buffer account
+    pub fn data(account_data: &[u8]) -> &[u8] {
+        &account_data[BUFFER_HEADER_SIZE..]
+    }
+
+    /// Read the little endian u32 at `index` of the content
+    pub fn read_u32(account_data: &[u8], index: usize) -> Option<u32> {
+        let offset = index.checked_mul(4)?;
+        let bytes = Self::data(account_data).get(offset..offset.checked_add(4)?)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+}
+
+impl IsInitialized for Buffer {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::Buffer
+    }
+}
+
+impl Sealed for Buffer {}
+
+impl Pack for Buffer {
+    const LEN: usize = BUFFER_HEADER_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn buffer() -> Buffer {
+        Buffer {
+            header: AuxiliaryHeader::new(&Pubkey::new_unique()),
+            account_type: AccountType::Buffer,
+            authority: Pubkey::new_unique(),
+            cash: Pubkey::new_unique(),
+            finalized: true,
+            hash: [9; 32],
+            data_len: MAX_BUFFER_DATA_SIZE as u32,
+        }
+    }
+
+    #[test]
+    fn round_trips_a_buffer_header() {
+        round_trip(buffer());
+    }
+
+    #[test]
+    fn reads_the_chunks_after_the_header() {
+        let buffer = buffer();
+        let mut data = packed(buffer.clone());
+        data.extend_from_slice(&[1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(Buffer::read_u32(&data, 1), Some(2));
+        assert_eq!(Buffer::read_u32(&data, 2), None);
+        // the header is unpacked from its own slice of the account
+        assert_eq!(Buffer::unpack(&data), Err(ProgramError::InvalidAccountData));
+        assert_eq!(Buffer::unpack(&data[..Buffer::LEN]), Ok(buffer));
+    }
+
+    #[test]
+    fn refuses_metadata_behind_the_same_header() {
+        let mut data = packed(buffer());
+        data[AUXILIARY_HEADER_SIZE] = AccountType::Metadata as u8;
+        assert_eq!(Buffer::unpack(&data), Err(ProgramError::UninitializedAccount));
+    }
+}
@@ -1,20 +1,94 @@
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{object_length, BorshDeserialize, BorshSerialize};
 use solana_program::{
     borsh1::try_from_slice_unchecked,
-    msg,
+    keccak, msg,
     program_error::ProgramError,
     program_pack::{IsInitialized, Pack, Sealed},
     pubkey::Pubkey,
 };
 
-use crate::error::CashError;
+use crate::{
+    core_math,
+    error::CashError,
+    fees::{compute_per_action_fee, FeeSchedule},
+    instruction::InitCashArgs,
+    PROGRAM_VERSION,
+};
 
 use super::AccountType;
 
-pub const CASH_DATA_SIZE: usize = 194;
+pub const CASH_DATA_SIZE: usize = 824;
+/// Longest clawback window a link may set, the payout stays delegated to the link until then
+pub const MAX_CLAWBACK_WINDOW: u64 = 24 * 60 * 60;
+/// Size of the links deployed by the first release. Links as small as this unpack with the
+/// fields appended since then unset, `MigrateBatch` grows them before they can be written
+pub const LEGACY_CASH_DATA_SIZE: usize = 194;
+/// Borsh size of a Cash with every option set, bump it with each field appended to the
+/// layout. Options take a tag byte on top of their value
+pub const CASH_MAX_SERIALIZED_SIZE: usize = 1 // account_type
+    + 32 // authority
+    + 1 // state
+    + 8 * 5 // amount, network_fee, base_fee_to_redeem, rent_fee_to_redeem, remaining_amount
+    + 2 // fee_bps
+    + 1 // distribution_type
+    + 32 * 2 // owner, mint
+    + 2 * 2 // total_redemptions, max_num_redemptions
+    + 8 // min_amount
+    + 1 + 32 // pass_key
+    + 4 // total_weight_ppm
+    + 32 // fee_payer
+    + 8 // rent_paid_by_fee_payer
+    + 1 + 8 // kyc_required_above
+    + 1 + 32 // kyc_authority
+    + 1 // completion_reason
+    + 1 // referral_ceilings_enabled
+    + 2 * 2 // max_referrer_fee_bps, max_referee_fee_bps
+    + 1 + 32 // buffer_hash
+    + 1 + 32 // fee_wallet
+    + 1 // in_progress
+    + 32 * 2 // vault, token_program
+    + 2 // burn_bps
+    + 8 // total_burned
+    + 1 // claim_requires_wallet_signature
+    + 8 // created_at
+    + 1 + 8 // canceled_at
+    + 1 + 8 // completed_at
+    + 1 + 32 * 2 + 2 // swap
+    + 1 + 32 // entropy_source
+    + 8 // last_entropy_round
+    + 1 // halted
+    + 1 + 8 * 2 // flash
+    + 2 // expired_slots
+    + 8 // forfeited_amount
+    + 1 // pass_rotations
+    + 1 + 32 // intended_recipient
+    + 1 + 8 // recovery_requested_at
+    + 8 // max_priority_fee_per_redeem
+    + 1 // allow_freezable_mint
+    + 1 + 8 // commit_reveal_delay
+    + 1 + 1 // decimals
+    + 2 * 2 // created_by_version, last_touched_version
+    + 1 + 32 // refund_destination
+    + (1 + 8) * 2 // approval_threshold, approval_ttl
+    + 8 // reserved_amount
+    + 2 // pending_approvals
+    + 1 + 2 // fee_wallet_epoch
+    + 1 // require_voucher
+    + 1 + 32 // mint_owner
+    + 1 + 8 // mint_supply_at_init
+    + 1 + 8 // auto_cancel_at
+    + 8 // auto_cancel_tip
+    + 1 + 32 // params_hash
+    + 1 + 8 // max_signature_age_slots
+    + 1 + 8; // clawback_window_seconds
+
+// a field pushing the maximal layout past the account size fails the build instead of
+// failing every pack of a link that sets it
+const _: () = assert!(CASH_MAX_SERIALIZED_SIZE <= CASH_DATA_SIZE);
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
 #[borsh(use_discriminant=true)]
 pub enum CashState {
     #[default]
@@ -22,25 +96,90 @@ pub enum CashState {
     Redeemed,
     Redeeming,
     Canceled,
+    /// The vault of a large link is being refunded in chunks, it can no longer be redeemed
+    CancelPending,
 }
 
+/// How the pot of a link is split between its redemptions, the values are part of the
+/// account and instruction encoding and never change
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
 #[borsh(use_discriminant=true)]
 pub enum DistributionType {
     #[default]
     Fixed = 0,
-    Random,
-    Weighted,
-    Equal,
+    Random = 1,
+    Weighted = 2,
+    Equal = 3,
+}
+
+/// Pays the redemptions of a link out in another mint through an allow listed amm
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct SwapConfig {
+    pub target_mint: Pubkey,
+    pub amm_program: Pubkey,
+    /// Largest shortfall accepted against the pool quote, in bps
+    pub max_slippage_bps: u16,
+}
+
+/// Flash drop schedule, redemption slot `i` opens at `start_at + i * interval` and expires
+/// unclaimed when the next slot opens
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct FlashSchedule {
+    /// Unix timestamp slot 0 opens at
+    pub start_at: u64,
+    /// Seconds between two slots
+    pub interval: u64,
+}
+
+/// Where the random draws of a link come from
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub enum EntropySource {
+    /// The most recent slot hash
+    #[default]
+    SlotHashes,
+    /// The randomness of an external vrf account, see `processor::entropy` for its layout
+    VrfAccount(Pubkey),
+}
+
+/// Why a cash link reached a terminal state
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+#[borsh(use_discriminant=true)]
+pub enum CompletionReason {
+    /// The link is still live
+    #[default]
+    None = 0,
+    /// Every redemption slot was used
+    SlotsExhausted,
+    /// The whole amount was paid out
+    FundsExhausted,
+    /// What remains cannot pay the min amount to every remaining slot
+    BelowMinThreshold,
+    Canceled,
+    Expired,
+    /// The vault was emergency withdrawn to the recovery wallet
+    Emergency,
+    /// Refunded to the owner by a config admin after the recovery timelock
+    Recovered,
 }
 
 #[repr(C)]
 #[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
 pub struct Cash {
     pub account_type: AccountType,
     pub authority: Pubkey,
     pub state: CashState,
+    /// The total pot of the link, never a per claim amount
     pub amount: u64,
     pub fee_bps: u16,
     pub network_fee: u64,
@@ -55,10 +194,198 @@ pub struct Cash {
     pub min_amount: u64,
     pub pass_key: Option<Pubkey>,//187
     pub total_weight_ppm: u32,
+    pub fee_payer: Pubkey,
+    pub rent_paid_by_fee_payer: u64,
+    pub kyc_required_above: Option<u64>,
+    pub kyc_authority: Option<Pubkey>,
+    pub completion_reason: CompletionReason,
+    /// When set the referral bps supplied at redeem time are clamped to the ceilings below
+    pub referral_ceilings_enabled: bool,
+    pub max_referrer_fee_bps: u16,
+    pub max_referee_fee_bps: u16,
+    /// Hash of the finalized buffer holding the weights schedule of the link
+    pub buffer_hash: Option<[u8; 32]>,
+    /// The platform fee wallet of a permissionless link, taken from the config at init
+    pub fee_wallet: Option<Pubkey>,
+    /// Set while a redemption performs its cpis, a nested call into the program must not
+    /// act on the link until the redemption committed its state
+    pub in_progress: bool,
+    /// The vault token account and its token program, fixed at init. Default for links
+    /// created before they were stored
+    pub vault: Pubkey,
+    pub token_program: Pubkey,
+    /// Share of every payout burned instead of paid to the recipient
+    pub burn_bps: u16,
+    pub total_burned: u64,
+    /// The redeeming wallet must sign, making the claim non custodial
+    pub claim_requires_wallet_signature: bool,
+    /// Unix timestamps of the init and of the terminal state of the link
+    pub created_at: u64,
+    pub canceled_at: Option<u64>,
+    pub completed_at: Option<u64>,
+    /// Swap every payout to another mint, fees are still paid in the link mint
+    pub swap: Option<SwapConfig>,
+    pub entropy_source: EntropySource,
+    /// The vrf round consumed by the last draw, a draw needs a newer round
+    pub last_entropy_round: u64,
+    /// Frozen by the authority and a config admin during an incident, nothing moves the funds
+    /// of a halted link until it is released or emergency withdrawn
+    pub halted: bool,
+    /// One redemption slot per interval, only for Fixed and Equal links
+    pub flash: Option<FlashSchedule>,
+    /// Flash slots that expired unclaimed, they count against `max_num_redemptions`
+    pub expired_slots: u16,
+    /// The amounts of the expired slots, returned to the owner with the rest of the vault
+    pub forfeited_amount: u64,
+    /// Times the pass key of the link was replaced
+    pub pass_rotations: u8,
+    /// The only wallet allowed to redeem the link
+    pub intended_recipient: Option<Pubkey>,
+    /// Set by a config admin when the owner and authority keys are lost, any later activity of
+    /// the link clears it
+    pub recovery_requested_at: Option<u64>,
+    /// Cap on the priority fees reimbursed to the fee payer by a single redemption
+    pub max_priority_fee_per_redeem: u64,
+    /// The owner accepted a mint whose freeze authority can freeze the vault
+    pub allow_freezable_mint: bool,
+    /// Slots between a wallet's commit and its redemption, set on commit-reveal links only
+    pub commit_reveal_delay: Option<u64>,
+    /// Decimals of the mint read at init, so the mint is not unpacked on every redemption.
+    /// Links created before they were stored read them from the mint
+    pub decimals: Option<u8>,
+    /// `PROGRAM_VERSION` of the program that created the link and of the last one that stored
+    /// it, 0 on links created before they were stored until an admin backfills them
+    pub created_by_version: u16,
+    pub last_touched_version: u16,
+    /// Receives the lamport refunds of the link when the owner can not spend them
+    pub refund_destination: Option<Pubkey>,
+    /// Payouts at or above this amount wait for the approval of the authority and the owner
+    pub approval_threshold: Option<u64>,
+    /// Seconds a redemption may wait for approval before it can only be rejected
+    pub approval_ttl: Option<u64>,
+    /// Part of `remaining_amount` locked by redemptions waiting for approval
+    pub reserved_amount: u64,
+    /// Redemptions waiting for approval, each holds a redemption slot
+    pub pending_approvals: u16,
+    /// Epoch of the fee wallet registry entry collecting the platform fees, replaces
+    /// `fee_wallet` so a rotated treasury wallet reaches live links
+    pub fee_wallet_epoch: Option<u16>,
+    /// Every redemption carries the hash of an off chain voucher, kept in its receipt
+    pub require_voucher: bool,
+    /// Program owning the mint at init, a mint closed and recreated under another program is
+    /// refused. Links created before it was stored compare against `token_program`
+    pub mint_owner: Option<Pubkey>,
+    /// Supply of the mint at init, kept for audits of reissued mints
+    pub mint_supply_at_init: Option<u64>,
+    /// Unix timestamp from which the auto-cancel crank may cancel the link, set on links
+    /// queued in the `AutoCancelQueue` at init
+    pub auto_cancel_at: Option<u64>,
+    /// Paid from the fee reserve in the vault to whoever cranks the auto-cancel of the link
+    pub auto_cancel_tip: u64,
+    /// Keccak of `CashParams` of the link, kept in step with every change of its economic
+    /// parameters. Unset on links created before it was stored
+    pub params_hash: Option<[u8; 32]>,
+    /// Slots an authority signature stays valid, a redemption then carries the slot it was
+    /// signed at. `None` lets signed redemptions land at any time
+    pub max_signature_age_slots: Option<u64>,
+    /// Seconds after a redemption during which the authority and the config admin may claw the
+    /// payout back, see `CashInstruction::ClawbackRedemption`
+    pub clawback_window_seconds: Option<u64>,
+}
+
+/// The economic parameters of a link, their borsh encoding is the canonical serialization
+/// `params_hash` is computed over. Fields are only ever appended
+#[derive(BorshSerialize, Debug, PartialEq, Clone)]
+pub struct CashParams {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub distribution_type: DistributionType,
+    pub max_num_redemptions: u16,
+    pub min_amount: u64,
+    pub fee_bps: u16,
+    pub network_fee: u64,
+    pub base_fee_to_redeem: u64,
+    pub rent_fee_to_redeem: u64,
+    pub max_priority_fee_per_redeem: u64,
+    pub burn_bps: u16,
+    pub buffer_hash: Option<[u8; 32]>,
+    pub swap: Option<SwapConfig>,
+    pub flash: Option<FlashSchedule>,
+    pub auto_cancel_at: Option<u64>,
 }
 
 impl Cash {
     pub const PREFIX: &'static str = "cash";
+    pub fn params(&self) -> CashParams {
+        CashParams {
+            mint: self.mint,
+            amount: self.amount,
+            distribution_type: self.distribution_type.clone(),
+            max_num_redemptions: self.max_num_redemptions,
+            min_amount: self.min_amount,
+            fee_bps: self.fee_bps,
+            network_fee: self.network_fee,
+            base_fee_to_redeem: self.base_fee_to_redeem,
+            rent_fee_to_redeem: self.rent_fee_to_redeem,
+            max_priority_fee_per_redeem: self.max_priority_fee_per_redeem,
+            burn_bps: self.burn_bps,
+            buffer_hash: self.buffer_hash,
+            swap: self.swap,
+            flash: self.flash,
+            auto_cancel_at: self.auto_cancel_at,
+        }
+    }
+    /// Keccak of the canonical serialization of the economic parameters, the borsh encoding
+    /// of `params`
+    pub fn compute_params_hash(&self) -> [u8; 32] {
+        let params = borsh::to_vec(&self.params()).unwrap_or_default();
+        keccak::hashv(&[&params]).to_bytes()
+    }
+    /// Store the hash of the current economic parameters, call after any change to them
+    pub fn update_params_hash(&mut self) {
+        self.params_hash = Some(self.compute_params_hash());
+    }
+    /// Fail on a redemption signed longer ago than the link allows, `signed_at_slot` is part
+    /// of the signed args so a relayer can not refresh it
+    pub fn assert_signature_fresh(
+        &self,
+        signed_at_slot: Option<u64>,
+        slot: u64,
+    ) -> Result<(), ProgramError> {
+        let max_age = match self.max_signature_age_slots {
+            Some(max_age) => max_age,
+            None => return Ok(()),
+        };
+        let signed_at_slot = signed_at_slot.ok_or_else(|| {
+            msg!("The link requires the slot the redemption was signed at");
+            CashError::SignatureExpired
+        })?;
+        if signed_at_slot > slot {
+            msg!("Signed at slot {} ahead of the current slot {}", signed_at_slot, slot);
+            return Err(ProgramError::InvalidArgument);
+        }
+        if slot - signed_at_slot > max_age {
+            msg!(
+                "Signed at slot {}, {} slots ago, the link allows {}",
+                signed_at_slot,
+                slot - signed_at_slot,
+                max_age
+            );
+            return Err(CashError::SignatureExpired.into());
+        }
+        Ok(())
+    }
+    /// Whether a payout made at `redeemed_at` may still be clawed back
+    pub fn clawback_open(&self, redeemed_at: u64, now: u64) -> bool {
+        self.clawback_window_seconds
+            .is_some_and(|window| now <= redeemed_at.saturating_add(window))
+    }
+    /// Drop a pending recovery request, the link is evidently not abandoned
+    pub fn clear_recovery(&mut self) {
+        if self.recovery_requested_at.take().is_some() {
+            msg!("Recovery request cleared");
+        }
+    }
     pub fn redeemed(&self) -> bool {
         self.state == CashState::Redeemed
     }
@@ -71,28 +398,228 @@ impl Cash {
     pub fn initialized(&self) -> bool {
         self.state == CashState::Initialized
     }
+    pub fn cancel_pending(&self) -> bool {
+        self.state == CashState::CancelPending
+    }
     pub fn is_fully_redeemed(&self) -> Result<bool, CashError> {
-        Ok(self.total_redemptions == self.max_num_redemptions
-            || self.remaining_amount == 0
-            || self.remaining_amount < self.min_total_required()?)
+        Ok(self.redemption_completion_reason()? != CompletionReason::None)
+    }
+    /// The reason the link is fully redeemed, `CompletionReason::None` while it is not
+    pub fn redemption_completion_reason(&self) -> Result<CompletionReason, CashError> {
+        Ok(if self.pending_approvals > 0 {
+            // the reserved payouts are still owed to their wallets
+            CompletionReason::None
+        } else if self.slots_used()? == self.max_num_redemptions {
+            CompletionReason::SlotsExhausted
+        } else if self.remaining_amount == 0 {
+            CompletionReason::FundsExhausted
+        } else if self.remaining_amount < self.min_total_required()? {
+            CompletionReason::BelowMinThreshold
+        } else {
+            CompletionReason::None
+        })
+    }
+    pub fn fee_schedule(&self) -> FeeSchedule {
+        FeeSchedule {
+            fee_bps: self.fee_bps,
+            network_fee: self.network_fee,
+            base_fee_to_redeem: self.base_fee_to_redeem,
+            rent_fee_to_redeem: self.rent_fee_to_redeem,
+        }
+    }
+    /// The priority fee reimbursed for a redemption, clamped to the cap of the link
+    pub fn priority_fee_to_redeem(&self, requested: Option<u64>) -> u64 {
+        requested.unwrap_or(0).min(self.max_priority_fee_per_redeem)
     }
     pub fn max_fee_to_redeem(&self) -> Result<u64, CashError> {
-        self.base_fee_to_redeem.checked_add(self.rent_fee_to_redeem).ok_or(CashError::Overflow)
+        compute_per_action_fee(&self.fee_schedule())
+    }
+    /// Clamp the referral bps supplied at redeem time to the ceilings agreed at init,
+    /// the returned flag is set when a value was lowered
+    pub fn clamp_referral_fee_bps(
+        &self,
+        referrer_fee_bps: Option<u16>,
+        referee_fee_bps: Option<u16>,
+    ) -> Result<(Option<u16>, Option<u16>, bool), CashError> {
+        if !self.referral_ceilings_enabled {
+            return Ok((referrer_fee_bps, referee_fee_bps, false));
+        }
+        let requested = referrer_fee_bps.unwrap_or(0) > 0 || referee_fee_bps.unwrap_or(0) > 0;
+        if self.max_referrer_fee_bps == 0 && self.max_referee_fee_bps == 0 && requested {
+            return Err(CashError::ReferralNotConfigured);
+        }
+        let clamp = |bps: Option<u16>, max: u16| bps.map(|bps| bps.min(max));
+        let clamped_referrer = clamp(referrer_fee_bps, self.max_referrer_fee_bps);
+        let clamped_referee = clamp(referee_fee_bps, self.max_referee_fee_bps);
+        let clamped = clamped_referrer != referrer_fee_bps || clamped_referee != referee_fee_bps;
+        Ok((clamped_referrer, clamped_referee, clamped))
+    }
+    pub fn assert_not_in_progress(&self) -> Result<(), CashError> {
+        if self.in_progress {
+            return Err(CashError::ReentrancyDetected);
+        }
+        Ok(())
+    }
+    pub fn assert_not_halted(&self) -> Result<(), CashError> {
+        if self.halted {
+            return Err(CashError::CashHalted);
+        }
+        Ok(())
+    }
+    /// A payout of this amount waits for approval before it is paid
+    pub fn approval_required(&self, amount: u64) -> bool {
+        matches!(self.approval_threshold, Some(threshold) if amount >= threshold)
+    }
+    /// Lock the payout of a redemption waiting for approval
+    pub fn reserve(&mut self, amount: u64) -> Result<(), CashError> {
+        self.reserved_amount = self
+            .reserved_amount
+            .checked_add(amount)
+            .ok_or(CashError::Overflow)?;
+        self.pending_approvals = self
+            .pending_approvals
+            .checked_add(1)
+            .ok_or(CashError::Overflow)?;
+        Ok(())
     }
+    /// Unlock the payout of an approved or rejected redemption
+    pub fn release(&mut self, amount: u64) -> Result<(), CashError> {
+        self.reserved_amount = self
+            .reserved_amount
+            .checked_sub(amount)
+            .ok_or(CashError::Underflow)?;
+        self.pending_approvals = self
+            .pending_approvals
+            .checked_sub(1)
+            .ok_or(CashError::Underflow)?;
+        Ok(())
+    }
+    pub fn kyc_required(&self, amount: u64) -> bool {
+        matches!(self.kyc_required_above, Some(threshold) if amount > threshold)
+    }
+    /// The total platform fee floored over the redemptions, the dust is swept to the owner
+    pub fn platform_fee_per_redeem(&self) -> Result<u64, CashError> {
+        Ok(core_math::platform_fee_per_redeem(
+            self.amount,
+            self.fee_bps,
+            self.max_num_redemptions,
+        )?)
+    }
+    /// A link paid out by a single redemption
+    pub fn is_single_claim(&self) -> bool {
+        self.max_num_redemptions == 1
+    }
+    /// The first field an init with these args would have set differently, `None` when the
+    /// link is what the init produces
+    pub fn first_init_mismatch(
+        &self,
+        args: &InitCashArgs,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        pass_key: Option<&Pubkey>,
+    ) -> Option<&'static str> {
+        if self.state != CashState::Initialized {
+            Some("state")
+        } else if self.amount != args.amount {
+            Some("amount")
+        } else if self.fee_bps != args.fee_bps {
+            Some("fee_bps")
+        } else if self.base_fee_to_redeem != args.base_fee_to_redeem {
+            Some("base_fee_to_redeem")
+        } else if self.rent_fee_to_redeem != args.rent_fee_to_redeem {
+            Some("rent_fee_to_redeem")
+        } else if self.network_fee != args.network_fee {
+            Some("network_fee")
+        } else if self.distribution_type != args.distribution_type {
+            Some("distribution_type")
+        } else if self.max_num_redemptions != args.max_num_redemptions {
+            Some("max_num_redemptions")
+        } else if self.owner != *owner {
+            Some("owner")
+        } else if self.mint != *mint {
+            Some("mint")
+        } else if self.pass_key.as_ref() != pass_key {
+            Some("pass_key")
+        } else {
+            None
+        }
+    }
+    /// Slots either redeemed or expired
+    pub fn slots_used(&self) -> Result<u16, CashError> {
+        self.total_redemptions
+            .checked_add(self.expired_slots)
+            .ok_or(CashError::Overflow)
+    }
+    /// Slots neither redeemed nor expired, a link using more slots than it has is corrupted
     pub fn max_num_redemptions_remaining(&self) -> Result<u16, CashError> {
         self.max_num_redemptions
-            .checked_sub(self.total_redemptions)
+            .checked_sub(self.slots_used()?)
+            .ok_or(CashError::InvalidRedemptionCounters)
+    }
+    /// Fail on counters that neither an init nor a redemption produces, e.g. after a faulty
+    /// migration, before any arithmetic on them wraps or divides by zero
+    pub fn assert_redemption_counters(&self) -> Result<(), CashError> {
+        let slots_left = self.max_num_redemptions_remaining()?;
+        if self.max_num_redemptions == 0 || self.pending_approvals > slots_left {
+            return Err(CashError::InvalidRedemptionCounters);
+        }
+        Ok(())
+    }
+    /// An equal share of `amount` for every redemption of the link, rounded down
+    pub fn per_redemption(&self, amount: u64) -> Result<u64, CashError> {
+        amount
+            .checked_div(self.max_num_redemptions as u64)
+            .ok_or(CashError::InvalidRedemptionCounters)
+    }
+    /// What the vault must hold for the remaining amount and the worst case fees of every
+    /// remaining redemption
+    pub fn required_reserve(&self) -> Result<u64, CashError> {
+        let fees_per_redeem = self
+            .platform_fee_per_redeem()?
+            .checked_add(self.max_fee_to_redeem()?)
+            .and_then(|fees| fees.checked_add(self.max_priority_fee_per_redeem))
+            .ok_or(CashError::Overflow)?;
+        fees_per_redeem
+            .checked_mul(self.max_num_redemptions_remaining()? as u64)
+            .and_then(|fees| fees.checked_add(self.remaining_amount))
             .ok_or(CashError::Overflow)
     }
+    /// The flash slot open at `now`, `None` before the first slot opens or without a schedule
+    pub fn open_flash_slot(&self, now: u64) -> Option<u64> {
+        let flash = self.flash?;
+        now.checked_sub(flash.start_at)?.checked_div(flash.interval)
+    }
+    /// A flash link may only be redeemed for the slot open at `now`, once
+    pub fn assert_flash_slot_open(&self, now: u64) -> Result<(), CashError> {
+        if self.flash.is_none() {
+            return Ok(());
+        }
+        let open_slot = self.open_flash_slot(now).ok_or(CashError::SlotNotYetOpen)?;
+        let next_slot = self.slots_used()? as u64;
+        if next_slot > open_slot {
+            return Err(CashError::SlotAlreadyClaimed);
+        }
+        if next_slot < open_slot {
+            // the missed slots have to be expired first
+            return Err(CashError::SlotExpired);
+        }
+        Ok(())
+    }
 
     pub fn min_total_required(&self) -> Result<u64, CashError> {
-        Ok(self.min_amount * self.max_num_redemptions_remaining()? as u64)
+        self.min_amount
+            .checked_mul(self.max_num_redemptions_remaining()? as u64)
+            .ok_or(CashError::Overflow)
     }
 }
 
 impl IsInitialized for Cash {
     fn is_initialized(&self) -> bool {
-        self.initialized() || self.redeeming() || self.redeemed() || self.canceled()
+        self.initialized()
+            || self.redeeming()
+            || self.redeemed()
+            || self.canceled()
+            || self.cancel_pending()
     }
 }
 
@@ -101,19 +628,354 @@ impl Sealed for Cash {}
 impl Pack for Cash {
     const LEN: usize = CASH_DATA_SIZE;
 
+    fn pack(mut src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        // a legacy link keeps its size as long as its layout fits
+        if !(LEGACY_CASH_DATA_SIZE..=Self::LEN).contains(&dst.len()) {
+            msg!("Failed to serialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if object_length(&src)? > dst.len() {
+            msg!("Link of {} bytes must be grown with MigrateBatch first", dst.len());
+            return Err(ProgramError::InvalidAccountData);
+        }
+        src.last_touched_version = PROGRAM_VERSION;
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
+    fn unpack_unchecked(src: &[u8]) -> Result<Self, ProgramError> {
+        // the size is checked by `unpack_from_slice`, which also accepts legacy links
+        Self::unpack_from_slice(src)
+    }
+
     fn pack_into_slice(&self, dst: &mut [u8]) {
         let mut slice = dst;
         self.serialize(&mut slice).unwrap()
     }
 
     fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        if src.len() != Self::LEN {
-            msg!("Failed to deserialize");
-            return Err(ProgramError::InvalidAccountData);
+        let result: Self = match src.len() {
+            Self::LEN => try_from_slice_unchecked(src)?,
+            len if len >= LEGACY_CASH_DATA_SIZE && len < Self::LEN => {
+                let mut padded = vec![0; Self::LEN];
+                padded[..len].copy_from_slice(src);
+                try_from_slice_unchecked(&padded)?
+            }
+            _ => {
+                msg!("Failed to deserialize");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The layout of the links deployed by the first release
+    #[derive(BorshSerialize)]
+    struct CashV0 {
+        account_type: AccountType,
+        authority: Pubkey,
+        state: CashState,
+        amount: u64,
+        fee_bps: u16,
+        network_fee: u64,
+        base_fee_to_redeem: u64,
+        rent_fee_to_redeem: u64,
+        remaining_amount: u64,
+        distribution_type: DistributionType,
+        owner: Pubkey,
+        mint: Pubkey,
+        total_redemptions: u16,
+        max_num_redemptions: u16,
+        min_amount: u64,
+        pass_key: Option<Pubkey>,
+        total_weight_ppm: u32,
+    }
+
+    fn v0_link(pass_key: Option<Pubkey>) -> (CashV0, Vec<u8>) {
+        let v0 = CashV0 {
+            account_type: AccountType::Cash,
+            authority: Pubkey::new_from_array([1; 32]),
+            state: CashState::Initialized,
+            amount: 1_000_000,
+            fee_bps: 150,
+            network_fee: 5_000,
+            base_fee_to_redeem: 10_000,
+            rent_fee_to_redeem: 2_039_280,
+            remaining_amount: 600_000,
+            distribution_type: DistributionType::Random,
+            owner: Pubkey::new_from_array([2; 32]),
+            mint: Pubkey::new_from_array([3; 32]),
+            total_redemptions: 2,
+            max_num_redemptions: 5,
+            min_amount: 1_000,
+            pass_key,
+            total_weight_ppm: 0,
+        };
+        let mut data = borsh::to_vec(&v0).unwrap();
+        data.resize(LEGACY_CASH_DATA_SIZE, 0);
+        (v0, data)
+    }
+
+    /// A link with every option set and a distinct value in each field
+    fn golden_cash() -> Cash {
+        Cash {
+            account_type: AccountType::Cash,
+            authority: Pubkey::new_from_array([1; 32]),
+            state: CashState::Redeeming,
+            amount: 5_000_000,
+            fee_bps: 250,
+            network_fee: 5_000,
+            base_fee_to_redeem: 7_000,
+            rent_fee_to_redeem: 2_039_280,
+            remaining_amount: 4_000_000,
+            distribution_type: DistributionType::Weighted,
+            owner: Pubkey::new_from_array([2; 32]),
+            mint: Pubkey::new_from_array([3; 32]),
+            total_redemptions: 3,
+            max_num_redemptions: 10,
+            min_amount: 1_000,
+            pass_key: Some(Pubkey::new_from_array([4; 32])),
+            total_weight_ppm: 300_000,
+            fee_payer: Pubkey::new_from_array([5; 32]),
+            rent_paid_by_fee_payer: 6_000,
+            kyc_required_above: Some(900_000),
+            kyc_authority: Some(Pubkey::new_from_array([6; 32])),
+            completion_reason: CompletionReason::FundsExhausted,
+            referral_ceilings_enabled: true,
+            max_referrer_fee_bps: 30,
+            max_referee_fee_bps: 20,
+            buffer_hash: Some([7; 32]),
+            fee_wallet: Some(Pubkey::new_from_array([8; 32])),
+            in_progress: true,
+            vault: Pubkey::new_from_array([9; 32]),
+            token_program: Pubkey::new_from_array([10; 32]),
+            burn_bps: 15,
+            total_burned: 11_000,
+            claim_requires_wallet_signature: true,
+            created_at: 1_700_000_000,
+            canceled_at: Some(1_700_000_100),
+            completed_at: Some(1_700_000_200),
+            swap: Some(SwapConfig {
+                target_mint: Pubkey::new_from_array([11; 32]),
+                amm_program: Pubkey::new_from_array([12; 32]),
+                max_slippage_bps: 75,
+            }),
+            entropy_source: EntropySource::VrfAccount(Pubkey::new_from_array([13; 32])),
+            last_entropy_round: 42,
+            halted: true,
+            flash: Some(FlashSchedule {
+                start_at: 1_700_001_000,
+                interval: 600,
+            }),
+            expired_slots: 2,
+            forfeited_amount: 12_000,
+            pass_rotations: 4,
+            intended_recipient: Some(Pubkey::new_from_array([14; 32])),
+            recovery_requested_at: Some(1_700_002_000),
+            max_priority_fee_per_redeem: 13_000,
+            allow_freezable_mint: true,
+            commit_reveal_delay: Some(8),
+            decimals: Some(6),
+            created_by_version: 1,
+            last_touched_version: PROGRAM_VERSION,
+            refund_destination: Some(Pubkey::new_from_array([15; 32])),
+            approval_threshold: Some(14_000),
+            approval_ttl: Some(3_600),
+            reserved_amount: 15_000,
+            pending_approvals: 5,
+            fee_wallet_epoch: Some(9),
+            require_voucher: true,
+            mint_owner: Some(Pubkey::new_from_array([16; 32])),
+            mint_supply_at_init: Some(16_000),
+            auto_cancel_at: Some(1_700_003_000),
+            auto_cancel_tip: 17_000,
+            params_hash: Some([17; 32]),
+            max_signature_age_slots: Some(150),
+            clawback_window_seconds: Some(86_400),
+        }
+    }
+
+    /// The borsh encoding of `golden_cash`, spelled out field by field so a reordered,
+    /// resized or retyped field breaks it
+    fn golden_cash_bytes() -> Vec<u8> {
+        let mut bytes = vec![1]; // account_type
+        bytes.extend([1; 32]); // authority
+        bytes.push(2); // state
+        bytes.extend(5_000_000u64.to_le_bytes()); // amount
+        bytes.extend(250u16.to_le_bytes()); // fee_bps
+        bytes.extend(5_000u64.to_le_bytes()); // network_fee
+        bytes.extend(7_000u64.to_le_bytes()); // base_fee_to_redeem
+        bytes.extend(2_039_280u64.to_le_bytes()); // rent_fee_to_redeem
+        bytes.extend(4_000_000u64.to_le_bytes()); // remaining_amount
+        bytes.push(2); // distribution_type
+        bytes.extend([2; 32]); // owner
+        bytes.extend([3; 32]); // mint
+        bytes.extend(3u16.to_le_bytes()); // total_redemptions
+        bytes.extend(10u16.to_le_bytes()); // max_num_redemptions
+        bytes.extend(1_000u64.to_le_bytes()); // min_amount
+        bytes.push(1); // pass_key
+        bytes.extend([4; 32]);
+        bytes.extend(300_000u32.to_le_bytes()); // total_weight_ppm
+        bytes.extend([5; 32]); // fee_payer
+        bytes.extend(6_000u64.to_le_bytes()); // rent_paid_by_fee_payer
+        bytes.push(1); // kyc_required_above
+        bytes.extend(900_000u64.to_le_bytes());
+        bytes.push(1); // kyc_authority
+        bytes.extend([6; 32]);
+        bytes.push(2); // completion_reason
+        bytes.push(1); // referral_ceilings_enabled
+        bytes.extend(30u16.to_le_bytes()); // max_referrer_fee_bps
+        bytes.extend(20u16.to_le_bytes()); // max_referee_fee_bps
+        bytes.push(1); // buffer_hash
+        bytes.extend([7; 32]);
+        bytes.push(1); // fee_wallet
+        bytes.extend([8; 32]);
+        bytes.push(1); // in_progress
+        bytes.extend([9; 32]); // vault
+        bytes.extend([10; 32]); // token_program
+        bytes.extend(15u16.to_le_bytes()); // burn_bps
+        bytes.extend(11_000u64.to_le_bytes()); // total_burned
+        bytes.push(1); // claim_requires_wallet_signature
+        bytes.extend(1_700_000_000u64.to_le_bytes()); // created_at
+        bytes.push(1); // canceled_at
+        bytes.extend(1_700_000_100u64.to_le_bytes());
+        bytes.push(1); // completed_at
+        bytes.extend(1_700_000_200u64.to_le_bytes());
+        bytes.push(1); // swap
+        bytes.extend([11; 32]);
+        bytes.extend([12; 32]);
+        bytes.extend(75u16.to_le_bytes());
+        bytes.push(1); // entropy_source
+        bytes.extend([13; 32]);
+        bytes.extend(42u64.to_le_bytes()); // last_entropy_round
+        bytes.push(1); // halted
+        bytes.push(1); // flash
+        bytes.extend(1_700_001_000u64.to_le_bytes());
+        bytes.extend(600u64.to_le_bytes());
+        bytes.extend(2u16.to_le_bytes()); // expired_slots
+        bytes.extend(12_000u64.to_le_bytes()); // forfeited_amount
+        bytes.push(4); // pass_rotations
+        bytes.push(1); // intended_recipient
+        bytes.extend([14; 32]);
+        bytes.push(1); // recovery_requested_at
+        bytes.extend(1_700_002_000u64.to_le_bytes());
+        bytes.extend(13_000u64.to_le_bytes()); // max_priority_fee_per_redeem
+        bytes.push(1); // allow_freezable_mint
+        bytes.push(1); // commit_reveal_delay
+        bytes.extend(8u64.to_le_bytes());
+        bytes.extend([1, 6]); // decimals
+        bytes.extend(1u16.to_le_bytes()); // created_by_version
+        bytes.extend(PROGRAM_VERSION.to_le_bytes()); // last_touched_version
+        bytes.push(1); // refund_destination
+        bytes.extend([15; 32]);
+        bytes.push(1); // approval_threshold
+        bytes.extend(14_000u64.to_le_bytes());
+        bytes.push(1); // approval_ttl
+        bytes.extend(3_600u64.to_le_bytes());
+        bytes.extend(15_000u64.to_le_bytes()); // reserved_amount
+        bytes.extend(5u16.to_le_bytes()); // pending_approvals
+        bytes.push(1); // fee_wallet_epoch
+        bytes.extend(9u16.to_le_bytes());
+        bytes.push(1); // require_voucher
+        bytes.push(1); // mint_owner
+        bytes.extend([16; 32]);
+        bytes.push(1); // mint_supply_at_init
+        bytes.extend(16_000u64.to_le_bytes());
+        bytes.push(1); // auto_cancel_at
+        bytes.extend(1_700_003_000u64.to_le_bytes());
+        bytes.extend(17_000u64.to_le_bytes()); // auto_cancel_tip
+        bytes.push(1); // params_hash
+        bytes.extend([17; 32]);
+        bytes.push(1); // max_signature_age_slots
+        bytes.extend(150u64.to_le_bytes());
+        bytes.push(1); // clawback_window_seconds
+        bytes.extend(86_400u64.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn matches_the_golden_layout() {
+        let golden = golden_cash_bytes();
+        assert_eq!(golden.len(), CASH_MAX_SERIALIZED_SIZE);
+        assert_eq!(borsh::to_vec(&golden_cash()).unwrap(), golden);
+
+        let mut data = vec![0xff; Cash::LEN];
+        Cash::pack(golden_cash(), &mut data).unwrap();
+        assert_eq!(&data[..golden.len()], &golden[..]);
+
+        let mut padded = golden.clone();
+        padded.resize(Cash::LEN, 0);
+        assert_eq!(Cash::unpack_unchecked(&padded), Ok(golden_cash()));
+    }
+
+    #[test]
+    fn unpacks_links_of_the_first_release() {
+        for pass_key in [None, Some(Pubkey::new_from_array([4; 32]))] {
+            let (v0, data) = v0_link(pass_key);
+            let cash = Cash::unpack_unchecked(&data).unwrap();
+            assert_eq!(cash.account_type, AccountType::Cash);
+            assert_eq!(cash.authority, v0.authority);
+            assert_eq!(cash.amount, v0.amount);
+            assert_eq!(cash.remaining_amount, v0.remaining_amount);
+            assert_eq!(cash.distribution_type, v0.distribution_type);
+            assert_eq!(cash.owner, v0.owner);
+            assert_eq!(cash.mint, v0.mint);
+            assert_eq!(cash.total_redemptions, v0.total_redemptions);
+            assert_eq!(cash.max_num_redemptions, v0.max_num_redemptions);
+            assert_eq!(cash.pass_key, v0.pass_key);
+            assert_eq!(cash.fee_payer, Pubkey::default());
+            assert_eq!(cash.vault, Pubkey::default());
+            assert_eq!(cash.decimals, None);
+            assert_eq!(cash.clawback_window_seconds, None);
+            assert!(!cash.in_progress);
         }
+    }
 
-        let result: Self = try_from_slice_unchecked(src)?;
+    #[test]
+    fn rejects_sizes_below_the_first_release() {
+        let (_, data) = v0_link(None);
+        assert_eq!(
+            Cash::unpack_unchecked(&data[..LEGACY_CASH_DATA_SIZE - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            Cash::unpack_unchecked(&[0; CASH_DATA_SIZE + 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
 
-        Ok(result)
+    #[test]
+    fn refuses_to_write_a_link_that_was_not_grown() {
+        let (_, mut data) = v0_link(None);
+        let cash = Cash::unpack_unchecked(&data).unwrap();
+        let before = data.clone();
+        assert_eq!(Cash::pack(cash.clone(), &mut data), Err(ProgramError::InvalidAccountData));
+        assert_eq!(data, before);
+
+        // grown to the current layout the link writes and reads back unchanged
+        data.resize(Cash::LEN, 0);
+        Cash::pack(cash.clone(), &mut data).unwrap();
+        let stored = Cash::unpack_unchecked(&data).unwrap();
+        assert_eq!(stored, Cash { last_touched_version: PROGRAM_VERSION, ..cash });
+    }
+
+    #[test]
+    fn keeps_the_clawback_window_open_to_its_last_second() {
+        let cash = Cash {
+            clawback_window_seconds: Some(600),
+            ..Cash::default()
+        };
+        assert!(cash.clawback_open(1_000, 1_000));
+        assert!(cash.clawback_open(1_000, 1_600));
+        assert!(!cash.clawback_open(1_000, 1_601));
+        // a window reaching past the end of time never closes
+        assert!(cash.clawback_open(u64::MAX - 1, u64::MAX));
+        assert!(!Cash::default().clawback_open(1_000, 1_000));
     }
 }
@@ -0,0 +1,114 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    keccak,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use super::AccountType;
+
+// account_type + cash + wallet + rent_payer + commitment + slot
+pub const REDEMPTION_COMMIT_DATA_SIZE: usize = 1 + 32 * 3 + 32 + 8;
+/// Longest delay a commit-reveal link may set, the slot hashes sysvar only keeps 512 slots so
+/// the reveal keeps a window of at least 256 slots
+pub const MAX_COMMIT_REVEAL_DELAY: u64 = 256;
+
+/// A wallet's commitment to redeem a commit-reveal link, the draw of its redemption comes from
+/// a slot hash produced after the commit
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct RedemptionCommit {
+    pub account_type: AccountType,
+    pub cash: Pubkey,
+    pub wallet: Pubkey,
+    /// Funded the account, gets the rent back when the commit is revealed or swept
+    pub rent_payer: Pubkey,
+    /// `keccak(wallet || nonce)`
+    pub commitment: [u8; 32],
+    /// Slot the commit landed in
+    pub slot: u64,
+}
+
+impl RedemptionCommit {
+    pub const PREFIX: &'static str = "commit";
+
+    /// The first slot whose hash may seed the draw, strictly after the commit
+    pub fn target_slot(&self, delay: u64) -> u64 {
+        self.slot.saturating_add(delay.max(1))
+    }
+}
+
+/// The commitment of a wallet to a secret nonce
+pub fn redemption_commitment(wallet: &Pubkey, nonce: &[u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[wallet.as_ref(), nonce]).to_bytes()
+}
+
+impl IsInitialized for RedemptionCommit {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::RedemptionCommit
+    }
+}
+
+impl Sealed for RedemptionCommit {}
+
+impl Pack for RedemptionCommit {
+    const LEN: usize = REDEMPTION_COMMIT_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn commit() -> RedemptionCommit {
+        let wallet = Pubkey::new_unique();
+        RedemptionCommit {
+            account_type: AccountType::RedemptionCommit,
+            cash: Pubkey::new_unique(),
+            wallet,
+            rent_payer: Pubkey::new_unique(),
+            commitment: redemption_commitment(&wallet, &[3; 32]),
+            slot: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn round_trips_a_commit() {
+        round_trip(commit());
+    }
+
+    #[test]
+    fn refuses_a_commit_without_its_slot() {
+        let data = packed(commit());
+        assert_eq!(
+            RedemptionCommit::unpack(&data[..RedemptionCommit::LEN - 8]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn reads_a_closed_commit_as_uninitialized() {
+        let data = [0; REDEMPTION_COMMIT_DATA_SIZE];
+        assert_eq!(RedemptionCommit::unpack(&data), Err(ProgramError::UninitializedAccount));
+    }
+}
@@ -0,0 +1,220 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::fees::FeeSchedule;
+
+use super::AccountType;
+
+pub const CONFIG_DATA_SIZE: usize = 192;
+/// Borsh size of a Config with every option set
+pub const CONFIG_MAX_SERIALIZED_SIZE: usize = 1 // account_type
+    + 32 // admin
+    + 1 // bump
+    + 1 // permissionless_init
+    + 32 // fee_wallet
+    + 2 + 8 * 3 // fees
+    + 1 // max_dust_decimals
+    + 1 + 2 // max_redemptions
+    + 1 + 2 // max_fee_reserve_bps
+    + 1 + 32 // swap_amm_program
+    + 1 + 32 // recovery_wallet
+    + 1 + 8 // recovery_timelock
+    + 8 // disabled_features
+    + 1 + 8; // referral_vesting_delay
+
+const _: () = assert!(CONFIG_MAX_SERIALIZED_SIZE <= CONFIG_DATA_SIZE);
+/// Payouts below `10^(decimals - max_dust_decimals)` raw units are considered dust
+pub const DEFAULT_MAX_DUST_DECIMALS: u8 = 6;
+/// Hard cap on the redemptions of a link, every redemption leaves receipts behind
+pub const MAX_REDEMPTIONS_CAP: u16 = 10_000;
+/// Redemption fee reserves may not exceed this share of the principal by default
+pub const DEFAULT_MAX_FEE_RESERVE_BPS: u16 = 5_000;
+/// Seconds between a recovery request and its execution by default, 90 days
+pub const DEFAULT_RECOVERY_TIMELOCK: u64 = 90 * 24 * 60 * 60;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct Config {
+    pub account_type: AccountType,
+    pub admin: Pubkey,
+    pub bump: u8,
+    /// Allows owners to create links without the platform authority co-signing
+    pub permissionless_init: bool,
+    /// Fee settings forced on permissionless links
+    pub fee_wallet: Pubkey,
+    pub fees: FeeSchedule,
+    pub max_dust_decimals: u8,
+    /// Lower cap on the redemptions of a link, never above `MAX_REDEMPTIONS_CAP`
+    pub max_redemptions: Option<u16>,
+    /// Replaces `DEFAULT_MAX_FEE_RESERVE_BPS`, tighter or looser
+    pub max_fee_reserve_bps: Option<u16>,
+    /// The amm program links may swap their payouts through
+    pub swap_amm_program: Option<Pubkey>,
+    /// Receives the vault of an emergency withdrawn link, to its token account of the link mint
+    pub recovery_wallet: Option<Pubkey>,
+    /// Replaces `DEFAULT_RECOVERY_TIMELOCK`
+    pub recovery_timelock: Option<u64>,
+    /// Bits of the `Feature`s disabled program wide
+    pub disabled_features: u64,
+    /// Seconds after the day of a redemption its referrer fee vests, escrowed until then. Set
+    /// to hold referrer fees back from wallets farming referrals
+    pub referral_vesting_delay: Option<u64>,
+}
+
+/// Code paths a config admin can disable program wide without a redeploy
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Feature {
+    Init,
+    Redeem,
+    Cancel,
+    Close,
+    ReferralSplit,
+    RandomDistribution,
+    NativeMint,
+    Swap,
+    /// Refuses redemptions to a platform fee wallet, disabled only by internal test deployments
+    FeeWalletRecipientGuard,
+}
+
+impl Feature {
+    pub fn bit(self) -> u64 {
+        1 << self as u8
+    }
+}
+
+impl Config {
+    pub const PREFIX: &'static str = "config";
+
+    pub fn recovery_timelock(&self) -> u64 {
+        self.recovery_timelock.unwrap_or(DEFAULT_RECOVERY_TIMELOCK)
+    }
+
+    pub fn feature_enabled(&self, feature: Feature) -> bool {
+        self.disabled_features & feature.bit() == 0
+    }
+}
+
+/// Smallest payout in raw units that is not dust for a mint with the given decimals
+pub fn min_payout_for_mint(decimals: u8, max_dust_decimals: u8) -> u64 {
+    10u64.saturating_pow(decimals.saturating_sub(max_dust_decimals) as u32)
+}
+
+impl IsInitialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::Config
+    }
+}
+
+impl Sealed for Config {}
+
+impl Pack for Config {
+    const LEN: usize = CONFIG_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config with every option set and a distinct value in each field
+    fn golden_config() -> Config {
+        Config {
+            account_type: AccountType::Config,
+            admin: Pubkey::new_from_array([1; 32]),
+            bump: 254,
+            permissionless_init: true,
+            fee_wallet: Pubkey::new_from_array([2; 32]),
+            fees: FeeSchedule {
+                fee_bps: 150,
+                network_fee: 5_000,
+                base_fee_to_redeem: 7_000,
+                rent_fee_to_redeem: 2_039_280,
+            },
+            max_dust_decimals: 4,
+            max_redemptions: Some(500),
+            max_fee_reserve_bps: Some(2_500),
+            swap_amm_program: Some(Pubkey::new_from_array([3; 32])),
+            recovery_wallet: Some(Pubkey::new_from_array([4; 32])),
+            recovery_timelock: Some(86_400),
+            disabled_features: Feature::Swap.bit() | Feature::NativeMint.bit(),
+            referral_vesting_delay: Some(604_800),
+        }
+    }
+
+    /// The borsh encoding of `golden_config`, spelled out field by field so a reordered,
+    /// resized or retyped field breaks it
+    fn golden_config_bytes() -> Vec<u8> {
+        let mut bytes = vec![2]; // account_type
+        bytes.extend([1; 32]); // admin
+        bytes.push(254); // bump
+        bytes.push(1); // permissionless_init
+        bytes.extend([2; 32]); // fee_wallet
+        bytes.extend(150u16.to_le_bytes()); // fees.fee_bps
+        bytes.extend(5_000u64.to_le_bytes()); // fees.network_fee
+        bytes.extend(7_000u64.to_le_bytes()); // fees.base_fee_to_redeem
+        bytes.extend(2_039_280u64.to_le_bytes()); // fees.rent_fee_to_redeem
+        bytes.push(4); // max_dust_decimals
+        bytes.push(1); // max_redemptions
+        bytes.extend(500u16.to_le_bytes());
+        bytes.push(1); // max_fee_reserve_bps
+        bytes.extend(2_500u16.to_le_bytes());
+        bytes.push(1); // swap_amm_program
+        bytes.extend([3; 32]);
+        bytes.push(1); // recovery_wallet
+        bytes.extend([4; 32]);
+        bytes.push(1); // recovery_timelock
+        bytes.extend(86_400u64.to_le_bytes());
+        bytes.extend(0b1100_0000u64.to_le_bytes()); // disabled_features
+        bytes.push(1); // referral_vesting_delay
+        bytes.extend(604_800u64.to_le_bytes());
+        bytes
+    }
+
+    #[test]
+    fn matches_the_golden_layout() {
+        let golden = golden_config_bytes();
+        assert_eq!(golden.len(), CONFIG_MAX_SERIALIZED_SIZE);
+        assert_eq!(borsh::to_vec(&golden_config()).unwrap(), golden);
+
+        let mut data = vec![0; Config::LEN];
+        Config::pack(golden_config(), &mut data).unwrap();
+        assert_eq!(&data[..golden.len()], &golden[..]);
+        assert!(data[golden.len()..].iter().all(|byte| *byte == 0));
+        assert_eq!(Config::unpack(&data), Ok(golden_config()));
+    }
+
+    #[test]
+    fn unset_options_take_their_defaults() {
+        let config = Config {
+            account_type: AccountType::Config,
+            ..Config::default()
+        };
+        assert_eq!(config.recovery_timelock(), DEFAULT_RECOVERY_TIMELOCK);
+        assert!(config.feature_enabled(Feature::Swap));
+        assert!(!golden_config().feature_enabled(Feature::Swap));
+        assert!(golden_config().feature_enabled(Feature::Redeem));
+    }
+}
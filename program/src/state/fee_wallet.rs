@@ -0,0 +1,131 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use super::AccountType;
+
+pub const MAX_FEE_WALLET_EPOCHS: usize = 32;
+// account_type + vec length prefix + 32 entries of epoch, wallet and superseded
+pub const FEE_WALLET_REGISTRY_DATA_SIZE: usize = 1 + 4 + (2 + 32 + 1) * MAX_FEE_WALLET_EPOCHS;
+
+/// A platform fee wallet and the epoch links store to reach it
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct FeeWalletEntry {
+    pub epoch: u16,
+    pub wallet: Pubkey,
+    /// The fees of the links of this epoch go to the latest live wallet instead
+    pub superseded: bool,
+}
+
+/// The platform fee wallets of the deployment, append only so every epoch a link stored
+/// stays auditable after the treasury rotated its wallet
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct FeeWalletRegistry {
+    pub account_type: AccountType,
+    pub entries: Vec<FeeWalletEntry>,
+}
+
+impl FeeWalletRegistry {
+    pub const PREFIX: &'static str = "fee_wallets";
+
+    /// The epoch the next wallet is added under
+    pub fn next_epoch(&self) -> Option<u16> {
+        match self.entries.last() {
+            Some(entry) => entry.epoch.checked_add(1),
+            None => Some(0),
+        }
+    }
+
+    /// The latest wallet that was not superseded, new links store its epoch
+    pub fn latest(&self) -> Option<&FeeWalletEntry> {
+        self.entries.iter().rev().find(|entry| !entry.superseded)
+    }
+
+    /// The entry collecting the fees of an epoch, the latest live one when the epoch was
+    /// superseded
+    pub fn resolve(&self, epoch: u16) -> Option<&FeeWalletEntry> {
+        let entry = self.entries.iter().find(|entry| entry.epoch == epoch)?;
+        if entry.superseded {
+            return self.latest();
+        }
+        Some(entry)
+    }
+}
+
+impl IsInitialized for FeeWalletRegistry {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::FeeWalletRegistry
+    }
+}
+
+impl Sealed for FeeWalletRegistry {}
+
+impl Pack for FeeWalletRegistry {
+    const LEN: usize = FEE_WALLET_REGISTRY_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn registry(epochs: u16) -> FeeWalletRegistry {
+        FeeWalletRegistry {
+            account_type: AccountType::FeeWalletRegistry,
+            entries: (0..epochs)
+                .map(|epoch| FeeWalletEntry {
+                    epoch,
+                    wallet: Pubkey::new_unique(),
+                    superseded: epoch % 2 == 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn round_trips_an_empty_and_a_full_registry() {
+        round_trip(registry(0));
+        round_trip(registry(MAX_FEE_WALLET_EPOCHS as u16));
+    }
+
+    #[test]
+    fn refuses_a_registry_without_its_last_epoch() {
+        let data = packed(registry(MAX_FEE_WALLET_EPOCHS as u16));
+        assert_eq!(
+            FeeWalletRegistry::unpack(&data[..FeeWalletRegistry::LEN - (2 + 32 + 1)]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn refuses_another_account_of_its_length() {
+        let mut data = packed(registry(1));
+        data[0] = AccountType::Config as u8;
+        assert_eq!(FeeWalletRegistry::unpack(&data), Err(ProgramError::UninitializedAccount));
+    }
+}
@@ -0,0 +1,121 @@
+use borsh::{object_length, BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use super::{AccountType, AuxiliaryHeader, AUXILIARY_HEADER_SIZE};
+
+pub const MAX_METADATA_URI_LEN: usize = 96;
+// header + account_type + cash + string length prefix + uri
+pub const METADATA_DATA_SIZE: usize = AUXILIARY_HEADER_SIZE + 1 + 32 + 4 + MAX_METADATA_URI_LEN;
+
+/// Display metadata of a cash link, kept apart so the fixed Cash layout is untouched
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct CashMetadata {
+    pub header: AuxiliaryHeader,
+    pub account_type: AccountType,
+    pub cash: Pubkey,
+    pub uri: String,
+}
+
+impl CashMetadata {
+    pub const PREFIX: &'static str = "meta";
+
+    /// A uri is non empty, at most `MAX_METADATA_URI_LEN` bytes and printable ascii without spaces
+    pub fn is_valid_uri(uri: &str) -> bool {
+        !uri.is_empty()
+            && uri.len() <= MAX_METADATA_URI_LEN
+            && uri.bytes().all(|byte| byte.is_ascii_graphic())
+    }
+}
+
+impl IsInitialized for CashMetadata {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::Metadata
+    }
+}
+
+impl Sealed for CashMetadata {}
+
+impl Pack for CashMetadata {
+    const LEN: usize = METADATA_DATA_SIZE;
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN || object_length(&src)? > Self::LEN {
+            msg!("Failed to serialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        // a shorter uri leaves the tail of the previous one behind
+        dst.fill(0);
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn metadata(uri: String) -> CashMetadata {
+        CashMetadata {
+            header: AuxiliaryHeader::new(&Pubkey::new_unique()),
+            account_type: AccountType::Metadata,
+            cash: Pubkey::new_unique(),
+            uri,
+        }
+    }
+
+    #[test]
+    fn round_trips_the_longest_uri() {
+        round_trip(metadata("u".repeat(MAX_METADATA_URI_LEN)));
+    }
+
+    #[test]
+    fn refuses_to_pack_a_longer_uri() {
+        let mut data = vec![0; CashMetadata::LEN];
+        assert_eq!(
+            CashMetadata::pack(metadata("u".repeat(MAX_METADATA_URI_LEN + 1)), &mut data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn clears_the_tail_of_a_longer_uri() {
+        let mut data = packed(metadata("u".repeat(MAX_METADATA_URI_LEN)));
+        let shorter = metadata("https://cash.link".to_string());
+        let len = object_length(&shorter).unwrap();
+        CashMetadata::pack(shorter.clone(), &mut data).unwrap();
+        assert_eq!(CashMetadata::unpack(&data), Ok(shorter));
+        assert!(data[len..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn refuses_a_buffer_behind_the_same_header() {
+        let mut data = packed(metadata(String::new()));
+        data[AUXILIARY_HEADER_SIZE] = AccountType::Buffer as u8;
+        assert_eq!(CashMetadata::unpack(&data), Err(ProgramError::UninitializedAccount));
+    }
+}
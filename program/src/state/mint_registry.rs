@@ -0,0 +1,197 @@
+use borsh::{object_length, BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::{error::CashError, utils::cmp_pubkeys};
+
+use super::AccountType;
+
+pub const MAX_REGISTERED_MINTS: usize = 32;
+pub const MAX_MINT_CAPS: usize = 16;
+// account_type + mode + vec length prefix + 32 pubkeys + vec length prefix + 16 mint caps
+pub const MINT_REGISTRY_DATA_SIZE: usize =
+    1 + 1 + 4 + 32 * MAX_REGISTERED_MINTS + 4 + 40 * MAX_MINT_CAPS;
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+#[borsh(use_discriminant=true)]
+pub enum MintMode {
+    /// Every mint is accepted, the registry is ignored
+    #[default]
+    Disabled = 0,
+    /// Only the registered mints are accepted
+    Allowlist,
+    /// Every mint except the registered ones is accepted
+    Denylist,
+}
+
+/// Most a single wallet may redeem of a mint across every link during a window
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct MintCap {
+    pub mint: Pubkey,
+    /// In raw units of the mint
+    pub daily_cap: u64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct MintRegistry {
+    pub account_type: AccountType,
+    pub mode: MintMode,
+    pub mints: Vec<Pubkey>,
+    /// Daily caps per wallet, independent of the mode, a mint without one is uncapped
+    pub caps: Vec<MintCap>,
+}
+
+impl MintRegistry {
+    pub const PREFIX: &'static str = "mints";
+
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.mints.iter().any(|registered| cmp_pubkeys(registered, mint))
+    }
+
+    pub fn is_allowed(&self, mint: &Pubkey) -> bool {
+        match self.mode {
+            MintMode::Disabled => true,
+            MintMode::Allowlist => self.contains(mint),
+            MintMode::Denylist => !self.contains(mint),
+        }
+    }
+
+    pub fn daily_cap(&self, mint: &Pubkey) -> Option<u64> {
+        self.caps
+            .iter()
+            .find(|cap| cmp_pubkeys(&cap.mint, mint))
+            .map(|cap| cap.daily_cap)
+    }
+
+    /// Set or clear the daily cap of a mint
+    pub fn set_daily_cap(
+        &mut self,
+        mint: &Pubkey,
+        daily_cap: Option<u64>,
+    ) -> Result<(), CashError> {
+        let position = self.caps.iter().position(|cap| cmp_pubkeys(&cap.mint, mint));
+        match (position, daily_cap) {
+            (Some(position), Some(daily_cap)) => self.caps[position].daily_cap = daily_cap,
+            (Some(position), None) => {
+                self.caps.swap_remove(position);
+            }
+            (None, Some(_)) if self.caps.len() >= MAX_MINT_CAPS => {
+                return Err(CashError::MintCapsFull)
+            }
+            (None, Some(daily_cap)) => self.caps.push(MintCap {
+                mint: *mint,
+                daily_cap,
+            }),
+            (None, None) => {}
+        }
+        Ok(())
+    }
+}
+
+impl IsInitialized for MintRegistry {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::MintRegistry
+    }
+}
+
+impl Sealed for MintRegistry {}
+
+impl Pack for MintRegistry {
+    const LEN: usize = MINT_REGISTRY_DATA_SIZE;
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN || object_length(&src)? > Self::LEN {
+            msg!("Failed to serialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::round_trip;
+
+    fn full_registry() -> MintRegistry {
+        MintRegistry {
+            account_type: AccountType::MintRegistry,
+            mode: MintMode::Denylist,
+            mints: (0..MAX_REGISTERED_MINTS).map(|_| Pubkey::new_unique()).collect(),
+            caps: (0..MAX_MINT_CAPS as u64)
+                .map(|daily_cap| MintCap {
+                    mint: Pubkey::new_unique(),
+                    daily_cap,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn round_trips_a_full_registry() {
+        round_trip(full_registry());
+    }
+
+    #[test]
+    fn refuses_to_pack_past_its_capacity() {
+        let mut registry = full_registry();
+        registry.mints.push(Pubkey::new_unique());
+        let mut data = vec![0; MintRegistry::LEN];
+        assert_eq!(
+            MintRegistry::pack(registry, &mut data),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn allows_mints_by_mode() {
+        let (listed, unlisted) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let registry = |mode| MintRegistry {
+            account_type: AccountType::MintRegistry,
+            mode,
+            mints: vec![listed],
+            caps: vec![],
+        };
+        let allowed = |mode| {
+            let registry = registry(mode);
+            (registry.is_allowed(&listed), registry.is_allowed(&unlisted))
+        };
+        assert_eq!(allowed(MintMode::Disabled), (true, true));
+        assert_eq!(allowed(MintMode::Allowlist), (true, false));
+        assert_eq!(allowed(MintMode::Denylist), (false, true));
+        // an empty allowlist refuses every mint
+        let empty = MintRegistry {
+            mode: MintMode::Allowlist,
+            ..MintRegistry::default()
+        };
+        assert!(!empty.is_allowed(&listed));
+    }
+}
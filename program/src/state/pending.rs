@@ -0,0 +1,114 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use super::AccountType;
+
+// account_type + cash + wallet + rent_payer + amount + requested_at + referrer_fee_bps
+// + referee_fee_bps + priority_fee_reimbursement
+pub const PENDING_REDEMPTION_DATA_SIZE: usize = 1 + 32 * 3 + 8 * 2 + (1 + 2) * 2 + 1 + 8;
+
+/// A redemption at or above the approval threshold of its link, waiting for the authority and
+/// the owner to approve it. Its amount stays reserved on the link until then
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct PendingRedemption {
+    pub account_type: AccountType,
+    pub cash: Pubkey,
+    pub wallet: Pubkey,
+    /// Funded the account, gets the rent back when the redemption is approved or rejected
+    pub rent_payer: Pubkey,
+    /// The payout planned at request time, the approval pays exactly this amount
+    pub amount: u64,
+    /// Unix timestamp of the request
+    pub requested_at: u64,
+    /// Referral bps of the request, already clamped to the ceilings of the link
+    pub referrer_fee_bps: Option<u16>,
+    pub referee_fee_bps: Option<u16>,
+    pub priority_fee_reimbursement: Option<u64>,
+}
+
+impl PendingRedemption {
+    pub const PREFIX: &'static str = "pending";
+
+    /// A request can no longer be approved once the ttl of its link elapsed
+    pub fn expired(&self, ttl: Option<u64>, now: u64) -> bool {
+        matches!(ttl, Some(ttl) if now >= self.requested_at.saturating_add(ttl))
+    }
+}
+
+impl IsInitialized for PendingRedemption {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::PendingRedemption
+    }
+}
+
+impl Sealed for PendingRedemption {}
+
+impl Pack for PendingRedemption {
+    const LEN: usize = PENDING_REDEMPTION_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn pending(fee_bps: Option<u16>, priority_fee_reimbursement: Option<u64>) -> PendingRedemption {
+        PendingRedemption {
+            account_type: AccountType::PendingRedemption,
+            cash: Pubkey::new_unique(),
+            wallet: Pubkey::new_unique(),
+            rent_payer: Pubkey::new_unique(),
+            amount: u64::MAX,
+            requested_at: 1_700_000_000,
+            referrer_fee_bps: fee_bps,
+            referee_fee_bps: fee_bps.map(|bps| bps / 2),
+            priority_fee_reimbursement,
+        }
+    }
+
+    #[test]
+    fn round_trips_a_pending_redemption_with_and_without_its_options() {
+        round_trip(pending(Some(100), Some(5_000)));
+        round_trip(pending(None, None));
+    }
+
+    #[test]
+    fn refuses_a_pending_redemption_without_its_reimbursement() {
+        let data = packed(pending(Some(100), Some(5_000)));
+        assert_eq!(
+            PendingRedemption::unpack(&data[..PendingRedemption::LEN - 9]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn refuses_another_account_of_its_length() {
+        let mut data = packed(pending(None, None));
+        data[0] = AccountType::WalletLimit as u8;
+        assert_eq!(PendingRedemption::unpack(&data), Err(ProgramError::UninitializedAccount));
+    }
+}
@@ -0,0 +1,163 @@
+use borsh::{object_length, BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use super::{
+    AuxiliaryHeader, AUXILIARY_HEADER_SIZE, LEGACY_REDEMPTION_RECEIPT_SIZE, REDEMPTION_RECEIPT_SIZE,
+};
+
+pub const REDEMPTION_INDEX_DATA_SIZE: usize = 72;
+/// Borsh size of a RedemptionIndex, header + wallet + amount
+pub const REDEMPTION_INDEX_MAX_SERIALIZED_SIZE: usize = AUXILIARY_HEADER_SIZE + 32 + 8;
+
+const _: () = assert!(REDEMPTION_INDEX_MAX_SERIALIZED_SIZE <= REDEMPTION_INDEX_DATA_SIZE);
+
+/// Points the n-th redemption of a cash link back at the redeeming wallet
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct RedemptionIndex {
+    pub header: AuxiliaryHeader,
+    pub wallet: Pubkey,
+    pub amount: u64,
+}
+
+impl RedemptionIndex {
+    pub const PREFIX: &'static str = "redemption_idx";
+}
+
+impl IsInitialized for RedemptionIndex {
+    fn is_initialized(&self) -> bool {
+        self.wallet != Pubkey::default()
+    }
+}
+
+impl Sealed for RedemptionIndex {}
+
+impl Pack for RedemptionIndex {
+    const LEN: usize = REDEMPTION_INDEX_DATA_SIZE;
+
+    fn pack(src: Self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        if dst.len() != Self::LEN || object_length(&src)? > Self::LEN {
+            msg!("Failed to serialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        src.pack_into_slice(dst);
+        Ok(())
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+/// The receipt of a wallet for a cash link, ties the payout to the authority that signed it
+/// and the off chain voucher that authorized it
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct RedemptionReceipt {
+    pub header: AuxiliaryHeader,
+    /// The link authority that signed the redemption, unset on receipts created before
+    pub authority: Pubkey,
+    pub voucher_hash: Option<[u8; 32]>,
+    /// The redeeming wallet, the amount paid to it and the unix timestamp of the payout,
+    /// unset on receipts created before they were recorded
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub redeemed_at: u64,
+    /// Part of `amount` clawed back within the clawback window of the link
+    pub clawed_back: u64,
+}
+
+impl Sealed for RedemptionReceipt {}
+
+impl Pack for RedemptionReceipt {
+    const LEN: usize = REDEMPTION_RECEIPT_SIZE;
+
+    fn unpack_unchecked(src: &[u8]) -> Result<Self, ProgramError> {
+        // the size is checked by `unpack_from_slice`, which also accepts legacy receipts
+        Self::unpack_from_slice(src)
+    }
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        let result: Self = match src.len() {
+            Self::LEN => try_from_slice_unchecked(src)?,
+            len if (LEGACY_REDEMPTION_RECEIPT_SIZE..Self::LEN).contains(&len) => {
+                let mut padded = vec![0; Self::LEN];
+                padded[..src.len()].copy_from_slice(src);
+                try_from_slice_unchecked(&padded)?
+            }
+            _ => {
+                msg!("Failed to deserialize");
+                return Err(ProgramError::InvalidAccountData);
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::round_trip;
+
+    #[test]
+    fn unpacks_receipts_of_the_first_release() {
+        let receipt = RedemptionReceipt::unpack_unchecked(&[0; LEGACY_REDEMPTION_RECEIPT_SIZE]);
+        assert_eq!(receipt, Ok(RedemptionReceipt::default()));
+        assert_eq!(
+            RedemptionReceipt::unpack_unchecked(&[]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn round_trips_a_receipt() {
+        let receipt = RedemptionReceipt {
+            header: AuxiliaryHeader::new(&Pubkey::new_unique()),
+            authority: Pubkey::new_unique(),
+            voucher_hash: Some([7; 32]),
+            wallet: Pubkey::new_unique(),
+            amount: u64::MAX,
+            redeemed_at: 1_700_000_000,
+            clawed_back: 1,
+        };
+        let mut data = vec![0; RedemptionReceipt::LEN];
+        RedemptionReceipt::pack(receipt.clone(), &mut data).unwrap();
+        assert_eq!(RedemptionReceipt::unpack_unchecked(&data), Ok(receipt));
+    }
+
+    #[test]
+    fn round_trips_an_index() {
+        let index = RedemptionIndex {
+            header: AuxiliaryHeader::new(&Pubkey::new_unique()),
+            wallet: Pubkey::new_unique(),
+            amount: u64::MAX,
+        };
+        round_trip(index);
+    }
+}
@@ -0,0 +1,245 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use std::convert::TryFrom;
+
+use crate::{error::CashError, math::SafeMath, utils::cmp_pubkeys};
+
+use super::AccountType;
+
+pub const MAX_REFERRAL_MINTS: usize = 4;
+pub const REFERRAL_MINT_EARNINGS_SIZE: usize = 32 + 8;
+/// Day buckets of escrowed referrer fees a referrer holds at once, across mints
+pub const MAX_VESTING_BUCKETS: usize = 8;
+pub const VESTING_BUCKET_SIZE: usize = 32 + 4 + 8;
+// account_type + referrer + redemption_count + vec length prefix + 4 mint totals + flagged_at
+// + vec length prefix + 8 vesting buckets
+pub const REFERRAL_STATS_DATA_SIZE: usize = 1
+    + 32
+    + 8
+    + 4
+    + REFERRAL_MINT_EARNINGS_SIZE * MAX_REFERRAL_MINTS
+    + 1
+    + 8
+    + 4
+    + VESTING_BUCKET_SIZE * MAX_VESTING_BUCKETS;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct ReferralMintEarnings {
+    pub mint: Pubkey,
+    pub total_earned: u64,
+}
+
+/// Referrer fees escrowed on a day, they vest a delay after the end of the day
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct VestingBucket {
+    pub mint: Pubkey,
+    /// Days since the unix epoch
+    pub day: u32,
+    pub amount: u64,
+}
+
+impl VestingBucket {
+    /// Accrued during the last seconds of its day a fee still waits the whole delay
+    pub fn vested(&self, vesting_delay: u64, at: u64) -> bool {
+        (self.day as u64 + 1)
+            .saturating_mul(SECONDS_PER_DAY)
+            .saturating_add(vesting_delay)
+            <= at
+    }
+}
+
+/// Lifetime referral earnings of a referrer across all links, counters saturate
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct ReferralStats {
+    pub account_type: AccountType,
+    pub referrer: Pubkey,
+    /// Redemptions that paid the referrer a fee
+    pub redemption_count: u64,
+    /// Earnings of the first `MAX_REFERRAL_MINTS` mints the referrer was paid in
+    pub mints: Vec<ReferralMintEarnings>,
+    /// Set by the admin, the fees unvested at that time are forfeited
+    pub flagged_at: Option<u64>,
+    /// Referrer fees held in escrow until they vest, oldest first
+    pub vesting: Vec<VestingBucket>,
+}
+
+impl ReferralStats {
+    pub const PREFIX: &'static str = "referral";
+
+    /// Record a referrer fee, a mint past the tracked ones only counts the redemption
+    pub fn record_earning(&mut self, mint: &Pubkey, amount: u64) {
+        self.redemption_count = self.redemption_count.saturating_add(1);
+        if let Some(earnings) = self
+            .mints
+            .iter_mut()
+            .find(|earnings| cmp_pubkeys(&earnings.mint, mint))
+        {
+            earnings.total_earned = earnings.total_earned.saturating_add(amount);
+        } else if self.mints.len() < MAX_REFERRAL_MINTS {
+            self.mints.push(ReferralMintEarnings {
+                mint: *mint,
+                total_earned: amount,
+            });
+        } else {
+            msg!("Referral earnings of mint {} are not tracked", mint);
+        }
+    }
+
+    /// Hold a referrer fee in the bucket of its day. With every bucket taken the oldest
+    /// bucket of the mint is folded into the new one, its fees then vest later, never earlier
+    pub fn escrow(&mut self, mint: &Pubkey, now: u64, amount: u64) -> Result<(), ProgramError> {
+        let day = u32::try_from(now / SECONDS_PER_DAY).map_err(|_| CashError::Overflow)?;
+        if let Some(bucket) = self
+            .vesting
+            .iter_mut()
+            .find(|bucket| bucket.day == day && cmp_pubkeys(&bucket.mint, mint))
+        {
+            bucket.amount = bucket.amount.error_add(amount)?;
+            return Ok(());
+        }
+        let mut amount = amount;
+        if self.vesting.len() >= MAX_VESTING_BUCKETS {
+            let oldest = self
+                .vesting
+                .iter()
+                .position(|bucket| cmp_pubkeys(&bucket.mint, mint))
+                .ok_or(CashError::ReferralVestingFull)?;
+            amount = amount.error_add(self.vesting.remove(oldest).amount)?;
+        }
+        self.vesting.push(VestingBucket {
+            mint: *mint,
+            day,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Remove the escrowed fees of the mint vested at `at`, returns their total
+    pub fn take_vested(
+        &mut self,
+        mint: &Pubkey,
+        vesting_delay: u64,
+        at: u64,
+    ) -> Result<u64, ProgramError> {
+        self.take_buckets(|bucket| {
+            cmp_pubkeys(&bucket.mint, mint) && bucket.vested(vesting_delay, at)
+        })
+    }
+
+    /// Remove the escrowed fees of the mint still unvested at `at`, returns their total
+    pub fn take_unvested(
+        &mut self,
+        mint: &Pubkey,
+        vesting_delay: u64,
+        at: u64,
+    ) -> Result<u64, ProgramError> {
+        self.take_buckets(|bucket| {
+            cmp_pubkeys(&bucket.mint, mint) && !bucket.vested(vesting_delay, at)
+        })
+    }
+
+    fn take_buckets(
+        &mut self,
+        predicate: impl Fn(&VestingBucket) -> bool,
+    ) -> Result<u64, ProgramError> {
+        let mut total: u64 = 0;
+        for bucket in self.vesting.iter().filter(|bucket| predicate(bucket)) {
+            total = total.error_add(bucket.amount)?;
+        }
+        self.vesting.retain(|bucket| !predicate(bucket));
+        Ok(total)
+    }
+}
+
+impl IsInitialized for ReferralStats {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::ReferralStats
+    }
+}
+
+impl Sealed for ReferralStats {}
+
+impl Pack for ReferralStats {
+    const LEN: usize = REFERRAL_STATS_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn stats(flagged_at: Option<u64>) -> ReferralStats {
+        ReferralStats {
+            account_type: AccountType::ReferralStats,
+            referrer: Pubkey::new_unique(),
+            redemption_count: u64::MAX,
+            mints: (0..MAX_REFERRAL_MINTS as u64)
+                .map(|total_earned| ReferralMintEarnings {
+                    mint: Pubkey::new_unique(),
+                    total_earned,
+                })
+                .collect(),
+            flagged_at,
+            vesting: (0..MAX_VESTING_BUCKETS as u32)
+                .map(|day| VestingBucket {
+                    mint: Pubkey::new_unique(),
+                    day,
+                    amount: u64::MAX - day as u64,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn round_trips_full_referral_stats() {
+        round_trip(stats(Some(1_700_000_000)));
+        round_trip(stats(None));
+    }
+
+    #[test]
+    fn refuses_stats_laid_out_before_vesting() {
+        let data = packed(stats(None));
+        let before_vesting = 1 + 32 + 8 + 4 + REFERRAL_MINT_EARNINGS_SIZE * MAX_REFERRAL_MINTS;
+        assert_eq!(
+            ReferralStats::unpack(&data[..before_vesting]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn refuses_another_account_of_its_length() {
+        let mut data = packed(stats(None));
+        data[0] = AccountType::Stats as u8;
+        assert_eq!(ReferralStats::unpack(&data), Err(ProgramError::UninitializedAccount));
+    }
+}
@@ -0,0 +1,149 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::utils::cmp_pubkeys;
+
+use super::AccountType;
+
+pub const MAX_STATS_MINTS: usize = 8;
+pub const MINT_STATS_SIZE: usize = 64;
+// account_type + 5 counters + vec length prefix + 8 mint breakdowns
+pub const STATS_DATA_SIZE: usize = 1 + 8 * 5 + 4 + MINT_STATS_SIZE * MAX_STATS_MINTS;
+
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct MintStats {
+    pub mint: Pubkey,
+    pub value_loaded: u64,
+    pub redeemed: u64,
+    pub fees_collected: u64,
+    pub refunded: u64,
+}
+
+/// Program wide totals, all counters saturate instead of failing on overflow
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct Stats {
+    pub account_type: AccountType,
+    pub link_count: u64,
+    pub value_loaded: u64,
+    pub redeemed: u64,
+    pub fees_collected: u64,
+    pub refunded: u64,
+    pub mints: Vec<MintStats>,
+}
+
+impl Stats {
+    pub const PREFIX: &'static str = "stats";
+
+    fn mint_stats(&mut self, mint: &Pubkey) -> Option<&mut MintStats> {
+        self.mints
+            .iter_mut()
+            .find(|mint_stats| cmp_pubkeys(&mint_stats.mint, mint))
+    }
+
+    pub fn record_init(&mut self, mint: &Pubkey, amount: u64) {
+        self.link_count = self.link_count.saturating_add(1);
+        self.value_loaded = self.value_loaded.saturating_add(amount);
+        if let Some(mint_stats) = self.mint_stats(mint) {
+            mint_stats.value_loaded = mint_stats.value_loaded.saturating_add(amount);
+        }
+    }
+
+    pub fn record_redemption(&mut self, mint: &Pubkey, amount: u64, fees: u64) {
+        self.redeemed = self.redeemed.saturating_add(amount);
+        self.fees_collected = self.fees_collected.saturating_add(fees);
+        if let Some(mint_stats) = self.mint_stats(mint) {
+            mint_stats.redeemed = mint_stats.redeemed.saturating_add(amount);
+            mint_stats.fees_collected = mint_stats.fees_collected.saturating_add(fees);
+        }
+    }
+
+    pub fn record_refund(&mut self, mint: &Pubkey, amount: u64) {
+        self.refunded = self.refunded.saturating_add(amount);
+        if let Some(mint_stats) = self.mint_stats(mint) {
+            mint_stats.refunded = mint_stats.refunded.saturating_add(amount);
+        }
+    }
+}
+
+impl IsInitialized for Stats {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::Stats
+    }
+}
+
+impl Sealed for Stats {}
+
+impl Pack for Stats {
+    const LEN: usize = STATS_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn stats() -> Stats {
+        Stats {
+            account_type: AccountType::Stats,
+            link_count: 1,
+            value_loaded: 2,
+            redeemed: 3,
+            fees_collected: 4,
+            refunded: u64::MAX,
+            mints: (0..MAX_STATS_MINTS as u64)
+                .map(|i| MintStats {
+                    mint: Pubkey::new_unique(),
+                    value_loaded: i,
+                    redeemed: i + 1,
+                    fees_collected: i + 2,
+                    refunded: u64::MAX - i,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn round_trips_stats_of_every_mint() {
+        round_trip(stats());
+    }
+
+    #[test]
+    fn refuses_stats_without_their_last_mint() {
+        let data = packed(stats());
+        assert_eq!(
+            Stats::unpack(&data[..Stats::LEN - MINT_STATS_SIZE]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+
+    #[test]
+    fn reads_stats_yet_to_be_initialized_as_uninitialized() {
+        assert_eq!(Stats::unpack(&[0; STATS_DATA_SIZE]), Err(ProgramError::UninitializedAccount));
+    }
+}
@@ -0,0 +1,131 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    borsh1::try_from_slice_unchecked,
+    msg,
+    program_error::ProgramError,
+    program_pack::{IsInitialized, Pack, Sealed},
+    pubkey::Pubkey,
+};
+
+use crate::error::CashError;
+
+use super::AccountType;
+
+// account_type + wallet + mint + rent_payer + window_start + amount
+pub const WALLET_LIMIT_DATA_SIZE: usize = 1 + 32 * 3 + 8 * 2;
+/// Length of the window the daily cap of a mint applies to
+pub const WALLET_LIMIT_WINDOW: u64 = 24 * 60 * 60;
+
+/// What a wallet redeemed of a capped mint across every link during its current window
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, BorshSerialize, BorshDeserialize, Default)]
+#[cfg_attr(feature = "codegen", derive(borsh::BorshSchema))]
+pub struct WalletLimit {
+    pub account_type: AccountType,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    /// Funded the account, gets the rent back when the account is swept
+    pub rent_payer: Pubkey,
+    /// Unix timestamp of the first redemption of the window
+    pub window_start: u64,
+    /// Payouts of the window, in raw units of the mint
+    pub amount: u64,
+}
+
+impl WalletLimit {
+    pub const PREFIX: &'static str = "limit";
+
+    /// The window ended, the next redemption opens a new one
+    pub fn stale(&self, now: u64) -> bool {
+        now >= self.window_start.saturating_add(WALLET_LIMIT_WINDOW)
+    }
+
+    /// Add a payout to the window, rolling it over once expired
+    pub fn record(&mut self, daily_cap: u64, now: u64, amount: u64) -> Result<(), CashError> {
+        if self.stale(now) {
+            self.window_start = now;
+            self.amount = 0;
+        }
+        let total = self.amount.checked_add(amount).ok_or(CashError::Overflow)?;
+        if total > daily_cap {
+            msg!(
+                "Wallet {} redeemed {} of its daily cap {}, {} more is refused",
+                self.wallet,
+                self.amount,
+                daily_cap,
+                amount
+            );
+            return Err(CashError::DailyLimitExceeded);
+        }
+        self.amount = total;
+        Ok(())
+    }
+}
+
+impl IsInitialized for WalletLimit {
+    fn is_initialized(&self) -> bool {
+        self.account_type == AccountType::WalletLimit
+    }
+}
+
+impl Sealed for WalletLimit {}
+
+impl Pack for WalletLimit {
+    const LEN: usize = WALLET_LIMIT_DATA_SIZE;
+
+    fn pack_into_slice(&self, dst: &mut [u8]) {
+        let mut slice = dst;
+        self.serialize(&mut slice).unwrap()
+    }
+
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() != Self::LEN {
+            msg!("Failed to deserialize");
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let result: Self = try_from_slice_unchecked(src)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{packed, round_trip};
+
+    fn limit() -> WalletLimit {
+        WalletLimit {
+            account_type: AccountType::WalletLimit,
+            wallet: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            rent_payer: Pubkey::new_unique(),
+            window_start: 1_700_000_000,
+            amount: u64::MAX,
+        }
+    }
+
+    #[test]
+    fn round_trips_a_wallet_limit() {
+        round_trip(limit());
+    }
+
+    #[test]
+    fn refuses_another_account_of_its_length() {
+        let mut data = packed(limit());
+        data[0] = AccountType::RedemptionCommit as u8;
+        assert_eq!(WalletLimit::unpack(&data), Err(ProgramError::UninitializedAccount));
+    }
+
+    #[test]
+    fn refuses_a_limit_of_another_length() {
+        let mut data = packed(limit());
+        data.push(0);
+        assert_eq!(WalletLimit::unpack(&data), Err(ProgramError::InvalidAccountData));
+        assert_eq!(
+            WalletLimit::unpack(&data[..WalletLimit::LEN - 1]),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! Account fixtures and a syscall runtime shared by the unit tests
+use crate::{
+    find_config_program_address, find_redemption_address, find_redemption_by_index_address,
+    instruction::{CancelCashRedemptionArgs, InitCashRedemptionArgs},
+    processor::redemption::RedemptionContext,
+    state::{
+        cash::{Cash, CashState, DistributionType},
+        config::Config,
+        redemption::{RedemptionIndex, RedemptionReceipt},
+        AccountType,
+    },
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::{IsInitialized, Pack},
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    sysvar::{self, slot_hashes},
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::{
+    error::TokenError,
+    instruction::TokenInstruction,
+    state::{Account, AccountState, Mint},
+};
+use std::{
+    cell::{Cell, RefCell},
+    fmt::Debug,
+    sync::Once,
+};
+
+/// Backing storage of an `AccountInfo`
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        Self {
+            key,
+            owner,
+            lamports: 1_000_000,
+            data,
+            is_signer: false,
+        }
+    }
+
+    /// A wallet signing the instruction
+    pub fn signer(key: Pubkey) -> Self {
+        Self {
+            is_signer: true,
+            ..Self::new(key, Pubkey::default(), vec![])
+        }
+    }
+
+    /// A packed account owned by `owner`
+    pub fn packed<T: Pack>(key: Pubkey, owner: Pubkey, state: T) -> Self {
+        Self::new(key, owner, packed(state))
+    }
+
+    /// An initialized token 2022 mint
+    pub fn mint(key: Pubkey, decimals: u8) -> Self {
+        let mint = Mint {
+            decimals,
+            is_initialized: true,
+            ..Mint::default()
+        };
+        Self::packed(key, spl_token_2022::id(), mint)
+    }
+
+    /// An initialized token 2022 account of `wallet`
+    pub fn token(key: Pubkey, mint: Pubkey, wallet: Pubkey, amount: u64) -> Self {
+        let token = Account {
+            mint,
+            owner: wallet,
+            amount,
+            state: AccountState::Initialized,
+            ..Account::default()
+        };
+        Self::packed(key, spl_token_2022::id(), token)
+    }
+
+    /// The rent sysvar at its default rates
+    pub fn rent() -> Self {
+        let rent = Rent::default();
+        let mut data = rent.lamports_per_byte_year.to_le_bytes().to_vec();
+        data.extend_from_slice(&rent.exemption_threshold.to_le_bytes());
+        data.push(rent.burn_percent);
+        Self::new(sysvar::rent::id(), sysvar::id(), data)
+    }
+
+    /// An account of the system program yet to be created with `len` bytes
+    pub fn uncreated(key: Pubkey, len: usize) -> Self {
+        Self {
+            lamports: 0,
+            ..Self::new(key, Pubkey::default(), vec![0; len])
+        }
+    }
+
+    pub fn unpack<T: Pack>(&self) -> T {
+        T::unpack_unchecked(&self.data).unwrap()
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+            0,
+        )
+    }
+}
+
+/// The data of an account of its layout holding `state`
+pub fn packed<T: Pack>(state: T) -> Vec<u8> {
+    let mut data = vec![0; T::LEN];
+    T::pack(state, &mut data).unwrap();
+    data
+}
+
+/// Pack `state` into an account of its layout and unpack it back unchanged
+pub fn round_trip<T: Pack + IsInitialized + Clone + PartialEq + Debug>(state: T) {
+    assert_eq!(T::unpack(&packed(state.clone())), Ok(state));
+}
+
+/// A live fixed link of the program, its vault holding the whole amount, under the config of
+/// an admin with a recovery wallet
+pub struct TestLink {
+    pub program_id: Pubkey,
+    pub cash_bump: u8,
+    pub authority: TestAccount,
+    pub admin: TestAccount,
+    pub config: TestAccount,
+    pub cash: TestAccount,
+    pub owner: TestAccount,
+    pub owner_token: TestAccount,
+    pub fee_payer: TestAccount,
+    pub vault: TestAccount,
+    pub mint: TestAccount,
+    pub token_program: TestAccount,
+}
+
+impl TestLink {
+    pub const REFERENCE: &'static str = "reference";
+
+    pub fn new(amount: u64, max_num_redemptions: u16) -> Self {
+        let program_id = Pubkey::new_unique();
+        let (cash_key, cash_bump) = Pubkey::find_program_address(
+            &[Cash::PREFIX.as_bytes(), Self::REFERENCE.as_bytes()],
+            &program_id,
+        );
+        let (authority, admin, owner) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let mint = Pubkey::new_unique();
+        let cash = Cash {
+            account_type: AccountType::Cash,
+            state: CashState::Initialized,
+            distribution_type: DistributionType::Fixed,
+            authority,
+            owner,
+            mint,
+            token_program: spl_token_2022::id(),
+            amount,
+            remaining_amount: amount,
+            max_num_redemptions,
+            ..Cash::default()
+        };
+        let config = Config {
+            account_type: AccountType::Config,
+            admin,
+            recovery_wallet: Some(Pubkey::new_unique()),
+            ..Config::default()
+        };
+        let mut link = Self {
+            program_id,
+            cash_bump,
+            authority: TestAccount::signer(authority),
+            admin: TestAccount::signer(admin),
+            config: TestAccount::packed(
+                find_config_program_address(&program_id).0,
+                program_id,
+                config,
+            ),
+            cash: TestAccount::packed(cash_key, program_id, cash),
+            owner: TestAccount::signer(owner),
+            owner_token: TestAccount::token(Pubkey::default(), mint, owner, 0),
+            fee_payer: TestAccount::signer(Pubkey::new_unique()),
+            vault: TestAccount::token(Pubkey::default(), mint, cash_key, amount),
+            mint: TestAccount::mint(mint, 6),
+            token_program: TestAccount::new(spl_token_2022::id(), Pubkey::default(), vec![]),
+        };
+        link.owner_token.key = link.ata(&owner);
+        link.vault.key = link.ata(&cash_key);
+        link
+    }
+
+    /// The associated token account of `wallet` for the mint of the link
+    pub fn ata(&self, wallet: &Pubkey) -> Pubkey {
+        let (mint, token_program) = (&self.mint.key, &self.token_program.key);
+        get_associated_token_address_with_program_id(wallet, mint, token_program)
+    }
+
+    pub fn args(&self) -> CancelCashRedemptionArgs {
+        CancelCashRedemptionArgs {
+            cash_bump: self.cash_bump,
+            cash_reference: Self::REFERENCE.to_string(),
+        }
+    }
+
+    /// A plain redemption of the link, no receipt beyond the one the link requires
+    pub fn redeem_args(&self) -> InitCashRedemptionArgs {
+        InitCashRedemptionArgs {
+            cash_bump: self.cash_bump,
+            cash_reference: Self::REFERENCE.to_string(),
+            referrer_fee_bps: None,
+            referee_fee_bps: None,
+            weight_ppm: None,
+            rate_usd: None,
+            redemption_bump: 255,
+            redemption_index_bump: 255,
+            priority_fee_reimbursement: None,
+            recipient_permit: None,
+            reveal_nonce: None,
+            create_receipt: false,
+            voucher_hash: None,
+            save_bps: None,
+            signed_at_slot: None,
+        }
+    }
+
+    pub fn cash(&self) -> Cash {
+        self.cash.unpack()
+    }
+
+    /// Change the stored link
+    pub fn update(&mut self, update: impl FnOnce(&mut Cash)) {
+        let mut cash = self.cash();
+        update(&mut cash);
+        Cash::pack(cash, &mut self.cash.data).unwrap();
+    }
+
+    pub fn recovery_wallet(&self) -> Pubkey {
+        self.config.unpack::<Config>().recovery_wallet.unwrap()
+    }
+
+    pub fn amount(token: &TestAccount) -> u64 {
+        token.unpack::<Account>().amount
+    }
+}
+
+/// A token redemption of a `TestLink` by a new wallet, every token account of it exists
+pub struct TestRedemption {
+    pub link: TestLink,
+    pub wallet: TestAccount,
+    pub platform_wallet: TestAccount,
+    pub platform_token: TestAccount,
+    pub fee_payer_token: TestAccount,
+    pub recipient_token: TestAccount,
+    pub redemption: TestAccount,
+    pub redemption_index: TestAccount,
+    pub redemption_index_bump: u8,
+    pub rent: TestAccount,
+    pub slot_hashes: TestAccount,
+    pub system_program: TestAccount,
+    /// Passed after the accounts of the context, e.g. for `find_optional_account`
+    pub remaining: Vec<TestAccount>,
+}
+
+impl TestRedemption {
+    pub fn new(link: TestLink) -> Self {
+        let (program_id, cash_key, mint) = (link.program_id, link.cash.key, link.mint.key);
+        let (wallet, platform_wallet) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let token = |wallet: &Pubkey| TestAccount::token(link.ata(wallet), mint, *wallet, 0);
+        // the index of the redemption is the one after the redemptions of the link so far
+        let next_index = link.cash().total_redemptions + 1;
+        let (index_key, redemption_index_bump) =
+            find_redemption_by_index_address(&program_id, &cash_key, next_index);
+        Self {
+            wallet: TestAccount::signer(wallet),
+            platform_wallet: TestAccount::new(platform_wallet, Pubkey::default(), vec![]),
+            platform_token: token(&platform_wallet),
+            fee_payer_token: token(&link.fee_payer.key),
+            recipient_token: token(&wallet),
+            redemption: TestAccount::uncreated(
+                find_redemption_address(&program_id, &cash_key, &wallet).0,
+                RedemptionReceipt::LEN,
+            ),
+            redemption_index: TestAccount::uncreated(index_key, RedemptionIndex::LEN),
+            redemption_index_bump,
+            rent: TestAccount::rent(),
+            slot_hashes: TestAccount::new(slot_hashes::id(), sysvar::id(), vec![]),
+            // the system program id is the default key
+            system_program: TestAccount::new(Pubkey::default(), Pubkey::default(), vec![]),
+            remaining: vec![],
+            link,
+        }
+    }
+
+    pub fn args(&self) -> InitCashRedemptionArgs {
+        InitCashRedemptionArgs {
+            redemption_index_bump: self.redemption_index_bump,
+            ..self.link.redeem_args()
+        }
+    }
+
+    /// Run `run` with the context of the redemption and every account passed to it
+    pub fn with_context<R>(
+        &mut self,
+        run: impl for<'a, 'b> FnOnce(&'b [AccountInfo<'a>], RedemptionContext<'a, 'b>) -> R,
+    ) -> R {
+        let cash = self.link.cash();
+        let vault_amount = TestLink::amount(&self.link.vault);
+        let recipient_owner = self.wallet.key;
+        let exists = |account: &TestAccount| account.lamports > 0 && !account.data.is_empty();
+        let recipient_token_exists = exists(&self.recipient_token);
+        let platform_token_exists = exists(&self.platform_token);
+        let fee_payer_token_exists = exists(&self.fee_payer_token);
+        let link = &mut self.link;
+        let accounts: Vec<AccountInfo> = vec![
+            &mut self.wallet,
+            &mut self.platform_wallet,
+            &mut self.platform_token,
+            &mut link.cash,
+            &mut link.owner,
+            &mut link.owner_token,
+            &mut link.fee_payer,
+            &mut self.fee_payer_token,
+            &mut link.vault,
+            &mut self.recipient_token,
+            &mut link.mint,
+            &mut self.redemption,
+            &mut self.redemption_index,
+            &mut self.rent,
+            &mut self.slot_hashes,
+            &mut link.token_program,
+            &mut self.system_program,
+        ]
+        .into_iter()
+        .chain(self.remaining.iter_mut())
+        .map(|account| account.info())
+        .collect();
+        let ctx = RedemptionContext {
+            wallet_info: &accounts[0],
+            platform_wallet_info: &accounts[1],
+            platform_token_info: &accounts[2],
+            cash_info: &accounts[3],
+            owner_wallet_info: &accounts[4],
+            owner_token_info: &accounts[5],
+            fee_payer_info: &accounts[6],
+            fee_payer_token_info: &accounts[7],
+            vault_token_info: &accounts[8],
+            recipient_token_info: &accounts[9],
+            mint_info: &accounts[10],
+            redemption_info: &accounts[11],
+            redemption_index_info: &accounts[12],
+            rent_info: &accounts[13],
+            recent_slothashes_info: &accounts[14],
+            token_program_info: &accounts[15],
+            system_program_info: &accounts[16],
+            referral: None,
+            savings_token_info: None,
+            swap: None,
+            memo_program_info: None,
+            cash,
+            clock: Clock {
+                unix_timestamp: NOW.with(Cell::get),
+                ..Clock::default()
+            },
+            vault_amount,
+            decimals: 6,
+            is_native: false,
+            recipient_token_exists,
+            platform_token_exists,
+            fee_payer_token_exists,
+            recipient_owner,
+        };
+        run(&accounts, ctx)
+    }
+}
+
+thread_local! {
+    static NOW: Cell<i64> = const { Cell::new(0) };
+    static TRACE: RefCell<Vec<Step>> = const { RefCell::new(vec![]) };
+}
+
+/// What the program did, in order, while a test ran it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Step {
+    /// Every read-only check of the instruction passed, see `record_validation`
+    Validated,
+    /// A cpi into the program
+    Invoked(Pubkey),
+}
+
+/// Install the runtime for the current test, its clock reads `now` and its trace is empty
+pub fn runtime(now: i64) {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(TestRuntime));
+    });
+    NOW.with(|clock| clock.set(now));
+    TRACE.with(|trace| trace.borrow_mut().clear());
+}
+
+/// Called by the program after its last check, a cpi traced before it spent the lamports of
+/// the fee payer on a transaction that could still fail
+pub fn record_validation() {
+    TRACE.with(|trace| trace.borrow_mut().push(Step::Validated));
+}
+
+/// The steps traced since the runtime was installed
+pub fn trace() -> Vec<Step> {
+    TRACE.with(|trace| trace.borrow().clone())
+}
+
+/// The programs invoked since the runtime was installed
+pub fn invoked() -> Vec<Pubkey> {
+    trace()
+        .into_iter()
+        .filter_map(|step| match step {
+            Step::Invoked(program_id) => Some(program_id),
+            Step::Validated => None,
+        })
+        .collect()
+}
+
+/// Serves the clock of the current test, runs the token instructions a link signs and creates
+/// associated token accounts, every other cpi is only recorded
+struct TestRuntime;
+
+impl SyscallStubs for TestRuntime {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: NOW.with(Cell::get),
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        TRACE.with(|trace| trace.borrow_mut().push(Step::Invoked(instruction.program_id)));
+        let account = |index: usize| {
+            let key = instruction.accounts[index].pubkey;
+            account_infos
+                .iter()
+                .find(|info| *info.key == key)
+                .ok_or(ProgramError::NotEnoughAccountKeys)
+        };
+        if instruction.program_id == spl_associated_token_account::id() {
+            // the token account is created in the data an uncreated fixture reserves for it
+            let (token_info, wallet_info, mint_info) = (account(1)?, account(2)?, account(3)?);
+            let token = Account {
+                mint: *mint_info.key,
+                owner: *wallet_info.key,
+                state: AccountState::Initialized,
+                ..Account::default()
+            };
+            **token_info.lamports.borrow_mut() = 1_000_000;
+            return Account::pack(token, &mut token_info.data.borrow_mut()[..Account::LEN]);
+        }
+        if instruction.program_id != spl_token_2022::id() {
+            return Ok(());
+        }
+        match TokenInstruction::unpack(&instruction.data)? {
+            TokenInstruction::TransferChecked { amount, .. } => {
+                let (source_info, destination_info) = (account(0)?, account(2)?);
+                let mut source = Account::unpack(&source_info.data.borrow())?;
+                spend(&mut source, account(3)?.key, amount)?;
+                Account::pack(source, &mut source_info.data.borrow_mut())?;
+                let mut destination = Account::unpack(&destination_info.data.borrow())?;
+                destination.amount += amount;
+                Account::pack(destination, &mut destination_info.data.borrow_mut())
+            }
+            TokenInstruction::ApproveChecked { amount, .. } => {
+                let source_info = account(0)?;
+                let mut source = Account::unpack(&source_info.data.borrow())?;
+                if source.owner != *account(3)?.key {
+                    return Err(TokenError::OwnerMismatch.into());
+                }
+                source.delegate = COption::Some(*account(2)?.key);
+                source.delegated_amount = amount;
+                Account::pack(source, &mut source_info.data.borrow_mut())
+            }
+            TokenInstruction::Revoke => {
+                let source_info = account(0)?;
+                let mut source = Account::unpack(&source_info.data.borrow())?;
+                source.delegate = COption::None;
+                source.delegated_amount = 0;
+                Account::pack(source, &mut source_info.data.borrow_mut())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Debit a token account as its owner or delegate, spending the delegation as the token
+/// program does
+fn spend(source: &mut Account, authority: &Pubkey, amount: u64) -> ProgramResult {
+    if source.amount < amount {
+        return Err(TokenError::InsufficientFunds.into());
+    }
+    if source.owner != *authority {
+        if source.delegate != COption::Some(*authority) {
+            return Err(TokenError::OwnerMismatch.into());
+        }
+        if source.delegated_amount < amount {
+            return Err(TokenError::InsufficientFunds.into());
+        }
+        source.delegated_amount -= amount;
+        if source.delegated_amount == 0 {
+            source.delegate = COption::None;
+        }
+    }
+    source.amount -= amount;
+    Ok(())
+}
@@ -2,10 +2,11 @@
 
 use std::convert::TryInto;
 
-use crate::error::CashError;
+use crate::{error::CashError, rounding::floor_share};
 
 use solana_program::{
     account_info::AccountInfo,
+    instruction::Instruction,
     entrypoint::ProgramResult,
     msg,
     program::{invoke, invoke_signed},
@@ -14,15 +15,43 @@ use solana_program::{
     program_pack::{IsInitialized, Pack},
     pubkey::{Pubkey, PUBKEY_BYTES},
     system_instruction,
-    sysvar::{rent::Rent, Sysvar},
+    sysvar::{self, rent::Rent, Sysvar},
     clock::Clock,
+    pubkey,
+};
+use spl_token_2022::{
+    extension::{
+        default_account_state::DefaultAccountState, memo_transfer::memo_required, BaseState,
+        BaseStateWithExtensions, StateWithExtensions,
+    },
+    state::{Account, AccountState, Mint},
+};
+use spl_associated_token_account::instruction::{
+    create_associated_token_account, create_associated_token_account_idempotent,
 };
-use spl_token_2022::{extension::{BaseState, StateWithExtensions}, state::Account};
-use spl_associated_token_account::instruction::create_associated_token_account;
 
 
 use arrayref::array_ref;
 
+/// Log the role and the key of the account failing a check and evaluate to `Err(error)`, the
+/// error code is unchanged so only the emitted logs gain context
+macro_rules! err_ctx {
+    ($error:expr, $role:expr, $account_info:expr) => {{
+        solana_program::msg!("Failed {} account {}", $role, $account_info.key);
+        Err($error.into())
+    }};
+}
+pub(crate) use err_ctx;
+
+/// Role logged for a failing account whose caller passed none
+const UNNAMED_ROLE: &str = "unnamed";
+
+/// The SPL memo program
+pub const MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// The upgradeable bpf loader, owner of the program data accounts
+pub const UPGRADEABLE_LOADER_ID: Pubkey = pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
 /// Assert uninitialized
 pub fn assert_uninitialized<T: IsInitialized>(account: &T) -> ProgramResult {
     if account.is_initialized() {
@@ -42,23 +71,75 @@ pub fn assert_signer(account: &AccountInfo) -> ProgramResult {
 }
 
 /// Assert owned by
-pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+pub fn assert_owned_by(
+    account: &AccountInfo,
+    owner: &Pubkey,
+    role: Option<&str>,
+) -> ProgramResult {
     if !cmp_pubkeys(&account.owner, owner)  {
-        Err(CashError::InvalidOwner.into())
+        err_ctx!(CashError::InvalidOwner, role.unwrap_or(UNNAMED_ROLE), account)
     } else {
         Ok(())
     }
 }
 
-/// Assert owned by
-pub fn assert_token_owned_by(token: &Account, owner: &Pubkey) -> ProgramResult {
-    if !cmp_pubkeys(&token.owner, owner) {
-        Err(CashError::InvalidOwner.into())
+/// Assert the token account is not frozen, a frozen account fails any transfer
+pub fn assert_not_frozen(token: &Account, error: CashError) -> ProgramResult {
+    if token.is_frozen() {
+        Err(error.into())
     } else {
         Ok(())
     }
 }
 
+/// Assert the token account holds the expected mint
+pub fn assert_token_mint(token: &Account, mint: &Pubkey, role: &str) -> ProgramResult {
+    if !cmp_pubkeys(&token.mint, mint) {
+        msg!("Invalid mint {} for the {} account", token.mint, role);
+        Err(CashError::InvalidMint.into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Assert a token account belongs to the wallet and holds the mint. Without an expected mint a
+/// wrapped SOL account is refused, its balance is the lamports of the account rather than
+/// tokens of the mint the caller moves
+pub fn assert_token_account(
+    token: &Account,
+    owner: &Pubkey,
+    mint: Option<&Pubkey>,
+    role: &str,
+) -> ProgramResult {
+    if !cmp_pubkeys(&token.owner, owner) {
+        msg!("Invalid owner {} for the {} account", token.owner, role);
+        return Err(CashError::InvalidOwner.into());
+    }
+    match mint {
+        Some(mint) => assert_token_mint(token, mint, role),
+        None if token.is_native() => {
+            msg!("Unexpected wrapped SOL {} account", role);
+            Err(CashError::InvalidMint.into())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Load a token account of the token program and assert it belongs to the wallet and holds
+/// the mint, so the cash pda never signs a transfer into an account playing the wrong role
+pub fn load_token_account(
+    token_info: &AccountInfo,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program_id: &Pubkey,
+    role: &str,
+) -> Result<Account, ProgramError> {
+    assert_owned_by(token_info, token_program_id, Some(role))?;
+    let token: Account = assert_initialized(token_info, Some(role))?;
+    assert_token_account(&token, wallet, Some(mint), role)?;
+    Ok(token)
+}
+
 /// Assert valid key
 pub fn assert_valid_token_program(
     key: &Pubkey,
@@ -75,11 +156,13 @@ pub fn assert_account_key(
     account_info: &AccountInfo,
     key: &Pubkey,
     error: Option<CashError>,
+    role: Option<&str>,
 ) -> ProgramResult {
     if !cmp_pubkeys(account_info.key, &key) {
+        let role = role.unwrap_or(UNNAMED_ROLE);
         match error {
-            Some(e) => Err(e.into()),
-            _ => Err(ProgramError::InvalidArgument),
+            Some(e) => err_ctx!(e, role, account_info),
+            _ => err_ctx!(ProgramError::InvalidArgument, role, account_info),
         }
     } else {
         Ok(())
@@ -108,21 +191,41 @@ pub fn assert_rent_exempt(rent: &Rent, account_info: &AccountInfo) -> ProgramRes
 // }
 
 /// assert initialized account
-pub fn assert_initialized<T>(account_info: &AccountInfo) -> Result<T, ProgramError>
+pub fn assert_initialized<T>(
+    account_info: &AccountInfo,
+    role: Option<&str>,
+) -> Result<T, ProgramError>
 where
     T: Pack + IsInitialized + BaseState,
 {    
-    let data = account_info.data.borrow();
-    let state_with_ext = StateWithExtensions::<T>::unpack(&data)?;
+    let role = role.unwrap_or(UNNAMED_ROLE);
+    let data = account_info.try_borrow_data()?;
+    let state_with_ext = match StateWithExtensions::<T>::unpack(&data) {
+        Ok(state_with_ext) => state_with_ext,
+        Err(error) => return err_ctx!(error, role, account_info),
+    };
 
     let account = state_with_ext.base;
     
     if !account.is_initialized() {
-        Err(CashError::AccountNotInitialized.into())
+        err_ctx!(CashError::AccountNotInitialized, role, account_info)
     } else {
         Ok(account)
     }
 }
+/// transfer the given amount of SOL from a program owned source to receiver
+pub fn transfer_account_lamports(
+    source: &AccountInfo,
+    receiver: &AccountInfo,
+    amount: u64,
+) -> Result<(), ProgramError> {
+    let mut from = source.try_borrow_mut_lamports()?;
+    let mut to = receiver.try_borrow_mut_lamports()?;
+    **from = from.checked_sub(amount).ok_or(CashError::Underflow)?;
+    **to = to.checked_add(amount).ok_or(CashError::Overflow)?;
+    Ok(())
+}
+
 /// transfer all the SOL from source to receiver
 pub fn empty_account_balance(
     source: &AccountInfo,
@@ -135,6 +238,35 @@ pub fn empty_account_balance(
     Ok(())
 }
 
+/// Reject a token account passed for two different wallets among the accounts moving funds
+///
+/// Each leg is a token account with the wallet it belongs to. The vault passed as the recipient
+/// would turn the payout into a self transfer still booked as redeemed, while a wallet playing
+/// two roles, e.g. the owner paying the fees, legitimately repeats its token account
+pub fn assert_distinct_token_accounts(legs: &[(&Pubkey, &Pubkey)]) -> ProgramResult {
+    for (i, (token, wallet)) in legs.iter().enumerate() {
+        for (other_token, other_wallet) in &legs[i + 1..] {
+            if cmp_pubkeys(token, other_token) && !cmp_pubkeys(wallet, other_wallet) {
+                msg!("Token account {} passed for {} and {}", token, wallet, other_wallet);
+                return Err(CashError::DuplicateAccount.into());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Run a transfer leg only when it moves something, every executed leg is logged by name
+pub fn transfer_if_nonzero<F>(leg: &str, amount: u64, transfer: F) -> ProgramResult
+where
+    F: FnOnce(u64) -> ProgramResult,
+{
+    if amount == 0 {
+        return Ok(());
+    }
+    msg!("Transfer {} {}", leg, amount);
+    transfer(amount)
+}
+
 pub fn transfer<'a>(
     is_native: bool,
     source_account_info: &AccountInfo<'a>,
@@ -191,6 +323,121 @@ pub fn spl_token_transfer<'a>(
     )
 }
 
+/// Offsets of the decimals and the initialized flag in the base mint layout, shared by
+/// spl-token and token-2022
+const MINT_DECIMALS_OFFSET: usize = 44;
+const MINT_INITIALIZED_OFFSET: usize = 45;
+
+/// Assert the mint is still owned by `owner` with `decimals`, reads two bytes of the base
+/// layout instead of unpacking the mint
+pub fn assert_mint_unchanged(
+    mint_info: &AccountInfo,
+    owner: &Pubkey,
+    decimals: u8,
+) -> ProgramResult {
+    if !cmp_pubkeys(mint_info.owner, owner) {
+        msg!("Mint owned by {} instead of {}", mint_info.owner, owner);
+        return Err(CashError::MintConfigurationChanged.into());
+    }
+    let data = mint_info.try_borrow_data()?;
+    if data.len() < Mint::LEN || data[MINT_INITIALIZED_OFFSET] != 1 {
+        msg!("Mint is no longer initialized");
+        return Err(CashError::MintConfigurationChanged.into());
+    }
+    if data[MINT_DECIMALS_OFFSET] != decimals {
+        msg!("Stored decimals {} but the mint has {}", decimals, data[MINT_DECIMALS_OFFSET]);
+        return Err(CashError::MintConfigurationChanged.into());
+    }
+    Ok(())
+}
+
+/// Whether the token account has the token-2022 MemoTransfer extension requiring incoming memos
+pub fn requires_memo(account: &AccountInfo) -> Result<bool, ProgramError> {
+    let data = account.try_borrow_data()?;
+    let state = StateWithExtensions::<Account>::unpack(&data)?;
+    Ok(memo_required(&state))
+}
+
+/// Whether a vault of the mint can burn, wrapped SOL can't be burned and a vault created
+/// frozen by the DefaultAccountState extension can't either
+pub fn mint_supports_burn(mint_info: &AccountInfo) -> Result<bool, ProgramError> {
+    if is_native_mint(mint_info.key) {
+        return Ok(false);
+    }
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    Ok(match mint.get_extension::<DefaultAccountState>() {
+        Ok(default_state) => default_state.state != AccountState::Frozen as u8,
+        Err(_) => true,
+    })
+}
+
+/// SPL burn instruction.
+pub fn spl_token_burn<'a>(
+    source: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    token_id: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    signers_seeds: &[&[&[u8]]],
+) -> Result<(), ProgramError> {
+    let ix = spl_token_2022::instruction::burn_checked(
+        token_id,
+        source.key,
+        mint.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+
+    invoke_signed(
+        &ix,
+        &[source.clone(), mint.clone(), authority.clone()],
+        signers_seeds,
+    )
+}
+
+/// SPL approve instruction.
+pub fn spl_token_approve<'a>(
+    source: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    delegate: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    token_id: &Pubkey,
+    amount: u64,
+    decimals: u8,
+) -> Result<(), ProgramError> {
+    let ix = spl_token_2022::instruction::approve_checked(
+        token_id,
+        source.key,
+        mint.key,
+        delegate.key,
+        owner.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+
+    invoke(
+        &ix,
+        &[source.clone(), mint.clone(), delegate.clone(), owner.clone()],
+    )
+}
+
+/// SPL memo instruction.
+pub fn spl_memo<'a>(memo_program: &AccountInfo<'a>, memo: &str) -> Result<(), ProgramError> {
+    invoke(
+        &Instruction {
+            program_id: *memo_program.key,
+            accounts: vec![],
+            data: memo.as_bytes().to_vec(),
+        },
+        &[memo_program.clone()],
+    )
+}
+
 /// Native Sync.
 pub fn sync_native<'a>(
     account: &AccountInfo<'a>,
@@ -241,6 +488,45 @@ pub fn spl_token_close<'a>(
     )
 }
 
+/// Close a native vault into its program owned authority, then pay at least `amount` lamports
+/// to the owner and the rent exempt reserve of the vault to `rent_receiver` directly from the
+/// authority's balance
+///
+/// Only the reserve recorded in the vault is rent, lamports sent to the vault without a
+/// sync_native belong to the owner with the rest of the leftover
+pub fn unwrap_native_vault<'a>(
+    vault: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    owner: &AccountInfo<'a>,
+    rent_receiver: &AccountInfo<'a>,
+    token_id: &Pubkey,
+    amount: u64,
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let vault_token: Account = assert_initialized(vault, Some("vault"))?;
+    let rent: u64 = Option::from(vault_token.is_native).ok_or(CashError::InvalidMint)?;
+    let lamports_before = authority.lamports();
+    spl_token_close(vault, authority, authority, token_id, signers_seeds)?;
+    let reclaimed = authority
+        .lamports()
+        .checked_sub(lamports_before)
+        .ok_or(CashError::Underflow)?;
+    let refund = reclaimed.checked_sub(rent).ok_or(CashError::Underflow)?;
+    if refund < amount {
+        return Err(CashError::Underflow.into());
+    }
+    if refund > amount {
+        msg!("Returning {} unsynced lamports of the vault to the owner", refund - amount);
+    }
+    if refund > 0 {
+        transfer_account_lamports(authority, owner, refund)?;
+    }
+    if rent > 0 {
+        transfer_account_lamports(authority, rent_receiver, rent)?;
+    }
+    Ok(())
+}
+
 /// SPL transfer instruction.
 pub fn spl_token_init<'a>(
     token_program_id: &Pubkey,
@@ -263,12 +549,9 @@ pub fn spl_token_init<'a>(
     )
 }
 
+/// Fee in basis points of the amount, rounded down
 pub fn calculate_fee(amount: u64, fee_basis_points: u64) -> Result<u64, ProgramError> {
-    Ok(amount
-        .checked_mul(fee_basis_points)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?
-        .checked_div(10000)
-        .ok_or::<ProgramError>(CashError::Overflow.into())?)
+    Ok(floor_share(amount, fee_basis_points, 10000)?)
 }
 
 pub fn calculate_amount_with_fee(amount: u64, fee_basis_points: u64) -> Result<u64, ProgramError> {
@@ -285,7 +568,13 @@ pub fn create_new_account_raw<'a>(
     system_program_info: &AccountInfo<'a>,
     size: usize,
     signer_seeds: &[&[u8]],
-) -> ProgramResult {
+) -> Result<u64, ProgramError> {
+    assert_account_key(
+        rent_sysvar_info,
+        &sysvar::rent::id(),
+        Some(CashError::InvalidRentSysvar),
+        Some("rent sysvar"),
+    )?;
     let rent = &Rent::from_account_info(rent_sysvar_info)?;
     let required_lamports = rent.minimum_balance(size);
 
@@ -316,7 +605,34 @@ pub fn create_new_account_raw<'a>(
         accounts,
         &[&signer_seeds],
     )?;
-    Ok(())
+    Ok(required_lamports)
+}
+
+/// Create the associated token account unless it already exists, returns the lamports
+/// the payer spent on it, zero when the creation was a no-op
+pub fn create_associated_token_account_idempotent_raw<'a>(
+    payer_info: &AccountInfo<'a>,
+    token_info: &AccountInfo<'a>,
+    wallet_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    token_program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+    let lamports_before = payer_info.lamports();
+    invoke(
+        &create_associated_token_account_idempotent(
+            payer_info.key,
+            wallet_info.key,
+            mint_info.key,
+            token_program_id,
+        ),
+        &[
+            payer_info.clone(),
+            token_info.clone(),
+            wallet_info.clone(),
+            mint_info.clone(),
+        ],
+    )?;
+    Ok(lamports_before.saturating_sub(payer_info.lamports()))
 }
 
 pub fn create_associated_token_account_raw<'a>(
@@ -345,6 +661,36 @@ pub fn cmp_pubkeys(a: &Pubkey, b: &Pubkey) -> bool {
     sol_memcmp(a.as_ref(), b.as_ref(), PUBKEY_BYTES) == 0
 }
 
+/// Find an optional account by key among the instruction accounts
+pub fn find_optional_account<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>],
+    key: &Pubkey,
+) -> Option<&'b AccountInfo<'a>> {
+    accounts
+        .iter()
+        .find(|account_info| cmp_pubkeys(account_info.key, key))
+}
+
+/// Whether the mint is the wrapped SOL mint of either token program
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    cmp_pubkeys(mint, &spl_token::native_mint::id())
+        || cmp_pubkeys(mint, &spl_token_2022::native_mint::id())
+}
+
+/// Unpack the state of a program account, the data borrow is released before returning so
+/// the account can be re-borrowed or handed to a cpi right after
+pub fn load_account<T: Pack + IsInitialized>(account: &AccountInfo) -> Result<T, ProgramError> {
+    let data = account.try_borrow_data()?;
+    T::unpack(&data)
+}
+
+/// Pack the state into a program account, fails with AccountBorrowFailed instead of panicking
+/// when the data is still borrowed elsewhere
+pub fn store_account<T: Pack>(state: T, account: &AccountInfo) -> ProgramResult {
+    let mut data = account.try_borrow_mut_data()?;
+    T::pack(state, &mut data)
+}
+
 pub fn exists(account: &AccountInfo) -> Result<bool, ProgramError> {
     Ok(account.try_lamports()? > 0)
 }
@@ -354,7 +700,7 @@ pub fn get_random_value<'a>(
     recent_slothashes: &AccountInfo<'a>,
     clock: &Clock,
 ) -> Result<u64, ProgramError> {
-    let data = recent_slothashes.data.borrow();
+    let data = recent_slothashes.try_borrow_data()?;
     let most_recent = array_ref![data, 12, 8];
     //Ok(u16::from_le_bytes(random_value))
     let mut bytes = [0u8; 8];
@@ -1,4 +1,15 @@
 export enum AccountType {
   Uninitialized = 0,
   Cash = 1,
+  Config = 2,
+  MintRegistry = 3,
+  Stats = 4,
+  Buffer = 5,
+  Metadata = 6,
+  ReferralStats = 7,
+  RedemptionCommit = 8,
+  PendingRedemption = 9,
+  FeeWalletRegistry = 10,
+  AutoCancelQueue = 11,
+  WalletLimit = 12,
 }
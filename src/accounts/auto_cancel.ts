@@ -0,0 +1,65 @@
+import { Borsh, StringPublicKey } from '@metaplex-foundation/mpl-core';
+import BN from 'bn.js';
+import { AccountType } from './account';
+
+export const MAX_AUTO_CANCEL_ENTRIES = 64;
+
+export type AutoCancelEntryDataArgs = {
+  cash: StringPublicKey;
+  dueAt: BN;
+};
+
+export class AutoCancelEntryData extends Borsh.Data<AutoCancelEntryDataArgs> {
+  static readonly SCHEMA = AutoCancelEntryData.struct([
+    ['cash', 'pubkeyAsString'],
+    ['dueAt', 'u64'],
+  ]);
+  cash: StringPublicKey;
+  /** Unix timestamp from which anyone may cancel the link */
+  dueAt: BN;
+
+  constructor(args: AutoCancelEntryDataArgs) {
+    super(args);
+  }
+}
+
+export type AutoCancelQueueDataArgs = {
+  accountType: AccountType;
+  head: number;
+  len: number;
+  entries: AutoCancelEntryData[];
+};
+
+export class AutoCancelQueueData extends Borsh.Data<AutoCancelQueueDataArgs> {
+  static readonly SCHEMA = new Map([
+    ...AutoCancelEntryData.SCHEMA,
+    ...AutoCancelQueueData.struct([
+      ['accountType', 'u8'],
+      ['head', 'u16'],
+      ['len', 'u16'],
+      ['entries', [AutoCancelEntryData]],
+    ]),
+  ]);
+  accountType: AccountType;
+  /** Ring buffer of `len` entries starting at `head` */
+  head: number;
+  len: number;
+  entries: AutoCancelEntryData[];
+
+  constructor(args: AutoCancelQueueDataArgs) {
+    super(args);
+  }
+
+  /** The queued entries, oldest first */
+  queued(): AutoCancelEntryData[] {
+    return Array.from(
+      { length: this.len },
+      (_, position) => this.entries[(this.head + position) % MAX_AUTO_CANCEL_ENTRIES],
+    );
+  }
+
+  /** The entries the crank may process at `now`, a unix timestamp */
+  due(now: number): AutoCancelEntryData[] {
+    return this.queued().filter((entry) => entry.dueAt.lten(now));
+  }
+}
@@ -11,13 +11,17 @@ import bs58 from 'bs58';
 import { CashProgram } from '../cash_program';
 import { AccountType } from './account';
 
-export const MAX_CASH_LINK_DATA_LEN = 194;
+export const MAX_CASH_LINK_DATA_LEN = 633;
+export const CASH_DATA_LEN = 824;
+/** Size of the links deployed by the first release, grown by `MigrateBatch` before a write */
+export const LEGACY_CASH_DATA_LEN = 194;
 
 export enum CashState {
   Initialized = 0,
   Redeemed = 1,
   Redeeming = 2,
   Canceled = 3,
+  CancelPending = 4,
 }
 
 export enum CashDistributionType {
@@ -27,6 +31,17 @@ export enum CashDistributionType {
   Equal = 3,
 }
 
+export enum CashCompletionReason {
+  None = 0,
+  SlotsExhausted = 1,
+  FundsExhausted = 2,
+  BelowMinThreshold = 3,
+  Canceled = 4,
+  Expired = 5,
+  Emergency = 6,
+  Recovered = 7,
+}
+
 export type CashDataArgs = {
   accountType: AccountType;
   authority: StringPublicKey;
@@ -38,36 +53,225 @@ export type CashDataArgs = {
   rentFeeToRedeem: BN;
   remainingAmount: BN;
   distributionType: CashDistributionType;
-  owner: number;
+  owner: StringPublicKey;
   mint: StringPublicKey;
   totalRedemptions: BN;
   maxNumRedemptions: BN;
   minAmount: BN;
-  fingerprintEnabled: boolean;
   passKey?: StringPublicKey;
   totalWeightPpm: number;
+  feePayer: StringPublicKey;
+  rentPaidByFeePayer: BN;
+  kycRequiredAbove?: BN;
+  kycAuthority?: StringPublicKey;
+  completionReason: CashCompletionReason;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  bufferHash?: number[];
+  feeWallet?: StringPublicKey;
+  inProgress: boolean;
+  vault: StringPublicKey;
+  tokenProgram: StringPublicKey;
+  burnBps: number;
+  totalBurned: BN;
+  claimRequiresWalletSignature: boolean;
+  createdAt: BN;
+  canceledAt?: BN;
+  completedAt?: BN;
+  swap?: SwapConfigData;
+  entropySource: EntropySourceData;
+  lastEntropyRound: BN;
+  halted: boolean;
+  flash?: FlashScheduleData;
+  expiredSlots: number;
+  forfeitedAmount: BN;
+  passRotations: number;
+  intendedRecipient?: StringPublicKey;
+  recoveryRequestedAt?: BN;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  decimals?: number;
+  /** Program versions that created and last stored the link, 0 on legacy links */
+  createdByVersion: number;
+  lastTouchedVersion: number;
+  refundDestination?: StringPublicKey;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  /** Part of the remaining amount locked by payouts waiting for approval */
+  reservedAmount: BN;
+  pendingApprovals: number;
+  /** Epoch of the fee wallet registry entry collecting the platform fees */
+  feeWalletEpoch?: number;
+  /** Every redemption carries a voucher hash, kept in its receipt */
+  requireVoucher: boolean;
+  /** Program owning the mint at init, unset on links created before it was stored */
+  mintOwner?: StringPublicKey;
+  mintSupplyAtInit?: BN;
+  /** Unix timestamp from which the auto-cancel crank may cancel the link */
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  /** Keccak of the economic parameters, unset on links created before it was stored */
+  paramsHash?: Uint8Array;
+  maxSignatureAgeSlots?: BN;
+  /** Seconds after a redemption during which the payout can be clawed back */
+  clawbackWindowSeconds?: BN;
+};
+
+export type FlashScheduleDataArgs = {
+  startAt: BN;
+  interval: BN;
+};
+
+export class FlashScheduleData extends Borsh.Data<FlashScheduleDataArgs> {
+  static readonly SCHEMA = FlashScheduleData.struct([
+    ['startAt', 'u64'],
+    ['interval', 'u64'],
+  ]);
+  startAt: BN;
+  interval: BN;
+}
+
+export type SwapConfigDataArgs = {
+  targetMint: StringPublicKey;
+  ammProgram: StringPublicKey;
+  maxSlippageBps: number;
 };
 
+export class SwapConfigData extends Borsh.Data<SwapConfigDataArgs> {
+  static readonly SCHEMA = SwapConfigData.struct([
+    ['targetMint', 'pubkeyAsString'],
+    ['ammProgram', 'pubkeyAsString'],
+    ['maxSlippageBps', 'u16'],
+  ]);
+  targetMint: StringPublicKey;
+  ammProgram: StringPublicKey;
+  maxSlippageBps: number;
+
+  constructor(args: SwapConfigDataArgs) {
+    super(args);
+  }
+}
+
+export class SlotHashesEntropy extends Borsh.Data {
+  static readonly SCHEMA = SlotHashesEntropy.struct([]);
+}
+
+export class VrfAccountEntropy extends Borsh.Data<{ account: StringPublicKey }> {
+  static readonly SCHEMA = VrfAccountEntropy.struct([['account', 'pubkeyAsString']]);
+  account: StringPublicKey;
+}
+
+export type EntropySourceDataArgs =
+  | { enum: 'slotHashes'; slotHashes: SlotHashesEntropy }
+  | { enum: 'vrfAccount'; vrfAccount: VrfAccountEntropy };
+
+export class EntropySourceData extends Borsh.Data<EntropySourceDataArgs> {
+  static readonly SCHEMA = new Map<any, any>([
+    ...SlotHashesEntropy.SCHEMA,
+    ...VrfAccountEntropy.SCHEMA,
+    [
+      EntropySourceData,
+      {
+        kind: 'enum',
+        field: 'enum',
+        values: [
+          ['slotHashes', SlotHashesEntropy],
+          ['vrfAccount', VrfAccountEntropy],
+        ],
+      },
+    ],
+  ]);
+  enum: 'slotHashes' | 'vrfAccount';
+  slotHashes?: SlotHashesEntropy;
+  vrfAccount?: VrfAccountEntropy;
+
+  static slotHashes() {
+    return new EntropySourceData({ enum: 'slotHashes', slotHashes: new SlotHashesEntropy() });
+  }
+
+  static vrfAccount(account: StringPublicKey) {
+    return new EntropySourceData({
+      enum: 'vrfAccount',
+      vrfAccount: new VrfAccountEntropy({ account }),
+    });
+  }
+}
+
 export class CashData extends Borsh.Data<CashDataArgs> {
-  static readonly SCHEMA = CashData.struct([
-    ['accountType', 'u8'],
-    ['authority', 'pubkeyAsString'],
-    ['state', 'u8'],
-    ['amount', 'u64'],
-    ['feeBps', 'u16'],
-    ['networkFee', 'u64'],
-    ['baseFeeToRedeem', 'u64'],
-    ['rentFeeToRedeem', 'u64'],
-    ['remainingAmount', 'u64'],
-    ['distributionType', 'u8'],
-    ['owner', 'pubkeyAsString'],
-    ['mint', 'pubkeyAsString'],
-    ['totalRedemptions', 'u16'],
-    ['maxNumRedemptions', 'u16'],
-    ['minAmount', 'u64'],
-    ['fingerprintEnabled', 'u8'],
-    ['passKey', { kind: 'option', type: 'pubkeyAsString' }],
-    ['totalWeightPpm', 'u32'],
+  static readonly SCHEMA = new Map([
+    ...SwapConfigData.SCHEMA,
+    ...EntropySourceData.SCHEMA,
+    ...FlashScheduleData.SCHEMA,
+    ...CashData.struct([
+      ['accountType', 'u8'],
+      ['authority', 'pubkeyAsString'],
+      ['state', 'u8'],
+      ['amount', 'u64'],
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+      ['remainingAmount', 'u64'],
+      ['distributionType', 'u8'],
+      ['owner', 'pubkeyAsString'],
+      ['mint', 'pubkeyAsString'],
+      ['totalRedemptions', 'u16'],
+      ['maxNumRedemptions', 'u16'],
+      ['minAmount', 'u64'],
+      ['passKey', { kind: 'option', type: 'pubkeyAsString' }],
+      ['totalWeightPpm', 'u32'],
+      ['feePayer', 'pubkeyAsString'],
+      ['rentPaidByFeePayer', 'u64'],
+      ['kycRequiredAbove', { kind: 'option', type: 'u64' }],
+      ['kycAuthority', { kind: 'option', type: 'pubkeyAsString' }],
+      ['completionReason', 'u8'],
+      ['referralCeilingsEnabled', 'u8'],
+      ['maxReferrerFeeBps', 'u16'],
+      ['maxRefereeFeeBps', 'u16'],
+      ['bufferHash', { kind: 'option', type: [32] }],
+      ['feeWallet', { kind: 'option', type: 'pubkeyAsString' }],
+      ['inProgress', 'u8'],
+      ['vault', 'pubkeyAsString'],
+      ['tokenProgram', 'pubkeyAsString'],
+      ['burnBps', 'u16'],
+      ['totalBurned', 'u64'],
+      ['claimRequiresWalletSignature', 'u8'],
+      ['createdAt', 'u64'],
+      ['canceledAt', { kind: 'option', type: 'u64' }],
+      ['completedAt', { kind: 'option', type: 'u64' }],
+      ['swap', { kind: 'option', type: SwapConfigData }],
+      ['entropySource', EntropySourceData],
+      ['lastEntropyRound', 'u64'],
+      ['halted', 'u8'],
+      ['flash', { kind: 'option', type: FlashScheduleData }],
+      ['expiredSlots', 'u16'],
+      ['forfeitedAmount', 'u64'],
+      ['passRotations', 'u8'],
+      ['intendedRecipient', { kind: 'option', type: 'pubkeyAsString' }],
+      ['recoveryRequestedAt', { kind: 'option', type: 'u64' }],
+      ['maxPriorityFeePerRedeem', 'u64'],
+      ['allowFreezableMint', 'u8'],
+      ['commitRevealDelay', { kind: 'option', type: 'u64' }],
+      ['decimals', { kind: 'option', type: 'u8' }],
+      ['createdByVersion', 'u16'],
+      ['lastTouchedVersion', 'u16'],
+      ['refundDestination', { kind: 'option', type: 'pubkeyAsString' }],
+      ['approvalThreshold', { kind: 'option', type: 'u64' }],
+      ['approvalTtl', { kind: 'option', type: 'u64' }],
+      ['reservedAmount', 'u64'],
+      ['pendingApprovals', 'u16'],
+      ['feeWalletEpoch', { kind: 'option', type: 'u16' }],
+      ['requireVoucher', 'u8'],
+      ['mintOwner', { kind: 'option', type: 'pubkeyAsString' }],
+      ['mintSupplyAtInit', { kind: 'option', type: 'u64' }],
+      ['autoCancelAt', { kind: 'option', type: 'u64' }],
+      ['autoCancelTip', 'u64'],
+      ['paramsHash', { kind: 'option', type: [32] }],
+      ['maxSignatureAgeSlots', { kind: 'option', type: 'u64' }],
+      ['clawbackWindowSeconds', { kind: 'option', type: 'u64' }],
+    ]),
   ]);
   accountType: AccountType;
   authority: StringPublicKey;
@@ -84,9 +288,65 @@ export class CashData extends Borsh.Data<CashDataArgs> {
   totalRedemptions: number;
   maxNumRedemptions: number;
   minAmount: BN;
-  fingerprintEnabled: boolean;
   passKey?: StringPublicKey;
   totalWeightPpm: number;
+  feePayer: StringPublicKey;
+  rentPaidByFeePayer: BN;
+  kycRequiredAbove?: BN;
+  kycAuthority?: StringPublicKey;
+  completionReason: CashCompletionReason;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  bufferHash?: number[];
+  feeWallet?: StringPublicKey;
+  inProgress: boolean;
+  vault: StringPublicKey;
+  tokenProgram: StringPublicKey;
+  burnBps: number;
+  totalBurned: BN;
+  claimRequiresWalletSignature: boolean;
+  createdAt: BN;
+  canceledAt?: BN;
+  completedAt?: BN;
+  swap?: SwapConfigData;
+  entropySource: EntropySourceData;
+  lastEntropyRound: BN;
+  halted: boolean;
+  flash?: FlashScheduleData;
+  expiredSlots: number;
+  forfeitedAmount: BN;
+  passRotations: number;
+  intendedRecipient?: StringPublicKey;
+  recoveryRequestedAt?: BN;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  decimals?: number;
+  /** Program versions that created and last stored the link, 0 on legacy links */
+  createdByVersion: number;
+  lastTouchedVersion: number;
+  refundDestination?: StringPublicKey;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  /** Part of the remaining amount locked by payouts waiting for approval */
+  reservedAmount: BN;
+  pendingApprovals: number;
+  /** Epoch of the fee wallet registry entry collecting the platform fees */
+  feeWalletEpoch?: number;
+  /** Every redemption carries a voucher hash, kept in its receipt */
+  requireVoucher: boolean;
+  /** Program owning the mint at init, unset on links created before it was stored */
+  mintOwner?: StringPublicKey;
+  mintSupplyAtInit?: BN;
+  /** Unix timestamp from which the auto-cancel crank may cancel the link */
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  /** Keccak of the economic parameters, unset on links created before it was stored */
+  paramsHash?: Uint8Array;
+  maxSignatureAgeSlots?: BN;
+  /** Seconds after a redemption during which the payout can be clawed back */
+  clawbackWindowSeconds?: BN;
 
   constructor(args: CashDataArgs) {
     super(args);
@@ -97,12 +357,22 @@ export class Cash extends Account<CashData> {
   static readonly PREFIX = 'cash';
   constructor(pubkey: AnyPublicKey, info: AccountInfo<Buffer>) {
     super(pubkey, info);
-    this.data = CashData.deserialize(this.info.data);
+    // a smaller link reads with the fields appended since its release unset
+    const data =
+      this.info.data.length < CASH_DATA_LEN
+        ? Buffer.concat([this.info.data, Buffer.alloc(CASH_DATA_LEN - this.info.data.length)])
+        : this.info.data;
+    this.data = CashData.deserialize(data);
     if (!this.assertOwner(CashProgram.PUBKEY)) {
       throw ERROR_INVALID_OWNER();
     }
   }
 
+  /** A link of an earlier release must be grown to the current layout before it is written */
+  isLegacy() {
+    return this.info.data.length < CASH_DATA_LEN;
+  }
+
   static getPDA(reference: string) {
     const [pubKey] = CashProgram.cashAccount(reference);
     return pubKey;
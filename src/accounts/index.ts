@@ -1 +1,6 @@
 export * from './cash';
+export * from './redemption';
+export * from './metadata';
+export * from './referral';
+export * from './auto_cancel';
+export * from './wallet_limit';
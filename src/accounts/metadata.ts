@@ -0,0 +1,57 @@
+import {
+  Borsh,
+  AnyPublicKey,
+  ERROR_INVALID_OWNER,
+  Account,
+  StringPublicKey,
+} from '@metaplex-foundation/mpl-core';
+import { AccountInfo, PublicKey } from '@solana/web3.js';
+import { CashProgram } from '../cash_program';
+import { AccountType } from './account';
+
+export const MAX_METADATA_URI_LEN = 96;
+export const METADATA_DATA_LEN = 32 + 1 + 32 + 4 + MAX_METADATA_URI_LEN;
+
+export type CashMetadataDataArgs = {
+  rentPayer: StringPublicKey;
+  accountType: AccountType;
+  cash: StringPublicKey;
+  uri: string;
+};
+
+export class CashMetadataData extends Borsh.Data<CashMetadataDataArgs> {
+  static readonly SCHEMA = CashMetadataData.struct([
+    ['rentPayer', 'pubkeyAsString'],
+    ['accountType', 'u8'],
+    ['cash', 'pubkeyAsString'],
+    ['uri', 'string'],
+  ]);
+  rentPayer: StringPublicKey;
+  accountType: AccountType;
+  cash: StringPublicKey;
+  uri: string;
+
+  constructor(args: CashMetadataDataArgs) {
+    super(args);
+  }
+}
+
+export class CashMetadata extends Account<CashMetadataData> {
+  static readonly PREFIX = 'meta';
+  constructor(pubkey: AnyPublicKey, info: AccountInfo<Buffer>) {
+    super(pubkey, info);
+    this.data = CashMetadataData.deserialize(this.info.data);
+    if (!this.assertOwner(CashProgram.PUBKEY)) {
+      throw ERROR_INVALID_OWNER();
+    }
+  }
+
+  static getPDA(cash: PublicKey) {
+    const [pubKey] = CashProgram.metadataAccount(cash);
+    return pubKey;
+  }
+}
+
+/** Whether the program accepts the uri: non empty, at most 96 bytes, printable ascii without spaces */
+export const isValidMetadataUri = (uri: string): boolean =>
+  uri.length > 0 && uri.length <= MAX_METADATA_URI_LEN && /^[\x21-\x7e]+$/.test(uri);
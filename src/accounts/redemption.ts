@@ -0,0 +1,79 @@
+import { Borsh, StringPublicKey } from '@metaplex-foundation/mpl-core';
+import BN from 'bn.js';
+
+export const REDEMPTION_INDEX_DATA_LEN = 72;
+
+export type RedemptionIndexDataArgs = {
+  rentPayer: StringPublicKey;
+  wallet: StringPublicKey;
+  amount: BN;
+};
+
+export class RedemptionIndexData extends Borsh.Data<RedemptionIndexDataArgs> {
+  static readonly SCHEMA = RedemptionIndexData.struct([
+    ['rentPayer', 'pubkeyAsString'],
+    ['wallet', 'pubkeyAsString'],
+    ['amount', 'u64'],
+  ]);
+  rentPayer: StringPublicKey;
+  wallet: StringPublicKey;
+  amount: BN;
+
+  constructor(args: RedemptionIndexDataArgs) {
+    super(args);
+  }
+
+  static fromAccountData(data: Buffer): RedemptionIndexData {
+    return RedemptionIndexData.deserialize(data);
+  }
+}
+
+export const REDEMPTION_RECEIPT_DATA_LEN = 32 + 32 + 1 + 32 + 32 + 8 * 3;
+/** Size of the receipts of the first release, a single byte flagging the wallet as redeemed */
+export const LEGACY_REDEMPTION_RECEIPT_DATA_LEN = 1;
+
+export type RedemptionReceiptDataArgs = {
+  rentPayer: StringPublicKey;
+  authority: StringPublicKey;
+  voucherHash?: Uint8Array;
+  wallet: StringPublicKey;
+  amount: BN;
+  redeemedAt: BN;
+  clawedBack: BN;
+};
+
+export class RedemptionReceiptData extends Borsh.Data<RedemptionReceiptDataArgs> {
+  static readonly SCHEMA = RedemptionReceiptData.struct([
+    ['rentPayer', 'pubkeyAsString'],
+    ['authority', 'pubkeyAsString'],
+    ['voucherHash', { kind: 'option', type: [32] }],
+    ['wallet', 'pubkeyAsString'],
+    ['amount', 'u64'],
+    ['redeemedAt', 'u64'],
+    ['clawedBack', 'u64'],
+  ]);
+  rentPayer: StringPublicKey;
+  /** The link authority that signed the redemption */
+  authority: StringPublicKey;
+  /** Hash of the off chain voucher that authorized the payout */
+  voucherHash?: Uint8Array;
+  /** The redeeming wallet, the payout and its unix timestamp, unset on older receipts */
+  wallet: StringPublicKey;
+  amount: BN;
+  redeemedAt: BN;
+  /** Part of the payout clawed back within the clawback window of the link */
+  clawedBack: BN;
+
+  constructor(args: RedemptionReceiptDataArgs) {
+    super(args);
+  }
+
+  /** Deserialize a receipt account, the fields an older one lacks read as defaults */
+  static fromAccountData(data: Buffer): RedemptionReceiptData {
+    return RedemptionReceiptData.deserialize(
+      data.length < REDEMPTION_RECEIPT_DATA_LEN
+        ? Buffer.concat([data, Buffer.alloc(REDEMPTION_RECEIPT_DATA_LEN - data.length)])
+        : data,
+    );
+  }
+}
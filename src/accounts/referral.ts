@@ -0,0 +1,108 @@
+import {
+  Borsh,
+  AnyPublicKey,
+  ERROR_INVALID_OWNER,
+  Account,
+  StringPublicKey,
+} from '@metaplex-foundation/mpl-core';
+import { AccountInfo, PublicKey } from '@solana/web3.js';
+import BN from 'bn.js';
+import { CashProgram } from '../cash_program';
+import { AccountType } from './account';
+
+export const MAX_REFERRAL_MINTS = 4;
+export const MAX_VESTING_BUCKETS = 8;
+export const REFERRAL_STATS_DATA_LEN =
+  1 + 32 + 8 + 4 + (32 + 8) * MAX_REFERRAL_MINTS + 1 + 8 + 4 + (32 + 4 + 8) * MAX_VESTING_BUCKETS;
+
+export type ReferralMintEarningsDataArgs = {
+  mint: StringPublicKey;
+  totalEarned: BN;
+};
+
+export class ReferralMintEarningsData extends Borsh.Data<ReferralMintEarningsDataArgs> {
+  static readonly SCHEMA = ReferralMintEarningsData.struct([
+    ['mint', 'pubkeyAsString'],
+    ['totalEarned', 'u64'],
+  ]);
+  mint: StringPublicKey;
+  totalEarned: BN;
+
+  constructor(args: ReferralMintEarningsDataArgs) {
+    super(args);
+  }
+}
+
+export type VestingBucketDataArgs = {
+  mint: StringPublicKey;
+  /** Days since the unix epoch */
+  day: number;
+  amount: BN;
+};
+
+export class VestingBucketData extends Borsh.Data<VestingBucketDataArgs> {
+  static readonly SCHEMA = VestingBucketData.struct([
+    ['mint', 'pubkeyAsString'],
+    ['day', 'u32'],
+    ['amount', 'u64'],
+  ]);
+  mint: StringPublicKey;
+  day: number;
+  amount: BN;
+
+  constructor(args: VestingBucketDataArgs) {
+    super(args);
+  }
+}
+
+export type ReferralStatsDataArgs = {
+  accountType: AccountType;
+  referrer: StringPublicKey;
+  redemptionCount: BN;
+  mints: ReferralMintEarningsData[];
+  flaggedAt: BN | null;
+  vesting: VestingBucketData[];
+};
+
+export class ReferralStatsData extends Borsh.Data<ReferralStatsDataArgs> {
+  static readonly SCHEMA = new Map([
+    ...ReferralMintEarningsData.SCHEMA,
+    ...VestingBucketData.SCHEMA,
+    ...ReferralStatsData.struct([
+      ['accountType', 'u8'],
+      ['referrer', 'pubkeyAsString'],
+      ['redemptionCount', 'u64'],
+      ['mints', [ReferralMintEarningsData]],
+      ['flaggedAt', { kind: 'option', type: 'u64' }],
+      ['vesting', [VestingBucketData]],
+    ]),
+  ]);
+  accountType: AccountType;
+  referrer: StringPublicKey;
+  redemptionCount: BN;
+  mints: ReferralMintEarningsData[];
+  /** Set by the admin, the fees unvested at that time are forfeited */
+  flaggedAt: BN | null;
+  /** Referrer fees held in escrow until they vest, oldest first */
+  vesting: VestingBucketData[];
+
+  constructor(args: ReferralStatsDataArgs) {
+    super(args);
+  }
+}
+
+export class ReferralStats extends Account<ReferralStatsData> {
+  static readonly PREFIX = 'referral';
+  constructor(pubkey: AnyPublicKey, info: AccountInfo<Buffer>) {
+    super(pubkey, info);
+    this.data = ReferralStatsData.deserialize(this.info.data);
+    if (!this.assertOwner(CashProgram.PUBKEY)) {
+      throw ERROR_INVALID_OWNER();
+    }
+  }
+
+  static getPDA(referrer: PublicKey) {
+    const [pubKey] = CashProgram.referralStatsAccount(referrer);
+    return pubKey;
+  }
+}
@@ -0,0 +1,50 @@
+import { Borsh, StringPublicKey } from '@metaplex-foundation/mpl-core';
+import BN from 'bn.js';
+import { AccountType } from './account';
+
+/** Seconds the daily cap of a mint applies to */
+export const WALLET_LIMIT_WINDOW = 24 * 60 * 60;
+
+export type WalletLimitDataArgs = {
+  accountType: AccountType;
+  wallet: StringPublicKey;
+  mint: StringPublicKey;
+  rentPayer: StringPublicKey;
+  windowStart: BN;
+  amount: BN;
+};
+
+export class WalletLimitData extends Borsh.Data<WalletLimitDataArgs> {
+  static readonly SCHEMA = WalletLimitData.struct([
+    ['accountType', 'u8'],
+    ['wallet', 'pubkeyAsString'],
+    ['mint', 'pubkeyAsString'],
+    ['rentPayer', 'pubkeyAsString'],
+    ['windowStart', 'u64'],
+    ['amount', 'u64'],
+  ]);
+  accountType: AccountType;
+  wallet: StringPublicKey;
+  mint: StringPublicKey;
+  /** Funded the account, gets the rent back when the account is swept */
+  rentPayer: StringPublicKey;
+  /** Unix timestamp of the first redemption of the window */
+  windowStart: BN;
+  /** Payouts of the window, in raw units of the mint */
+  amount: BN;
+
+  constructor(args: WalletLimitDataArgs) {
+    super(args);
+  }
+
+  /** The window ended at `now`, a unix timestamp, the next redemption opens a new one */
+  stale(now: number): boolean {
+    return this.windowStart.addn(WALLET_LIMIT_WINDOW).lten(now);
+  }
+
+  /** What the wallet may still redeem at `now` under a daily cap */
+  remaining(dailyCap: BN, now: number): BN {
+    const used = this.stale(now) ? new BN(0) : this.amount;
+    return BN.max(dailyCap.sub(used), new BN(0));
+  }
+}
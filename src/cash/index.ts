@@ -1,11 +1,14 @@
 import {
+  AccountMeta,
   PublicKey,
   Transaction,
   TransactionInstruction,
   SYSVAR_RENT_PUBKEY,
   SYSVAR_CLOCK_PUBKEY,
   SYSVAR_SLOT_HASHES_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   SystemProgram,
+  Ed25519Program,
   Connection,
   Keypair,
   Commitment,
@@ -22,21 +25,97 @@ import {
 } from '@solana/web3.js';
 import * as spl from '@solana/spl-token';
 import BN from 'bn.js';
-import { InitializeCashInput, ResultContext, CashInput, RedeemCashInput } from './types';
+import bs58 from 'bs58';
+import {
+  InitializeCashInput,
+  ResultContext,
+  CashInput,
+  RedeemCashInput,
+  CashHistory,
+  CashHistoryRedemption,
+} from './types';
 import { CashProgram } from '../cash_program';
-import { Cash, CashState } from '../accounts/cash';
 import {
+  Cash,
+  CashState,
+  EntropySourceData,
+  FlashScheduleData,
+  SwapConfigData,
+} from '../accounts/cash';
+import { RedemptionIndexData, RedemptionReceiptData } from '../accounts/redemption';
+import { AutoCancelEntryData, AutoCancelQueueData } from '../accounts/auto_cancel';
+import { CashMetadata, isValidMetadataUri } from '../accounts/metadata';
+import {
+  CancelBeginArgs,
+  CancelBeginParams,
   CancelCashArgs,
   CancelCashParams,
+  CancelFinalizeArgs,
+  CancelFinalizeParams,
+  CancelLinkSeedsData,
+  ProcessAutoCancelQueueArgs,
+  ProcessAutoCancelQueueParams,
+  EmergencyHaltArgs,
+  EmergencyHaltParams,
+  EmergencyReleaseArgs,
+  EmergencyWithdrawArgs,
+  EmergencyWithdrawParams,
+  ExpireSlotArgs,
+  CloseReferralStatsArgs,
+  CloseReferralStatsParams,
+  ClaimReferralFeesArgs,
+  ClaimReferralFeesParams,
+  FlagReferrerArgs,
+  FlagReferrerParams,
+  CommitRedemptionArgs,
+  CommitRedemptionParams,
+  SweepCommitArgs,
+  SweepCommitParams,
+  ExecuteRecoveryArgs,
+  ExecuteRecoveryParams,
+  InitiateRecoveryArgs,
+  InitiateRecoveryParams,
+  RotatePassKeyArgs,
+  RotatePassKeyParams,
+  SplitCashArgs,
+  SplitCashParams,
+  BackfillVersionArgs,
+  BackfillVersionParams,
+  MigrateBatchArgs,
+  MigrateBatchParams,
+  ApproveRedemptionArgs,
+  ApproveRedemptionParams,
+  RejectRedemptionArgs,
+  RejectRedemptionParams,
   InitCashArgs,
   InitCashParams,
   CloseCashArgs,
   CloseCashParams,
+  ClawbackRedemptionArgs,
+  ClawbackRedemptionParams,
+  RevokeClawbackArgs,
+  RevokeClawbackParams,
+  RecipientPermitData,
   RedeemCashLinkArgs,
   RedeemCashLinkParams,
+  recipientPermitMessage,
+  UpdateMetadataArgs,
+  UpdateMetadataParams,
 } from '../transactions';
 import { Account } from '@metaplex-foundation/mpl-core';
 
+/** Give every occurrence of a key the union of its privileges, as the program builder does */
+const mergeDuplicateKeys = (keys: AccountMeta[]) => {
+  for (const key of keys) {
+    const duplicates = keys.filter((other) => other.pubkey.equals(key.pubkey));
+    key.isSigner = duplicates.some((other) => other.isSigner);
+    key.isWritable = duplicates.some((other) => other.isWritable);
+  }
+};
+
+/** Receipts fetched per getMultipleAccountsInfo call, the rpc limit */
+const HISTORY_PAGE_SIZE = 100;
+
 export const FAILED_TO_FIND_ACCOUNT = 'Failed to find account';
 export const INVALID_ACCOUNT_OWNER = 'Invalid account owner';
 export const INVALID_AUTHORITY = 'Invalid _authority';
@@ -53,9 +132,70 @@ export const FEE_MISMATCH = 'Fee mismatch';
 export const TRANSACTION_SEND_ERROR = 'Transaction send error';
 export const FINGERPRINT_NOT_FOUND = 'Fingerprint required';
 export const REFERRER_WALLET = 'Referrer required';
+export const INVALID_METADATA_URI = 'Invalid metadata uri';
+export const SWAP_POOL_REQUIRED = 'Swap pool required';
+export const INVALID_UI_AMOUNT = 'Invalid ui amount';
 
 export const kTokenProgramRent = 2039280;
 
+/**
+ * Total the owner must hold to create a cash link, mirrors `compute_required_total` in the program:
+ * the amount, the platform fee, the redemption fees and priority fee cap for every slot and the
+ * network fee.
+ */
+export type InitTotals = {
+  principal: BN;
+  platformFee: BN;
+  networkFee: BN;
+  redemptionFeeReserve: BN;
+  total: BN;
+};
+
+/** Mirrors the breakdown the program returns from a simulated init */
+export const computeInitTotals = (input: InitializeCashInput): InitTotals => {
+  const principal = new BN(input.amount);
+  const platformFee = principal.muln(input.feeBps ?? 0).divn(10000);
+  const networkFee = new BN(input.networkFee ?? 0);
+  const redemptionFeeReserve = new BN(input.baseFeeToRedeem ?? 0)
+    .add(new BN(input.rentFeeToRedeem ?? 0))
+    .add(new BN(input.maxPriorityFeePerRedeem ?? 0))
+    .muln(input.maxNumRedemptions);
+  return {
+    principal,
+    platformFee,
+    networkFee,
+    redemptionFeeReserve,
+    total: principal.add(platformFee).add(redemptionFeeReserve).add(networkFee),
+  };
+};
+
+export const computeRequiredTotal = (input: InitializeCashInput): BN =>
+  computeInitTotals(input).total;
+
+/**
+ * Parse a ui amount into raw token units, mirrors `ui_amount_to_raw` in the program crate:
+ * digits with at most one `.` and at most `decimals` fractional digits, no exponent or sign.
+ */
+export const uiAmountToRaw = (ui: string, decimals: number): BN => {
+  const match = /^(\d+)(?:\.(\d+))?$/.exec(ui);
+  if (!match || (match[2] ?? '').length > decimals) {
+    throw new Error(INVALID_UI_AMOUNT);
+  }
+  const raw = new BN(match[1] + (match[2] ?? '').padEnd(decimals, '0'));
+  if (raw.bitLength() > 64) {
+    throw new Error(INVALID_UI_AMOUNT);
+  }
+  return raw;
+};
+
+/** Format raw token units as a ui amount with the trailing fractional zeros trimmed */
+export const rawToUiAmount = (raw: BN, decimals: number): string => {
+  const digits = raw.toString().padStart(decimals + 1, '0');
+  const whole = digits.slice(0, digits.length - decimals);
+  const fraction = digits.slice(digits.length - decimals).replace(/0+$/, '');
+  return fraction ? `${whole}.${fraction}` : whole;
+};
+
 export class CashClient {
   private _feePayer: Keypair;
   private _authority: Keypair;
@@ -238,6 +378,11 @@ export class CashClient {
     const programId = new PublicKey(input.tokenProgramId);
     const ownerTokenAccount = spl.getAssociatedTokenAddressSync(mint, owner, true, programId);
     const instructions = [];
+    if (cash.isLegacy()) {
+      instructions.push(
+        this.migrateBatchInstruction({ feePayer: this.feePayer, cashes: [cash.pubkey] }),
+      );
+    }
     const cancelInstruction = await this.cancelInstruction({
       authority: this.authority,
       cash: cash.pubkey,
@@ -284,6 +429,9 @@ export class CashClient {
         isSigner: false,
         isWritable: false,
       },
+      ...(params.refundDestination
+        ? [{ pubkey: params.refundDestination, isSigner: false, isWritable: true }]
+        : []),
     ];
     return new TransactionInstruction({
       keys,
@@ -295,6 +443,443 @@ export class CashClient {
     });
   };
 
+  cancelBeginInstruction = (params: CancelBeginParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: CancelBeginArgs.serialize({
+        cashBump: params.cashBump,
+        cashReference: params.cashReference,
+        maxAmount: params.maxAmount,
+      }),
+      keys: [
+        { pubkey: params.authority, isSigner: true, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+        { pubkey: params.owner, isSigner: false, isWritable: false },
+        { pubkey: params.ownerToken, isSigner: false, isWritable: true },
+        { pubkey: params.vaultToken, isSigner: false, isWritable: true },
+        { pubkey: params.mint, isSigner: false, isWritable: false },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  cancelFinalizeInstruction = (params: CancelFinalizeParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: CancelFinalizeArgs.serialize({
+        cashBump: params.cashBump,
+        cashReference: params.cashReference,
+      }),
+      keys: [
+        { pubkey: params.authority, isSigner: true, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+        { pubkey: params.feePayer, isSigner: false, isWritable: true },
+        { pubkey: params.vaultToken, isSigner: false, isWritable: true },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  processAutoCancelQueueInstruction = (
+    params: ProcessAutoCancelQueueParams,
+  ): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: ProcessAutoCancelQueueArgs.serialize({
+        links: params.links.map(
+          (link) =>
+            new CancelLinkSeedsData({
+              cashBump: link.cashBump,
+              cashReference: link.cashReference,
+            }),
+        ),
+      }),
+      keys: [
+        { pubkey: params.cranker, isSigner: true, isWritable: false },
+        { pubkey: CashProgram.autoCancelQueueAccount()[0], isSigner: false, isWritable: true },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+        ...params.links.flatMap((link) => [
+          { pubkey: link.cash, isSigner: false, isWritable: true },
+          { pubkey: link.owner, isSigner: false, isWritable: true },
+          { pubkey: link.ownerToken, isSigner: false, isWritable: true },
+          { pubkey: link.feePayer, isSigner: false, isWritable: true },
+          { pubkey: link.vaultToken, isSigner: false, isWritable: true },
+          { pubkey: link.mint, isSigner: false, isWritable: false },
+          { pubkey: link.crankerToken, isSigner: false, isWritable: true },
+        ]),
+      ],
+    });
+  };
+
+  emergencyHaltInstruction = (
+    params: EmergencyHaltParams,
+    halted: boolean,
+  ): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: halted ? EmergencyHaltArgs.serialize() : EmergencyReleaseArgs.serialize(),
+      keys: [
+        { pubkey: params.authority, isSigner: true, isWritable: false },
+        { pubkey: params.admin, isSigner: true, isWritable: false },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+      ],
+    });
+  };
+
+  emergencyWithdrawInstruction = (params: EmergencyWithdrawParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: EmergencyWithdrawArgs.serialize({
+        cashBump: params.cashBump,
+        cashReference: params.cashReference,
+      }),
+      keys: [
+        { pubkey: params.authority, isSigner: true, isWritable: false },
+        { pubkey: params.admin, isSigner: true, isWritable: false },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+        { pubkey: params.vaultToken, isSigner: false, isWritable: true },
+        { pubkey: params.recoveryToken, isSigner: false, isWritable: true },
+        { pubkey: params.mint, isSigner: false, isWritable: false },
+        { pubkey: params.feePayer, isSigner: false, isWritable: true },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  clawbackRedemptionInstruction = (params: ClawbackRedemptionParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: ClawbackRedemptionArgs.serialize({
+        cashBump: params.cashBump,
+        cashReference: params.cashReference,
+        amount: params.amount,
+      }),
+      keys: [
+        { pubkey: params.authority, isSigner: true, isWritable: false },
+        { pubkey: params.admin, isSigner: true, isWritable: false },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+        {
+          pubkey: CashProgram.redemptionAccount(params.cash, params.wallet)[0],
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: params.recipientToken, isSigner: false, isWritable: true },
+        { pubkey: params.destination, isSigner: false, isWritable: true },
+        { pubkey: params.mint, isSigner: false, isWritable: false },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  revokeClawbackInstruction = (params: RevokeClawbackParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: RevokeClawbackArgs.serialize({
+        cashBump: params.cashBump,
+        cashReference: params.cashReference,
+      }),
+      keys: [
+        { pubkey: params.cranker, isSigner: true, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: false },
+        {
+          pubkey: CashProgram.redemptionAccount(params.cash, params.wallet)[0],
+          isSigner: false,
+          isWritable: false,
+        },
+        { pubkey: params.recipientToken, isSigner: false, isWritable: true },
+        { pubkey: params.vaultToken, isSigner: false, isWritable: true },
+        { pubkey: params.mint, isSigner: false, isWritable: false },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+        ...(params.memoProgram
+          ? [{ pubkey: params.memoProgram, isSigner: false, isWritable: false }]
+          : []),
+      ],
+    });
+  };
+
+  expireSlotInstruction = (cash: PublicKey): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: ExpireSlotArgs.serialize(),
+      keys: [{ pubkey: cash, isSigner: false, isWritable: true }],
+    });
+  };
+
+  rotatePassKeyInstruction = (params: RotatePassKeyParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: RotatePassKeyArgs.serialize({ force: !params.currentPassKey }),
+      keys: [
+        { pubkey: params.authority, isSigner: true, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+        { pubkey: params.newPassKey, isSigner: false, isWritable: false },
+        ...(params.currentPassKey
+          ? [{ pubkey: params.currentPassKey, isSigner: true, isWritable: false }]
+          : []),
+      ],
+    });
+  };
+
+  splitCashInstruction = (params: SplitCashParams): TransactionInstruction => {
+    const { mint, tokenProgramId } = params;
+    const [cash, cashBump] = CashProgram.cashAccount(params.cashReference);
+    const [child, childBump] = CashProgram.cashAccount(params.childReference);
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: SplitCashArgs.serialize({
+        cashBump,
+        cashReference: params.cashReference,
+        childBump,
+        childReference: params.childReference,
+        amount: params.amount,
+        maxNumRedemptions: params.maxNumRedemptions,
+      }),
+      keys: [
+        { pubkey: params.authority, isSigner: true, isWritable: false },
+        { pubkey: params.owner, isSigner: true, isWritable: false },
+        { pubkey: this.feePayer, isSigner: true, isWritable: true },
+        { pubkey: cash, isSigner: false, isWritable: true },
+        {
+          pubkey: spl.getAssociatedTokenAddressSync(mint, cash, true, tokenProgramId),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: child, isSigner: false, isWritable: true },
+        {
+          pubkey: spl.getAssociatedTokenAddressSync(mint, child, true, tokenProgramId),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: mint, isSigner: false, isWritable: false },
+        { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: tokenProgramId, isSigner: false, isWritable: false },
+        { pubkey: spl.ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  initiateRecoveryInstruction = (params: InitiateRecoveryParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: InitiateRecoveryArgs.serialize(),
+      keys: [
+        { pubkey: params.admin, isSigner: true, isWritable: false },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+      ],
+    });
+  };
+
+  executeRecoveryInstruction = (params: ExecuteRecoveryParams): TransactionInstruction => {
+    const { admin, cash, owner, mint, feePayer, tokenProgramId } = params;
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: ExecuteRecoveryArgs.serialize({
+        cashBump: params.cashBump,
+        cashReference: params.cashReference,
+      }),
+      keys: [
+        { pubkey: admin, isSigner: true, isWritable: true },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: cash, isSigner: false, isWritable: true },
+        { pubkey: owner, isSigner: false, isWritable: true },
+        {
+          pubkey: spl.getAssociatedTokenAddressSync(mint, owner, true, tokenProgramId),
+          isSigner: false,
+          isWritable: true,
+        },
+        {
+          pubkey: spl.getAssociatedTokenAddressSync(mint, cash, true, tokenProgramId),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: mint, isSigner: false, isWritable: false },
+        { pubkey: feePayer, isSigner: false, isWritable: true },
+        { pubkey: tokenProgramId, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: spl.ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  closeReferralStatsInstruction = (params: CloseReferralStatsParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: CloseReferralStatsArgs.serialize(),
+      keys: [
+        { pubkey: params.admin, isSigner: true, isWritable: false },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        {
+          pubkey: CashProgram.referralStatsAccount(params.referrer)[0],
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: params.destination, isSigner: false, isWritable: true },
+      ],
+    });
+  };
+
+  claimReferralFeesInstruction = (params: ClaimReferralFeesParams): TransactionInstruction => {
+    const [referralStats] = CashProgram.referralStatsAccount(params.referrer);
+    // native fees are lamports of the referral stats, paid to the referrer itself
+    const isNativeToken =
+      params.mint.equals(spl.NATIVE_MINT) || params.mint.equals(spl.NATIVE_MINT_2022);
+    const escrow = isNativeToken
+      ? referralStats
+      : spl.getAssociatedTokenAddressSync(params.mint, referralStats, true, params.tokenProgramId);
+    const referrerToken = isNativeToken
+      ? params.referrer
+      : spl.getAssociatedTokenAddressSync(
+          params.mint,
+          params.referrer,
+          true,
+          params.tokenProgramId,
+        );
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: ClaimReferralFeesArgs.serialize({ mint: params.mint.toBase58() }),
+      keys: [
+        { pubkey: params.referrer, isSigner: true, isWritable: true },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: referralStats, isSigner: false, isWritable: true },
+        { pubkey: escrow, isSigner: false, isWritable: true },
+        { pubkey: referrerToken, isSigner: false, isWritable: true },
+        { pubkey: params.mint, isSigner: false, isWritable: false },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        { pubkey: spl.ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  flagReferrerInstruction = (params: FlagReferrerParams): TransactionInstruction => {
+    const [referralStats] = CashProgram.referralStatsAccount(params.referrer);
+    // native fees are lamports of the referral stats, forfeited to the fee wallet itself
+    const isNativeToken =
+      params.mint.equals(spl.NATIVE_MINT) || params.mint.equals(spl.NATIVE_MINT_2022);
+    const escrow = isNativeToken
+      ? referralStats
+      : spl.getAssociatedTokenAddressSync(params.mint, referralStats, true, params.tokenProgramId);
+    const feeWalletToken = isNativeToken
+      ? params.feeWallet
+      : spl.getAssociatedTokenAddressSync(
+          params.mint,
+          params.feeWallet,
+          true,
+          params.tokenProgramId,
+        );
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: FlagReferrerArgs.serialize({ mint: params.mint.toBase58() }),
+      keys: [
+        { pubkey: params.admin, isSigner: true, isWritable: false },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: referralStats, isSigner: false, isWritable: true },
+        { pubkey: escrow, isSigner: false, isWritable: true },
+        { pubkey: feeWalletToken, isSigner: false, isWritable: true },
+        { pubkey: params.mint, isSigner: false, isWritable: false },
+        { pubkey: params.tokenProgramId, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  commitRedemptionInstruction = (params: CommitRedemptionParams): TransactionInstruction => {
+    const [commit, commitBump] = CashProgram.commitAccount(params.cash, params.wallet);
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: CommitRedemptionArgs.serialize({ commitment: params.commitment, commitBump }),
+      keys: [
+        { pubkey: params.wallet, isSigner: true, isWritable: false },
+        { pubkey: this.feePayer, isSigner: true, isWritable: true },
+        { pubkey: params.cash, isSigner: false, isWritable: false },
+        { pubkey: commit, isSigner: false, isWritable: true },
+        { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  sweepCommitInstruction = (params: SweepCommitParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: SweepCommitArgs.serialize(),
+      keys: [
+        {
+          pubkey: CashProgram.commitAccount(params.cash, params.wallet)[0],
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: params.rentPayer, isSigner: false, isWritable: true },
+        { pubkey: params.cash, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  backfillVersionInstruction = (params: BackfillVersionParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: BackfillVersionArgs.serialize({ createdByVersion: params.createdByVersion }),
+      keys: [
+        { pubkey: params.admin, isSigner: true, isWritable: true },
+        { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      ],
+    });
+  };
+
+  /** The number of migrated links is the return data of the instruction */
+  migrateBatchInstruction = (params: MigrateBatchParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: MigrateBatchArgs.serialize({ count: params.cashes.length }),
+      keys: [
+        { pubkey: params.feePayer, isSigner: true, isWritable: true },
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ...params.cashes.map((cash) => ({ pubkey: cash, isSigner: false, isWritable: true })),
+      ],
+    });
+  };
+
+  approveRedemptionInstruction = async (
+    params: ApproveRedemptionParams,
+  ): Promise<TransactionInstruction> => {
+    const redeem = await this.redeemInstruction({ ...params, hasApprovalThreshold: true });
+    // the owner wallet co-signs the accounts of the redemption
+    const keys = redeem.keys.map((key) =>
+      key.pubkey.equals(params.ownerWallet) ? { ...key, isSigner: true } : key,
+    );
+    // the approval takes the args of the redemption under its own instruction index
+    const data = Buffer.from(redeem.data);
+    data.writeUInt8(ApproveRedemptionArgs.INSTRUCTION, 0);
+    return new TransactionInstruction({
+      keys,
+      programId: CashProgram.PUBKEY,
+      data,
+    });
+  };
+
+  rejectRedemptionInstruction = (params: RejectRedemptionParams): TransactionInstruction => {
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: RejectRedemptionArgs.serialize(),
+      keys: [
+        { pubkey: params.signer, isSigner: true, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: true },
+        {
+          pubkey: CashProgram.pendingRedemptionAccount(params.cash, params.wallet)[0],
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: params.rentPayer, isSigner: false, isWritable: true },
+      ],
+    });
+  };
+
   lookUpTableAddresses = () => {
     return [
       this.feePayer,
@@ -324,11 +909,15 @@ export class CashClient {
     if (cash.data.totalRedemptions !== 0) {
       throw new Error(ACCOUNT_HAS_REDEMPTIONS);
     }
+    const [metadataAddress] = CashProgram.metadataAccount(cashAddress);
+    const metadata = await this.connection.getAccountInfo(metadataAddress, input.commitment);
     const instructions = [
       this.closeInstruction({
         cash: cashAddress,
         authority: this.authority,
         destinationWallet: this.feePayer,
+        feePayer: this.feePayer,
+        metadata: metadata ? metadataAddress : undefined,
       }),
     ];
     if (input.computeBudget) {
@@ -396,15 +985,53 @@ export class CashClient {
           isWritable: true,
         },
         { pubkey: params.destinationWallet, isSigner: false, isWritable: true },
+        { pubkey: params.feePayer, isSigner: false, isWritable: true },
         {
           pubkey: SystemProgram.programId,
           isSigner: false,
           isWritable: false,
         },
+        ...(params.buffer ? [{ pubkey: params.buffer, isSigner: false, isWritable: true }] : []),
+        ...(params.metadata ? [{ pubkey: params.metadata, isSigner: false, isWritable: true }] : []),
+        ...(params.rentPayers ?? []).map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })),
+        ...(params.refundDestination
+          ? [{ pubkey: params.refundDestination, isSigner: false, isWritable: true }]
+          : []),
+      ],
+    });
+  };
+
+  updateMetadataInstruction = (params: UpdateMetadataParams): TransactionInstruction => {
+    if (!isValidMetadataUri(params.uri)) {
+      throw new Error(INVALID_METADATA_URI);
+    }
+    return new TransactionInstruction({
+      programId: CashProgram.PUBKEY,
+      data: UpdateMetadataArgs.serialize({ uri: params.uri }),
+      keys: [
+        { pubkey: params.owner, isSigner: true, isWritable: false },
+        { pubkey: params.cash, isSigner: false, isWritable: false },
+        {
+          pubkey: CashProgram.metadataAccount(params.cash)[0],
+          isSigner: false,
+          isWritable: true,
+        },
       ],
     });
   };
 
+  getMetadata = async (
+    cashAddress: PublicKey,
+    commitment?: Commitment,
+  ): Promise<CashMetadata | null> => {
+    const [metadataAddress] = CashProgram.metadataAccount(cashAddress);
+    const accountInfo = await this.connection.getAccountInfo(metadataAddress, commitment);
+    if (accountInfo === null) {
+      return null;
+    }
+    return new CashMetadata(metadataAddress, accountInfo);
+  };
+
   initialize = async (input: InitializeCashInput): Promise<ResultContext> => {
     const { instructions, signers } = await this.initializeTransaction(input);
     const { context, value } = await this.connection.getLatestBlockhashAndContext(input.commitment);
@@ -506,6 +1133,47 @@ export class CashClient {
       fingerprintEnabled: input.fingerprintEnabled,
       tokenProgramId: tokenProgramId,
       cashReference: input.cashReference,
+      kycRequiredAbove: input.kycRequiredAbove ? new BN(input.kycRequiredAbove) : undefined,
+      kycAuthority: input.kycAuthority ? new PublicKey(input.kycAuthority) : undefined,
+      maxReferrerFeeBps: input.maxReferrerFeeBps,
+      maxRefereeFeeBps: input.maxRefereeFeeBps,
+      burnBps: input.burnBps,
+      metadataUri: input.metadataUri,
+      claimRequiresWalletSignature: input.claimRequiresWalletSignature,
+      // the amm of a swapping link is checked against the config allow list
+      config: input.swap ? CashProgram.configAccount()[0] : undefined,
+      swap: input.swap
+        ? {
+            targetMint: new PublicKey(input.swap.targetMint),
+            ammProgram: new PublicKey(input.swap.ammProgram),
+            maxSlippageBps: input.swap.maxSlippageBps,
+          }
+        : undefined,
+      vrfAccount: input.vrfAccount ? new PublicKey(input.vrfAccount) : undefined,
+      flash: input.flash
+        ? { startAt: new BN(input.flash.startAt), interval: new BN(input.flash.interval) }
+        : undefined,
+      intendedRecipient: input.intendedRecipient
+        ? new PublicKey(input.intendedRecipient)
+        : undefined,
+      maxPriorityFeePerRedeem: input.maxPriorityFeePerRedeem
+        ? new BN(input.maxPriorityFeePerRedeem)
+        : undefined,
+      allowFreezableMint: input.allowFreezableMint,
+      commitRevealDelay: input.commitRevealDelay ? new BN(input.commitRevealDelay) : undefined,
+      refundDestination: input.refundDestination
+        ? new PublicKey(input.refundDestination)
+        : undefined,
+      approvalThreshold: input.approvalThreshold ? new BN(input.approvalThreshold) : undefined,
+      approvalTtl: input.approvalTtl ? new BN(input.approvalTtl) : undefined,
+      requireVoucher: input.requireVoucher,
+      autoCancelAt: input.autoCancelAt ? new BN(input.autoCancelAt) : undefined,
+      autoCancelTip: input.autoCancelTip ? new BN(input.autoCancelTip) : undefined,
+      maxSignatureAgeSlots:
+        input.maxSignatureAgeSlots !== undefined ? new BN(input.maxSignatureAgeSlots) : undefined,
+      clawbackWindowSeconds: input.clawbackWindowSeconds
+        ? new BN(input.clawbackWindowSeconds)
+        : undefined,
     };
     const instructions = [];
     instructions.push(await this.initInstruction(initParams));
@@ -531,7 +1199,42 @@ export class CashClient {
       minAmount: params.minAmount,
       isLocked: !!params.passKey,
       cashReference: params.cashReference,
+      kycRequiredAbove: params.kycRequiredAbove,
+      kycAuthority: params.kycAuthority?.toBase58(),
+      referralCeilingsEnabled:
+        params.maxReferrerFeeBps !== undefined || params.maxRefereeFeeBps !== undefined,
+      maxReferrerFeeBps: params.maxReferrerFeeBps ?? 0,
+      maxRefereeFeeBps: params.maxRefereeFeeBps ?? 0,
+      burnBps: params.burnBps ?? 0,
+      metadataUri: params.metadataUri,
+      claimRequiresWalletSignature: !!params.claimRequiresWalletSignature,
+      swap: params.swap
+        ? new SwapConfigData({
+            targetMint: params.swap.targetMint.toBase58(),
+            ammProgram: params.swap.ammProgram.toBase58(),
+            maxSlippageBps: params.swap.maxSlippageBps,
+          })
+        : undefined,
+      entropySource: params.vrfAccount
+        ? EntropySourceData.vrfAccount(params.vrfAccount.toBase58())
+        : EntropySourceData.slotHashes(),
+      flash: params.flash
+        ? new FlashScheduleData({ startAt: params.flash.startAt, interval: params.flash.interval })
+        : undefined,
+      intendedRecipient: params.intendedRecipient?.toBase58(),
+      maxPriorityFeePerRedeem: params.maxPriorityFeePerRedeem ?? new BN(0),
+      allowFreezableMint: !!params.allowFreezableMint,
+      commitRevealDelay: params.commitRevealDelay,
+      refundDestination: params.refundDestination?.toBase58(),
+      approvalThreshold: params.approvalThreshold,
+      approvalTtl: params.approvalTtl,
+      requireVoucher: !!params.requireVoucher,
+      autoCancelAt: params.autoCancelAt,
+      autoCancelTip: params.autoCancelTip ?? new BN(0),
+      maxSignatureAgeSlots: params.maxSignatureAgeSlots,
+      clawbackWindowSeconds: params.clawbackWindowSeconds,
     });
+    const permissionless = params.owner.equals(params.authority);
     const keys = [
       {
         pubkey: params.authority,
@@ -607,6 +1310,43 @@ export class CashClient {
         isSigner: false,
         isWritable: false,
       },
+      ...(params.config || permissionless
+        ? [
+            {
+              pubkey: params.config ?? CashProgram.configAccount()[0],
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : []),
+      // a permissionless init is refused without the mint registry, created or not
+      ...(permissionless
+        ? [
+            {
+              pubkey: CashProgram.mintRegistryAccount()[0],
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : []),
+      ...(params.metadataUri
+        ? [
+            {
+              pubkey: CashProgram.metadataAccount(params.cash)[0],
+              isSigner: false,
+              isWritable: true,
+            },
+          ]
+        : []),
+      ...(params.autoCancelAt
+        ? [
+            {
+              pubkey: CashProgram.autoCancelQueueAccount()[0],
+              isSigner: false,
+              isWritable: true,
+            },
+          ]
+        : []),
     ];
     return new TransactionInstruction({
       keys,
@@ -714,6 +1454,10 @@ export class CashClient {
     if (cash == null) {
       throw new Error(FAILED_TO_FIND_ACCOUNT);
     }
+    const [redemptionIndexAddress, redemptionIndexBump] = CashProgram.redemptionIndexAccount(
+      cashAddress,
+      cash.data.totalRedemptions + 1 + (input.redemptionsAhead ?? 0),
+    );
     if (input.referrerFeeBps && !input.referrer) {
       throw new Error(REFERRER_WALLET);
     }
@@ -750,8 +1494,18 @@ export class CashClient {
       //   : []),
     ];
     //const tempToken = isNativeToken ? unwrapTokenAccount.publicKey : null;
+    if (cash.isLegacy()) {
+      instructions.push(
+        this.migrateBatchInstruction({ feePayer: this.feePayer, cashes: [cash.pubkey] }),
+      );
+    }
+    // a swapping link pays the recipient in the target mint
+    const swapConfig = cash.data.swap;
+    if (swapConfig && !input.swapPool) {
+      throw new Error(SWAP_POOL_REQUIRED);
+    }
     const walletTokenAccount = spl.getAssociatedTokenAddressSync(
-      mint,
+      swapConfig ? new PublicKey(swapConfig.targetMint) : mint,
       walletAddress,
       true,
       tokenProgramId,
@@ -769,13 +1523,39 @@ export class CashClient {
       true,
       tokenProgramId,
     );
+    const recipientPermit = input.recipientPermit
+      ? new RecipientPermitData({
+          signature: bs58.decode(input.recipientPermit.signature),
+          expiry: new BN(input.recipientPermit.expiry),
+        })
+      : undefined;
     const redeemInstruction = await this.redeemInstruction({
       mint,
       cashBump,
       passKey,
       redemptionAddress,
       redemptionBump,
+      redemptionIndexAddress,
+      redemptionIndexBump,
       wallet: walletAddress,
+      // a wallet bound link is only paid out with the signature of its recipient, either on
+      // the transaction or on a permit
+      walletIsSigner:
+        !recipientPermit &&
+        (cash.data.claimRequiresWalletSignature || !!cash.data.intendedRecipient),
+      swap: swapConfig
+        ? {
+            ammProgram: new PublicKey(swapConfig.ammProgram),
+            pool: new PublicKey(input.swapPool.pool),
+            poolSource: new PublicKey(input.swapPool.poolSource),
+            poolTarget: new PublicKey(input.swapPool.poolTarget),
+            targetMint: new PublicKey(swapConfig.targetMint),
+          }
+        : undefined,
+      vrfAccount:
+        cash.data.entropySource.enum === 'vrfAccount'
+          ? new PublicKey(cash.data.entropySource.vrfAccount.account)
+          : undefined,
       walletToken: walletTokenAccount,
       platformWallet: this.feeWallet,
       platformFeeToken: platformTokenAccount,
@@ -788,12 +1568,42 @@ export class CashClient {
       feePayer: this.feePayer,
       referrer,
       referrerToken,
+      referralStats:
+        referrer && (input.trackReferralEarnings || input.referralVesting)
+          ? CashProgram.referralStatsAccount(referrer)[0]
+          : undefined,
+      referralVesting: input.referralVesting,
+      walletLimit: input.walletLimit,
+      saveBps: input.saveBps,
+      signedAtSlot: input.signedAtSlot !== undefined ? new BN(input.signedAtSlot) : undefined,
+      savingsToken: input.savingsToken ? new PublicKey(input.savingsToken) : undefined,
       tokenProgramId,
       refereeFeeBps: input.refereeFeeBps,
       referrerFeeBps: input.referrerFeeBps,
       cashReference: input.cashReference,
       weightPpm: input.weightPpm,
+      priorityFeeReimbursement: input.priorityFeeReimbursement
+        ? new BN(input.priorityFeeReimbursement)
+        : undefined,
+      recipientPermit,
+      revealNonce: input.revealNonce ? Buffer.from(input.revealNonce, 'hex') : undefined,
+      createReceipt: input.createReceipt,
+      voucherHash: input.voucherHash ? Buffer.from(input.voucherHash, 'hex') : undefined,
     });
+    if (recipientPermit) {
+      instructions.push(
+        Ed25519Program.createInstructionWithPublicKey({
+          publicKey: walletAddress.toBytes(),
+          message: recipientPermitMessage(
+            cash.pubkey,
+            walletAddress,
+            recipientPermit.expiry,
+            cash.data.totalRedemptions,
+          ),
+          signature: recipientPermit.signature,
+        }),
+      );
+    }
     instructions.push(redeemInstruction);
     // if (isNativeToken) {
     //   instructions.push(
@@ -841,38 +1651,140 @@ export class CashClient {
       rateUsd,
       redemptionBump,
       redemptionAddress,
+      redemptionIndexBump,
+      redemptionIndexAddress,
       platformWallet,
       ownerWallet,
+      buffer,
+      walletIsSigner,
+      swap,
+      vrfAccount,
+      referralStats,
+      priorityFeeReimbursement,
+      recipientPermit,
+      revealNonce,
+      createReceipt,
+      voucherHash,
+      hasApprovalThreshold,
+      hasFeeWalletEpoch,
+      referralVesting,
+      walletLimit,
+      saveBps,
+      savingsToken,
+      signedAtSlot,
     } = params;
+    const isNativeToken = mint.equals(spl.NATIVE_MINT) || mint.equals(spl.NATIVE_MINT_2022);
 
     const keys = [
       { pubkey: authority, isSigner: true, isWritable: false },
-      { pubkey: wallet, isSigner: false, isWritable: true },
+      { pubkey: wallet, isSigner: !!walletIsSigner, isWritable: true },
       { pubkey: platformWallet, isSigner: false, isWritable: true },
       { pubkey: platformFeeToken, isSigner: false, isWritable: true },
       { pubkey: cash, isSigner: false, isWritable: true },
-      ...(passKey ? [{ pubkey: passKey, isSigner: true, isWritable: false }] : []),
       { pubkey: ownerWallet, isSigner: false, isWritable: true },
       { pubkey: ownerToken, isSigner: false, isWritable: true },
       { pubkey: feePayer, isSigner: true, isWritable: true },
       { pubkey: feePayerToken, isSigner: false, isWritable: true },
       { pubkey: vaultToken, isSigner: false, isWritable: true },
       { pubkey: walletToken, isSigner: false, isWritable: true },
-      { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: true },
       { pubkey: redemptionAddress, isSigner: false, isWritable: true },
+      { pubkey: redemptionIndexAddress, isSigner: false, isWritable: true },
       { pubkey: SYSVAR_CLOCK_PUBKEY, isSigner: false, isWritable: false },
       { pubkey: SYSVAR_RENT_PUBKEY, isSigner: false, isWritable: false },
       { pubkey: SYSVAR_SLOT_HASHES_PUBKEY, isSigner: false, isWritable: false },
       { pubkey: tokenProgramId, isSigner: false, isWritable: false },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: spl.ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ...(passKey ? [{ pubkey: passKey, isSigner: true, isWritable: false }] : []),
       ...(referrer
         ? [
             { pubkey: referrer, isSigner: false, isWritable: true },
             { pubkey: referrerToken, isSigner: false, isWritable: true },
           ]
         : []),
-      { pubkey: spl.ASSOCIATED_TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+      ...(saveBps !== undefined
+        ? [{ pubkey: savingsToken, isSigner: false, isWritable: true }]
+        : []),
+      ...(buffer ? [{ pubkey: buffer, isSigner: false, isWritable: false }] : []),
+      ...(swap
+        ? [
+            { pubkey: swap.ammProgram, isSigner: false, isWritable: false },
+            { pubkey: swap.pool, isSigner: false, isWritable: true },
+            { pubkey: swap.poolSource, isSigner: false, isWritable: true },
+            { pubkey: swap.poolTarget, isSigner: false, isWritable: true },
+            { pubkey: swap.targetMint, isSigner: false, isWritable: false },
+          ]
+        : []),
+      ...(vrfAccount ? [{ pubkey: vrfAccount, isSigner: false, isWritable: false }] : []),
+      ...(referralStats ? [{ pubkey: referralStats, isSigner: false, isWritable: true }] : []),
+      // a vesting referrer fee is held by the associated token account of the referral stats
+      ...(referralStats && referralVesting && !isNativeToken
+        ? [
+            {
+              pubkey: spl.getAssociatedTokenAddressSync(
+                mint,
+                referralStats,
+                true,
+                tokenProgramId,
+              ),
+              isSigner: false,
+              isWritable: true,
+            },
+          ]
+        : []),
+      ...(recipientPermit
+        ? [{ pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false }]
+        : []),
+      // the commit rent goes back to the fee payer that funded it
+      ...(revealNonce
+        ? [
+            {
+              pubkey: CashProgram.commitAccount(cash, wallet)[0],
+              isSigner: false,
+              isWritable: true,
+            },
+          ]
+        : []),
+      // a wallet redeems again once its pending redemption is approved or rejected
+      ...(hasApprovalThreshold
+        ? [
+            {
+              pubkey: CashProgram.pendingRedemptionAccount(cash, wallet)[0],
+              isSigner: false,
+              isWritable: true,
+            },
+          ]
+        : []),
+      // the platform wallet must be the one the registry resolves for the epoch of the link
+      ...(hasFeeWalletEpoch
+        ? [
+            {
+              pubkey: CashProgram.feeWalletRegistryAccount()[0],
+              isSigner: false,
+              isWritable: false,
+            },
+          ]
+        : []),
+      // the payout counts against the daily cap of the wallet, its limit is created on first use
+      ...(walletLimit
+        ? [
+            {
+              pubkey: CashProgram.mintRegistryAccount()[0],
+              isSigner: false,
+              isWritable: false,
+            },
+            {
+              pubkey: CashProgram.walletLimitAccount(wallet, mint)[0],
+              isSigner: false,
+              isWritable: true,
+            },
+          ]
+        : []),
+      // the disabled features of the config gate every redemption
+      { pubkey: CashProgram.configAccount()[0], isSigner: false, isWritable: false },
     ];
+    mergeDuplicateKeys(keys);
 
     const data = RedeemCashLinkArgs.serialize({
       cashBump,
@@ -881,6 +1793,14 @@ export class CashClient {
       weightPpm,
       rateUsd,
       redemptionBump,
+      redemptionIndexBump,
+      priorityFeeReimbursement,
+      recipientPermit,
+      revealNonce,
+      createReceipt: !!createReceipt,
+      voucherHash,
+      saveBps,
+      signedAtSlot,
     });
 
     return new TransactionInstruction({
@@ -927,6 +1847,101 @@ export class CashClient {
     }
   };
 
+  hasRedeemed = async (
+    cash: PublicKey,
+    wallet: PublicKey,
+    commitment?: Commitment,
+  ): Promise<boolean> => {
+    const [redemptionAddress] = CashProgram.redemptionAccount(cash, wallet);
+    const accountInfo = await this.connection.getAccountInfo(redemptionAddress, commitment);
+    if (accountInfo !== null) {
+      return true;
+    }
+    // a single claim link skips the receipt by default, its first index names the wallet
+    const first = await this.getRedemptionByIndex(cash, 1, commitment);
+    return first !== null && first.wallet === wallet.toBase58();
+  };
+
+  /** The queued links the auto-cancel crank may cancel at `now`, oldest first */
+  getDueAutoCancels = async (
+    now: number,
+    commitment?: Commitment,
+  ): Promise<AutoCancelEntryData[]> => {
+    const [queueAddress] = CashProgram.autoCancelQueueAccount();
+    const accountInfo = await this.connection.getAccountInfo(queueAddress, commitment);
+    if (accountInfo === null) {
+      return [];
+    }
+    return AutoCancelQueueData.deserialize(accountInfo.data).due(now);
+  };
+
+  getRedemptionReceipt = async (
+    cash: PublicKey,
+    wallet: PublicKey,
+    commitment?: Commitment,
+  ): Promise<RedemptionReceiptData | null> => {
+    const [redemptionAddress] = CashProgram.redemptionAccount(cash, wallet);
+    const accountInfo = await this.connection.getAccountInfo(redemptionAddress, commitment);
+    if (accountInfo === null) {
+      return null;
+    }
+    return RedemptionReceiptData.fromAccountData(accountInfo.data);
+  };
+
+  getRedemptionByIndex = async (
+    cash: PublicKey,
+    index: number,
+    commitment?: Commitment,
+  ): Promise<RedemptionIndexData | null> => {
+    const [redemptionIndexAddress] = CashProgram.redemptionIndexAccount(cash, index);
+    const accountInfo = await this.connection.getAccountInfo(redemptionIndexAddress, commitment);
+    if (accountInfo === null) {
+      return null;
+    }
+    return RedemptionIndexData.fromAccountData(accountInfo.data);
+  };
+
+  /**
+   * Rebuild the story of a link from its account and its redemption index receipts. The
+   * receipts are numbered from 1 and fetched a page at a time, when the link is gone they are
+   * read until the first missing one.
+   */
+  getHistory = async (cashReference: string, commitment?: Commitment): Promise<CashHistory> => {
+    const [address] = CashProgram.cashAccount(cashReference);
+    const cash = await this.getCash(address, commitment);
+    const count = cash ? cash.data.totalRedemptions : 0xffff;
+    const redemptions: CashHistoryRedemption[] = [];
+    for (let start = 1; start <= count; start += HISTORY_PAGE_SIZE) {
+      const end = Math.min(count, start + HISTORY_PAGE_SIZE - 1);
+      const addresses = Array.from(
+        { length: end - start + 1 },
+        (_, offset) => CashProgram.redemptionIndexAccount(address, start + offset)[0],
+      );
+      const infos = await this.connection.getMultipleAccountsInfo(addresses, commitment);
+      const missing = infos.findIndex((info) => info === null);
+      infos.slice(0, missing === -1 ? infos.length : missing).forEach((info, offset) => {
+        const { wallet, amount } = RedemptionIndexData.fromAccountData(info.data);
+        redemptions.push({ index: start + offset, address: addresses[offset], wallet, amount });
+      });
+      if (missing !== -1) {
+        break;
+      }
+    }
+    const redeemed = redemptions.reduce((sum, redemption) => sum.add(redemption.amount), new BN(0));
+    return {
+      address,
+      cash,
+      redemptions,
+      redeemed,
+      reconciled: cash
+        ? redeemed
+            .add(cash.data.remainingAmount)
+            .add(cash.data.forfeitedAmount)
+            .eq(cash.data.amount)
+        : undefined,
+    };
+  };
+
   getOrCreateAssociatedAccount = async (
     mint: PublicKey,
     owner: PublicKey,
@@ -1,5 +1,6 @@
-import { Commitment } from '@solana/web3.js';
-import { CashDistributionType } from 'src/accounts';
+import { Commitment, PublicKey } from '@solana/web3.js';
+import BN from 'bn.js';
+import { Cash, CashDistributionType } from 'src/accounts';
 export interface InitializeCashInput {
   wallet: string;
   mint?: string;
@@ -21,6 +22,45 @@ export interface InitializeCashInput {
   addressLookupTable?: string;
   asLegacyTransaction: boolean;
   tokenProgramId: string;
+  kycRequiredAbove?: string;
+  kycAuthority?: string;
+  maxReferrerFeeBps?: number;
+  maxRefereeFeeBps?: number;
+  burnBps?: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature?: boolean;
+  swap?: {
+    targetMint: string;
+    ammProgram: string;
+    maxSlippageBps: number;
+  };
+  vrfAccount?: string;
+  flash?: {
+    startAt: number;
+    interval: number;
+  };
+  intendedRecipient?: string;
+  maxPriorityFeePerRedeem?: string;
+  /** Accept a mint with a freeze authority, its holder can freeze the vault */
+  allowFreezableMint?: boolean;
+  /** Slots a wallet of a Random link must commit ahead of its redemption */
+  commitRevealDelay?: number;
+  /** Receives the lamport refunds when the owner is a program owned account */
+  refundDestination?: string;
+  /** Payouts at or above this amount wait for the approval of the authority and the owner */
+  approvalThreshold?: string;
+  /** Seconds a payout may wait for approval */
+  approvalTtl?: number;
+  /** Refuse redemptions that carry no voucher hash */
+  requireVoucher?: boolean;
+  /** Unix timestamp from which anyone may crank the cancel of the link */
+  autoCancelAt?: number;
+  /** Paid to the crank from the fee reserve of the link, in the link mint */
+  autoCancelTip?: string;
+  /** Slots a signed redemption stays valid, unset lets it land at any time */
+  maxSignatureAgeSlots?: number;
+  /** Seconds the authority and the config admin may claw a payout back, the wallet signs */
+  clawbackWindowSeconds?: number;
 }
 
 export interface ResultContext {
@@ -48,8 +88,62 @@ export interface RedeemCashInput extends CashInput {
   referrerFeeBps?: number;
   refereeFeeBps?: number;
   referrer?: string;
+  /** Accrue the referrer fee on the lifetime referral stats of the referrer */
+  trackReferralEarnings?: boolean;
+  /** The config vests referrer fees, they are held by the referral stats of the referrer */
+  referralVesting?: boolean;
+  /** Priority fees the fee payer attaches to the redemption, in the link mint */
+  priorityFeeReimbursement?: string;
+  /**
+   * A claim the wallet signed off chain over `recipientPermitMessage`, lets a relayer redeem
+   * to a wallet that does not sign the transaction. The signature is base58 encoded and the
+   * expiry a unix timestamp
+   */
+  recipientPermit?: {
+    signature: string;
+    expiry: number;
+  };
+  /** The hex nonce committed to ahead of the redemption of a commit-reveal link */
+  revealNonce?: string;
+  /** Create the redemption receipt of a single claim link, other links always create it */
+  createReceipt?: boolean;
+  /** The hex hash of the off chain voucher authorizing the payout, kept in the receipt */
+  voucherHash?: string;
+  /** The mint registry sets a daily cap per wallet for the mint of the link */
+  walletLimit?: boolean;
+  /** Share of the payout in bps saved to `savingsToken`, the dust stays with the payout */
+  saveBps?: number;
+  /** A token account of the wallet in the link mint, required with `saveBps` */
+  savingsToken?: string;
+  /** Slot the authority signed the redemption at, required by links with a signature age */
+  signedAtSlot?: number;
+  /** Redeems of the same link placed before this one in the transaction */
+  redemptionsAhead?: number;
   tokenProgramId: string;
   cashReference: string;
   rateUsd?: string;
   weightPpm?: number;
+  swapPool?: {
+    pool: string;
+    poolSource: string;
+    poolTarget: string;
+  };
+}
+
+export interface CashHistoryRedemption {
+  index: number;
+  address: PublicKey;
+  wallet: string;
+  amount: BN;
+}
+
+export interface CashHistory {
+  address: PublicKey;
+  /** null once the link was closed, or when it never existed */
+  cash: Cash | null;
+  redemptions: CashHistoryRedemption[];
+  /** Sum of the receipt amounts, burns included */
+  redeemed: BN;
+  /** Whether receipts, remaining and forfeited amounts add up to the amount, unknown once closed */
+  reconciled?: boolean;
 }
@@ -5,6 +5,17 @@ import { Cash } from './accounts';
 export class CashProgram extends Program {
   static readonly PREFIX = 'cash';
   static readonly REDEMPTION_PREFIX = 'redemption';
+  static readonly REDEMPTION_INDEX_PREFIX = 'redemption_idx';
+  static readonly BUFFER_PREFIX = 'buffer';
+  static readonly CONFIG_PREFIX = 'config';
+  static readonly METADATA_PREFIX = 'meta';
+  static readonly REFERRAL_PREFIX = 'referral';
+  static readonly COMMIT_PREFIX = 'commit';
+  static readonly PENDING_PREFIX = 'pending';
+  static readonly FEE_WALLETS_PREFIX = 'fee_wallets';
+  static readonly AUTO_CANCEL_PREFIX = 'auto_cancel';
+  static readonly MINT_REGISTRY_PREFIX = 'mints';
+  static readonly WALLET_LIMIT_PREFIX = 'limit';
   static readonly PUBKEY = new PublicKey('cashXAE5UP18RyU7ByFWfxu93kGg69KzoktacNQDukW');
 
   static cashAccount(reference: string): [PublicKey, number] {
@@ -20,4 +31,83 @@ export class CashProgram extends Program {
       CashProgram.PUBKEY,
     );
   }
+
+  static configAccount(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.CONFIG_PREFIX)],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static bufferAccount(reference: string): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.BUFFER_PREFIX), Buffer.from(reference)],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static metadataAccount(cash: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.METADATA_PREFIX), cash.toBuffer()],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static referralStatsAccount(referrer: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.REFERRAL_PREFIX), referrer.toBuffer()],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static commitAccount(cash: PublicKey, wallet: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.COMMIT_PREFIX), cash.toBuffer(), wallet.toBuffer()],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static pendingRedemptionAccount(cash: PublicKey, wallet: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.PENDING_PREFIX), cash.toBuffer(), wallet.toBuffer()],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static feeWalletRegistryAccount(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.FEE_WALLETS_PREFIX)],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static autoCancelQueueAccount(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.AUTO_CANCEL_PREFIX)],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static mintRegistryAccount(): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.MINT_REGISTRY_PREFIX)],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static walletLimitAccount(wallet: PublicKey, mint: PublicKey): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.WALLET_LIMIT_PREFIX), wallet.toBuffer(), mint.toBuffer()],
+      CashProgram.PUBKEY,
+    );
+  }
+
+  static redemptionIndexAccount(cash: PublicKey, index: number): [PublicKey, number] {
+    const indexBytes = Buffer.alloc(2);
+    indexBytes.writeUInt16LE(index);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from(CashProgram.REDEMPTION_INDEX_PREFIX), cash.toBuffer(), indexBytes],
+      CashProgram.PUBKEY,
+    );
+  }
 }
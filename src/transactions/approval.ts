@@ -0,0 +1,33 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+import { RecipientPermitData, RedeemArgs, RedeemCashLinkParams, REDEEM_ARGS_FIELDS } from './redeem_cash';
+
+/** Return data of a redemption that waits for approval instead of paying out */
+export const PENDING_APPROVAL = 255;
+
+export class ApproveRedemptionArgs extends Borsh.Data<RedeemArgs> {
+  static readonly SCHEMA = new Map([
+    ...RecipientPermitData.SCHEMA,
+    ...ApproveRedemptionArgs.struct(REDEEM_ARGS_FIELDS),
+  ]);
+
+  static readonly INSTRUCTION = 31;
+  instruction = ApproveRedemptionArgs.INSTRUCTION;
+}
+
+/** The params of the redemption being approved, the owner wallet co-signs */
+export type ApproveRedemptionParams = RedeemCashLinkParams;
+
+export class RejectRedemptionArgs extends Borsh.Data {
+  static readonly SCHEMA = RejectRedemptionArgs.struct([['instruction', 'u8']]);
+  instruction = 32;
+}
+
+export type RejectRedemptionParams = {
+  /** The cash authority or the owner, anyone once the approval ttl elapsed */
+  signer: PublicKey;
+  cash: PublicKey;
+  wallet: PublicKey;
+  /** The fee payer that funded the pending redemption */
+  rentPayer: PublicKey;
+};
@@ -1,5 +1,6 @@
 import { Borsh } from '@metaplex-foundation/mpl-core';
 import { PublicKey } from '@solana/web3.js';
+import BN from 'bn.js';
 
 export type InitCancelArgs = {
   cashBump: number;
@@ -28,4 +29,115 @@ export type CancelCashParams = {
   cashBump: number;
   tokenProgramId: PublicKey;
   mint: PublicKey;
+  /** The refund destination of the link, required when the owner can not spend lamports */
+  refundDestination?: PublicKey;
+};
+
+export type InitCancelBeginArgs = {
+  cashBump: number;
+  cashReference: string;
+  maxAmount: BN;
+};
+
+export class CancelBeginArgs extends Borsh.Data<InitCancelBeginArgs> {
+  static readonly SCHEMA = CancelBeginArgs.struct([
+    ['instruction', 'u8'],
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+    ['maxAmount', 'u64'],
+  ]);
+  instruction = 16;
+  cashBump: number;
+  cashReference: string;
+  maxAmount: BN;
+}
+
+export type CancelBeginParams = {
+  authority: PublicKey;
+  cash: PublicKey;
+  owner: PublicKey;
+  ownerToken: PublicKey;
+  vaultToken: PublicKey;
+  mint: PublicKey;
+  tokenProgramId: PublicKey;
+  cashBump: number;
+  cashReference: string;
+  maxAmount: BN;
+};
+
+export class CancelFinalizeArgs extends Borsh.Data<InitCancelArgs> {
+  static readonly SCHEMA = CancelFinalizeArgs.struct([
+    ['instruction', 'u8'],
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+  ]);
+  instruction = 17;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type CancelFinalizeParams = {
+  authority: PublicKey;
+  cash: PublicKey;
+  feePayer: PublicKey;
+  vaultToken: PublicKey;
+  tokenProgramId: PublicKey;
+  cashBump: number;
+  cashReference: string;
+};
+
+export type CancelLinkSeedsDataArgs = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class CancelLinkSeedsData extends Borsh.Data<CancelLinkSeedsDataArgs> {
+  static readonly SCHEMA = CancelLinkSeedsData.struct([
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+  ]);
+  cashBump: number;
+  cashReference: string;
+
+  constructor(args: CancelLinkSeedsDataArgs) {
+    super(args);
+  }
+}
+
+export type ProcessAutoCancelQueueArgsData = {
+  links: CancelLinkSeedsData[];
+};
+
+export class ProcessAutoCancelQueueArgs extends Borsh.Data<ProcessAutoCancelQueueArgsData> {
+  static readonly SCHEMA = new Map([
+    ...CancelLinkSeedsData.SCHEMA,
+    ...ProcessAutoCancelQueueArgs.struct([
+      ['instruction', 'u8'],
+      ['links', [CancelLinkSeedsData]],
+    ]),
+  ]);
+  instruction = 38;
+  links: CancelLinkSeedsData[];
+}
+
+/** Links canceled by a single crank of the auto-cancel queue */
+export const MAX_AUTO_CANCEL_BATCH = 5;
+
+export type AutoCancelLink = {
+  cash: PublicKey;
+  owner: PublicKey;
+  ownerToken: PublicKey;
+  feePayer: PublicKey;
+  vaultToken: PublicKey;
+  mint: PublicKey;
+  /** The token account of the cranker in the link mint, receives the tip */
+  crankerToken: PublicKey;
+  cashBump: number;
+  cashReference: string;
+};
+
+export type ProcessAutoCancelQueueParams = {
+  cranker: PublicKey;
+  tokenProgramId: PublicKey;
+  links: AutoCancelLink[];
 };
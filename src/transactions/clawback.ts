@@ -0,0 +1,66 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+import BN from 'bn.js';
+
+export type ClawbackRedemptionArgsData = {
+  cashBump: number;
+  cashReference: string;
+  amount: BN;
+};
+
+export class ClawbackRedemptionArgs extends Borsh.Data<ClawbackRedemptionArgsData> {
+  static readonly SCHEMA = ClawbackRedemptionArgs.struct([
+    ['instruction', 'u8'],
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+    ['amount', 'u64'],
+  ]);
+  instruction = 41;
+  cashBump: number;
+  cashReference: string;
+  amount: BN;
+}
+
+export type RevokeClawbackArgsData = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class RevokeClawbackArgs extends Borsh.Data<RevokeClawbackArgsData> {
+  static readonly SCHEMA = RevokeClawbackArgs.struct([
+    ['instruction', 'u8'],
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+  ]);
+  instruction = 42;
+  cashBump: number;
+  cashReference: string;
+}
+
+/** The payout a clawback instruction acts on */
+export type ClawbackPayoutParams = {
+  cash: PublicKey;
+  cashBump: number;
+  cashReference: string;
+  /** The redeeming wallet, its redemption receipt is derived from it */
+  wallet: PublicKey;
+  /** The token account the payout went to */
+  recipientToken: PublicKey;
+  mint: PublicKey;
+  tokenProgramId: PublicKey;
+};
+
+export type ClawbackRedemptionParams = ClawbackPayoutParams & {
+  authority: PublicKey;
+  admin: PublicKey;
+  /** The vault of a live link, the token account of the owner otherwise */
+  destination: PublicKey;
+  amount: BN;
+};
+
+export type RevokeClawbackParams = ClawbackPayoutParams & {
+  cranker: PublicKey;
+  vaultToken: PublicKey;
+  /** The memo program, when the recipient token account requires memos */
+  memoProgram?: PublicKey;
+};
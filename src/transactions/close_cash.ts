@@ -10,4 +10,11 @@ export type CloseCashParams = {
   authority: PublicKey;
   cash: PublicKey;
   destinationWallet: PublicKey;
+  feePayer: PublicKey;
+  buffer?: PublicKey;
+  metadata?: PublicKey;
+  /** Rent payers recorded by the buffer and the metadata, each is refunded what it funded */
+  rentPayers?: PublicKey[];
+  /** The refund destination of the link, required when the destination can not spend lamports */
+  refundDestination?: PublicKey;
 };
@@ -0,0 +1,38 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+
+export type CommitRedemptionArgsData = {
+  /** `keccak(wallet || nonce)` of the nonce revealed by the redemption */
+  commitment: Uint8Array;
+  commitBump: number;
+};
+
+export class CommitRedemptionArgs extends Borsh.Data<CommitRedemptionArgsData> {
+  static readonly SCHEMA = CommitRedemptionArgs.struct([
+    ['instruction', 'u8'],
+    ['commitment', [32]],
+    ['commitBump', 'u8'],
+  ]);
+
+  instruction = 27;
+  commitment: Uint8Array;
+  commitBump: number;
+}
+
+export type CommitRedemptionParams = {
+  wallet: PublicKey;
+  cash: PublicKey;
+  commitment: Uint8Array;
+};
+
+export class SweepCommitArgs extends Borsh.Data {
+  static readonly SCHEMA = SweepCommitArgs.struct([['instruction', 'u8']]);
+  instruction = 28;
+}
+
+export type SweepCommitParams = {
+  cash: PublicKey;
+  wallet: PublicKey;
+  /** The fee payer that funded the commit */
+  rentPayer: PublicKey;
+};
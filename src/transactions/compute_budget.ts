@@ -0,0 +1,156 @@
+import {
+  ComputeBudgetProgram,
+  Connection,
+  PublicKey,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
+} from '@solana/web3.js';
+
+export type ComputeBudgetKind = 'init' | 'redeem' | 'cancel' | 'close' | 'processAutoCancelQueue';
+
+export interface ComputeBudgetFlags {
+  /** The link is locked behind a pass key */
+  locked?: boolean;
+  /** A referrer takes a share of the fee */
+  referral?: boolean;
+  /** The associated token account of the recipient is created by the instruction */
+  ataMissing?: boolean;
+  /** The mint is owned by token-2022 and carries extensions */
+  token2022?: boolean;
+  /** The link holds wrapped SOL */
+  nativeMint?: boolean;
+}
+
+/** Headroom added on top of a ceiling, in basis points */
+export const CU_BUDGET_TOLERANCE_BPS = 1_000;
+
+/**
+ * Compute unit ceilings of the base configuration of each instruction and the cost each flag
+ * adds on top. A link of the auto-cancel crank counts once per link in the batch.
+ *
+ * They are upper bounds for clients that can not simulate, prefer `simulatedComputeBudget`.
+ * `test/compute_budget.test.ts` simulates recorded transactions of each kind against a
+ * validator and fails once one consumes more than its ceiling here
+ */
+export const CU_BUDGETS: Record<
+  ComputeBudgetKind,
+  { base: number } & Record<keyof ComputeBudgetFlags, number>
+> = {
+  init: {
+    base: 60_000,
+    locked: 2_000,
+    referral: 0,
+    ataMissing: 25_000,
+    token2022: 15_000,
+    nativeMint: 8_000,
+  },
+  redeem: {
+    base: 150_000,
+    locked: 5_000,
+    referral: 30_000,
+    ataMissing: 30_000,
+    token2022: 25_000,
+    nativeMint: 15_000,
+  },
+  cancel: {
+    base: 45_000,
+    locked: 2_000,
+    referral: 0,
+    ataMissing: 25_000,
+    token2022: 12_000,
+    nativeMint: 10_000,
+  },
+  close: {
+    base: 25_000,
+    locked: 0,
+    referral: 0,
+    ataMissing: 0,
+    token2022: 8_000,
+    nativeMint: 5_000,
+  },
+  processAutoCancelQueue: {
+    base: 55_000,
+    locked: 0,
+    referral: 0,
+    ataMissing: 0,
+    token2022: 15_000,
+    nativeMint: 10_000,
+  },
+};
+
+/** The runtime refuses a compute unit limit above this */
+export const MAX_COMPUTE_UNIT_LIMIT = 1_400_000;
+
+const withTolerance = (units: number): number =>
+  Math.min(units + Math.ceil((units * CU_BUDGET_TOLERANCE_BPS) / 10_000), MAX_COMPUTE_UNIT_LIMIT);
+
+/** The ceiling of the configuration of an instruction, without headroom */
+export const computeBudgetCeiling = (
+  kind: ComputeBudgetKind,
+  flags: ComputeBudgetFlags = {},
+): number => {
+  const budget = CU_BUDGETS[kind];
+  let units = budget.base;
+  for (const flag of Object.keys(flags) as (keyof ComputeBudgetFlags)[]) {
+    if (flags[flag]) {
+      units += budget[flag];
+    }
+  }
+  return units;
+};
+
+/**
+ * The compute unit limit to request for an instruction, the ceiling of its configuration plus
+ * `CU_BUDGET_TOLERANCE_BPS` of headroom
+ */
+export const recommendedComputeBudget = (
+  kind: ComputeBudgetKind,
+  flags: ComputeBudgetFlags = {},
+): number => {
+  return withTolerance(computeBudgetCeiling(kind, flags));
+};
+
+/**
+ * Compute units the instructions consume, simulated at the runtime maximum limit without
+ * signatures. Undefined when the simulation fails or the node does not report them
+ */
+export const simulateComputeUnits = async (
+  connection: Pick<Connection, 'simulateTransaction'>,
+  instructions: TransactionInstruction[],
+  payer: PublicKey,
+): Promise<number | undefined> => {
+  const message = new TransactionMessage({
+    payerKey: payer,
+    // replaced by the node, the simulation needs no recent blockhash
+    recentBlockhash: PublicKey.default.toBase58(),
+    instructions: [
+      ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_COMPUTE_UNIT_LIMIT }),
+      ...instructions.filter((ix) => !ix.programId.equals(ComputeBudgetProgram.programId)),
+    ],
+  }).compileToV0Message();
+  const { value } = await connection.simulateTransaction(new VersionedTransaction(message), {
+    sigVerify: false,
+    replaceRecentBlockhash: true,
+  });
+  if (value.err || value.unitsConsumed === undefined) {
+    return undefined;
+  }
+  return value.unitsConsumed;
+};
+
+/**
+ * The compute unit limit to request for the instructions: the simulated usage plus
+ * `CU_BUDGET_TOLERANCE_BPS` of headroom, the ceiling of the configuration when the
+ * simulation gives no answer
+ */
+export const simulatedComputeBudget = async (
+  connection: Pick<Connection, 'simulateTransaction'>,
+  instructions: TransactionInstruction[],
+  payer: PublicKey,
+  kind: ComputeBudgetKind,
+  flags: ComputeBudgetFlags = {},
+): Promise<number> => {
+  const units = await simulateComputeUnits(connection, instructions, payer);
+  return units === undefined ? recommendedComputeBudget(kind, flags) : withTolerance(units);
+};
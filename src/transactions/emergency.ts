@@ -0,0 +1,47 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+
+export class EmergencyHaltArgs extends Borsh.Data {
+  static readonly SCHEMA = EmergencyHaltArgs.struct([['instruction', 'u8']]);
+  instruction = 18;
+}
+
+export class EmergencyReleaseArgs extends Borsh.Data {
+  static readonly SCHEMA = EmergencyReleaseArgs.struct([['instruction', 'u8']]);
+  instruction = 19;
+}
+
+export type EmergencyHaltParams = {
+  authority: PublicKey;
+  admin: PublicKey;
+  cash: PublicKey;
+};
+
+export type EmergencyWithdrawArgsData = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class EmergencyWithdrawArgs extends Borsh.Data<EmergencyWithdrawArgsData> {
+  static readonly SCHEMA = EmergencyWithdrawArgs.struct([
+    ['instruction', 'u8'],
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+  ]);
+  instruction = 20;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type EmergencyWithdrawParams = {
+  authority: PublicKey;
+  admin: PublicKey;
+  cash: PublicKey;
+  vaultToken: PublicKey;
+  recoveryToken: PublicKey;
+  mint: PublicKey;
+  feePayer: PublicKey;
+  tokenProgramId: PublicKey;
+  cashBump: number;
+  cashReference: string;
+};
@@ -0,0 +1,6 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+
+export class ExpireSlotArgs extends Borsh.Data {
+  static readonly SCHEMA = ExpireSlotArgs.struct([['instruction', 'u8']]);
+  instruction = 21;
+}
@@ -2,3 +2,15 @@ export * from './init_cash';
 export * from './redeem_cash';
 export * from './cancel_cash';
 export * from './close_cash';
+export * from './update_metadata';
+export * from './emergency';
+export * from './expire_slot';
+export * from './rotate_pass_key';
+export * from './recovery';
+export * from './referral';
+export * from './commit';
+export * from './split_cash';
+export * from './migration';
+export * from './approval';
+export * from './compute_budget';
+export * from './clawback';
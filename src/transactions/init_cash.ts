@@ -1,7 +1,12 @@
 import { Borsh } from '@metaplex-foundation/mpl-core';
 import { PublicKey } from '@solana/web3.js';
 import BN from 'bn.js';
-import { CashDistributionType } from 'src/accounts';
+import {
+  CashDistributionType,
+  EntropySourceData,
+  FlashScheduleData,
+  SwapConfigData,
+} from 'src/accounts';
 
 export type InitArgs = {
   amount: BN;
@@ -15,22 +20,73 @@ export type InitArgs = {
   minAmount?: BN;
   cashReference: string;
   isLocked: boolean;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  burnBps: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature: boolean;
+  swap?: SwapConfigData;
+  entropySource: EntropySourceData;
+  flash?: FlashScheduleData;
+  intendedRecipient?: string;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  requireVoucher: boolean;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
 };
 
 export class InitCashArgs extends Borsh.Data<InitArgs> {
-  static readonly SCHEMA = InitCashArgs.struct([
-    ['instruction', 'u8'],
-    ['amount', 'u64'],
-    ['feeBps', 'u16'],
-    ['networkFee', 'u64'],
-    ['baseFeeToRedeem', 'u64'],
-    ['rentFeeToRedeem', 'u64'],
-    ['cashBump', 'u8'],
-    ['distributionType', 'u8'],
-    ['maxNumRedemptions', 'u16'],
-    ['minAmount', { kind: 'option', type: 'u64' }],
-    ['cashReference', 'string'],
-    ['isLocked', 'u8'],
+  static readonly SCHEMA = new Map([
+    ...SwapConfigData.SCHEMA,
+    ...EntropySourceData.SCHEMA,
+    ...FlashScheduleData.SCHEMA,
+    ...InitCashArgs.struct([
+      ['instruction', 'u8'],
+      ['amount', 'u64'],
+      ['feeBps', 'u16'],
+      ['networkFee', 'u64'],
+      ['baseFeeToRedeem', 'u64'],
+      ['rentFeeToRedeem', 'u64'],
+      ['cashBump', 'u8'],
+      ['distributionType', 'u8'],
+      ['maxNumRedemptions', 'u16'],
+      ['minAmount', { kind: 'option', type: 'u64' }],
+      ['cashReference', 'string'],
+      ['isLocked', 'u8'],
+      ['kycRequiredAbove', { kind: 'option', type: 'u64' }],
+      ['kycAuthority', { kind: 'option', type: 'pubkeyAsString' }],
+      ['referralCeilingsEnabled', 'u8'],
+      ['maxReferrerFeeBps', 'u16'],
+      ['maxRefereeFeeBps', 'u16'],
+      ['burnBps', 'u16'],
+      ['metadataUri', { kind: 'option', type: 'string' }],
+      ['claimRequiresWalletSignature', 'u8'],
+      ['swap', { kind: 'option', type: SwapConfigData }],
+      ['entropySource', EntropySourceData],
+      ['flash', { kind: 'option', type: FlashScheduleData }],
+      ['intendedRecipient', { kind: 'option', type: 'pubkeyAsString' }],
+      ['maxPriorityFeePerRedeem', 'u64'],
+      ['allowFreezableMint', 'u8'],
+      ['commitRevealDelay', { kind: 'option', type: 'u64' }],
+      ['refundDestination', { kind: 'option', type: 'pubkeyAsString' }],
+      ['approvalThreshold', { kind: 'option', type: 'u64' }],
+      ['approvalTtl', { kind: 'option', type: 'u64' }],
+      ['requireVoucher', 'u8'],
+      ['autoCancelAt', { kind: 'option', type: 'u64' }],
+      ['autoCancelTip', 'u64'],
+      ['maxSignatureAgeSlots', { kind: 'option', type: 'u64' }],
+      ['clawbackWindowSeconds', { kind: 'option', type: 'u64' }],
+    ]),
   ]);
 
   instruction = 0;
@@ -45,6 +101,29 @@ export class InitCashArgs extends Borsh.Data<InitArgs> {
   minAmount?: BN;
   cashReference: string;
   isLocked: boolean;
+  kycRequiredAbove?: BN;
+  kycAuthority?: string;
+  referralCeilingsEnabled: boolean;
+  maxReferrerFeeBps: number;
+  maxRefereeFeeBps: number;
+  burnBps: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature: boolean;
+  swap?: SwapConfigData;
+  entropySource: EntropySourceData;
+  flash?: FlashScheduleData;
+  intendedRecipient?: string;
+  maxPriorityFeePerRedeem: BN;
+  allowFreezableMint: boolean;
+  commitRevealDelay?: BN;
+  refundDestination?: string;
+  approvalThreshold?: BN;
+  approvalTtl?: BN;
+  requireVoucher: boolean;
+  autoCancelAt?: BN;
+  autoCancelTip: BN;
+  maxSignatureAgeSlots?: BN;
+  clawbackWindowSeconds?: BN;
 }
 
 export type InitCashParams = {
@@ -68,4 +147,43 @@ export type InitCashParams = {
   fingerprintEnabled?: boolean;
   tokenProgramId: PublicKey;
   cashReference: string;
+  kycRequiredAbove?: BN;
+  kycAuthority?: PublicKey;
+  maxReferrerFeeBps?: number;
+  maxRefereeFeeBps?: number;
+  burnBps?: number;
+  metadataUri?: string;
+  claimRequiresWalletSignature?: boolean;
+  swap?: {
+    targetMint: PublicKey;
+    ammProgram: PublicKey;
+    maxSlippageBps: number;
+  };
+  vrfAccount?: PublicKey;
+  flash?: {
+    startAt: BN;
+    interval: BN;
+  };
+  intendedRecipient?: PublicKey;
+  maxPriorityFeePerRedeem?: BN;
+  allowFreezableMint?: boolean;
+  /** Slots a wallet of a Random link must commit ahead of its redemption */
+  commitRevealDelay?: BN;
+  /** Receives the lamport refunds when the owner is a program owned account */
+  refundDestination?: PublicKey;
+  /** Payouts at or above this amount wait for the approval of the authority and the owner */
+  approvalThreshold?: BN;
+  /** Seconds a payout may wait for approval */
+  approvalTtl?: BN;
+  /** Refuse redemptions that carry no voucher hash */
+  requireVoucher?: boolean;
+  /** Queue the link for the auto-cancel crank from this unix timestamp */
+  autoCancelAt?: BN;
+  /** Paid to the crank from the fee reserve of the link when it cancels it */
+  autoCancelTip?: BN;
+  /** Slots a signed redemption stays valid, unset lets it land at any time */
+  maxSignatureAgeSlots?: BN;
+  /** Seconds the authority and the config admin may claw a payout back, the wallet signs */
+  clawbackWindowSeconds?: BN;
+  config?: PublicKey;
 };
@@ -0,0 +1,44 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+
+export type BackfillVersionArgsData = {
+  createdByVersion: number;
+};
+
+export class BackfillVersionArgs extends Borsh.Data<BackfillVersionArgsData> {
+  static readonly SCHEMA = BackfillVersionArgs.struct([
+    ['instruction', 'u8'],
+    ['createdByVersion', 'u16'],
+  ]);
+  instruction = 30;
+  createdByVersion: number;
+}
+
+export type BackfillVersionParams = {
+  /** Pays the rent of the grown account */
+  admin: PublicKey;
+  cash: PublicKey;
+  createdByVersion: number;
+};
+
+export type MigrateBatchArgsData = {
+  count: number;
+};
+
+export class MigrateBatchArgs extends Borsh.Data<MigrateBatchArgsData> {
+  static readonly SCHEMA = MigrateBatchArgs.struct([
+    ['instruction', 'u8'],
+    ['count', 'u8'],
+  ]);
+  instruction = 37;
+  count: number;
+}
+
+/** Links grown by a single batch */
+export const MAX_MIGRATE_BATCH = 12;
+
+export type MigrateBatchParams = {
+  /** Pays the rent of the grown accounts */
+  feePayer: PublicKey;
+  cashes: PublicKey[];
+};
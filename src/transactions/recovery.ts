@@ -0,0 +1,40 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+
+export class InitiateRecoveryArgs extends Borsh.Data {
+  static readonly SCHEMA = InitiateRecoveryArgs.struct([['instruction', 'u8']]);
+  instruction = 23;
+}
+
+export type InitiateRecoveryParams = {
+  admin: PublicKey;
+  cash: PublicKey;
+};
+
+export type ExecuteRecoveryArgsData = {
+  cashBump: number;
+  cashReference: string;
+};
+
+export class ExecuteRecoveryArgs extends Borsh.Data<ExecuteRecoveryArgsData> {
+  static readonly SCHEMA = ExecuteRecoveryArgs.struct([
+    ['instruction', 'u8'],
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+  ]);
+  instruction = 24;
+  cashBump: number;
+  cashReference: string;
+}
+
+export type ExecuteRecoveryParams = {
+  /** Pays for the owner token account when it is missing */
+  admin: PublicKey;
+  cash: PublicKey;
+  owner: PublicKey;
+  mint: PublicKey;
+  feePayer: PublicKey;
+  tokenProgramId: PublicKey;
+  cashBump: number;
+  cashReference: string;
+};
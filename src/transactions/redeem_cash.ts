@@ -1,5 +1,44 @@
 import { Borsh } from '@metaplex-foundation/mpl-core';
 import { PublicKey } from '@solana/web3.js';
+import BN from 'bn.js';
+
+/** Domain separating the claim permits a recipient signs off chain */
+export const PERMIT_DOMAIN = 'cash-claim';
+
+export type RecipientPermitDataArgs = {
+  signature: Uint8Array;
+  expiry: BN;
+};
+
+export class RecipientPermitData extends Borsh.Data<RecipientPermitDataArgs> {
+  static readonly SCHEMA = RecipientPermitData.struct([
+    ['signature', [64]],
+    ['expiry', 'u64'],
+  ]);
+  signature: Uint8Array;
+  expiry: BN;
+}
+
+/**
+ * The message a recipient signs to permit a claim of the link to its wallet, bound to the
+ * number of redemptions of the link at the time of signing
+ */
+export const recipientPermitMessage = (
+  cash: PublicKey,
+  wallet: PublicKey,
+  expiry: BN,
+  totalRedemptions: number,
+): Buffer => {
+  const redemptions = Buffer.alloc(2);
+  redemptions.writeUInt16LE(totalRedemptions);
+  return Buffer.concat([
+    Buffer.from(PERMIT_DOMAIN),
+    cash.toBuffer(),
+    wallet.toBuffer(),
+    expiry.toArrayLike(Buffer, 'le', 8),
+    redemptions,
+  ]);
+};
 
 export type RedeemArgs = {
   cashBump: number;
@@ -9,18 +48,40 @@ export type RedeemArgs = {
   weightPpm?: number;
   rateUsd?: string;
   redemptionBump: number;
+  redemptionIndexBump: number;
+  priorityFeeReimbursement?: BN;
+  recipientPermit?: RecipientPermitData;
+  revealNonce?: Uint8Array;
+  createReceipt: boolean;
+  voucherHash?: Uint8Array;
+  saveBps?: number;
+  signedAtSlot?: BN;
 };
 
+/** Fields of the redeem args, shared by the approval of a pending redemption */
+export const REDEEM_ARGS_FIELDS: [string, unknown][] = [
+  ['instruction', 'u8'],
+  ['cashBump', 'u8'],
+  ['cashReference', 'string'],
+  ['referrerFeeBps', { kind: 'option', type: 'u16' }],
+  ['refereeFeeBps', { kind: 'option', type: 'u16' }],
+  ['weightPpm', { kind: 'option', type: 'u32' }],
+  ['rateUsd', { kind: 'option', type: 'string' }],
+  ['redemptionBump', 'u8'],
+  ['redemptionIndexBump', 'u8'],
+  ['priorityFeeReimbursement', { kind: 'option', type: 'u64' }],
+  ['recipientPermit', { kind: 'option', type: RecipientPermitData }],
+  ['revealNonce', { kind: 'option', type: [32] }],
+  ['createReceipt', 'u8'],
+  ['voucherHash', { kind: 'option', type: [32] }],
+  ['saveBps', { kind: 'option', type: 'u16' }],
+  ['signedAtSlot', { kind: 'option', type: 'u64' }],
+];
+
 export class RedeemCashLinkArgs extends Borsh.Data<RedeemArgs> {
-  static readonly SCHEMA = RedeemCashLinkArgs.struct([
-    ['instruction', 'u8'],
-    ['cashBump', 'u8'],
-    ['cashReference', 'string'],
-    ['referrerFeeBps', { kind: 'option', type: 'u16' }],
-    ['refereeFeeBps', { kind: 'option', type: 'u16' }],
-    ['weightPpm', { kind: 'option', type: 'u32' }],
-    ['rateUsd', { kind: 'option', type: 'string' }],
-    ['redemptionBump', 'u8'],
+  static readonly SCHEMA = new Map([
+    ...RecipientPermitData.SCHEMA,
+    ...RedeemCashLinkArgs.struct(REDEEM_ARGS_FIELDS),
   ]);
 
   instruction = 1;
@@ -51,4 +112,41 @@ export type RedeemCashLinkParams = {
   rateUsd?: string;
   redemptionBump: number;
   redemptionAddress: PublicKey;
+  redemptionIndexBump: number;
+  redemptionIndexAddress: PublicKey;
+  buffer?: PublicKey;
+  walletIsSigner?: boolean;
+  swap?: {
+    ammProgram: PublicKey;
+    pool: PublicKey;
+    poolSource: PublicKey;
+    poolTarget: PublicKey;
+    targetMint: PublicKey;
+  };
+  vrfAccount?: PublicKey;
+  referralStats?: PublicKey;
+  /** Priority fees attached by the fee payer, clamped to the cap of the link */
+  priorityFeeReimbursement?: BN;
+  /** A claim signed off chain by the wallet, verified by an Ed25519 instruction before the redeem */
+  recipientPermit?: RecipientPermitData;
+  /** The nonce the wallet committed to, required by commit-reveal links */
+  revealNonce?: Uint8Array;
+  /** Create the redemption receipt of a single claim link, other links always create it */
+  createReceipt?: boolean;
+  /** Hash of the off chain voucher authorizing the payout, stored in the receipt */
+  voucherHash?: Uint8Array;
+  /** The link holds payouts above a threshold for approval, its pending redemption pda is passed */
+  hasApprovalThreshold?: boolean;
+  /** The link stores a fee wallet epoch, the fee wallet registry pda is passed */
+  hasFeeWalletEpoch?: boolean;
+  /** The config vests referrer fees, the escrow token account of the referral stats is passed */
+  referralVesting?: boolean;
+  /** The mint registry caps the link mint per wallet, the registry and wallet limit are passed */
+  walletLimit?: boolean;
+  /** Share of the payout in bps sent to `savingsToken`, token links without a swap only */
+  saveBps?: number;
+  /** A token account of the wallet in the link mint, receives the saved share */
+  savingsToken?: PublicKey;
+  /** Slot the authority signed the redemption at, required by links with a signature age */
+  signedAtSlot?: BN;
 };
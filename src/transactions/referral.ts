@@ -0,0 +1,52 @@
+import { Borsh, StringPublicKey } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+
+export class CloseReferralStatsArgs extends Borsh.Data {
+  static readonly SCHEMA = CloseReferralStatsArgs.struct([['instruction', 'u8']]);
+  instruction = 25;
+}
+
+export type CloseReferralStatsParams = {
+  admin: PublicKey;
+  referrer: PublicKey;
+  /** Receives the rent of the closed account */
+  destination: PublicKey;
+};
+
+export type ReferralFeesArgsData = {
+  /** The mint the escrowed fees are in */
+  mint: StringPublicKey;
+};
+
+export class ClaimReferralFeesArgs extends Borsh.Data<ReferralFeesArgsData> {
+  static readonly SCHEMA = ClaimReferralFeesArgs.struct([
+    ['instruction', 'u8'],
+    ['mint', 'pubkeyAsString'],
+  ]);
+  instruction = 35;
+  mint: StringPublicKey;
+}
+
+export type ClaimReferralFeesParams = {
+  referrer: PublicKey;
+  mint: PublicKey;
+  tokenProgramId: PublicKey;
+};
+
+export class FlagReferrerArgs extends Borsh.Data<ReferralFeesArgsData> {
+  static readonly SCHEMA = FlagReferrerArgs.struct([
+    ['instruction', 'u8'],
+    ['mint', 'pubkeyAsString'],
+  ]);
+  instruction = 36;
+  mint: StringPublicKey;
+}
+
+export type FlagReferrerParams = {
+  admin: PublicKey;
+  referrer: PublicKey;
+  /** The fee wallet of the config, receives the forfeited fees */
+  feeWallet: PublicKey;
+  mint: PublicKey;
+  tokenProgramId: PublicKey;
+};
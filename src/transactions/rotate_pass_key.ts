@@ -0,0 +1,23 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+
+export type RotatePassKeyArgsData = {
+  force: boolean;
+};
+
+export class RotatePassKeyArgs extends Borsh.Data<RotatePassKeyArgsData> {
+  static readonly SCHEMA = RotatePassKeyArgs.struct([
+    ['instruction', 'u8'],
+    ['force', 'u8'],
+  ]);
+  instruction = 22;
+  force: boolean;
+}
+
+export type RotatePassKeyParams = {
+  authority: PublicKey;
+  cash: PublicKey;
+  newPassKey: PublicKey;
+  /** Omitted for a forced rotation when the current pass key is lost */
+  currentPassKey?: PublicKey;
+};
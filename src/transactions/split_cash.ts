@@ -0,0 +1,44 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+import BN from 'bn.js';
+
+export type SplitCashArgsData = {
+  cashBump: number;
+  cashReference: string;
+  childBump: number;
+  childReference: string;
+  amount: BN;
+  maxNumRedemptions: number;
+};
+
+export class SplitCashArgs extends Borsh.Data<SplitCashArgsData> {
+  static readonly SCHEMA = SplitCashArgs.struct([
+    ['instruction', 'u8'],
+    ['cashBump', 'u8'],
+    ['cashReference', 'string'],
+    ['childBump', 'u8'],
+    ['childReference', 'string'],
+    ['amount', 'u64'],
+    ['maxNumRedemptions', 'u16'],
+  ]);
+  instruction = 29;
+  cashBump: number;
+  cashReference: string;
+  childBump: number;
+  childReference: string;
+  amount: BN;
+  maxNumRedemptions: number;
+}
+
+export type SplitCashParams = {
+  authority: PublicKey;
+  owner: PublicKey;
+  mint: PublicKey;
+  tokenProgramId: PublicKey;
+  cashReference: string;
+  childReference: string;
+  /** Principal moved to the child, in raw token units */
+  amount: BN;
+  /** Redemptions moved to the child */
+  maxNumRedemptions: number;
+};
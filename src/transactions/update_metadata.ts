@@ -0,0 +1,21 @@
+import { Borsh } from '@metaplex-foundation/mpl-core';
+import { PublicKey } from '@solana/web3.js';
+
+export type UpdateMetadataArgsData = {
+  uri: string;
+};
+
+export class UpdateMetadataArgs extends Borsh.Data<UpdateMetadataArgsData> {
+  static readonly SCHEMA = UpdateMetadataArgs.struct([
+    ['instruction', 'u8'],
+    ['uri', 'string'],
+  ]);
+  instruction = 15;
+  uri: string;
+}
+
+export type UpdateMetadataParams = {
+  owner: PublicKey;
+  cash: PublicKey;
+  uri: string;
+};
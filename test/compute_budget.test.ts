@@ -0,0 +1,80 @@
+import test from 'tape';
+import { readFileSync } from 'fs';
+import {
+  Connection,
+  Keypair,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedMessage,
+} from '@solana/web3.js';
+import {
+  ComputeBudgetFlags,
+  ComputeBudgetKind,
+  computeBudgetCeiling,
+  recommendedComputeBudget,
+  simulateComputeUnits,
+  simulatedComputeBudget,
+} from '../src/transactions/compute_budget';
+
+const stubConnection = (unitsConsumed?: number, err: unknown = null) => ({
+  simulateTransaction: async () => ({
+    context: { slot: 0 },
+    value: { err, logs: [], unitsConsumed },
+  }),
+});
+
+const noop = new TransactionInstruction({
+  programId: Keypair.generate().publicKey,
+  keys: [],
+  data: Buffer.alloc(0),
+});
+const payer = Keypair.generate().publicKey;
+
+test('the simulated usage gets the headroom of the table', async (t) => {
+  const connection = stubConnection(100_000) as unknown as Connection;
+  t.equal(await simulatedComputeBudget(connection, [noop], payer, 'redeem'), 110_000);
+});
+
+test('a failed simulation falls back to the table', async (t) => {
+  const failed = stubConnection(100_000, { InstructionError: [1, 'Custom'] });
+  const silent = stubConnection(undefined);
+  for (const connection of [failed, silent] as unknown as Connection[]) {
+    t.equal(await simulateComputeUnits(connection, [noop], payer), undefined);
+    t.equal(
+      await simulatedComputeBudget(connection, [noop], payer, 'redeem', { token2022: true }),
+      recommendedComputeBudget('redeem', { token2022: true }),
+    );
+  }
+});
+
+test('the limit never exceeds the runtime maximum', async (t) => {
+  const connection = stubConnection(1_399_000) as unknown as Connection;
+  t.equal(await simulatedComputeBudget(connection, [noop], payer, 'redeem'), 1_400_000);
+});
+
+/**
+ * Recorded transactions, one per configuration to measure, their messages serialized without
+ * address lookup tables. Capture them against a local validator with the program deployed:
+ * `[{ "kind": "redeem", "flags": { "token2022": true }, "transaction": "<base64 message>" }]`
+ */
+type Measurement = { kind: ComputeBudgetKind; flags: ComputeBudgetFlags; transaction: string };
+
+const rpcUrl = process.env.CASH_CU_RPC_URL;
+const fixtures = process.env.CASH_CU_FIXTURES;
+
+test('measured usage stays within the table', { skip: !rpcUrl || !fixtures }, async (t) => {
+  const connection = new Connection(rpcUrl as string, 'confirmed');
+  const measurements: Measurement[] = JSON.parse(readFileSync(fixtures as string, 'utf8'));
+  for (const { kind, flags, transaction } of measurements) {
+    const message = TransactionMessage.decompile(
+      VersionedMessage.deserialize(Buffer.from(transaction, 'base64')),
+    );
+    const units = await simulateComputeUnits(connection, message.instructions, message.payerKey);
+    const ceiling = computeBudgetCeiling(kind, flags);
+    t.ok(units !== undefined, `${kind} ${JSON.stringify(flags)} simulates`);
+    t.ok(
+      (units as number) <= ceiling,
+      `${kind} ${JSON.stringify(flags)} consumed ${units}, the table allows ${ceiling}`,
+    );
+  }
+});